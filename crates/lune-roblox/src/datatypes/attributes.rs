@@ -10,7 +10,7 @@ use super::extension::DomValueExt;
     # Errors
 
     - If the name starts with the prefix "RBX".
-    - If the name contains any characters other than alphanumeric characters and underscore.
+    - If the name contains any characters other than ASCII alphanumeric characters and underscore.
     - If the name is longer than 100 characters.
 */
 pub fn ensure_valid_attribute_name(name: impl AsRef<str>) -> LuaResult<()> {
@@ -19,7 +19,7 @@ pub fn ensure_valid_attribute_name(name: impl AsRef<str>) -> LuaResult<()> {
         Err(LuaError::RuntimeError(
             "Attribute names must not start with the prefix \"RBX\"".to_string(),
         ))
-    } else if !name.chars().all(|c| c == '_' || c.is_alphanumeric()) {
+    } else if !name.chars().all(|c| c == '_' || c.is_ascii_alphanumeric()) {
         Err(LuaError::RuntimeError(
             "Attribute names must only use alphanumeric characters and underscore".to_string(),
         ))
@@ -51,6 +51,7 @@ impl<'lua> DomValueToLua<'lua> for LuaValue<'lua> {
                 DomValue::Float32(n) => Ok(LuaValue::Number(*n as f64)),
                 DomValue::String(s) => Ok(LuaValue::String(lua.create_string(s)?)),
                 DomValue::BinaryString(s) => Ok(LuaValue::String(lua.create_string(s)?)),
+                DomValue::SharedString(s) => Ok(LuaValue::String(lua.create_string(s.data())?)),
                 DomValue::Content(s) => Ok(LuaValue::String(
                     lua.create_string(AsRef::<str>::as_ref(s))?,
                 )),
@@ -104,6 +105,9 @@ impl<'lua> LuaToDomValue<'lua> for LuaValue<'lua> {
                 (LuaValue::String(s), DomType::BinaryString) => {
                     Ok(DomValue::BinaryString(s.as_ref().into()))
                 }
+                (LuaValue::String(s), DomType::SharedString) => Ok(DomValue::SharedString(
+                    dom::SharedString::new(s.as_bytes().to_vec()),
+                )),
                 (LuaValue::String(s), DomType::Content) => {
                     Ok(DomValue::Content(s.to_str()?.to_string().into()))
                 }
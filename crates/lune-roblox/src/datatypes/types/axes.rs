@@ -32,14 +32,14 @@ impl LuaExportsTable<'_> for Axes {
 
             let mut check = |e: &EnumItem| {
                 if e.parent.desc.name == "Axis" {
-                    match &e.name {
+                    match e.name {
                         name if name == "X" => x = true,
                         name if name == "Y" => y = true,
                         name if name == "Z" => z = true,
                         _ => {}
                     }
                 } else if e.parent.desc.name == "NormalId" {
-                    match &e.name {
+                    match e.name {
                         name if name == "Left" || name == "Right" => x = true,
                         name if name == "Top" || name == "Bottom" => y = true,
                         name if name == "Front" || name == "Back" => z = true,
@@ -2,7 +2,7 @@ use core::fmt;
 
 use mlua::prelude::*;
 use rand::seq::SliceRandom;
-use rbx_dom_weak::types::BrickColor as DomBrickColor;
+use rbx_dom_weak::types::{BrickColor as DomBrickColor, Color3uint8 as DomColor3uint8};
 
 use lune_utils::TableBuilder;
 
@@ -30,16 +30,19 @@ impl LuaExportsTable<'_> for BrickColor {
     fn create_exports_table(lua: &Lua) -> LuaResult<LuaTable> {
         type ArgsNumber = u16;
         type ArgsName = String;
-        type ArgsRgb = (u8, u8, u8);
+        type ArgsRgb = (f32, f32, f32);
         type ArgsColor3<'lua> = LuaUserDataRef<'lua, Color3>;
 
+        // NOTE: Rgb components must be checked before a single number, since
+        // extra arguments are ignored and the red component would be used
+        // as the number, and they are given in the 0-1 range, same as Color3
         let brick_color_new = |lua, args: LuaMultiValue| {
-            if let Ok(number) = ArgsNumber::from_lua_multi(args.clone(), lua) {
+            if let Ok((r, g, b)) = ArgsRgb::from_lua_multi(args.clone(), lua) {
+                Ok(Self::from(Color3 { r, g, b }))
+            } else if let Ok(number) = ArgsNumber::from_lua_multi(args.clone(), lua) {
                 Ok(color_from_number(number))
             } else if let Ok(name) = ArgsName::from_lua_multi(args.clone(), lua) {
                 Ok(color_from_name(name))
-            } else if let Ok((r, g, b)) = ArgsRgb::from_lua_multi(args.clone(), lua) {
-                Ok(color_from_rgb(r, g, b))
             } else if let Ok(color) = ArgsColor3::from_lua_multi(args.clone(), lua) {
                 Ok(Self::from(*color))
             } else {
@@ -112,9 +115,7 @@ impl fmt::Display for BrickColor {
 
 impl From<Color3> for BrickColor {
     fn from(value: Color3) -> Self {
-        let r = value.r.clamp(u8::MIN as f32, u8::MAX as f32) as u8;
-        let g = value.g.clamp(u8::MIN as f32, u8::MAX as f32) as u8;
-        let b = value.b.clamp(u8::MIN as f32, u8::MAX as f32) as u8;
+        let DomColor3uint8 { r, g, b } = DomColor3uint8::from(value);
         color_from_rgb(r, g, b)
     }
 }
@@ -161,14 +162,20 @@ impl From<BrickColorDef> for BrickColor {
     }
 }
 
-const BRICK_COLOR_DEFAULT_VALUE: BrickColorDef =
-    &BRICK_COLOR_VALUES[(BRICK_COLOR_DEFAULT - 1) as usize];
+// NOTE: Brick color numbers are not contiguous, so the
+// default value must be looked up by its number, not index
+fn default_value() -> BrickColorDef {
+    BRICK_COLOR_VALUES
+        .iter()
+        .find(|color| color.0 == BRICK_COLOR_DEFAULT)
+        .expect("Missing default brick color")
+}
 
 fn color_from_number(index: u16) -> BrickColor {
     BRICK_COLOR_VALUES
         .iter()
         .find(|color| color.0 == index)
-        .unwrap_or(BRICK_COLOR_DEFAULT_VALUE)
+        .unwrap_or_else(default_value)
         .into()
 }
 
@@ -177,32 +184,22 @@ fn color_from_name(name: impl AsRef<str>) -> BrickColor {
     BRICK_COLOR_VALUES
         .iter()
         .find(|color| color.1 == name)
-        .unwrap_or(BRICK_COLOR_DEFAULT_VALUE)
+        .unwrap_or_else(default_value)
         .into()
 }
 
 fn color_from_rgb(r: u8, g: u8, b: u8) -> BrickColor {
-    let r = r as i16;
-    let g = g as i16;
-    let b = b as i16;
+    let distance = |color: BrickColorDef| {
+        let (cr, cg, cb) = color.2;
+        let dr = i32::from(r) - i32::from(cr);
+        let dg = i32::from(g) - i32::from(cg);
+        let db = i32::from(b) - i32::from(cb);
+        dr * dr + dg * dg + db * db
+    };
     BRICK_COLOR_VALUES
         .iter()
-        .fold(
-            (None, u16::MAX),
-            |(closest_color, closest_distance), color| {
-                let cr = color.2 .0 as i16;
-                let cg = color.2 .1 as i16;
-                let cb = color.2 .2 as i16;
-                let distance = ((r - cr) + (g - cg) + (b - cb)).unsigned_abs();
-                if distance < closest_distance {
-                    (Some(color), distance)
-                } else {
-                    (closest_color, closest_distance)
-                }
-            },
-        )
-        .0
-        .unwrap_or(BRICK_COLOR_DEFAULT_VALUE)
+        .min_by_key(|color| distance(color))
+        .unwrap_or_else(default_value)
         .into()
 }
 
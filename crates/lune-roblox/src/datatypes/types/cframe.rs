@@ -220,6 +220,18 @@ impl LuaUserData for CFrame {
                 )))
             },
         );
+        methods.add_method(
+            "FuzzyEq",
+            |_, this, (rhs, epsilon): (LuaUserDataRef<CFrame>, Option<f32>)| {
+                let epsilon = epsilon.unwrap_or(1e-5);
+                let this_cols = this.0.to_cols_array();
+                let rhs_cols = rhs.0.to_cols_array();
+                Ok(this_cols
+                    .iter()
+                    .zip(rhs_cols.iter())
+                    .all(|(a, b)| (a - b).abs() <= epsilon))
+            },
+        );
         methods.add_method("Orthonormalize", |_, this, ()| {
             let rotation = Quat::from_mat4(&this.0);
             let translation = this.0.w_axis.truncate();
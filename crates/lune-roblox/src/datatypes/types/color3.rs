@@ -32,16 +32,20 @@ impl LuaExportsTable<'_> for Color3 {
     const EXPORT_NAME: &'static str = "Color3";
 
     fn create_exports_table(lua: &Lua) -> LuaResult<LuaTable> {
-        let color3_from_rgb = |_, (r, g, b): (Option<u8>, Option<u8>, Option<u8>)| {
+        // NOTE: Roblox does not clamp or round the given components here, so
+        // values outside of the 0-255 range and fractional values are allowed
+        let color3_from_rgb = |_, (r, g, b): (Option<f32>, Option<f32>, Option<f32>)| {
             Ok(Color3 {
-                r: (r.unwrap_or_default() as f32) / 255f32,
-                g: (g.unwrap_or_default() as f32) / 255f32,
-                b: (b.unwrap_or_default() as f32) / 255f32,
+                r: r.unwrap_or_default() / 255f32,
+                g: g.unwrap_or_default() / 255f32,
+                b: b.unwrap_or_default() / 255f32,
             })
         };
 
         let color3_from_hsv = |_, (h, s, v): (f32, f32, f32)| {
             // https://axonflux.com/handy-rgb-to-hsl-and-rgb-to-hsv-color-model-c
+            // Hue wraps around, so that negative hues or hues above 1 are still valid
+            let h = h.rem_euclid(1.0);
             let i = (h * 6.0).floor();
             let f = h * 6.0 - i;
             let p = v * (1.0 - s);
@@ -172,12 +176,10 @@ impl LuaUserData for Color3 {
             Ok((hue, sat, max))
         });
         methods.add_method("ToHex", |_, this, ()| {
-            Ok(format!(
-                "{:02X}{:02X}{:02X}",
-                (this.r * 255.0).clamp(u8::MIN as f32, u8::MAX as f32) as u8,
-                (this.g * 255.0).clamp(u8::MIN as f32, u8::MAX as f32) as u8,
-                (this.b * 255.0).clamp(u8::MIN as f32, u8::MAX as f32) as u8,
-            ))
+            // NOTE: We round the same way as when saving the color as a
+            // Color3uint8 property, so that the hex string always matches it
+            let DomColor3uint8 { r, g, b } = DomColor3uint8::from(*this);
+            Ok(format!("{r:02X}{g:02X}{b:02X}"))
         });
         // Metamethods
         methods.add_meta_method(LuaMetaMethod::Eq, userdata_impl_eq);
@@ -185,7 +187,7 @@ impl LuaUserData for Color3 {
         methods.add_meta_method(LuaMetaMethod::Unm, userdata_impl_unm);
         methods.add_meta_method(LuaMetaMethod::Add, userdata_impl_add);
         methods.add_meta_method(LuaMetaMethod::Sub, userdata_impl_sub);
-        methods.add_meta_method(LuaMetaMethod::Mul, userdata_impl_mul_f32);
+        methods.add_meta_function(LuaMetaMethod::Mul, userdata_impl_mul_f32::<Self>);
         methods.add_meta_method(LuaMetaMethod::Div, userdata_impl_div_f32);
     }
 }
@@ -7,7 +7,7 @@ use rbx_dom_weak::types::{
 
 use lune_utils::TableBuilder;
 
-use crate::exports::LuaExportsTable;
+use crate::{datatypes::util::validate_sequence_keypoint_times, exports::LuaExportsTable};
 
 use super::{super::*, Color3, ColorSequenceKeypoint};
 
@@ -21,6 +21,32 @@ pub struct ColorSequence {
     pub(crate) keypoints: Vec<ColorSequenceKeypoint>,
 }
 
+impl ColorSequence {
+    /**
+        Evaluates the color of the sequence at the given time, by linearly
+        interpolating between the two keypoints surrounding that time.
+
+        Times before the first keypoint or after the last
+        keypoint evaluate to the color of that keypoint.
+    */
+    pub(crate) fn evaluate(&self, time: f32) -> Color3 {
+        let (Some(first), Some(last)) = (self.keypoints.first(), self.keypoints.last()) else {
+            return Color3::default();
+        };
+        if time <= first.time {
+            return first.color;
+        }
+        for pair in self.keypoints.windows(2) {
+            let (k0, k1) = (pair[0], pair[1]);
+            if time < k1.time {
+                let alpha = (time - k0.time) / (k1.time - k0.time);
+                return k0.color + (k1.color - k0.color) * alpha;
+            }
+        }
+        last.color
+    }
+}
+
 impl LuaExportsTable<'_> for ColorSequence {
     const EXPORT_NAME: &'static str = "ColorSequence";
 
@@ -57,6 +83,8 @@ impl LuaExportsTable<'_> for ColorSequence {
                     ],
                 })
             } else if let Ok(keypoints) = ArgsKeypoints::from_lua_multi(args, lua) {
+                let times = keypoints.iter().map(|k| k.time).collect::<Vec<_>>();
+                validate_sequence_keypoint_times("ColorSequence", &times)?;
                 Ok(ColorSequence {
                     keypoints: keypoints.iter().map(|k| **k).collect(),
                 })
@@ -80,6 +108,9 @@ impl LuaUserData for ColorSequence {
     }
 
     fn add_methods<'lua, M: LuaUserDataMethods<'lua, Self>>(methods: &mut M) {
+        // Methods
+        methods.add_method("Evaluate", |_, this, time: f32| Ok(this.evaluate(time)));
+        // Metamethods
         methods.add_meta_method(LuaMetaMethod::Eq, userdata_impl_eq);
         methods.add_meta_method(LuaMetaMethod::ToString, userdata_impl_to_string);
     }
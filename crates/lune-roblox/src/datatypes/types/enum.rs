@@ -26,16 +26,20 @@ impl LuaUserData for Enum {
     fn add_methods<'lua, M: LuaUserDataMethods<'lua, Self>>(methods: &mut M) {
         // Methods
         methods.add_method("GetEnumItems", |_, this, ()| {
-            Ok(this
+            let mut items = this
                 .desc
                 .items
                 .iter()
                 .map(|(name, value)| EnumItem {
                     parent: this.clone(),
-                    name: name.to_string(),
+                    name: &**name,
                     value: *value,
                 })
-                .collect::<Vec<_>>())
+                .collect::<Vec<_>>();
+            // NOTE: Enum items are stored in an unordered map, but
+            // Roblox always returns them sorted by their values
+            items.sort_by_key(|item| item.value);
+            Ok(items)
         });
         methods.add_meta_method(LuaMetaMethod::Index, |_, this, name: String| {
             match EnumItem::from_enum_and_name(this, &name) {
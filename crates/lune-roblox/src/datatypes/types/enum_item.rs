@@ -13,7 +13,7 @@ use super::{super::*, Enum};
 #[derive(Debug, Clone)]
 pub struct EnumItem {
     pub(crate) parent: Enum,
-    pub(crate) name: String,
+    pub(crate) name: &'static str,
     pub(crate) value: u32,
 }
 
@@ -24,7 +24,7 @@ impl EnumItem {
             if *name == enum_name {
                 Some(Self {
                     parent: parent.clone(),
-                    name: enum_name.to_string(),
+                    name: &**name,
                     value: *v,
                 })
             } else {
@@ -38,7 +38,7 @@ impl EnumItem {
             if *v == value {
                 Some(Self {
                     parent: parent.clone(),
-                    name: name.to_string(),
+                    name: &**name,
                     value,
                 })
             } else {
@@ -63,7 +63,7 @@ impl EnumItem {
 
 impl LuaUserData for EnumItem {
     fn add_fields<'lua, F: LuaUserDataFields<'lua, Self>>(fields: &mut F) {
-        fields.add_field_method_get("Name", |_, this| Ok(this.name.clone()));
+        fields.add_field_method_get("Name", |_, this| Ok(this.name));
         fields.add_field_method_get("Value", |_, this| Ok(this.value));
         fields.add_field_method_get("EnumType", |_, this| Ok(this.parent.clone()));
     }
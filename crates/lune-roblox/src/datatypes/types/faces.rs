@@ -40,7 +40,7 @@ impl LuaExportsTable<'_> for Faces {
 
             let mut check = |e: &EnumItem| {
                 if e.parent.desc.name == "NormalId" {
-                    match &e.name {
+                    match e.name {
                         name if name == "Right" => right = true,
                         name if name == "Top" => top = true,
                         name if name == "Back" => back = true,
@@ -72,7 +72,7 @@ impl LuaExportsTable<'_> for Font {
             };
 
         let font_from_id =
-            |_, (id, weight, style): (i32, Option<FontWeight>, Option<FontStyle>)| {
+            |_, (id, weight, style): (u64, Option<FontWeight>, Option<FontStyle>)| {
                 Ok(Font {
                     family: format!("rbxassetid://{id}"),
                     weight: weight.unwrap_or_default(),
@@ -8,15 +8,19 @@ mod r#enum;
 mod r#enum_item;
 mod r#enums;
 mod faces;
+mod float_curve_key;
 mod font;
 mod number_range;
 mod number_sequence;
 mod number_sequence_keypoint;
 mod physical_properties;
+mod raycast_params;
+mod raycast_result;
 mod ray;
 mod rect;
 mod region3;
 mod region3int16;
+mod rotation_curve_key;
 mod udim;
 mod udim2;
 mod vector2;
@@ -31,6 +35,7 @@ pub use color3::Color3;
 pub use color_sequence::ColorSequence;
 pub use color_sequence_keypoint::ColorSequenceKeypoint;
 pub use faces::Faces;
+pub use float_curve_key::FloatCurveKey;
 pub use font::Font;
 pub use number_range::NumberRange;
 pub use number_sequence::NumberSequence;
@@ -40,9 +45,12 @@ pub use r#enum::Enum;
 pub use r#enum_item::EnumItem;
 pub use r#enums::Enums;
 pub use ray::Ray;
+pub use raycast_params::RaycastParams;
+pub use raycast_result::RaycastResult;
 pub use rect::Rect;
 pub use region3::Region3;
 pub use region3int16::Region3int16;
+pub use rotation_curve_key::RotationCurveKey;
 pub use udim::UDim;
 pub use udim2::UDim2;
 pub use vector2::Vector2;
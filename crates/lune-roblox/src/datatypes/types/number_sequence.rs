@@ -7,7 +7,7 @@ use rbx_dom_weak::types::{
 
 use lune_utils::TableBuilder;
 
-use crate::exports::LuaExportsTable;
+use crate::{datatypes::util::validate_sequence_keypoint_times, exports::LuaExportsTable};
 
 use super::{super::*, NumberSequenceKeypoint};
 
@@ -21,6 +21,34 @@ pub struct NumberSequence {
     pub(crate) keypoints: Vec<NumberSequenceKeypoint>,
 }
 
+impl NumberSequence {
+    /**
+        Evaluates the value of the sequence at the given time, by linearly
+        interpolating between the two keypoints surrounding that time.
+
+        Times before the first keypoint or after the last
+        keypoint evaluate to the value of that keypoint.
+
+        Note that envelopes of keypoints are not taken into account.
+    */
+    pub(crate) fn evaluate(&self, time: f32) -> f32 {
+        let (Some(first), Some(last)) = (self.keypoints.first(), self.keypoints.last()) else {
+            return 0.0;
+        };
+        if time <= first.time {
+            return first.value;
+        }
+        for pair in self.keypoints.windows(2) {
+            let (k0, k1) = (pair[0], pair[1]);
+            if time < k1.time {
+                let alpha = (time - k0.time) / (k1.time - k0.time);
+                return k0.value + (k1.value - k0.value) * alpha;
+            }
+        }
+        last.value
+    }
+}
+
 impl LuaExportsTable<'_> for NumberSequence {
     const EXPORT_NAME: &'static str = "NumberSequence";
 
@@ -61,6 +89,8 @@ impl LuaExportsTable<'_> for NumberSequence {
                     ],
                 })
             } else if let Ok(keypoints) = ArgsKeypoints::from_lua_multi(args, lua) {
+                let times = keypoints.iter().map(|k| k.time).collect::<Vec<_>>();
+                validate_sequence_keypoint_times("NumberSequence", &times)?;
                 Ok(NumberSequence {
                     keypoints: keypoints.iter().map(|k| **k).collect(),
                 })
@@ -84,6 +114,9 @@ impl LuaUserData for NumberSequence {
     }
 
     fn add_methods<'lua, M: LuaUserDataMethods<'lua, Self>>(methods: &mut M) {
+        // Methods
+        methods.add_method("Evaluate", |_, this, time: f32| Ok(this.evaluate(time)));
+        // Metamethods
         methods.add_meta_method(LuaMetaMethod::Eq, userdata_impl_eq);
         methods.add_meta_method(LuaMetaMethod::ToString, userdata_impl_to_string);
     }
@@ -26,10 +26,16 @@ impl LuaExportsTable<'_> for NumberSequenceKeypoint {
 
     fn create_exports_table(lua: &Lua) -> LuaResult<LuaTable> {
         let number_sequence_keypoint_new = |_, (time, value, envelope): (f32, f32, Option<f32>)| {
+            let envelope = envelope.unwrap_or_default();
+            if envelope < 0.0 {
+                return Err(LuaError::runtime(format!(
+                    "NumberSequenceKeypoint envelope must not be negative, got {envelope}"
+                )));
+            }
             Ok(NumberSequenceKeypoint {
                 time,
                 value,
-                envelope: envelope.unwrap_or_default(),
+                envelope,
             })
         };
 
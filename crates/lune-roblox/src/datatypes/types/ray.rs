@@ -24,7 +24,9 @@ pub struct Ray {
 
 impl Ray {
     fn closest_point(&self, point: Vec3) -> Vec3 {
-        let norm = self.direction.normalize();
+        // NOTE: A ray with a zero direction is a single point, its
+        // origin, so that is also the closest point to anything else
+        let norm = self.direction.normalize_or_zero();
         let lhs = point - self.origin;
 
         let dot_product = lhs.dot(norm).max(0.0);
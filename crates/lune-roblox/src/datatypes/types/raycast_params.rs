@@ -0,0 +1,135 @@
+use core::fmt;
+
+use mlua::prelude::*;
+
+use lune_utils::TableBuilder;
+
+use crate::{exports::LuaExportsTable, instance::Instance};
+
+use super::{super::*, EnumItem};
+
+/**
+    An implementation of the [RaycastParams](https://create.roblox.com/docs/reference/engine/datatypes/RaycastParams) Roblox datatype.
+
+    This implements the properties & methods of the `RaycastParams` class that are relevant
+    when raycasting against parts in a document. Collision groups are not supported, and
+    `IgnoreWater` is accepted but has no effect, since terrain is never hit by raycasts.
+*/
+#[derive(Debug, Clone)]
+pub struct RaycastParams {
+    pub(crate) filter_instances: Vec<Instance>,
+    pub(crate) filter_type: EnumItem,
+    pub(crate) ignore_water: bool,
+    pub(crate) respect_can_collide: bool,
+}
+
+impl RaycastParams {
+    /**
+        Checks if the filter only includes the filtered instances, instead of excluding them.
+    */
+    pub(crate) fn is_include_filter(&self) -> bool {
+        self.filter_type.name == "Include" || self.filter_type.name == "Whitelist"
+    }
+}
+
+impl Default for RaycastParams {
+    fn default() -> Self {
+        Self {
+            filter_instances: Vec::new(),
+            filter_type: EnumItem::from_enum_name_and_name("RaycastFilterType", "Exclude")
+                .expect("RaycastFilterType.Exclude exists"),
+            ignore_water: false,
+            respect_can_collide: false,
+        }
+    }
+}
+
+impl LuaExportsTable<'_> for RaycastParams {
+    const EXPORT_NAME: &'static str = "RaycastParams";
+
+    fn create_exports_table(lua: &Lua) -> LuaResult<LuaTable> {
+        let raycast_params_new = |_, ()| Ok(RaycastParams::default());
+
+        TableBuilder::new(lua)?
+            .with_function("new", raycast_params_new)?
+            .build_readonly()
+    }
+}
+
+impl LuaUserData for RaycastParams {
+    fn add_fields<'lua, F: LuaUserDataFields<'lua, Self>>(fields: &mut F) {
+        // Getters
+        fields.add_field_method_get("FilterDescendantsInstances", |_, this| {
+            Ok(this.filter_instances.clone())
+        });
+        fields.add_field_method_get("FilterType", |_, this| Ok(this.filter_type.clone()));
+        fields.add_field_method_get("IgnoreWater", |_, this| Ok(this.ignore_water));
+        fields.add_field_method_get("RespectCanCollide", |_, this| Ok(this.respect_can_collide));
+        // Setters
+        fields.add_field_method_set(
+            "FilterDescendantsInstances",
+            |_, this, value: Vec<LuaUserDataRef<Instance>>| {
+                this.filter_instances = value.iter().map(|inst| (**inst).clone()).collect();
+                Ok(())
+            },
+        );
+        fields.add_field_method_set("FilterType", |_, this, value: EnumItem| {
+            if value.parent.desc.name == "RaycastFilterType" {
+                this.filter_type = value;
+                Ok(())
+            } else {
+                Err(LuaError::RuntimeError(format!(
+                    "EnumItem must be a RaycastFilterType, got {}",
+                    value.parent.desc.name
+                )))
+            }
+        });
+        fields.add_field_method_set("IgnoreWater", |_, this, value: bool| {
+            this.ignore_water = value;
+            Ok(())
+        });
+        fields.add_field_method_set("RespectCanCollide", |_, this, value: bool| {
+            this.respect_can_collide = value;
+            Ok(())
+        });
+    }
+
+    fn add_methods<'lua, M: LuaUserDataMethods<'lua, Self>>(methods: &mut M) {
+        methods.add_method_mut("AddToFilter", |_, this, value: LuaValue| {
+            match value {
+                LuaValue::Table(t) => {
+                    for inst in t.sequence_values::<LuaUserDataRef<Instance>>() {
+                        this.filter_instances.push((*inst?).clone());
+                    }
+                }
+                LuaValue::UserData(ud) => {
+                    this.filter_instances.push(ud.borrow::<Instance>()?.clone());
+                }
+                _ => {
+                    return Err(LuaError::RuntimeError(format!(
+                        "Expected an Instance or a table of Instances, got {}",
+                        value.type_name()
+                    )))
+                }
+            }
+            Ok(())
+        });
+        methods.add_meta_method(LuaMetaMethod::ToString, userdata_impl_to_string);
+    }
+}
+
+impl fmt::Display for RaycastParams {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{{{}}}, {}, {}",
+            self.filter_instances
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", "),
+            self.filter_type,
+            self.respect_can_collide
+        )
+    }
+}
@@ -0,0 +1,46 @@
+use core::fmt;
+
+use mlua::prelude::*;
+
+use crate::instance::Instance;
+
+use super::{super::*, EnumItem, Vector3};
+
+/**
+    An implementation of the [RaycastResult](https://create.roblox.com/docs/reference/engine/datatypes/RaycastResult) Roblox datatype.
+
+    This implements all documented properties of the `RaycastResult` class as of October 2026.
+    Results can only be created by raycasting, using `WorldRoot:Raycast`.
+*/
+#[derive(Debug, Clone)]
+pub struct RaycastResult {
+    pub(crate) instance: Instance,
+    pub(crate) position: Vector3,
+    pub(crate) normal: Vector3,
+    pub(crate) material: EnumItem,
+    pub(crate) distance: f32,
+}
+
+impl LuaUserData for RaycastResult {
+    fn add_fields<'lua, F: LuaUserDataFields<'lua, Self>>(fields: &mut F) {
+        fields.add_field_method_get("Instance", |_, this| Ok(this.instance.clone()));
+        fields.add_field_method_get("Position", |_, this| Ok(this.position));
+        fields.add_field_method_get("Normal", |_, this| Ok(this.normal));
+        fields.add_field_method_get("Material", |_, this| Ok(this.material.clone()));
+        fields.add_field_method_get("Distance", |_, this| Ok(this.distance));
+    }
+
+    fn add_methods<'lua, M: LuaUserDataMethods<'lua, Self>>(methods: &mut M) {
+        methods.add_meta_method(LuaMetaMethod::ToString, userdata_impl_to_string);
+    }
+}
+
+impl fmt::Display for RaycastResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{{{}}}, {{{}}}, {}",
+            self.instance, self.position, self.material
+        )
+    }
+}
@@ -85,7 +85,7 @@ impl LuaUserData for Rect {
 
 impl fmt::Display for Rect {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}, {}", self.min, self.max)
+        write!(f, "{}, {}", Vector2(self.min), Vector2(self.max))
     }
 }
 
@@ -50,6 +50,11 @@ impl LuaUserData for Region3 {
     fn add_methods<'lua, M: LuaUserDataMethods<'lua, Self>>(methods: &mut M) {
         // Methods
         methods.add_method("ExpandToGrid", |_, this, resolution: f32| {
+            if !resolution.is_finite() || resolution <= 0.0 {
+                return Err(LuaError::RuntimeError(format!(
+                    "Resolution must be a positive number, got {resolution}"
+                )));
+            }
             Ok(Region3 {
                 min: (this.min / resolution).floor() * resolution,
                 max: (this.max / resolution).ceil() * resolution,
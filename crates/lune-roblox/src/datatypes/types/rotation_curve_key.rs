@@ -0,0 +1,88 @@
+use core::fmt;
+
+use mlua::prelude::*;
+
+use lune_utils::TableBuilder;
+
+use crate::exports::LuaExportsTable;
+
+use super::{
+    super::*,
+    float_curve_key::{key_interpolation_mode_item, key_interpolation_mode_value},
+    CFrame, EnumItem,
+};
+
+/**
+    An implementation of the [RotationCurveKey](https://create.roblox.com/docs/reference/engine/datatypes/RotationCurveKey) Roblox datatype.
+
+    This implements all documented properties, methods & constructors of the `RotationCurveKey` class as of October 2026.
+
+    Note that there is no corresponding type in `rbx-dom` yet, meaning
+    that this datatype can not currently be serialized in model files.
+*/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RotationCurveKey {
+    pub(crate) time: f32,
+    pub(crate) value: CFrame,
+    pub(crate) interpolation: u32,
+    pub(crate) left_tangent: Option<f32>,
+    pub(crate) right_tangent: Option<f32>,
+}
+
+impl LuaExportsTable<'_> for RotationCurveKey {
+    const EXPORT_NAME: &'static str = "RotationCurveKey";
+
+    fn create_exports_table(lua: &Lua) -> LuaResult<LuaTable> {
+        let rotation_curve_key_new = |_,
+                                      (time, value, interpolation): (
+            f32,
+            LuaUserDataRef<CFrame>,
+            LuaUserDataRef<EnumItem>,
+        )| {
+            Ok(RotationCurveKey {
+                time,
+                value: *value,
+                interpolation: key_interpolation_mode_value(&interpolation)?,
+                left_tangent: None,
+                right_tangent: None,
+            })
+        };
+
+        TableBuilder::new(lua)?
+            .with_function("new", rotation_curve_key_new)?
+            .build_readonly()
+    }
+}
+
+impl LuaUserData for RotationCurveKey {
+    fn add_fields<'lua, F: LuaUserDataFields<'lua, Self>>(fields: &mut F) {
+        // Getters
+        fields.add_field_method_get("Time", |_, this| Ok(this.time));
+        fields.add_field_method_get("Value", |_, this| Ok(this.value));
+        fields.add_field_method_get("Interpolation", |_, this| {
+            Ok(key_interpolation_mode_item(this.interpolation))
+        });
+        fields.add_field_method_get("LeftTangent", |_, this| Ok(this.left_tangent));
+        fields.add_field_method_get("RightTangent", |_, this| Ok(this.right_tangent));
+        // Setters
+        fields.add_field_method_set("LeftTangent", |_, this, value: Option<f32>| {
+            this.left_tangent = value;
+            Ok(())
+        });
+        fields.add_field_method_set("RightTangent", |_, this, value: Option<f32>| {
+            this.right_tangent = value;
+            Ok(())
+        });
+    }
+
+    fn add_methods<'lua, M: LuaUserDataMethods<'lua, Self>>(methods: &mut M) {
+        methods.add_meta_method(LuaMetaMethod::Eq, userdata_impl_eq);
+        methods.add_meta_method(LuaMetaMethod::ToString, userdata_impl_to_string);
+    }
+}
+
+impl fmt::Display for RotationCurveKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} > {}", self.time, self.value)
+    }
+}
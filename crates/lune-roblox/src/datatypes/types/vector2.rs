@@ -7,7 +7,7 @@ use rbx_dom_weak::types::Vector2 as DomVector2;
 
 use lune_utils::TableBuilder;
 
-use crate::exports::LuaExportsTable;
+use crate::{datatypes::util::DEFAULT_FUZZY_EPSILON, exports::LuaExportsTable};
 
 use super::super::*;
 
@@ -65,7 +65,8 @@ impl LuaUserData for Vector2 {
         });
         methods.add_method(
             "FuzzyEq",
-            |_, this, (rhs, epsilon): (LuaUserDataRef<Vector2>, f32)| {
+            |_, this, (rhs, epsilon): (LuaUserDataRef<Vector2>, Option<f32>)| {
+                let epsilon = epsilon.unwrap_or(DEFAULT_FUZZY_EPSILON);
                 let eq_x = (rhs.0.x - this.0.x).abs() <= epsilon;
                 let eq_y = (rhs.0.y - this.0.y).abs() <= epsilon;
                 Ok(eq_x && eq_y)
@@ -77,12 +78,24 @@ impl LuaUserData for Vector2 {
                 Ok(Vector2(this.0.lerp(rhs.0, alpha)))
             },
         );
-        methods.add_method("Max", |_, this, rhs: LuaUserDataRef<Vector2>| {
-            Ok(Vector2(this.0.max(rhs.0)))
-        });
-        methods.add_method("Min", |_, this, rhs: LuaUserDataRef<Vector2>| {
-            Ok(Vector2(this.0.min(rhs.0)))
-        });
+        methods.add_method(
+            "Max",
+            |_, this, others: LuaVariadic<LuaUserDataRef<Vector2>>| {
+                if others.is_empty() {
+                    return Err(LuaError::runtime("Expected at least one Vector2"));
+                }
+                Ok(Vector2(others.iter().fold(this.0, |acc, v| acc.max(v.0))))
+            },
+        );
+        methods.add_method(
+            "Min",
+            |_, this, others: LuaVariadic<LuaUserDataRef<Vector2>>| {
+                if others.is_empty() {
+                    return Err(LuaError::runtime("Expected at least one Vector2"));
+                }
+                Ok(Vector2(others.iter().fold(this.0, |acc, v| acc.min(v.0))))
+            },
+        );
         methods.add_method("Abs", |_, this, ()| Ok(Vector2(this.0.abs())));
         methods.add_method("Ceil", |_, this, ()| Ok(Vector2(this.0.ceil())));
         methods.add_method("Floor", |_, this, ()| Ok(Vector2(this.0.floor())));
@@ -93,7 +106,7 @@ impl LuaUserData for Vector2 {
         methods.add_meta_method(LuaMetaMethod::Unm, userdata_impl_unm);
         methods.add_meta_method(LuaMetaMethod::Add, userdata_impl_add);
         methods.add_meta_method(LuaMetaMethod::Sub, userdata_impl_sub);
-        methods.add_meta_method(LuaMetaMethod::Mul, userdata_impl_mul_f32);
+        methods.add_meta_function(LuaMetaMethod::Mul, userdata_impl_mul_f32::<Self>);
         methods.add_meta_method(LuaMetaMethod::Div, userdata_impl_div_f32);
         methods.add_meta_method(LuaMetaMethod::IDiv, userdata_impl_idiv_f32);
     }
@@ -50,7 +50,7 @@ impl LuaUserData for Vector2int16 {
         methods.add_meta_method(LuaMetaMethod::Unm, userdata_impl_unm);
         methods.add_meta_method(LuaMetaMethod::Add, userdata_impl_add);
         methods.add_meta_method(LuaMetaMethod::Sub, userdata_impl_sub);
-        methods.add_meta_method(LuaMetaMethod::Mul, userdata_impl_mul_i32);
+        methods.add_meta_function(LuaMetaMethod::Mul, userdata_impl_mul_i32::<Self>);
         methods.add_meta_method(LuaMetaMethod::Div, userdata_impl_div_i32);
     }
 }
@@ -7,7 +7,10 @@ use rbx_dom_weak::types::Vector3 as DomVector3;
 
 use lune_utils::TableBuilder;
 
-use crate::{datatypes::util::round_float_decimal, exports::LuaExportsTable};
+use crate::{
+    datatypes::util::{round_float_decimal, DEFAULT_FUZZY_EPSILON},
+    exports::LuaExportsTable,
+};
 
 use super::{super::*, EnumItem};
 
@@ -30,7 +33,7 @@ impl LuaExportsTable<'_> for Vector3 {
     fn create_exports_table(lua: &Lua) -> LuaResult<LuaTable> {
         let vector3_from_axis = |_, normal_id: LuaUserDataRef<EnumItem>| {
             if normal_id.parent.desc.name == "Axis" {
-                Ok(match normal_id.name.as_str() {
+                Ok(match normal_id.name {
                     "X" => Vector3(Vec3::X),
                     "Y" => Vector3(Vec3::Y),
                     "Z" => Vector3(Vec3::Z),
@@ -50,7 +53,7 @@ impl LuaExportsTable<'_> for Vector3 {
 
         let vector3_from_normal_id = |_, normal_id: LuaUserDataRef<EnumItem>| {
             if normal_id.parent.desc.name == "NormalId" {
-                Ok(match normal_id.name.as_str() {
+                Ok(match normal_id.name {
                     "Left" => Vector3(Vec3::X),
                     "Top" => Vector3(Vec3::Y),
                     "Front" => Vector3(-Vec3::Z),
@@ -114,7 +117,8 @@ impl LuaUserData for Vector3 {
         });
         methods.add_method(
             "FuzzyEq",
-            |_, this, (rhs, epsilon): (LuaUserDataRef<Vector3>, f32)| {
+            |_, this, (rhs, epsilon): (LuaUserDataRef<Vector3>, Option<f32>)| {
+                let epsilon = epsilon.unwrap_or(DEFAULT_FUZZY_EPSILON);
                 let eq_x = (rhs.0.x - this.0.x).abs() <= epsilon;
                 let eq_y = (rhs.0.y - this.0.y).abs() <= epsilon;
                 let eq_z = (rhs.0.z - this.0.z).abs() <= epsilon;
@@ -127,12 +131,24 @@ impl LuaUserData for Vector3 {
                 Ok(Vector3(this.0.lerp(rhs.0, alpha)))
             },
         );
-        methods.add_method("Max", |_, this, rhs: LuaUserDataRef<Vector3>| {
-            Ok(Vector3(this.0.max(rhs.0)))
-        });
-        methods.add_method("Min", |_, this, rhs: LuaUserDataRef<Vector3>| {
-            Ok(Vector3(this.0.min(rhs.0)))
-        });
+        methods.add_method(
+            "Max",
+            |_, this, others: LuaVariadic<LuaUserDataRef<Vector3>>| {
+                if others.is_empty() {
+                    return Err(LuaError::runtime("Expected at least one Vector3"));
+                }
+                Ok(Vector3(others.iter().fold(this.0, |acc, v| acc.max(v.0))))
+            },
+        );
+        methods.add_method(
+            "Min",
+            |_, this, others: LuaVariadic<LuaUserDataRef<Vector3>>| {
+                if others.is_empty() {
+                    return Err(LuaError::runtime("Expected at least one Vector3"));
+                }
+                Ok(Vector3(others.iter().fold(this.0, |acc, v| acc.min(v.0))))
+            },
+        );
         methods.add_method("Abs", |_, this, ()| Ok(Vector3(this.0.abs())));
         methods.add_method("Ceil", |_, this, ()| Ok(Vector3(this.0.ceil())));
         methods.add_method("Floor", |_, this, ()| Ok(Vector3(this.0.floor())));
@@ -143,7 +159,7 @@ impl LuaUserData for Vector3 {
         methods.add_meta_method(LuaMetaMethod::Unm, userdata_impl_unm);
         methods.add_meta_method(LuaMetaMethod::Add, userdata_impl_add);
         methods.add_meta_method(LuaMetaMethod::Sub, userdata_impl_sub);
-        methods.add_meta_method(LuaMetaMethod::Mul, userdata_impl_mul_f32);
+        methods.add_meta_function(LuaMetaMethod::Mul, userdata_impl_mul_f32::<Self>);
         methods.add_meta_method(LuaMetaMethod::Div, userdata_impl_div_f32);
         methods.add_meta_method(LuaMetaMethod::IDiv, userdata_impl_idiv_f32);
     }
@@ -1,8 +1,13 @@
+use mlua::prelude::*;
+
 // HACK: We round to the nearest Very Small Decimal
 // to reduce writing out floating point accumulation
 // errors to files (mostly relevant for xml formats)
 const ROUNDING: usize = 65_536; // 2 ^ 16
 
+// The default epsilon used by FuzzyEq methods when none is given, same as in Roblox
+pub const DEFAULT_FUZZY_EPSILON: f32 = 1e-5;
+
 pub fn round_float_decimal(value: f32) -> f32 {
     let place = ROUNDING as f32;
 
@@ -14,3 +19,40 @@ pub fn round_float_decimal(value: f32) -> f32 {
 
     whole + fract
 }
+
+// Roblox limits the amount of keypoints that a sequence may contain
+const MAX_SEQUENCE_KEYPOINTS: usize = 20;
+
+/**
+    Validates the times of keypoints given to a sequence constructor, same as in Roblox:
+
+    - There must be at least 2 and at most 20 keypoints
+    - Keypoints must be ordered by time, ascending
+    - The first keypoint must be at time 0, and the last at time 1
+*/
+pub fn validate_sequence_keypoint_times(sequence_name: &str, times: &[f32]) -> LuaResult<()> {
+    if times.len() < 2 {
+        return Err(LuaError::runtime(format!(
+            "{sequence_name}: requires at least 2 keypoints"
+        )));
+    }
+    if times.len() > MAX_SEQUENCE_KEYPOINTS {
+        return Err(LuaError::runtime(format!(
+            "{sequence_name}: requires at most {MAX_SEQUENCE_KEYPOINTS} keypoints, got {}",
+            times.len()
+        )));
+    }
+    if !times.windows(2).all(|pair| pair[0] <= pair[1]) {
+        return Err(LuaError::runtime(format!(
+            "{sequence_name}: all keypoints must be ordered by time"
+        )));
+    }
+    #[allow(clippy::float_cmp)]
+    let has_bounds = times.first() == Some(&0.0) && times.last() == Some(&1.0);
+    if !has_bounds {
+        return Err(LuaError::runtime(format!(
+            "{sequence_name} must start at time 0 and end at time 1"
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,304 @@
+use std::io::{self, Write};
+
+use rayon::prelude::*;
+
+const HEADER_LEN: usize = 32;
+const CHUNK_HEADER_LEN: usize = 16;
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xB5, 0x2F, 0xFD];
+const END_CHUNK_NAME: &[u8] = b"END\0";
+
+// NOTE: Decompressing in parallel is not worth the extra
+// copy of the file for smaller files, which are most files
+const MIN_PARALLEL_LEN: usize = 1024 * 1024;
+
+struct Chunk<'a> {
+    header: &'a [u8],
+    data: &'a [u8],
+    is_compressed: bool,
+    uncompressed_len: u32,
+}
+
+impl Chunk<'_> {
+    fn name(&self) -> &[u8] {
+        &self.header[0..4]
+    }
+
+    fn is_lz4(&self) -> bool {
+        // NOTE: We only decompress lz4 chunks here, which is what roblox uses
+        // in practice, zstd chunks are kept and decompressed by rbx_binary
+        self.is_compressed && !self.data.starts_with(ZSTD_MAGIC)
+    }
+
+    fn write_header(&self, out: &mut [u8], compressed_len: u32, uncompressed_len: u32) {
+        out[0..4].copy_from_slice(self.name());
+        out[4..8].copy_from_slice(&compressed_len.to_le_bytes());
+        out[8..12].copy_from_slice(&uncompressed_len.to_le_bytes());
+        out[12..16].copy_from_slice(&self.header[12..16]);
+    }
+}
+
+/**
+    Decompresses all compressed chunks of a roblox binary file in parallel,
+    returning a new binary file where every chunk is stored uncompressed.
+
+    Decompressing chunks is a large part of the time spent reading a binary
+    file, and [`rbx_binary`] does it on a single thread, one chunk at a time,
+    so doing it here up front lets us use more than one thread for it.
+
+    Chunks are decompressed directly into the returned file, meaning
+    no other copies of the decompressed chunks are made along the way.
+
+    Returns `None` if the file is too small for this to be worth it, or if the file
+    could not be read as a binary file, in which case the original bytes should
+    be given to [`rbx_binary`] instead, which will also produce a proper error.
+*/
+pub(super) fn decompress_chunks_parallel(bytes: &[u8]) -> Option<Vec<u8>> {
+    if bytes.len() < MIN_PARALLEL_LEN {
+        return None;
+    }
+
+    let (chunks, trailing) = read_chunks(bytes)?;
+    let total_len = chunks
+        .iter()
+        .fold(HEADER_LEN + trailing.len(), |len, chunk| {
+            let data_len = if chunk.is_lz4() {
+                chunk.uncompressed_len as usize
+            } else {
+                chunk.data.len()
+            };
+            len + CHUNK_HEADER_LEN + data_len
+        });
+
+    // Lay out all of the chunk headers first, splitting the output
+    // into one slice per chunk that can then be filled in parallel
+    let mut output = vec![0; total_len];
+    let (header, mut rest) = output.split_at_mut(HEADER_LEN);
+    header.copy_from_slice(&bytes[..HEADER_LEN]);
+    let mut slices = Vec::with_capacity(chunks.len());
+    for chunk in &chunks {
+        let (header, tail) = rest.split_at_mut(CHUNK_HEADER_LEN);
+        let data_len = if chunk.is_lz4() {
+            chunk.write_header(header, 0, chunk.uncompressed_len);
+            chunk.uncompressed_len as usize
+        } else {
+            header.copy_from_slice(chunk.header);
+            chunk.data.len()
+        };
+        let (data, tail) = tail.split_at_mut(data_len);
+        slices.push((chunk, data));
+        rest = tail;
+    }
+    rest.copy_from_slice(trailing);
+
+    slices.into_par_iter().try_for_each(|(chunk, out)| {
+        if chunk.is_lz4() {
+            let len = i32::try_from(chunk.uncompressed_len).ok()?;
+            let written = lz4::block::decompress_to_buffer(chunk.data, Some(len), out).ok()?;
+            (written == out.len()).then_some(())
+        } else {
+            out.copy_from_slice(chunk.data);
+            Some(())
+        }
+    })?;
+
+    Some(output)
+}
+
+/**
+    Compresses all uncompressed chunks of a roblox binary file in parallel
+    using lz4, the same as [`rbx_binary`] does, and writes the result.
+
+    This is the counterpart to [`decompress_chunks_parallel`], and expects
+    a binary file that was serialized without any compression. The given
+    file is dropped as soon as all of its chunks have been compressed, so
+    that it is never kept in memory while the result is being written.
+
+    # Errors
+
+    Errors if the given bytes are not a valid binary file, or if writing fails.
+*/
+pub(super) fn compress_chunks_parallel(bytes: Vec<u8>, mut writer: impl Write) -> io::Result<()> {
+    let Some((chunks, trailing)) = read_chunks(&bytes) else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "serialized binary file has malformed chunks",
+        ));
+    };
+
+    let compress = |chunk: &Chunk| -> io::Result<(Vec<u8>, Vec<u8>)> {
+        let mut header = vec![0; CHUNK_HEADER_LEN];
+        // NOTE: The end chunk must always be stored uncompressed
+        if chunk.is_compressed || chunk.data.is_empty() || chunk.name() == END_CHUNK_NAME {
+            header.copy_from_slice(chunk.header);
+            return Ok((header, chunk.data.to_vec()));
+        }
+        let compressed = lz4::block::compress(chunk.data, None, false)?;
+        let compressed_len = u32::try_from(compressed.len())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        chunk.write_header(&mut header, compressed_len, chunk.uncompressed_len);
+        Ok((header, compressed))
+    };
+
+    let compressed = if bytes.len() < MIN_PARALLEL_LEN {
+        chunks
+            .iter()
+            .map(compress)
+            .collect::<io::Result<Vec<_>>>()?
+    } else {
+        chunks
+            .par_iter()
+            .map(compress)
+            .collect::<io::Result<Vec<_>>>()?
+    };
+
+    let header = bytes[..HEADER_LEN].to_vec();
+    let trailing = trailing.to_vec();
+    drop(chunks);
+    drop(bytes);
+
+    writer.write_all(&header)?;
+    for (chunk_header, data) in compressed {
+        writer.write_all(&chunk_header)?;
+        writer.write_all(&data)?;
+    }
+    writer.write_all(&trailing)?;
+
+    Ok(())
+}
+
+/**
+    Reads the headers of all chunks in a binary file, up to and including the end
+    chunk, along with any trailing bytes that come after the end chunk.
+
+    Returns `None` if the file is truncated or the chunk headers are malformed.
+*/
+fn read_chunks(bytes: &[u8]) -> Option<(Vec<Chunk<'_>>, &[u8])> {
+    let mut chunks = Vec::new();
+    let mut offset = HEADER_LEN;
+    loop {
+        let header = bytes.get(offset..offset + CHUNK_HEADER_LEN)?;
+        let compressed_len = u32::from_le_bytes(header[4..8].try_into().ok()?) as usize;
+        let uncompressed_len = u32::from_le_bytes(header[8..12].try_into().ok()?);
+        offset += CHUNK_HEADER_LEN;
+
+        // NOTE: Chunks that are not compressed have a compressed length
+        // of zero, and their data is stored directly after the header
+        let is_compressed = compressed_len != 0;
+        let data_len = if is_compressed {
+            compressed_len
+        } else {
+            uncompressed_len as usize
+        };
+
+        let chunk = Chunk {
+            header,
+            data: bytes.get(offset..offset + data_len)?,
+            is_compressed,
+            uncompressed_len,
+        };
+        offset += data_len;
+
+        let is_end = chunk.name() == END_CHUNK_NAME;
+        chunks.push(chunk);
+        if is_end {
+            return Some((chunks, &bytes[offset..]));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(name: [u8; 4], compressed: bool, data: &[u8]) -> Vec<u8> {
+        // NOTE: Empty chunks are always stored uncompressed, same as in rbx_binary
+        let compressed = compressed && !data.is_empty();
+        let stored = if compressed {
+            lz4::block::compress(data, None, false).unwrap()
+        } else {
+            data.to_vec()
+        };
+        let compressed_len = if compressed { stored.len() } else { 0 };
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&name);
+        bytes.extend_from_slice(&u32::try_from(compressed_len).unwrap().to_le_bytes());
+        bytes.extend_from_slice(&u32::try_from(data.len()).unwrap().to_le_bytes());
+        bytes.extend_from_slice(&[0; 4]);
+        bytes.extend_from_slice(&stored);
+        bytes
+    }
+
+    fn file(compressed: bool, instances: &[u8], properties: &[u8]) -> Vec<u8> {
+        let mut bytes = b"<roblox!\x89\xff\x0d\x0a\x1a\x0a".to_vec();
+        bytes.resize(HEADER_LEN, 0);
+        bytes.extend(chunk(*b"INST", compressed, instances));
+        bytes.extend(chunk(*b"PROP", compressed, properties));
+        bytes.extend(chunk(*b"PRNT", compressed, &[]));
+        bytes.extend(chunk(*b"END\0", false, b"</roblox>"));
+        bytes
+    }
+
+    fn large_data(len: usize) -> Vec<u8> {
+        // NOTE: Half of the data is pseudo-random and does not compress, so that the
+        // compressed file is still above the size limit, and the other half repeats
+        let mut state = 0x2545_f491_u32;
+        let mut data = Vec::with_capacity(len);
+        for _ in 0..len / 2 {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            data.push(state.to_le_bytes()[0]);
+        }
+        data.extend(b"properties".iter().cycle().take(len - data.len()));
+        data
+    }
+
+    #[test]
+    fn small_files_are_not_decompressed() {
+        let bytes = file(true, b"instances", b"properties");
+        assert!(decompress_chunks_parallel(&bytes).is_none());
+    }
+
+    #[test]
+    fn large_files_are_decompressed() {
+        let instances = b"instances".repeat(1000);
+        let properties = large_data(4 * MIN_PARALLEL_LEN);
+        let compressed = file(true, &instances, &properties);
+        assert!(compressed.len() > MIN_PARALLEL_LEN);
+
+        let decompressed = decompress_chunks_parallel(&compressed).unwrap();
+        assert_eq!(decompressed, file(false, &instances, &properties));
+    }
+
+    #[test]
+    fn malformed_files_are_not_decompressed() {
+        let properties = large_data(4 * MIN_PARALLEL_LEN);
+        let mut compressed = file(true, b"instances", &properties);
+        compressed.truncate(compressed.len() - 20);
+        assert!(decompress_chunks_parallel(&compressed).is_none());
+    }
+
+    #[test]
+    fn large_files_roundtrip() {
+        let instances = b"instances".repeat(1000);
+        let properties = large_data(4 * MIN_PARALLEL_LEN);
+        let uncompressed = file(false, &instances, &properties);
+
+        let mut compressed = Vec::new();
+        compress_chunks_parallel(uncompressed.clone(), &mut compressed).unwrap();
+        assert!(compressed.len() > MIN_PARALLEL_LEN);
+        assert!(compressed.len() < uncompressed.len());
+        assert_eq!(compressed, file(true, &instances, &properties));
+
+        let decompressed = decompress_chunks_parallel(&compressed).unwrap();
+        assert_eq!(decompressed, uncompressed);
+    }
+
+    #[test]
+    fn small_files_are_compressed() {
+        let uncompressed = file(false, b"instances", b"properties");
+        let mut compressed = Vec::new();
+        compress_chunks_parallel(uncompressed, &mut compressed).unwrap();
+        assert_eq!(compressed, file(true, b"instances", b"properties"));
+    }
+}
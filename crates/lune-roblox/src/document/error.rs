@@ -13,7 +13,7 @@ pub enum DocumentError {
     WriteError(String),
     #[error("Failed to convert into a DataModel - the given document is not a place")]
     IntoDataModelInvalidArgs,
-    #[error("Failed to convert into array of Instances - the given document is a model")]
+    #[error("Failed to convert into array of Instances - the given document is not a model")]
     IntoInstanceArrayInvalidArgs,
     #[error("Failed to convert into a place - the given instance is not a DataModel")]
     FromDataModelInvalidArgs,
@@ -1,9 +1,12 @@
+use std::io::Write;
+
 use rbx_dom_weak::{types::Ref as DomRef, InstanceBuilder as DomInstanceBuilder, WeakDom};
 use rbx_xml::{
     DecodeOptions as XmlDecodeOptions, DecodePropertyBehavior as XmlDecodePropertyBehavior,
     EncodeOptions as XmlEncodeOptions, EncodePropertyBehavior as XmlEncodePropertyBehavior,
 };
 
+mod chunks;
 mod error;
 mod format;
 mod kind;
@@ -13,6 +16,7 @@ pub use error::*;
 pub use format::*;
 pub use kind::*;
 
+use chunks::{compress_chunks_parallel, decompress_chunks_parallel};
 use postprocessing::*;
 
 use crate::instance::{data_model, Instance};
@@ -93,8 +97,11 @@ impl Document {
         let bytes = bytes.as_ref();
         let format = DocumentFormat::from_bytes(bytes).ok_or(DocumentError::UnknownFormat)?;
         let dom = match format {
-            DocumentFormat::Binary => rbx_binary::from_reader(bytes)
-                .map_err(|err| DocumentError::ReadError(err.to_string())),
+            DocumentFormat::Binary => {
+                let decompressed = decompress_chunks_parallel(bytes);
+                rbx_binary::from_reader(decompressed.as_deref().unwrap_or(bytes))
+                    .map_err(|err| DocumentError::ReadError(err.to_string()))
+            }
             DocumentFormat::Xml => {
                 let xml_options = XmlDecodeOptions::new()
                     .property_behavior(XmlDecodePropertyBehavior::ReadUnknown);
@@ -166,24 +173,45 @@ impl Document {
     */
     pub fn to_bytes_with_format(&self, format: DocumentFormat) -> DocumentResult<Vec<u8>> {
         let mut bytes = Vec::new();
+        self.to_writer_with_format(&mut bytes, format)?;
+        Ok(bytes)
+    }
+
+    /**
+        Encodes the document directly into the given writer, such as a file.
+
+        Unlike [`Document::to_bytes_with_format`], this does not need to hold
+        the entire encoded document in memory at once, which is preferred for
+        large documents that are going to be written to a file anyway.
+
+        # Errors
+
+        Errors if the document can not be encoded, or if writing to the writer fails.
+    */
+    pub fn to_writer_with_format(
+        &self,
+        writer: impl Write,
+        format: DocumentFormat,
+    ) -> DocumentResult<()> {
         match format {
             DocumentFormat::Binary => {
-                rbx_binary::to_writer(&mut bytes, &self.dom, self.dom.root().children())
+                // NOTE: Chunks are compressed separately and in parallel
+                // below, rbx_binary would compress them one at a time
+                let mut uncompressed = Vec::new();
+                rbx_binary::Serializer::new()
+                    .compression_type(rbx_binary::CompressionType::None)
+                    .serialize(&mut uncompressed, &self.dom, self.dom.root().children())
+                    .map_err(|err| DocumentError::WriteError(err.to_string()))?;
+                compress_chunks_parallel(uncompressed, writer)
                     .map_err(|err| DocumentError::WriteError(err.to_string()))
             }
             DocumentFormat::Xml => {
                 let xml_options = XmlEncodeOptions::new()
                     .property_behavior(XmlEncodePropertyBehavior::WriteUnknown);
-                rbx_xml::to_writer(
-                    &mut bytes,
-                    &self.dom,
-                    self.dom.root().children(),
-                    xml_options,
-                )
-                .map_err(|err| DocumentError::WriteError(err.to_string()))
+                rbx_xml::to_writer(writer, &self.dom, self.dom.root().children(), xml_options)
+                    .map_err(|err| DocumentError::WriteError(err.to_string()))
             }
-        }?;
-        Ok(bytes)
+        }
     }
 
     /**
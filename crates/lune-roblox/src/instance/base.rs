@@ -2,6 +2,8 @@
 
 use mlua::prelude::*;
 
+use lune_utils::catch_panic;
+
 use rbx_dom_weak::{
     types::{Variant as DomValue, VariantType as DomType},
     Instance as DomInstance,
@@ -17,7 +19,7 @@ use crate::{
     shared::instance::{class_is_a, find_property_info},
 };
 
-use super::{data_model, registry::InstanceRegistry, Instance};
+use super::{data_model, registry::InstanceRegistry, Instance, PROPERTY_NAME_TAGS};
 
 #[allow(clippy::too_many_lines)]
 pub fn add_methods<'lua, M: LuaUserDataMethods<'lua, Instance>>(m: &mut M) {
@@ -26,8 +28,16 @@ pub fn add_methods<'lua, M: LuaUserDataMethods<'lua, Instance>>(m: &mut M) {
         userdata_impl_to_string(lua, this, ())
     });
     m.add_meta_method(LuaMetaMethod::Eq, userdata_impl_eq);
-    m.add_meta_method(LuaMetaMethod::Index, instance_property_get);
-    m.add_meta_method_mut(LuaMetaMethod::NewIndex, instance_property_set);
+    m.add_meta_method(LuaMetaMethod::Index, |lua, this, prop_name| {
+        catch_panic("Instance.__index", || {
+            instance_property_get(lua, this, prop_name)
+        })
+    });
+    m.add_meta_method_mut(LuaMetaMethod::NewIndex, |lua, this, args| {
+        catch_panic("Instance.__newindex", || {
+            instance_property_set(lua, this, args)
+        })
+    });
     m.add_method("Clone", |lua, this, ()| {
         ensure_not_destroyed(this)?;
         this.clone_instance().into_lua(lua)
@@ -40,9 +50,16 @@ pub fn add_methods<'lua, M: LuaUserDataMethods<'lua, Instance>>(m: &mut M) {
         this.clear_all_children();
         Ok(())
     });
-    m.add_method("GetChildren", |lua, this, ()| {
+    m.add_method("GetChildren", |lua, this, sort_by: Option<ChildSortKey>| {
         ensure_not_destroyed(this)?;
-        this.get_children().into_lua(lua)
+        match sort_by {
+            None => this.get_children(),
+            Some(ChildSortKey::Name) => this.get_children_sorted_by(|child| child.name.clone()),
+            Some(ChildSortKey::ClassName) => {
+                this.get_children_sorted_by(|child| child.class.clone())
+            }
+        }
+        .into_lua(lua)
     });
     m.add_method("GetDescendants", |lua, this, ()| {
         ensure_not_destroyed(this)?;
@@ -112,6 +129,29 @@ pub fn add_methods<'lua, M: LuaUserDataMethods<'lua, Instance>>(m: &mut M) {
             }
         },
     );
+    m.add_method("FindFirstDescendant", |lua, this, name: String| {
+        ensure_not_destroyed(this)?;
+        this.find_descendant(|child| child.name == name)
+            .into_lua(lua)
+    });
+    m.add_method("QueryDescendants", |lua, this, query: DescendantQuery| {
+        ensure_not_destroyed(this)?;
+        let found = this.find_descendants(|child| query.matches(child));
+        // NOTE: Name patterns are matched using Lua after searching, since
+        // matching may run arbitrary Lua code that needs to access the dom
+        let Some(pattern) = &query.name_pattern else {
+            return found.into_lua(lua);
+        };
+        let string_find: LuaFunction = lua.globals().get::<_, LuaTable>("string")?.get("find")?;
+        let mut matching = Vec::with_capacity(found.len());
+        for inst in found {
+            let result: LuaValue = string_find.call((inst.get_name(), pattern.as_str()))?;
+            if !result.is_nil() {
+                matching.push(inst);
+            }
+        }
+        matching.into_lua(lua)
+    });
     m.add_method("IsA", |_, this, class_name: String| {
         ensure_not_destroyed(this)?;
         Ok(class_is_a(&this.class_name, class_name).unwrap_or(false))
@@ -190,7 +230,96 @@ pub fn add_methods<'lua, M: LuaUserDataMethods<'lua, Instance>>(m: &mut M) {
     });
 }
 
-fn ensure_not_destroyed(inst: &Instance) -> LuaResult<()> {
+/**
+    A key to sort children by, used by `GetChildren`.
+*/
+#[derive(Debug, Clone, Copy)]
+enum ChildSortKey {
+    Name,
+    ClassName,
+}
+
+impl<'lua> FromLua<'lua> for ChildSortKey {
+    fn from_lua(value: LuaValue<'lua>, _: &'lua Lua) -> LuaResult<Self> {
+        if let LuaValue::String(s) = &value {
+            match s.to_str()? {
+                "Name" => Ok(Self::Name),
+                "ClassName" => Ok(Self::ClassName),
+                other => Err(LuaError::RuntimeError(format!(
+                    "Invalid sort key '{other}' - expected 'Name' or 'ClassName'"
+                ))),
+            }
+        } else {
+            Err(LuaError::FromLuaConversionError {
+                from: value.type_name(),
+                to: "ChildSortKey",
+                message: Some(format!(
+                    "Invalid sort key - expected string, got {}",
+                    value.type_name()
+                )),
+            })
+        }
+    }
+}
+
+/**
+    A query for descendants of an instance, used by `QueryDescendants`.
+
+    Descendants must match all of the filters that were given to be included.
+*/
+struct DescendantQuery {
+    class_name: Option<String>,
+    name: Option<String>,
+    name_pattern: Option<String>,
+    tag: Option<String>,
+}
+
+impl DescendantQuery {
+    fn matches(&self, inst: &DomInstance) -> bool {
+        if let Some(class_name) = &self.class_name {
+            if !class_is_a(&inst.class, class_name).unwrap_or(false) {
+                return false;
+            }
+        }
+        if let Some(name) = &self.name {
+            if &inst.name != name {
+                return false;
+            }
+        }
+        if let Some(tag) = &self.tag {
+            match inst.properties.get(PROPERTY_NAME_TAGS) {
+                Some(DomValue::Tags(tags)) => tags.iter().any(|t| t == tag.as_str()),
+                _ => false,
+            }
+        } else {
+            true
+        }
+    }
+}
+
+impl<'lua> FromLua<'lua> for DescendantQuery {
+    fn from_lua(value: LuaValue<'lua>, _: &'lua Lua) -> LuaResult<Self> {
+        if let LuaValue::Table(t) = value {
+            Ok(Self {
+                class_name: t.get("className")?,
+                name: t.get("name")?,
+                name_pattern: t.get("namePattern")?,
+                tag: t.get("tag")?,
+            })
+        } else {
+            Err(LuaError::FromLuaConversionError {
+                from: value.type_name(),
+                to: "DescendantQuery",
+                message: Some(format!(
+                    "Invalid descendant query - expected table, got {}",
+                    value.type_name()
+                )),
+            })
+        }
+    }
+}
+
+pub(crate) fn ensure_not_destroyed(inst: &Instance) -> LuaResult<()> {
     if inst.is_destroyed() {
         Err(LuaError::RuntimeError(
             "Instance has been destroyed".to_string(),
@@ -321,6 +450,21 @@ fn instance_property_set<'lua>(
             }
             type Parent<'lua> = Option<LuaUserDataRef<'lua, Instance>>;
             let parent = Parent::from_lua(prop_value, lua)?;
+            if let Some(parent) = &parent {
+                // NOTE: An instance may never be parented to itself or to any of its
+                // descendants, since that would detach it and its new parent from the tree
+                let is_circular = parent.dom_ref == this.dom_ref
+                    || parent
+                        .find_ancestor(|ancestor| ancestor.referent() == this.dom_ref)
+                        .is_some();
+                if is_circular {
+                    return Err(LuaError::RuntimeError(format!(
+                        "Failed to set Parent - setting the parent of '{}' to '{}' would result in a circular reference",
+                        this.get_name(),
+                        parent.get_name()
+                    )));
+                }
+            }
             this.set_parent(parent.map(|p| p.clone()));
             return Ok(());
         }
@@ -329,17 +473,38 @@ fn instance_property_set<'lua>(
 
     if let Some(info) = find_property_info(&this.class_name, &prop_name) {
         if let Some(enum_name) = info.enum_name {
-            match LuaUserDataRef::<EnumItem>::from_lua(prop_value, lua) {
-                Ok(given_enum) if given_enum.parent.desc.name == enum_name => {
-                    this.set_property(prop_name, DomValue::Enum((*given_enum).clone().into()));
-                    Ok(())
+            // NOTE: Roblox also allows setting enum properties using
+            // the name or the numeric value of an enum item directly
+            let given_enum = match &prop_value {
+                LuaValue::String(s) => {
+                    let item_name = s.to_str()?;
+                    EnumItem::from_enum_name_and_name(&enum_name, item_name).ok_or_else(|| {
+                        LuaError::RuntimeError(format!(
+                            "Failed to set property '{prop_name}' - '{item_name}' is not a valid member of Enum.{enum_name}"
+                        ))
+                    })?
                 }
-                Ok(given_enum) => Err(LuaError::RuntimeError(format!(
-                    "Failed to set property '{}' - expected Enum.{}, got Enum.{}",
-                    prop_name, enum_name, given_enum.parent.desc.name
-                ))),
-                Err(e) => Err(e),
-            }
+                LuaValue::Integer(_) | LuaValue::Number(_) => {
+                    let item_value = u32::from_lua(prop_value.clone(), lua)?;
+                    EnumItem::from_enum_name_and_value(&enum_name, item_value).ok_or_else(|| {
+                        LuaError::RuntimeError(format!(
+                            "Failed to set property '{prop_name}' - Enum.{enum_name} does not contain numeric value {item_value}"
+                        ))
+                    })?
+                }
+                _ => {
+                    let given_enum = LuaUserDataRef::<EnumItem>::from_lua(prop_value.clone(), lua)?;
+                    if given_enum.parent.desc.name != enum_name {
+                        return Err(LuaError::RuntimeError(format!(
+                            "Failed to set property '{}' - expected Enum.{}, got Enum.{}",
+                            prop_name, enum_name, given_enum.parent.desc.name
+                        )));
+                    }
+                    (*given_enum).clone()
+                }
+            };
+            this.set_property(prop_name, DomValue::Enum(given_enum.into()));
+            Ok(())
         } else if let Some(dom_type) = info.value_type {
             match prop_value.lua_to_dom_value(lua, Some(dom_type)) {
                 Ok(dom_value) => {
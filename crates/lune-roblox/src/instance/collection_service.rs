@@ -0,0 +1,67 @@
+use std::collections::BTreeSet;
+
+use mlua::prelude::*;
+use rbx_dom_weak::types::Variant as DomValue;
+
+use crate::shared::classes::add_class_restricted_method;
+
+use super::{data_model, Instance, PROPERTY_NAME_TAGS};
+
+pub const CLASS_NAME: &str = "CollectionService";
+
+pub fn add_methods<'lua, M: LuaUserDataMethods<'lua, Instance>>(m: &mut M) {
+    add_class_restricted_method(m, CLASS_NAME, "GetTagged", collection_service_get_tagged);
+    add_class_restricted_method(m, CLASS_NAME, "GetAllTags", collection_service_get_all_tags);
+}
+
+/**
+    Gets the `DataModel` that this `CollectionService` belongs to.
+
+    Tags are only ever queried within this `DataModel`, the same as in Roblox.
+*/
+fn get_data_model(this: &Instance) -> LuaResult<Instance> {
+    this.get_parent()
+        .filter(|parent| parent.get_class_name() == data_model::CLASS_NAME)
+        .ok_or_else(|| {
+            LuaError::RuntimeError(
+                "CollectionService must be a child of a DataModel to query tags".to_string(),
+            )
+        })
+}
+
+/**
+    Gets all instances in the `DataModel` that have the given tag.
+
+    ### See Also
+    * [`GetTagged`](https://create.roblox.com/docs/reference/engine/classes/CollectionService#GetTagged)
+      on the Roblox Developer Hub
+*/
+fn collection_service_get_tagged(
+    _: &Lua,
+    this: &Instance,
+    tag: String,
+) -> LuaResult<Vec<Instance>> {
+    let data_model = get_data_model(this)?;
+    Ok(data_model.find_descendants(|inst| {
+        matches!(
+            inst.properties.get(PROPERTY_NAME_TAGS),
+            Some(DomValue::Tags(tags)) if tags.iter().any(|t| t == tag.as_str())
+        )
+    }))
+}
+
+/**
+    Gets all unique tags used by instances in the `DataModel`, sorted alphabetically.
+
+    ### See Also
+    * [`GetAllTags`](https://create.roblox.com/docs/reference/engine/classes/CollectionService#GetAllTags)
+      on the Roblox Developer Hub
+*/
+fn collection_service_get_all_tags(_: &Lua, this: &Instance, (): ()) -> LuaResult<Vec<String>> {
+    let data_model = get_data_model(this)?;
+    let mut all_tags = BTreeSet::new();
+    for descendant in data_model.get_descendants() {
+        all_tags.extend(descendant.get_tags());
+    }
+    Ok(all_tags.into_iter().collect())
+}
@@ -1,6 +1,7 @@
 #![allow(clippy::missing_panics_doc)]
 
 use std::{
+    borrow::Cow,
     collections::{BTreeMap, VecDeque},
     fmt,
     hash::{Hash, Hasher},
@@ -18,14 +19,17 @@ use lune_utils::TableBuilder;
 
 use crate::{
     exports::LuaExportsTable,
-    shared::instance::{class_exists, class_is_a},
+    shared::instance::{class_exists, class_is_a, intern_class_name},
 };
 
 pub(crate) mod base;
+pub(crate) mod collection_service;
 pub(crate) mod data_model;
 pub(crate) mod terrain;
 pub(crate) mod workspace;
+pub(crate) mod world_root;
 
+pub mod raw_properties;
 pub mod registry;
 
 const PROPERTY_NAME_ATTRIBUTES: &str = "Attributes";
@@ -37,7 +41,7 @@ static INTERNAL_DOM: Lazy<Mutex<WeakDom>> =
 #[derive(Debug, Clone)]
 pub struct Instance {
     pub(crate) dom_ref: DomRef,
-    pub(crate) class_name: String,
+    pub(crate) class_name: Cow<'static, str>,
 }
 
 impl Instance {
@@ -75,7 +79,7 @@ impl Instance {
 
             Some(Self {
                 dom_ref,
-                class_name: instance.class.clone(),
+                class_name: intern_class_name(&instance.class),
             })
         } else {
             None
@@ -103,7 +107,7 @@ impl Instance {
 
         Self {
             dom_ref,
-            class_name: class_name.to_string(),
+            class_name: intern_class_name(class_name),
         }
     }
 
@@ -258,7 +262,7 @@ impl Instance {
     */
     #[must_use]
     pub fn get_class_name(&self) -> &str {
-        self.class_name.as_str()
+        &self.class_name
     }
 
     /**
@@ -364,6 +368,22 @@ impl Instance {
             .insert(name.as_ref().to_string(), value);
     }
 
+    /**
+        Removes a property from the instance.
+
+        Note that this will not fail even if the property
+        does not exist or was never set on the instance.
+    */
+    pub fn remove_property(&self, name: impl AsRef<str>) {
+        INTERNAL_DOM
+            .lock()
+            .expect("Failed to lock document")
+            .get_by_ref_mut(self.dom_ref)
+            .expect("Failed to find instance in document")
+            .properties
+            .remove(name.as_ref());
+    }
+
     /**
         Gets an attribute for the instance, if it exists.
 
@@ -548,6 +568,10 @@ impl Instance {
     /**
         Gets all of the current children of this `Instance`.
 
+        Children are always returned in the same order as they are stored in the
+        weak dom, which is also the order they will be serialized in, meaning that
+        this order is stable between runs and across serialization roundtrips.
+
         Note that this is a somewhat expensive operation and that other
         operations using weak dom referents should be preferred if possible.
 
@@ -568,6 +592,38 @@ impl Instance {
         children.into_iter().map(Self::new).collect()
     }
 
+    /**
+        Gets all of the current children of this `Instance`, sorted
+        by the key that the given callback returns for each child.
+
+        Sorting is stable, so children with equal keys are
+        kept in the same order as in [`Instance::get_children`].
+    */
+    pub fn get_children_sorted_by<F>(&self, key: F) -> Vec<Instance>
+    where
+        F: Fn(&DomInstance) -> String,
+    {
+        let dom = INTERNAL_DOM.lock().expect("Failed to lock document");
+
+        let mut children = dom
+            .get_by_ref(self.dom_ref)
+            .expect("Failed to find instance in document")
+            .children()
+            .iter()
+            .filter_map(|child_ref| {
+                let child = dom.get_by_ref(*child_ref)?;
+                Some((key(child), *child_ref))
+            })
+            .collect::<Vec<_>>();
+        children.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        drop(dom); // Self::new needs mutex handle, drop it first
+        children
+            .into_iter()
+            .map(|(_, child_ref)| Self::new(child_ref))
+            .collect()
+    }
+
     /**
         Gets all of the current descendants of this `Instance` using a breadth-first search.
 
@@ -728,6 +784,40 @@ impl Instance {
 
         None
     }
+
+    /**
+        Finds all descendants of the instance that match the
+        given predicate callback, using a breadth-first search.
+
+        This is much cheaper than getting all descendants and then
+        filtering them, since only matching instances get created.
+    */
+    pub fn find_descendants<F>(&self, mut predicate: F) -> Vec<Instance>
+    where
+        F: FnMut(&DomInstance) -> bool,
+    {
+        let dom = INTERNAL_DOM.lock().expect("Failed to lock document");
+
+        let mut found = Vec::new();
+        let mut queue = VecDeque::from_iter(
+            dom.get_by_ref(self.dom_ref)
+                .expect("Failed to find instance in document")
+                .children(),
+        );
+
+        while let Some(queue_item) = queue
+            .pop_front()
+            .and_then(|queue_ref| dom.get_by_ref(*queue_ref))
+        {
+            if predicate(queue_item) {
+                found.push(queue_item.referent());
+            }
+            queue.extend(queue_item.children());
+        }
+
+        drop(dom); // Self::new needs mutex handle, drop it first
+        found.into_iter().map(Self::new).collect()
+    }
 }
 
 impl LuaExportsTable<'_> for Instance {
@@ -770,8 +860,10 @@ impl LuaUserData for Instance {
 
     fn add_methods<'lua, M: LuaUserDataMethods<'lua, Self>>(methods: &mut M) {
         base::add_methods(methods);
+        collection_service::add_methods(methods);
         data_model::add_methods(methods);
         terrain::add_methods(methods);
+        world_root::add_methods(methods);
     }
 }
 
@@ -0,0 +1,95 @@
+use mlua::prelude::*;
+
+use rbx_dom_weak::types::{Enum as DomEnum, Variant as DomValue, VariantType as DomType};
+
+use crate::{
+    datatypes::{
+        conversion::{DomValueToLua, LuaToDomValue},
+        types::EnumItem,
+    },
+    shared::instance::find_property_info,
+};
+
+use super::{base::ensure_not_destroyed, Instance};
+
+/**
+    Gets the raw value of a property on an instance, bypassing the reflection database.
+
+    This returns `nil` if the property has not been set on the
+    instance, instead of falling back to any default value, and enums
+    are returned as their numeric values instead of as enum items.
+
+    # Errors
+
+    Errors if the instance has been destroyed, or if the
+    property value can not be converted into a Lua value.
+*/
+pub fn get_raw_property<'lua>(
+    lua: &'lua Lua,
+    (instance, prop_name): (LuaUserDataRef<'lua, Instance>, String),
+) -> LuaResult<LuaValue<'lua>> {
+    ensure_not_destroyed(&instance)?;
+    match instance.get_property(&prop_name) {
+        None => Ok(LuaValue::Nil),
+        Some(DomValue::Enum(enum_value)) => enum_value.to_u32().into_lua(lua),
+        Some(prop) => LuaValue::dom_value_to_lua(lua, &prop).map_err(|e| {
+            LuaError::RuntimeError(format!("Failed to get raw property '{prop_name}' - {e}"))
+        }),
+    }
+}
+
+/**
+    Sets the raw value of a property on an instance, bypassing the reflection database.
+
+    The type of the property is kept if it has already been set on the instance,
+    otherwise the type from the reflection database is used if there is one, and
+    if not, the type is inferred from the given value. Setting a property to `nil`
+    removes it from the instance entirely.
+
+    # Errors
+
+    Errors if the instance has been destroyed, or if the
+    given value can not be converted into the property type.
+*/
+pub fn set_raw_property<'lua>(
+    lua: &'lua Lua,
+    (instance, prop_name, prop_value): (LuaUserDataRef<'lua, Instance>, String, LuaValue<'lua>),
+) -> LuaResult<()> {
+    ensure_not_destroyed(&instance)?;
+
+    if prop_value.is_nil() {
+        instance.remove_property(&prop_name);
+        return Ok(());
+    }
+
+    let prop_type = instance
+        .get_property(&prop_name)
+        .map(|prop| prop.ty())
+        .or_else(|| {
+            find_property_info(&instance.class_name, &prop_name).and_then(|info| {
+                if info.enum_name.is_some() {
+                    Some(DomType::Enum)
+                } else {
+                    info.value_type
+                }
+            })
+        });
+
+    let dom_value = match (prop_type, &prop_value) {
+        (Some(DomType::Enum), LuaValue::Integer(_) | LuaValue::Number(_)) => {
+            let enum_value = u32::from_lua(prop_value.clone(), lua)?;
+            Ok(DomValue::Enum(DomEnum::from_u32(enum_value)))
+        }
+        (Some(DomType::Enum), LuaValue::UserData(u)) => {
+            let given_enum = u.borrow::<EnumItem>()?;
+            Ok(DomValue::Enum((*given_enum).clone().into()))
+        }
+        _ => prop_value.lua_to_dom_value(lua, prop_type),
+    }
+    .map_err(|e| {
+        LuaError::RuntimeError(format!("Failed to set raw property '{prop_name}' - {e}"))
+    })?;
+
+    instance.set_property(prop_name, dom_value);
+    Ok(())
+}
@@ -0,0 +1,272 @@
+use std::collections::HashSet;
+
+use glam::{Vec3, Vec3Swizzles};
+use mlua::prelude::*;
+use rbx_dom_weak::types::{Enum as DomEnum, Ref as DomRef, Variant as DomValue};
+
+use crate::{
+    datatypes::types::{CFrame, EnumItem, RaycastParams, RaycastResult, Vector3},
+    shared::{
+        classes::add_class_restricted_method,
+        instance::{class_is_a, find_property_info},
+    },
+};
+
+use super::Instance;
+
+pub const CLASS_NAME: &str = "WorldRoot";
+
+// NOTE: Directions shorter than this are treated as parallel to an axis,
+// and rays are never considered to be hitting anything along that axis
+const EPSILON: f32 = 1e-6;
+
+pub fn add_methods<'lua, M: LuaUserDataMethods<'lua, Instance>>(methods: &mut M) {
+    add_class_restricted_method(methods, CLASS_NAME, "Raycast", world_root_raycast);
+}
+
+/**
+    Casts a ray against all parts that are descendants of this world root,
+    returning the closest hit, or `None` if the ray did not hit anything.
+
+    Parts are treated as boxes, with the exception of balls and cylinders,
+    meaning that wedges and meshes are hit using their bounding boxes.
+    Terrain is never hit, and rays starting inside of a part never hit that part.
+
+    ### See Also
+    * [`Raycast`](https://create.roblox.com/docs/reference/engine/classes/WorldRoot#Raycast)
+      on the Roblox Developer Hub
+*/
+fn world_root_raycast(
+    _: &Lua,
+    this: &Instance,
+    (origin, direction, params): (
+        LuaUserDataRef<Vector3>,
+        LuaUserDataRef<Vector3>,
+        Option<LuaUserDataRef<RaycastParams>>,
+    ),
+) -> LuaResult<Option<RaycastResult>> {
+    let params = params.map(|p| (*p).clone()).unwrap_or_default();
+    let filter = params
+        .filter_instances
+        .iter()
+        .map(|inst| inst.dom_ref)
+        .collect::<HashSet<_>>();
+
+    let parts = this.find_descendants(|inst| {
+        inst.class != "Terrain" && class_is_a(&inst.class, "BasePart").unwrap_or(false)
+    });
+
+    let mut closest: Option<(f32, Vec3, Instance)> = None;
+    for part in parts {
+        if is_filtered(&part, &filter) != params.is_include_filter() {
+            continue;
+        }
+        if !get_bool(&part, "CanQuery")
+            || (params.respect_can_collide && !get_bool(&part, "CanCollide"))
+        {
+            continue;
+        }
+        if let Some((t, normal)) = raycast_part(&part, origin.0, direction.0) {
+            if !closest
+                .as_ref()
+                .is_some_and(|(closest_t, _, _)| *closest_t <= t)
+            {
+                closest = Some((t, normal, part));
+            }
+        }
+    }
+
+    Ok(closest.map(|(t, normal, part)| {
+        let material = get_property_or_default(&part, "Material")
+            .and_then(|value| match value {
+                DomValue::Enum(e) => EnumItem::from_enum_name_and_value("Material", e.to_u32()),
+                _ => None,
+            })
+            .or_else(|| EnumItem::from_enum_name_and_name("Material", "Plastic"))
+            .expect("Material.Plastic exists");
+        RaycastResult {
+            instance: part,
+            position: Vector3(origin.0 + direction.0 * t),
+            normal: Vector3(normal),
+            material,
+            distance: direction.0.length() * t,
+        }
+    }))
+}
+
+/**
+    Checks if the given part, or any of its ancestors, is in the given set of filtered instances.
+*/
+fn is_filtered(part: &Instance, filter: &HashSet<DomRef>) -> bool {
+    filter.contains(&part.dom_ref)
+        || part
+            .find_ancestor(|ancestor| filter.contains(&ancestor.referent()))
+            .is_some()
+}
+
+fn get_property_or_default(part: &Instance, name: &str) -> Option<DomValue> {
+    part.get_property(name).or_else(|| {
+        let info = find_property_info(part.get_class_name(), name)?;
+        info.value_default.cloned().or_else(|| {
+            info.enum_default
+                .map(|value| DomValue::Enum(DomEnum::from_u32(value)))
+        })
+    })
+}
+
+fn get_bool(part: &Instance, name: &str) -> bool {
+    match get_property_or_default(part, name) {
+        Some(DomValue::Bool(value)) => value,
+        _ => true,
+    }
+}
+
+/**
+    Casts a ray against a single part, returning the fraction of the direction
+    at which the part was hit, as well as the surface normal in world space.
+*/
+fn raycast_part(part: &Instance, origin: Vec3, direction: Vec3) -> Option<(f32, Vec3)> {
+    let cframe = match get_property_or_default(part, "CFrame")? {
+        DomValue::CFrame(cframe) => CFrame::from(cframe),
+        _ => return None,
+    };
+    let size = match get_property_or_default(part, "Size")? {
+        DomValue::Vector3(size) => Vector3::from(size).0,
+        _ => return None,
+    };
+    let shape = if part.get_class_name() == "Part" {
+        match get_property_or_default(part, "Shape") {
+            Some(DomValue::Enum(shape)) => {
+                EnumItem::from_enum_name_and_value("PartType", shape.to_u32()).map(|item| item.name)
+            }
+            _ => None,
+        }
+    } else {
+        None
+    };
+
+    // Cast the ray in the local space of the part, which
+    // lets us treat every shape as being axis-aligned
+    let inverse = cframe.0.inverse();
+    let local_origin = inverse.transform_point3(origin);
+    let local_direction = inverse.transform_vector3(direction);
+    let half_size = size / 2.0;
+
+    let (t, local_normal) = match shape {
+        Some("Ball") => raycast_ball(local_origin, local_direction, half_size.min_element()),
+        Some("Cylinder") => raycast_cylinder(local_origin, local_direction, half_size),
+        _ => raycast_box(local_origin, local_direction, half_size),
+    }?;
+
+    if (0.0..=1.0).contains(&t) {
+        let normal = cframe.0.transform_vector3(local_normal).normalize();
+        Some((t, normal))
+    } else {
+        None
+    }
+}
+
+fn raycast_box(origin: Vec3, direction: Vec3, half_size: Vec3) -> Option<(f32, Vec3)> {
+    let mut t_enter = f32::NEG_INFINITY;
+    let mut t_exit = f32::INFINITY;
+    let mut normal = Vec3::ZERO;
+
+    for axis in 0..3 {
+        let (o, d, h) = (origin[axis], direction[axis], half_size[axis]);
+        if d.abs() < EPSILON {
+            if o.abs() > h {
+                return None;
+            }
+            continue;
+        }
+        let (t_near, t_far) = {
+            let t0 = (-h - o) / d;
+            let t1 = (h - o) / d;
+            (t0.min(t1), t0.max(t1))
+        };
+        if t_near > t_enter {
+            t_enter = t_near;
+            normal = Vec3::ZERO;
+            normal[axis] = -d.signum();
+        }
+        t_exit = t_exit.min(t_far);
+    }
+
+    // NOTE: A negative entry means the ray started inside of the box
+    if t_enter > t_exit || t_enter < 0.0 {
+        None
+    } else {
+        Some((t_enter, normal))
+    }
+}
+
+fn raycast_ball(origin: Vec3, direction: Vec3, radius: f32) -> Option<(f32, Vec3)> {
+    let a = direction.length_squared();
+    let b = 2.0 * origin.dot(direction);
+    let c = origin.length_squared() - radius * radius;
+    if c < 0.0 || a < EPSILON {
+        return None;
+    }
+
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+
+    let t = (-b - discriminant.sqrt()) / (2.0 * a);
+    if t < 0.0 {
+        return None;
+    }
+    Some((t, (origin + direction * t).normalize()))
+}
+
+fn raycast_cylinder(origin: Vec3, direction: Vec3, half_size: Vec3) -> Option<(f32, Vec3)> {
+    // Cylinders extend along their local X axis, with their
+    // radius being limited by the smaller of the other two axes
+    let radius = half_size.y.min(half_size.z);
+    let (origin_yz, direction_yz) = (origin.yz(), direction.yz());
+
+    // Find where the ray is inside of the infinitely long cylinder
+    let (side_enter, side_exit) = if direction_yz.length_squared() < EPSILON {
+        if origin_yz.length_squared() > radius * radius {
+            return None;
+        }
+        (f32::NEG_INFINITY, f32::INFINITY)
+    } else {
+        let a = direction_yz.length_squared();
+        let b = 2.0 * origin_yz.dot(direction_yz);
+        let c = origin_yz.length_squared() - radius * radius;
+        let discriminant = b * b - 4.0 * a * c;
+        if discriminant < 0.0 {
+            return None;
+        }
+        let root = discriminant.sqrt();
+        ((-b - root) / (2.0 * a), (-b + root) / (2.0 * a))
+    };
+
+    // Then find where the ray is between the two caps
+    let (cap_enter, cap_exit) = if direction.x.abs() < EPSILON {
+        if origin.x.abs() > half_size.x {
+            return None;
+        }
+        (f32::NEG_INFINITY, f32::INFINITY)
+    } else {
+        let t0 = (-half_size.x - origin.x) / direction.x;
+        let t1 = (half_size.x - origin.x) / direction.x;
+        (t0.min(t1), t0.max(t1))
+    };
+
+    let t_enter = side_enter.max(cap_enter);
+    let t_exit = side_exit.min(cap_exit);
+    if t_enter > t_exit || t_enter < 0.0 {
+        return None;
+    }
+
+    let normal = if cap_enter >= side_enter {
+        Vec3::new(-direction.x.signum(), 0.0, 0.0)
+    } else {
+        let hit = origin + direction * t_enter;
+        Vec3::new(0.0, hit.y, hit.z).normalize()
+    };
+    Some((t_enter, normal))
+}
@@ -26,17 +26,20 @@ fn create_all_exports(lua: &Lua) -> LuaResult<Vec<(&'static str, LuaValue)>> {
         export::<ColorSequence>(lua)?,
         export::<ColorSequenceKeypoint>(lua)?,
         export::<Faces>(lua)?,
+        export::<FloatCurveKey>(lua)?,
         export::<Font>(lua)?,
         export::<NumberRange>(lua)?,
         export::<NumberSequence>(lua)?,
         export::<NumberSequenceKeypoint>(lua)?,
         export::<PhysicalProperties>(lua)?,
         export::<Ray>(lua)?,
+        export::<RaycastParams>(lua)?,
         export::<Rect>(lua)?,
         export::<UDim>(lua)?,
         export::<UDim2>(lua)?,
         export::<Region3>(lua)?,
         export::<Region3int16>(lua)?,
+        export::<RotationCurveKey>(lua)?,
         export::<Vector2>(lua)?,
         export::<Vector2int16>(lua)?,
         export::<Vector3>(lua)?,
@@ -44,6 +44,24 @@ impl DatabaseClass {
         Some(sup.to_string())
     }
 
+    /**
+        Get the full chain of superclasses of this class, starting with the
+        closest superclass and ending with the root class, usually `Instance`.
+
+        Will be empty if no parent class exists.
+    */
+    #[must_use]
+    pub fn get_superclasses(&self) -> Vec<String> {
+        let db = rbx_reflection_database::get();
+        let mut superclasses = Vec::new();
+        let mut current = self.0.superclass.as_ref().and_then(|s| db.classes.get(s));
+        while let Some(class) = current {
+            superclasses.push(class.name.to_string());
+            current = class.superclass.as_ref().and_then(|s| db.classes.get(s));
+        }
+        superclasses
+    }
+
     /**
         Get all known properties for this class.
     */
@@ -88,6 +106,7 @@ impl LuaUserData for DatabaseClass {
     fn add_fields<'lua, F: LuaUserDataFields<'lua, Self>>(fields: &mut F) {
         fields.add_field_method_get("Name", |_, this| Ok(this.get_name()));
         fields.add_field_method_get("Superclass", |_, this| Ok(this.get_superclass()));
+        fields.add_field_method_get("Superclasses", |_, this| Ok(this.get_superclasses()));
         fields.add_field_method_get("Properties", |_, this| Ok(this.get_properties()));
         fields.add_field_method_get("DefaultProperties", |lua, this| {
             let defaults = this.get_defaults();
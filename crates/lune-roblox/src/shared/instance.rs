@@ -97,6 +97,20 @@ pub(crate) fn find_property_info(
     class_info
 }
 
+/**
+    Gets the name of an instance class, borrowing it from the reflection database if the
+    class exists there, so that instances of known classes can share their class names.
+
+    Unknown class names, such as ones from newer or custom files, are copied instead.
+*/
+pub(crate) fn intern_class_name(class_name: &str) -> Cow<'static, str> {
+    let db = rbx_reflection_database::get();
+    match db.classes.get_key_value(class_name) {
+        Some((name, _)) => Cow::Borrowed(&**name),
+        None => Cow::Owned(class_name.to_string()),
+    }
+}
+
 /**
     Checks if an instance class exists in the reflection database.
 */
@@ -74,16 +74,37 @@ where
     Ok(*datatype - *value)
 }
 
-pub fn userdata_impl_mul_f32<D>(_: &Lua, datatype: &D, rhs: LuaValue) -> LuaResult<D>
+/**
+    Multiplication with a number is commutative, so a number on the
+    left hand side (`2 * vector`) is swapped over to the right hand
+    side, making it behave the same as `vector * 2` would.
+*/
+fn swap_number_to_rhs<'lua>(
+    lhs: LuaValue<'lua>,
+    rhs: LuaValue<'lua>,
+) -> (LuaValue<'lua>, LuaValue<'lua>) {
+    if matches!(lhs, LuaValue::Number(_) | LuaValue::Integer(_)) {
+        (rhs, lhs)
+    } else {
+        (lhs, rhs)
+    }
+}
+
+pub fn userdata_impl_mul_f32<'lua, D>(
+    lua: &'lua Lua,
+    (lhs, rhs): (LuaValue<'lua>, LuaValue<'lua>),
+) -> LuaResult<D>
 where
     D: LuaUserData + ops::Mul<D, Output = D> + ops::Mul<f32, Output = D> + Copy + 'static,
 {
+    let (lhs, rhs) = swap_number_to_rhs(lhs, rhs);
+    let datatype = *LuaUserDataRef::<D>::from_lua(lhs, lua)?;
     match &rhs {
-        LuaValue::Number(n) => return Ok(*datatype * *n as f32),
-        LuaValue::Integer(i) => return Ok(*datatype * *i as f32),
+        LuaValue::Number(n) => return Ok(datatype * *n as f32),
+        LuaValue::Integer(i) => return Ok(datatype * *i as f32),
         LuaValue::UserData(ud) => {
             if let Ok(vec) = ud.borrow::<D>() {
-                return Ok(*datatype * *vec);
+                return Ok(datatype * *vec);
             }
         }
         _ => {}
@@ -99,16 +120,21 @@ where
     })
 }
 
-pub fn userdata_impl_mul_i32<D>(_: &Lua, datatype: &D, rhs: LuaValue) -> LuaResult<D>
+pub fn userdata_impl_mul_i32<'lua, D>(
+    lua: &'lua Lua,
+    (lhs, rhs): (LuaValue<'lua>, LuaValue<'lua>),
+) -> LuaResult<D>
 where
     D: LuaUserData + ops::Mul<D, Output = D> + ops::Mul<i32, Output = D> + Copy + 'static,
 {
+    let (lhs, rhs) = swap_number_to_rhs(lhs, rhs);
+    let datatype = *LuaUserDataRef::<D>::from_lua(lhs, lua)?;
     match &rhs {
-        LuaValue::Number(n) => return Ok(*datatype * *n as i32),
-        LuaValue::Integer(i) => return Ok(*datatype * *i),
+        LuaValue::Number(n) => return Ok(datatype * *n as i32),
+        LuaValue::Integer(i) => return Ok(datatype * *i),
         LuaValue::UserData(ud) => {
             if let Ok(vec) = ud.borrow::<D>() {
-                return Ok(*datatype * *vec);
+                return Ok(datatype * *vec);
             }
         }
         _ => {}
@@ -0,0 +1,53 @@
+use std::path::Path;
+
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+const ZIP_EMPTY_MAGIC: &[u8] = b"PK\x05\x06";
+const GZIP_MAGIC: &[u8] = &[0x1F, 0x8B];
+
+const EXTENSIONS: &[(&str, ArchiveFormat)] = &[
+    (".zip", ArchiveFormat::Zip),
+    (".tar", ArchiveFormat::Tar),
+    (".tar.gz", ArchiveFormat::TarGz),
+    (".tgz", ArchiveFormat::TarGz),
+];
+
+/**
+    A format of archive that can be read and written.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    Zip,
+    Tar,
+    TarGz,
+}
+
+impl ArchiveFormat {
+    /**
+        Detects the format of an archive from its first few bytes.
+
+        Tar archives do not have any magic bytes at the start of them,
+        so anything that is not a zip or gzip file is assumed to be tar.
+    */
+    pub fn detect(header: &[u8]) -> Self {
+        if header.starts_with(ZIP_MAGIC) || header.starts_with(ZIP_EMPTY_MAGIC) {
+            Self::Zip
+        } else if header.starts_with(GZIP_MAGIC) {
+            Self::TarGz
+        } else {
+            Self::Tar
+        }
+    }
+
+    /**
+        Gets the format of an archive from the extension of its file name.
+
+        Returns `None` if the extension is not `.zip`, `.tar`, `.tar.gz`, or `.tgz`.
+    */
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?.to_ascii_lowercase();
+        EXTENSIONS
+            .iter()
+            .find(|(extension, _)| name.ends_with(extension))
+            .map(|(_, format)| *format)
+    }
+}
@@ -0,0 +1,190 @@
+#![allow(clippy::cargo_common_metadata)]
+
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Cursor, Write},
+    path::PathBuf,
+};
+
+use bstr::BString;
+use mlua::prelude::*;
+use mlua_luau_scheduler::LuaSpawnExt;
+
+use lune_std_fs::{check_read, check_write};
+use lune_utils::TableBuilder;
+
+mod format;
+mod options;
+mod path;
+mod read;
+mod write;
+
+use self::format::ArchiveFormat;
+use self::options::{ArchiveFiles, ArchiveTarOptions};
+use self::read::{extract_entries, read_entries, EntryInfo};
+use self::write::{collect_dir_entries, write_entries};
+
+/**
+    Creates the `archive` standard library module.
+
+    # Errors
+
+    Errors when out of memory.
+*/
+pub fn module(lua: &Lua) -> LuaResult<LuaTable> {
+    TableBuilder::new_named(lua, "archive")?
+        .with_async_function("zip", archive_zip)?
+        .with_async_function("unzip", archive_unzip)?
+        .with_async_function("tar", archive_tar)?
+        .with_async_function("untar", archive_untar)?
+        .with_async_function("list", archive_list)?
+        .with_async_function("create", archive_create)?
+        .with_async_function("extract", archive_extract)?
+        .build_readonly()
+}
+
+async fn archive_zip(lua: &Lua, files: ArchiveFiles) -> LuaResult<LuaAnyUserData> {
+    let bytes = lua
+        .spawn_blocking(move || write_archive_bytes(ArchiveFormat::Zip, &files))
+        .await
+        .into_lua_err()?;
+    lua.create_buffer(bytes)
+}
+
+async fn archive_unzip(lua: &Lua, contents: BString) -> LuaResult<LuaTable> {
+    let contents = Vec::from(contents);
+    let files = lua
+        .spawn_blocking(move || read_archive_bytes(ArchiveFormat::Zip, contents))
+        .await
+        .into_lua_err()?;
+    create_files_table(lua, files)
+}
+
+async fn archive_tar(
+    lua: &Lua,
+    (files, options): (ArchiveFiles, ArchiveTarOptions),
+) -> LuaResult<LuaAnyUserData> {
+    let format = if options.gzip {
+        ArchiveFormat::TarGz
+    } else {
+        ArchiveFormat::Tar
+    };
+    let bytes = lua
+        .spawn_blocking(move || write_archive_bytes(format, &files))
+        .await
+        .into_lua_err()?;
+    lua.create_buffer(bytes)
+}
+
+async fn archive_untar(lua: &Lua, contents: BString) -> LuaResult<LuaTable> {
+    let contents = Vec::from(contents);
+    let files = lua
+        .spawn_blocking(move || {
+            // NOTE: Compressed and uncompressed tar files are both accepted here,
+            // but zip files are not, since they have their own unzip function
+            let format = match ArchiveFormat::detect(&contents) {
+                ArchiveFormat::Zip => ArchiveFormat::Tar,
+                format => format,
+            };
+            read_archive_bytes(format, contents)
+        })
+        .await
+        .into_lua_err()?;
+    create_files_table(lua, files)
+}
+
+async fn archive_list(lua: &Lua, path: String) -> LuaResult<LuaTable> {
+    check_read(lua, &path)?;
+    let entries = lua
+        .spawn_blocking(move || {
+            let (format, reader) = open_archive_file(&path)?;
+            let mut entries = Vec::new();
+            read_entries(format, reader, |info, _| {
+                entries.push(info.clone());
+                Ok(())
+            })?;
+            Ok::<_, io::Error>(entries)
+        })
+        .await
+        .into_lua_err()?;
+    create_entries_table(lua, entries)
+}
+
+async fn archive_create(lua: &Lua, (path, source): (String, String)) -> LuaResult<()> {
+    check_read(lua, &source)?;
+    check_write(lua, &path)?;
+    let path = PathBuf::from(path);
+    let format = ArchiveFormat::from_path(&path).ok_or_else(|| {
+        LuaError::runtime(format!(
+            "Unknown archive format for '{}' - expected a .zip, .tar, .tar.gz or .tgz file",
+            path.display()
+        ))
+    })?;
+    lua.spawn_blocking(move || {
+        let entries = collect_dir_entries(source.as_ref())?;
+        let writer = BufWriter::new(File::create(&path)?);
+        write_entries(format, writer, &entries)?.flush()
+    })
+    .await
+    .into_lua_err()
+}
+
+async fn archive_extract(lua: &Lua, (path, dest): (String, String)) -> LuaResult<()> {
+    check_read(lua, &path)?;
+    check_write(lua, &dest)?;
+    lua.spawn_blocking(move || {
+        let (format, reader) = open_archive_file(&path)?;
+        extract_entries(format, reader, dest.as_ref())
+    })
+    .await
+    .into_lua_err()
+}
+
+/**
+    Opens the archive file at the given path, detecting its format from its contents.
+*/
+fn open_archive_file(path: &str) -> io::Result<(ArchiveFormat, BufReader<File>)> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let format = ArchiveFormat::detect(reader.fill_buf()?);
+    Ok((format, reader))
+}
+
+fn write_archive_bytes(format: ArchiveFormat, files: &ArchiveFiles) -> io::Result<Vec<u8>> {
+    let cursor = write_entries(format, Cursor::new(Vec::new()), &files.0)?;
+    Ok(cursor.into_inner())
+}
+
+fn read_archive_bytes(format: ArchiveFormat, bytes: Vec<u8>) -> io::Result<Vec<(String, Vec<u8>)>> {
+    let mut files = Vec::new();
+    read_entries(format, Cursor::new(bytes), |info, contents| {
+        if !info.is_dir {
+            let mut bytes = Vec::new();
+            contents.read_to_end(&mut bytes)?;
+            files.push((info.path.clone(), bytes));
+        }
+        Ok(())
+    })?;
+    Ok(files)
+}
+
+fn create_files_table(lua: &Lua, files: Vec<(String, Vec<u8>)>) -> LuaResult<LuaTable> {
+    let table = lua.create_table_with_capacity(0, files.len())?;
+    for (path, bytes) in files {
+        table.raw_set(path, lua.create_buffer(bytes)?)?;
+    }
+    Ok(table)
+}
+
+fn create_entries_table(lua: &Lua, entries: Vec<EntryInfo>) -> LuaResult<LuaTable> {
+    let table = lua.create_table_with_capacity(entries.len(), 0)?;
+    for entry in entries {
+        table.raw_push(
+            TableBuilder::new(lua)?
+                .with_value("path", entry.path)?
+                .with_value("isDirectory", entry.is_dir)?
+                .with_value("size", entry.size)?
+                .build()?,
+        )?;
+    }
+    Ok(table)
+}
@@ -0,0 +1,73 @@
+use bstr::BString;
+use mlua::prelude::*;
+
+use super::{path::safe_entry_path, write::NewEntry};
+
+/**
+    Files to write to an archive, given as a table of paths to file contents.
+
+    Files are sorted by their paths, so that archives created
+    from the same files are always written in the same order.
+*/
+#[derive(Debug, Clone)]
+pub struct ArchiveFiles(pub(crate) Vec<NewEntry>);
+
+impl<'lua> FromLua<'lua> for ArchiveFiles {
+    fn from_lua(value: LuaValue<'lua>, lua: &'lua Lua) -> LuaResult<Self> {
+        let LuaValue::Table(table) = value else {
+            return Err(LuaError::FromLuaConversionError {
+                from: value.type_name(),
+                to: "ArchiveFiles",
+                message: Some(format!(
+                    "Invalid files - expected table, got {}",
+                    value.type_name()
+                )),
+            });
+        };
+
+        let mut entries = Vec::new();
+        for pair in table.pairs::<String, LuaValue>() {
+            let (name, contents) = pair?;
+            let path = safe_entry_path(&name).into_lua_err()?;
+            if path.is_empty() {
+                return Err(LuaError::runtime(format!(
+                    "Invalid files - '{name}' is not a valid file path"
+                )));
+            }
+            let contents = BString::from_lua(contents, lua)?;
+            entries.push(NewEntry::from_bytes(path, contents.into()));
+        }
+        entries.sort_by(|a, b| a.path.cmp(&b.path));
+
+        Ok(Self(entries))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ArchiveTarOptions {
+    pub(crate) gzip: bool,
+}
+
+impl<'lua> FromLua<'lua> for ArchiveTarOptions {
+    fn from_lua(value: LuaValue<'lua>, _: &'lua Lua) -> LuaResult<Self> {
+        Ok(match value {
+            LuaValue::Nil => Self::default(),
+            LuaValue::Table(t) => {
+                let gzip: Option<bool> = t.get("gzip")?;
+                Self {
+                    gzip: gzip.unwrap_or(false),
+                }
+            }
+            _ => {
+                return Err(LuaError::FromLuaConversionError {
+                    from: value.type_name(),
+                    to: "ArchiveTarOptions",
+                    message: Some(format!(
+                        "Invalid tar options - expected table, got {}",
+                        value.type_name()
+                    )),
+                })
+            }
+        })
+    }
+}
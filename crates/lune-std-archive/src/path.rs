@@ -0,0 +1,30 @@
+use std::io;
+
+/**
+    Converts the name of an archive entry into a normalized, relative path using forward slashes.
+
+    Returns an empty path for entries such as `./`, which refer to the root of the archive itself.
+
+    # Errors
+
+    Errors if the name is absolute, contains a drive prefix, or contains any `..`
+    components, since extracting such an entry could write outside of the destination.
+*/
+pub fn safe_entry_path(name: &str) -> io::Result<String> {
+    let is_unsafe = name.starts_with(['/', '\\'])
+        || name
+            .split(['/', '\\'])
+            .any(|part| part == ".." || part.contains(':'));
+    if is_unsafe {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Archive entry '{name}' has an unsafe path"),
+        ));
+    }
+
+    Ok(name
+        .split(['/', '\\'])
+        .filter(|part| !part.is_empty() && *part != ".")
+        .collect::<Vec<_>>()
+        .join("/"))
+}
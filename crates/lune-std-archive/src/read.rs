@@ -0,0 +1,140 @@
+use std::{
+    fs::{self, File},
+    io::{self, Read, Seek},
+    path::Path,
+};
+
+use flate2::read::GzDecoder;
+
+use super::{format::ArchiveFormat, path::safe_entry_path};
+
+const UNIX_FILE_TYPE_MASK: u32 = 0o170_000;
+const UNIX_SYMLINK_TYPE: u32 = 0o120_000;
+
+/**
+    Information about a single file or directory in an archive.
+*/
+#[derive(Debug, Clone)]
+pub struct EntryInfo {
+    pub path: String,
+    pub is_dir: bool,
+    pub size: u64,
+    pub mode: Option<u32>,
+}
+
+impl EntryInfo {
+    fn new(name: &str, is_dir: bool, size: u64, mode: Option<u32>) -> io::Result<Option<Self>> {
+        let path = safe_entry_path(name)?;
+        if path.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            path,
+            is_dir,
+            size,
+            mode,
+        }))
+    }
+}
+
+/**
+    Reads all files and directories in an archive, one at a time, calling
+    the given function with information about each entry and its contents.
+
+    Symbolic links, hard links, and other special entries are skipped,
+    since extracting them could be used to write outside of a directory.
+
+    # Errors
+
+    Errors if the archive could not be read, if any entry has an unsafe
+    path, or if the given function returns an error for any entry.
+*/
+pub fn read_entries<R: Read + Seek>(
+    format: ArchiveFormat,
+    reader: R,
+    mut visit: impl FnMut(&EntryInfo, &mut dyn Read) -> io::Result<()>,
+) -> io::Result<()> {
+    match format {
+        ArchiveFormat::Zip => {
+            let mut archive = zip::ZipArchive::new(reader)?;
+            for index in 0..archive.len() {
+                let mut file = archive.by_index(index)?;
+                let mode = file.unix_mode();
+                if mode.is_some_and(|m| m & UNIX_FILE_TYPE_MASK == UNIX_SYMLINK_TYPE) {
+                    continue;
+                }
+                if let Some(info) = EntryInfo::new(file.name(), file.is_dir(), file.size(), mode)? {
+                    visit(&info, &mut file)?;
+                }
+            }
+            Ok(())
+        }
+        ArchiveFormat::Tar => read_tar_entries(tar::Archive::new(reader), visit),
+        ArchiveFormat::TarGz => read_tar_entries(tar::Archive::new(GzDecoder::new(reader)), visit),
+    }
+}
+
+fn read_tar_entries<R: Read>(
+    mut archive: tar::Archive<R>,
+    mut visit: impl FnMut(&EntryInfo, &mut dyn Read) -> io::Result<()>,
+) -> io::Result<()> {
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let kind = entry.header().entry_type();
+        if !kind.is_file() && !kind.is_dir() {
+            continue;
+        }
+        let name = String::from_utf8_lossy(&entry.path_bytes()).into_owned();
+        let mode = entry.header().mode().ok();
+        if let Some(info) = EntryInfo::new(&name, kind.is_dir(), entry.size(), mode)? {
+            visit(&info, &mut entry)?;
+        }
+    }
+    Ok(())
+}
+
+/**
+    Extracts all files and directories in an archive into the given directory,
+    creating it and any other missing directories along the way.
+
+    Files are streamed directly from the archive to disk, one at a time.
+
+    # Errors
+
+    Errors if the archive could not be read, if any entry has an
+    unsafe path, or if any file or directory could not be written.
+*/
+pub fn extract_entries<R: Read + Seek>(
+    format: ArchiveFormat,
+    reader: R,
+    dest: &Path,
+) -> io::Result<()> {
+    fs::create_dir_all(dest)?;
+    read_entries(format, reader, |info, contents| {
+        let path = dest.join(&info.path);
+        if info.is_dir {
+            return fs::create_dir_all(path);
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = File::create(&path)?;
+        io::copy(contents, &mut file)?;
+        set_file_mode(&file, info.mode)
+    })
+}
+
+#[cfg(unix)]
+fn set_file_mode(file: &File, mode: Option<u32>) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    match mode {
+        Some(mode) => file.set_permissions(fs::Permissions::from_mode(mode & 0o777)),
+        None => Ok(()),
+    }
+}
+
+#[cfg(not(unix))]
+#[allow(clippy::unnecessary_wraps)]
+fn set_file_mode(_: &File, _: Option<u32>) -> io::Result<()> {
+    Ok(())
+}
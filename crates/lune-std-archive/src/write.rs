@@ -0,0 +1,197 @@
+use std::{
+    fs::{self, File, Metadata},
+    io::{self, Read, Seek, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use flate2::{write::GzEncoder, Compression};
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+use super::format::ArchiveFormat;
+
+const DEFAULT_FILE_MODE: u32 = 0o644;
+const DEFAULT_DIR_MODE: u32 = 0o755;
+
+/**
+    The contents of an entry that will be written to an archive.
+*/
+#[derive(Debug, Clone)]
+pub enum EntryContents {
+    Bytes(Vec<u8>),
+    File(PathBuf),
+    Directory,
+}
+
+/**
+    A single file or directory that will be written to an archive.
+*/
+#[derive(Debug, Clone)]
+pub struct NewEntry {
+    pub path: String,
+    pub contents: EntryContents,
+    pub mode: u32,
+    pub modified: u64,
+}
+
+impl NewEntry {
+    /**
+        Creates a new file entry with the given contents.
+    */
+    pub fn from_bytes(path: String, bytes: Vec<u8>) -> Self {
+        Self {
+            path,
+            contents: EntryContents::Bytes(bytes),
+            mode: DEFAULT_FILE_MODE,
+            modified: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs()),
+        }
+    }
+
+    fn from_metadata(path: String, contents: EntryContents, meta: &Metadata) -> Self {
+        let default_mode = match contents {
+            EntryContents::Directory => DEFAULT_DIR_MODE,
+            _ => DEFAULT_FILE_MODE,
+        };
+        Self {
+            path,
+            contents,
+            mode: file_mode(meta).unwrap_or(default_mode),
+            modified: meta
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map_or(0, |d| d.as_secs()),
+        }
+    }
+
+    fn size(&self) -> io::Result<u64> {
+        Ok(match &self.contents {
+            EntryContents::Bytes(bytes) => bytes.len() as u64,
+            EntryContents::File(path) => fs::metadata(path)?.len(),
+            EntryContents::Directory => 0,
+        })
+    }
+
+    fn reader(&self) -> io::Result<Box<dyn Read + '_>> {
+        Ok(match &self.contents {
+            EntryContents::Bytes(bytes) => Box::new(bytes.as_slice()),
+            EntryContents::File(path) => Box::new(File::open(path)?),
+            EntryContents::Directory => Box::new(io::empty()),
+        })
+    }
+}
+
+/**
+    Collects all files and directories inside of the given directory, recursively,
+    as entries with paths relative to the directory, sorted by path.
+
+    Symbolic links are skipped, same as when reading archives.
+
+    # Errors
+
+    Errors if the directory, or any directory inside of it, could not be read.
+*/
+pub fn collect_dir_entries(dir: &Path) -> io::Result<Vec<NewEntry>> {
+    let mut entries = Vec::new();
+    collect_dir_entries_inner(dir, "", &mut entries)?;
+    Ok(entries)
+}
+
+fn collect_dir_entries_inner(dir: &Path, prefix: &str, out: &mut Vec<NewEntry>) -> io::Result<()> {
+    let mut children = fs::read_dir(dir)?.collect::<io::Result<Vec<_>>>()?;
+    children.sort_by_key(fs::DirEntry::file_name);
+    for child in children {
+        let meta = fs::symlink_metadata(child.path())?;
+        let name = child.file_name().to_string_lossy().into_owned();
+        let path = format!("{prefix}{name}");
+        if meta.is_dir() {
+            out.push(NewEntry::from_metadata(
+                path.clone(),
+                EntryContents::Directory,
+                &meta,
+            ));
+            collect_dir_entries_inner(&child.path(), &format!("{path}/"), out)?;
+        } else if meta.is_file() {
+            out.push(NewEntry::from_metadata(
+                path,
+                EntryContents::File(child.path()),
+                &meta,
+            ));
+        }
+    }
+    Ok(())
+}
+
+/**
+    Writes the given entries to a new archive, returning the writer once the archive is finished.
+
+    File contents are streamed into the archive, one file at a time.
+
+    # Errors
+
+    Errors if any entry could not be read, or if the archive could not be written.
+*/
+pub fn write_entries<W: Write + Seek>(
+    format: ArchiveFormat,
+    writer: W,
+    entries: &[NewEntry],
+) -> io::Result<W> {
+    match format {
+        ArchiveFormat::Zip => write_zip_entries(writer, entries),
+        ArchiveFormat::Tar => write_tar_entries(tar::Builder::new(writer), entries),
+        ArchiveFormat::TarGz => {
+            let encoder = GzEncoder::new(writer, Compression::default());
+            write_tar_entries(tar::Builder::new(encoder), entries)?.finish()
+        }
+    }
+}
+
+fn write_zip_entries<W: Write + Seek>(writer: W, entries: &[NewEntry]) -> io::Result<W> {
+    let mut zip = ZipWriter::new(writer);
+    for entry in entries {
+        let size = entry.size()?;
+        let options = SimpleFileOptions::default()
+            .compression_method(CompressionMethod::Deflated)
+            .unix_permissions(entry.mode)
+            .large_file(size >= u64::from(u32::MAX));
+        if let EntryContents::Directory = entry.contents {
+            zip.add_directory(entry.path.as_str(), options)?;
+        } else {
+            zip.start_file(entry.path.as_str(), options)?;
+            io::copy(&mut entry.reader()?, &mut zip)?;
+        }
+    }
+    Ok(zip.finish()?)
+}
+
+fn write_tar_entries<W: Write>(
+    mut builder: tar::Builder<W>,
+    entries: &[NewEntry],
+) -> io::Result<W> {
+    for entry in entries {
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(match entry.contents {
+            EntryContents::Directory => tar::EntryType::Directory,
+            _ => tar::EntryType::Regular,
+        });
+        header.set_size(entry.size()?);
+        header.set_mode(entry.mode);
+        header.set_mtime(entry.modified);
+        builder.append_data(&mut header, &entry.path, entry.reader()?)?;
+    }
+    builder.into_inner()
+}
+
+#[cfg(unix)]
+#[allow(clippy::unnecessary_wraps)]
+fn file_mode(meta: &Metadata) -> Option<u32> {
+    use std::os::unix::fs::PermissionsExt;
+    Some(meta.permissions().mode() & 0o777)
+}
+
+#[cfg(not(unix))]
+fn file_mode(_: &Metadata) -> Option<u32> {
+    None
+}
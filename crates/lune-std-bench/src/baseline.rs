@@ -0,0 +1,92 @@
+use std::{collections::BTreeMap, fs, io};
+
+use lune_utils::TableBuilder;
+use mlua::prelude::*;
+
+use super::stats::BenchStats;
+
+/**
+    A file containing stored results of previous benchmark runs, keyed by benchmark name.
+
+    Results are stored as JSON, sorted by name, so that the file
+    can be checked into version control and diffed easily.
+*/
+#[derive(Debug, Clone, Default)]
+pub struct BaselineFile(BTreeMap<String, BenchStats>);
+
+impl BaselineFile {
+    /**
+        Reads the baseline file at the given path, or returns
+        an empty baseline if the file does not exist yet.
+
+        # Errors
+
+        Errors if the file exists but could not be read or is not a valid baseline file.
+    */
+    pub fn read(path: &str) -> LuaResult<Self> {
+        match fs::read(path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .map(Self)
+                .map_err(|e| LuaError::runtime(format!("Invalid baseline file '{path}' - {e}"))),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into_lua_err()),
+        }
+    }
+
+    /**
+        Writes the baseline file to the given path, replacing any existing file.
+
+        # Errors
+
+        Errors if the file could not be written.
+    */
+    pub fn write(&self, path: &str) -> LuaResult<()> {
+        let mut json = serde_json::to_string_pretty(&self.0).into_lua_err()?;
+        json.push('\n');
+        fs::write(path, json).into_lua_err()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&BenchStats> {
+        self.0.get(name)
+    }
+
+    pub fn set(&mut self, name: String, stats: BenchStats) {
+        self.0.insert(name, stats);
+    }
+}
+
+/**
+    A comparison between the results of a benchmark and its stored baseline.
+
+    Medians are compared instead of means, since they are much
+    less sensitive to outliers such as garbage collection pauses.
+*/
+#[derive(Debug, Clone, Copy)]
+pub struct BenchComparison {
+    pub baseline: BenchStats,
+    pub change: f64,
+    pub regressed: bool,
+}
+
+impl BenchComparison {
+    pub fn new(baseline: BenchStats, current: &BenchStats, threshold: f64) -> Self {
+        let change = if baseline.median > 0.0 {
+            (current.median - baseline.median) / baseline.median
+        } else {
+            0.0
+        };
+        Self {
+            baseline,
+            change,
+            regressed: change > threshold,
+        }
+    }
+
+    pub fn into_lua_table(self, lua: &Lua) -> LuaResult<LuaTable> {
+        TableBuilder::new(lua)?
+            .with_value("baseline", self.baseline.into_lua_table(lua)?)?
+            .with_value("change", self.change)?
+            .with_value("regressed", self.regressed)?
+            .build()
+    }
+}
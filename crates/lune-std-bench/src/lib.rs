@@ -0,0 +1,87 @@
+#![allow(clippy::cargo_common_metadata)]
+
+use std::time::Instant;
+
+use mlua::prelude::*;
+
+use lune_std_fs::{check_read, check_write};
+use lune_utils::TableBuilder;
+
+mod baseline;
+mod options;
+mod stats;
+
+use self::baseline::{BaselineFile, BenchComparison};
+use self::options::BenchOptions;
+use self::stats::BenchStats;
+
+/**
+    Creates the `bench` standard library module.
+
+    # Errors
+
+    Errors when out of memory.
+*/
+pub fn module(lua: &Lua) -> LuaResult<LuaTable> {
+    TableBuilder::new_named(lua, "bench")?
+        .with_function("run", bench_run)?
+        .build_readonly()
+}
+
+fn bench_run<'lua>(
+    lua: &'lua Lua,
+    (name, func, options): (String, LuaFunction<'lua>, BenchOptions),
+) -> LuaResult<LuaTable<'lua>> {
+    for _ in 0..options.warmup {
+        func.call::<_, ()>(())?;
+    }
+
+    let mut samples = Vec::with_capacity(options.iterations);
+    for _ in 0..options.iterations {
+        let start = Instant::now();
+        func.call::<_, ()>(())?;
+        samples.push(start.elapsed().as_secs_f64());
+    }
+    let stats = BenchStats::from_samples(samples);
+
+    let comparison = match &options.baseline {
+        Some(path) => compare_baseline(lua, path, &name, &stats, &options)?,
+        None => None,
+    };
+
+    let table = stats.into_lua_table(lua)?;
+    table.set("name", name)?;
+    if let Some(comparison) = comparison {
+        table.set("comparison", comparison.into_lua_table(lua)?)?;
+    }
+    Ok(table)
+}
+
+/**
+    Compares the given results against the stored baseline with the same name, if any.
+
+    Results are written to the baseline file when it does not contain any results
+    for the benchmark yet, or when the baseline has been explicitly asked to update.
+*/
+fn compare_baseline(
+    lua: &Lua,
+    path: &str,
+    name: &str,
+    stats: &BenchStats,
+    options: &BenchOptions,
+) -> LuaResult<Option<BenchComparison>> {
+    check_read(lua, path)?;
+    let mut file = BaselineFile::read(path)?;
+
+    let comparison = file
+        .get(name)
+        .map(|baseline| BenchComparison::new(*baseline, stats, options.threshold));
+
+    if comparison.is_none() || options.update_baseline {
+        check_write(lua, path)?;
+        file.set(name.to_string(), *stats);
+        file.write(path)?;
+    }
+
+    Ok(comparison)
+}
@@ -0,0 +1,70 @@
+use mlua::prelude::*;
+
+const DEFAULT_WARMUP: usize = 10;
+const DEFAULT_ITERATIONS: usize = 100;
+const DEFAULT_THRESHOLD: f64 = 0.1;
+
+#[derive(Debug, Clone)]
+pub struct BenchOptions {
+    pub(crate) warmup: usize,
+    pub(crate) iterations: usize,
+    pub(crate) baseline: Option<String>,
+    pub(crate) threshold: f64,
+    pub(crate) update_baseline: bool,
+}
+
+impl Default for BenchOptions {
+    fn default() -> Self {
+        Self {
+            warmup: DEFAULT_WARMUP,
+            iterations: DEFAULT_ITERATIONS,
+            baseline: None,
+            threshold: DEFAULT_THRESHOLD,
+            update_baseline: false,
+        }
+    }
+}
+
+impl<'lua> FromLua<'lua> for BenchOptions {
+    fn from_lua(value: LuaValue<'lua>, _: &'lua Lua) -> LuaResult<Self> {
+        Ok(match value {
+            LuaValue::Nil => Self::default(),
+            LuaValue::Table(t) => {
+                let defaults = Self::default();
+                let warmup: Option<usize> = t.get("warmup")?;
+                let iterations: Option<usize> = t.get("iterations")?;
+                let baseline: Option<String> = t.get("baseline")?;
+                let threshold: Option<f64> = t.get("threshold")?;
+                let update_baseline: Option<bool> = t.get("updateBaseline")?;
+                let options = Self {
+                    warmup: warmup.unwrap_or(defaults.warmup),
+                    iterations: iterations.unwrap_or(defaults.iterations),
+                    baseline,
+                    threshold: threshold.unwrap_or(defaults.threshold),
+                    update_baseline: update_baseline.unwrap_or(defaults.update_baseline),
+                };
+                if options.iterations == 0 {
+                    return Err(LuaError::runtime(
+                        "Invalid bench options - iterations must be greater than zero",
+                    ));
+                }
+                if !options.threshold.is_finite() || options.threshold < 0.0 {
+                    return Err(LuaError::runtime(
+                        "Invalid bench options - threshold must be a positive number",
+                    ));
+                }
+                options
+            }
+            _ => {
+                return Err(LuaError::FromLuaConversionError {
+                    from: value.type_name(),
+                    to: "BenchOptions",
+                    message: Some(format!(
+                        "Invalid bench options - expected table, got {}",
+                        value.type_name()
+                    )),
+                })
+            }
+        })
+    }
+}
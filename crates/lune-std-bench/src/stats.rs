@@ -0,0 +1,67 @@
+use lune_utils::TableBuilder;
+use mlua::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/**
+    A statistical summary of the time taken by each iteration of a benchmark.
+
+    All times are in seconds, same as `os.clock` and the `task` library.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BenchStats {
+    pub iterations: usize,
+    pub mean: f64,
+    pub median: f64,
+    pub p99: f64,
+    pub min: f64,
+    pub max: f64,
+    pub stddev: f64,
+}
+
+impl BenchStats {
+    /**
+        Summarizes the given samples, which must not be empty.
+    */
+    pub fn from_samples(mut samples: Vec<f64>) -> Self {
+        assert!(!samples.is_empty(), "benchmark samples must not be empty");
+        samples.sort_by(f64::total_cmp);
+
+        let count = samples.len() as f64;
+        let mean = samples.iter().sum::<f64>() / count;
+        let variance = samples.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / count;
+
+        Self {
+            iterations: samples.len(),
+            mean,
+            median: percentile(&samples, 0.5),
+            p99: percentile(&samples, 0.99),
+            min: samples[0],
+            max: samples[samples.len() - 1],
+            stddev: variance.sqrt(),
+        }
+    }
+
+    pub fn into_lua_table(self, lua: &Lua) -> LuaResult<LuaTable> {
+        TableBuilder::new(lua)?
+            .with_value("iterations", self.iterations)?
+            .with_value("mean", self.mean)?
+            .with_value("median", self.median)?
+            .with_value("p99", self.p99)?
+            .with_value("min", self.min)?
+            .with_value("max", self.max)?
+            .with_value("stddev", self.stddev)?
+            .build()
+    }
+}
+
+/**
+    Gets the given percentile of sorted samples, interpolating
+    linearly between the two closest samples when necessary.
+*/
+fn percentile(sorted: &[f64], fraction: f64) -> f64 {
+    let rank = fraction * (sorted.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    let weight = rank - rank.floor();
+    sorted[lower] + (sorted[upper] - sorted[lower]) * weight
+}
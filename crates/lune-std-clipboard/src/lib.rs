@@ -0,0 +1,60 @@
+#![allow(clippy::cargo_common_metadata)]
+
+use arboard::{Clipboard, Error as ClipboardError};
+use mlua::prelude::*;
+
+use lune_utils::TableBuilder;
+
+/**
+    The system clipboard, created the first time it is used,
+    and then kept around for as long as the Lua state exists.
+
+    On Linux, clipboard contents are owned by the process that wrote them,
+    and are handed over to the clipboard manager once the clipboard is dropped,
+    so keeping it around lets contents outlive the script that wrote them.
+*/
+struct SystemClipboard(Clipboard);
+
+/**
+    Creates the `clipboard` standard library module.
+
+    # Errors
+
+    Errors when out of memory.
+*/
+pub fn module(lua: &Lua) -> LuaResult<LuaTable> {
+    TableBuilder::new_named(lua, "clipboard")?
+        .with_function("read", clipboard_read)?
+        .with_function("write", clipboard_write)?
+        .build_readonly()
+}
+
+fn clipboard_read(lua: &Lua, (): ()) -> LuaResult<Option<String>> {
+    with_clipboard(lua, |clipboard| match clipboard.get_text() {
+        Ok(text) => Ok(Some(text)),
+        Err(ClipboardError::ContentNotAvailable) => Ok(None),
+        Err(e) => Err(e),
+    })
+}
+
+fn clipboard_write(lua: &Lua, text: String) -> LuaResult<()> {
+    with_clipboard(lua, |clipboard| clipboard.set_text(text))
+}
+
+fn with_clipboard<R>(
+    lua: &Lua,
+    f: impl FnOnce(&mut Clipboard) -> Result<R, ClipboardError>,
+) -> LuaResult<R> {
+    if lua.app_data_ref::<SystemClipboard>().is_none() {
+        let clipboard = Clipboard::new().map_err(clipboard_error)?;
+        lua.set_app_data(SystemClipboard(clipboard));
+    }
+    let mut clipboard = lua
+        .app_data_mut::<SystemClipboard>()
+        .expect("Missing clipboard in app data");
+    f(&mut clipboard.0).map_err(clipboard_error)
+}
+
+fn clipboard_error(e: ClipboardError) -> LuaError {
+    LuaError::runtime(format!("Failed to access clipboard - {e}"))
+}
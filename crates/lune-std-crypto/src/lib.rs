@@ -0,0 +1,99 @@
+#![allow(clippy::cargo_common_metadata)]
+
+use std::fmt::Write;
+
+use bstr::BString;
+use mlua::prelude::*;
+
+use lune_std_serde::HashAlgorithm;
+use lune_utils::TableBuilder;
+
+/**
+    Creates the `crypto` standard library module.
+
+    # Errors
+
+    Errors when out of memory.
+*/
+pub fn module(lua: &Lua) -> LuaResult<LuaTable> {
+    TableBuilder::new_named(lua, "crypto")?
+        .with_function("hash", crypto_hash)?
+        .with_function("hmac", crypto_hmac)?
+        .with_function("randomBytes", crypto_random_bytes)?
+        .build_readonly()
+}
+
+fn crypto_hash(
+    lua: &Lua,
+    (algorithm, data, encoding): (HashAlgorithm, BString, Option<DigestEncoding>),
+) -> LuaResult<LuaString> {
+    let bytes = algorithm.digest(&data);
+    encoding.unwrap_or_default().encode(lua, &bytes)
+}
+
+fn crypto_hmac(
+    lua: &Lua,
+    (algorithm, key, data, encoding): (HashAlgorithm, BString, BString, Option<DigestEncoding>),
+) -> LuaResult<LuaString> {
+    let bytes = algorithm.hmac(&key, &data)?;
+    encoding.unwrap_or_default().encode(lua, &bytes)
+}
+
+fn crypto_random_bytes(lua: &Lua, count: usize) -> LuaResult<LuaAnyUserData> {
+    let mut bytes = vec![0; count];
+    getrandom::getrandom(&mut bytes)
+        .map_err(|e| LuaError::runtime(format!("Failed to generate random bytes - {e}")))?;
+    lua.create_buffer(bytes)
+}
+
+/**
+    The encoding used for the output of `crypto.hash` and `crypto.hmac`.
+*/
+#[derive(Debug, Clone, Copy, Default)]
+enum DigestEncoding {
+    #[default]
+    Hex,
+    Raw,
+}
+
+impl DigestEncoding {
+    fn encode<'lua>(self, lua: &'lua Lua, bytes: &[u8]) -> LuaResult<LuaString<'lua>> {
+        match self {
+            Self::Raw => lua.create_string(bytes),
+            Self::Hex => lua.create_string(hex_string(bytes)),
+        }
+    }
+}
+
+fn hex_string(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .fold(String::with_capacity(bytes.len() * 2), |mut output, b| {
+            let _ = write!(output, "{b:02x}");
+            output
+        })
+}
+
+impl<'lua> FromLua<'lua> for DigestEncoding {
+    fn from_lua(value: LuaValue<'lua>, _: &'lua Lua) -> LuaResult<Self> {
+        if let LuaValue::String(s) = &value {
+            match s.to_string_lossy().to_ascii_lowercase().trim() {
+                "hex" => Ok(Self::Hex),
+                "raw" => Ok(Self::Raw),
+                kind => Err(LuaError::FromLuaConversionError {
+                    from: value.type_name(),
+                    to: "DigestEncoding",
+                    message: Some(format!(
+                        "Invalid encoding '{kind}', valid encodings are: hex, raw"
+                    )),
+                }),
+            }
+        } else {
+            Err(LuaError::FromLuaConversionError {
+                from: value.type_name(),
+                to: "DigestEncoding",
+                message: None,
+            })
+        }
+    }
+}
@@ -60,6 +60,24 @@ impl DateTime {
         Ok(Self { inner })
     }
 
+    /**
+        Creates a new `DateTime` struct from the given `unix_timestamp_millis`,
+        which is an integer of milliseconds passed since the UNIX epoch.
+
+        Unlike [`DateTime::from_unix_timestamp_float`], this has no loss of precision.
+
+        See [`chrono::DateTime::from_timestamp_millis`] for additional details.
+
+        # Errors
+
+        Returns an error if the input value is out of range.
+    */
+    pub fn from_unix_timestamp_millis(unix_timestamp_millis: i64) -> DateTimeResult<Self> {
+        let inner = ChronoDateTime::<Utc>::from_timestamp_millis(unix_timestamp_millis)
+            .ok_or(DateTimeError::OutOfRangeUnspecified)?;
+        Ok(Self { inner })
+    }
+
     /**
         Transforms individual date & time values into a new
         `DateTime` struct, using the universal (UTC) time zone.
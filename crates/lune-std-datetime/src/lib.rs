@@ -18,7 +18,7 @@ pub use self::date_time::DateTime;
     Errors when out of memory.
 */
 pub fn module(lua: &Lua) -> LuaResult<LuaTable> {
-    TableBuilder::new(lua)?
+    TableBuilder::new_named(lua, "datetime")?
         .with_function("fromIsoDate", |_, iso_date: String| {
             Ok(DateTime::from_iso_date(iso_date)?)
         })?
@@ -31,6 +31,9 @@ pub fn module(lua: &Lua) -> LuaResult<LuaTable> {
         .with_function("fromUnixTimestamp", |_, timestamp| {
             Ok(DateTime::from_unix_timestamp_float(timestamp)?)
         })?
+        .with_function("fromUnixTimestampMillis", |_, timestamp| {
+            Ok(DateTime::from_unix_timestamp_millis(timestamp)?)
+        })?
         .with_function("now", |_, ()| Ok(DateTime::now()))?
         .build_readonly()
 }
@@ -0,0 +1,160 @@
+use std::{cmp::Ordering, collections::HashSet, fmt};
+
+use mlua::prelude::*;
+
+use super::format::{format_key, format_value};
+
+const MAX_DIFFERENCES_SHOWN: usize = 10;
+
+/**
+    A single difference found when deeply comparing two values.
+
+    A side is `None` when the key exists in one of the tables but not the other.
+*/
+#[derive(Debug, Clone)]
+pub struct Difference {
+    path: String,
+    actual: Option<String>,
+    expected: Option<String>,
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.actual, &self.expected) {
+            (Some(actual), Some(expected)) => {
+                write!(f, "{}: expected {expected}, got {actual}", self.path)
+            }
+            (None, Some(expected)) => write!(f, "{}: missing, expected {expected}", self.path),
+            (Some(actual), None) => write!(f, "{}: unexpected {actual}", self.path),
+            (None, None) => write!(f, "{}: missing", self.path),
+        }
+    }
+}
+
+/**
+    Deeply compares two values, returning all of the differences between them.
+
+    Tables are compared key by key, recursively, without invoking any metamethods.
+    Any other values are compared using `==`, except for `NaN`, which is
+    considered equal to itself so that tables containing it can be compared.
+
+    # Errors
+
+    Errors if an `__eq` metamethod errors while comparing values.
+*/
+pub fn deep_diff(actual: &LuaValue, expected: &LuaValue) -> LuaResult<Vec<Difference>> {
+    let mut differences = Vec::new();
+    let mut visited = HashSet::new();
+    diff_recursive(actual, expected, "value", &mut differences, &mut visited)?;
+    Ok(differences)
+}
+
+/**
+    Formats differences as a list, one per line with the given indentation,
+    limiting the number of differences shown to keep output readable.
+*/
+pub fn format_differences(differences: &[Difference], indent: &str) -> String {
+    let mut lines = differences
+        .iter()
+        .take(MAX_DIFFERENCES_SHOWN)
+        .map(|d| format!("{indent}{d}"))
+        .collect::<Vec<_>>();
+    if differences.len() > MAX_DIFFERENCES_SHOWN {
+        lines.push(format!(
+            "{indent}...and {} more",
+            differences.len() - MAX_DIFFERENCES_SHOWN
+        ));
+    }
+    lines.join("\n")
+}
+
+fn diff_recursive(
+    actual: &LuaValue,
+    expected: &LuaValue,
+    path: &str,
+    out: &mut Vec<Difference>,
+    visited: &mut HashSet<(usize, usize)>,
+) -> LuaResult<()> {
+    if let (LuaValue::Table(a), LuaValue::Table(e)) = (actual, expected) {
+        // NOTE: Tables that are being compared further up are skipped,
+        // since comparing them again would never finish for cyclic tables
+        if a == e || !visited.insert((a.to_pointer() as usize, e.to_pointer() as usize)) {
+            return Ok(());
+        }
+
+        for (key, expected_value) in sorted_pairs(e)? {
+            let actual_value = a.raw_get::<_, LuaValue>(key.clone())?;
+            let path = format!("{path}{}", format_key(&key));
+            if actual_value.is_nil() {
+                out.push(Difference {
+                    path,
+                    actual: None,
+                    expected: Some(format_value(&expected_value)),
+                });
+            } else {
+                diff_recursive(&actual_value, &expected_value, &path, out, visited)?;
+            }
+        }
+
+        for (key, actual_value) in sorted_pairs(a)? {
+            if e.raw_get::<_, LuaValue>(key.clone())?.is_nil() {
+                out.push(Difference {
+                    path: format!("{path}{}", format_key(&key)),
+                    actual: Some(format_value(&actual_value)),
+                    expected: None,
+                });
+            }
+        }
+
+        return Ok(());
+    }
+
+    if !values_equal(actual, expected)? {
+        out.push(Difference {
+            path: path.to_string(),
+            actual: Some(format_value(actual)),
+            expected: Some(format_value(expected)),
+        });
+    }
+
+    Ok(())
+}
+
+#[allow(clippy::float_cmp)]
+fn values_equal(a: &LuaValue, b: &LuaValue) -> LuaResult<bool> {
+    match (as_number(a), as_number(b)) {
+        (Some(a), Some(b)) => Ok(a == b || (a.is_nan() && b.is_nan())),
+        _ => a.equals(b),
+    }
+}
+
+fn as_number(value: &LuaValue) -> Option<f64> {
+    match value {
+        LuaValue::Integer(i) => Some(f64::from(*i)),
+        LuaValue::Number(n) => Some(*n),
+        _ => None,
+    }
+}
+
+/**
+    Gets all key-value pairs of a table, sorted so that differences
+    are always listed in the same order - numbers first, then strings.
+*/
+fn sorted_pairs<'lua>(table: &LuaTable<'lua>) -> LuaResult<Vec<(LuaValue<'lua>, LuaValue<'lua>)>> {
+    let mut pairs = table
+        .clone()
+        .pairs::<LuaValue, LuaValue>()
+        .collect::<LuaResult<Vec<_>>>()?;
+    pairs.sort_by(|(a, _), (b, _)| match (as_number(a), as_number(b)) {
+        (Some(a), Some(b)) => a.total_cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => match (a, b) {
+            (LuaValue::String(a), LuaValue::String(b)) => a.as_bytes().cmp(b.as_bytes()),
+            (LuaValue::String(_), _) => Ordering::Less,
+            (_, LuaValue::String(_)) => Ordering::Greater,
+            _ => Ordering::Equal,
+        },
+    });
+    Ok(pairs)
+}
@@ -0,0 +1,250 @@
+use std::rc::Rc;
+
+use mlua::prelude::*;
+
+use super::{
+    diff::{deep_diff, format_differences},
+    format::format_value,
+    near::{max_difference, near_components},
+};
+
+const DEFAULT_EPSILON: f64 = 1e-5;
+
+/**
+    An expectation about a value, created using `expect(value)`.
+
+    Each matcher method errors with a readable message if the value does
+    not match, and the `never` field gives an inverted expectation.
+*/
+#[derive(Debug, Clone)]
+pub struct Expectation {
+    value: Rc<LuaRegistryKey>,
+    negated: bool,
+}
+
+impl Expectation {
+    pub fn new(lua: &Lua, value: LuaValue) -> LuaResult<Self> {
+        Ok(Self {
+            value: Rc::new(lua.create_registry_value(value)?),
+            negated: false,
+        })
+    }
+
+    fn value<'lua>(&self, lua: &'lua Lua) -> LuaResult<LuaValue<'lua>> {
+        lua.registry_value(&self.value)
+    }
+
+    /**
+        Checks the result of a matcher, taking into account if this expectation
+        is negated, and creates a failure message if the check did not succeed.
+
+        The message function receives either `""` or `" not"`, for use in messages.
+    */
+    fn check(&self, pass: bool, message: impl FnOnce(&str) -> String) -> LuaResult<()> {
+        if pass == self.negated {
+            Err(LuaError::runtime(message(if self.negated {
+                " not"
+            } else {
+                ""
+            })))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl LuaUserData for Expectation {
+    fn add_fields<'lua, F: LuaUserDataFields<'lua, Self>>(fields: &mut F) {
+        fields.add_meta_field(LuaMetaMethod::Type, "Expectation");
+        fields.add_field_method_get("value", |lua, this| this.value(lua));
+        fields.add_field_method_get("never", |_, this| {
+            Ok(Self {
+                value: Rc::clone(&this.value),
+                negated: !this.negated,
+            })
+        });
+    }
+
+    fn add_methods<'lua, M: LuaUserDataMethods<'lua, Self>>(methods: &mut M) {
+        methods.add_method("toBe", expect_to_be);
+        methods.add_method("toEqual", expect_to_equal);
+        methods.add_method("toBeNear", expect_to_be_near);
+        methods.add_method("toThrow", expect_to_throw);
+        methods.add_method("toBeType", expect_to_be_type);
+        methods.add_method("toBeNil", expect_to_be_nil);
+        methods.add_method("toBeTruthy", expect_to_be_truthy);
+        methods.add_method("toBeFalsy", expect_to_be_falsy);
+        methods.add_method("toContain", expect_to_contain);
+    }
+}
+
+fn expect_to_be(lua: &Lua, this: &Expectation, expected: LuaValue) -> LuaResult<()> {
+    let actual = this.value(lua)?;
+    this.check(actual.equals(&expected)?, |not| {
+        format!(
+            "Expected {}{not} to be {}",
+            format_value(&actual),
+            format_value(&expected)
+        )
+    })
+}
+
+fn expect_to_equal(lua: &Lua, this: &Expectation, expected: LuaValue) -> LuaResult<()> {
+    let actual = this.value(lua)?;
+    let differences = deep_diff(&actual, &expected)?;
+    this.check(differences.is_empty(), |not| {
+        if differences.is_empty() {
+            format!(
+                "Expected {}{not} to deeply equal {}",
+                format_value(&actual),
+                format_value(&expected)
+            )
+        } else {
+            format!(
+                "Expected value to deeply equal expected value, but found {} difference{}:\n\n{}",
+                differences.len(),
+                if differences.len() == 1 { "" } else { "s" },
+                format_differences(&differences, "  ")
+            )
+        }
+    })
+}
+
+fn expect_to_be_near(
+    lua: &Lua,
+    this: &Expectation,
+    (expected, epsilon): (LuaValue, Option<f64>),
+) -> LuaResult<()> {
+    let actual = this.value(lua)?;
+    let epsilon = epsilon.unwrap_or(DEFAULT_EPSILON);
+    let difference = match (near_components(&actual)?, near_components(&expected)?) {
+        (Some(a), Some(e)) => max_difference(&a, &e),
+        _ => None,
+    };
+    let Some(difference) = difference else {
+        return Err(LuaError::runtime(format!(
+            "Expected number or vector values of the same kind to compare, got {} and {}",
+            format_value(&actual),
+            format_value(&expected)
+        )));
+    };
+    this.check(difference <= epsilon, |not| {
+        format!(
+            "Expected {}{not} to be near {} (epsilon {epsilon}), difference was {difference}",
+            format_value(&actual),
+            format_value(&expected)
+        )
+    })
+}
+
+fn expect_to_throw(lua: &Lua, this: &Expectation, expected: Option<String>) -> LuaResult<()> {
+    let actual = this.value(lua)?;
+    let LuaValue::Function(func) = &actual else {
+        return Err(LuaError::runtime(format!(
+            "Expected a function to call, got {}",
+            format_value(&actual)
+        )));
+    };
+    let error = func.call::<_, ()>(()).err().map(|e| error_message(&e));
+    let pass = match (&error, &expected) {
+        (Some(error), Some(expected)) => error.contains(expected.as_str()),
+        (Some(_), None) => true,
+        (None, _) => false,
+    };
+    this.check(pass, |not| {
+        let expectation = match &expected {
+            Some(expected) => format!("an error containing {expected:?}"),
+            None => String::from("an error"),
+        };
+        match &error {
+            Some(error) => {
+                format!("Expected function{not} to throw {expectation}, but it threw:\n\n{error}")
+            }
+            None => format!("Expected function to throw {expectation}, but it did not"),
+        }
+    })
+}
+
+fn expect_to_be_type(lua: &Lua, this: &Expectation, expected: String) -> LuaResult<()> {
+    let actual = this.value(lua)?;
+    let typeof_fn = lua.globals().get::<_, LuaFunction>("typeof")?;
+    let actual_type = typeof_fn.call::<_, String>(actual.clone())?;
+    this.check(actual_type == expected, |not| {
+        format!(
+            "Expected {}{not} to be of type {expected}, got {actual_type}",
+            format_value(&actual)
+        )
+    })
+}
+
+fn expect_to_be_nil(lua: &Lua, this: &Expectation, (): ()) -> LuaResult<()> {
+    let actual = this.value(lua)?;
+    this.check(actual.is_nil(), |not| {
+        format!("Expected {}{not} to be nil", format_value(&actual))
+    })
+}
+
+fn expect_to_be_truthy(lua: &Lua, this: &Expectation, (): ()) -> LuaResult<()> {
+    let actual = this.value(lua)?;
+    this.check(is_truthy(&actual), |not| {
+        format!("Expected {}{not} to be truthy", format_value(&actual))
+    })
+}
+
+fn expect_to_be_falsy(lua: &Lua, this: &Expectation, (): ()) -> LuaResult<()> {
+    let actual = this.value(lua)?;
+    this.check(!is_truthy(&actual), |not| {
+        format!("Expected {}{not} to be falsy", format_value(&actual))
+    })
+}
+
+fn expect_to_contain(lua: &Lua, this: &Expectation, item: LuaValue) -> LuaResult<()> {
+    let actual = this.value(lua)?;
+    let pass = match (&actual, &item) {
+        (LuaValue::String(s), LuaValue::String(i)) => {
+            let (s, i) = (s.as_bytes(), i.as_bytes());
+            i.is_empty() || s.windows(i.len()).any(|w| w == i)
+        }
+        (LuaValue::Table(t), _) => {
+            let mut found = false;
+            for pair in t.clone().pairs::<LuaValue, LuaValue>() {
+                let (_, value) = pair?;
+                if value.equals(&item)? {
+                    found = true;
+                    break;
+                }
+            }
+            found
+        }
+        _ => {
+            return Err(LuaError::runtime(format!(
+                "Expected a table, or a string and a substring, got {} and {}",
+                format_value(&actual),
+                format_value(&item)
+            )))
+        }
+    };
+    this.check(pass, |not| {
+        format!(
+            "Expected {}{not} to contain {}",
+            format_value(&actual),
+            format_value(&item)
+        )
+    })
+}
+
+fn is_truthy(value: &LuaValue) -> bool {
+    !matches!(value, LuaValue::Nil | LuaValue::Boolean(false))
+}
+
+/**
+    Gets the innermost message of an error, without any
+    of the tracebacks added by calling through Rust.
+*/
+fn error_message(error: &LuaError) -> String {
+    match error {
+        LuaError::CallbackError { cause, .. } => error_message(cause),
+        LuaError::RuntimeError(message) => message.clone(),
+        error => error.to_string(),
+    }
+}
@@ -0,0 +1,38 @@
+use mlua::prelude::*;
+
+use lune_utils::fmt::{pretty_format_value, ValueFormatConfig};
+
+const FORMAT_CONFIG: ValueFormatConfig = ValueFormatConfig::new().with_max_depth(0);
+
+/**
+    Formats a value for use in a failure message.
+
+    Unlike when printing, strings are always quoted, so that they can be
+    told apart from numbers, and vectors include all of their components.
+*/
+pub fn format_value(value: &LuaValue) -> String {
+    match value {
+        LuaValue::String(s) => format!("{:?}", s.to_string_lossy()),
+        LuaValue::Vector(v) => v.to_string(),
+        _ => pretty_format_value(value, &FORMAT_CONFIG),
+    }
+}
+
+/**
+    Formats a table key as a path segment, such as `.name` or `[1]`.
+*/
+pub fn format_key(key: &LuaValue) -> String {
+    if let LuaValue::String(s) = key {
+        if let Ok(s) = s.to_str() {
+            let first_valid = s
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_ascii_alphabetic() || c == '_');
+            let all_valid = s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+            if first_valid && all_valid {
+                return format!(".{s}");
+            }
+        }
+    }
+    format!("[{}]", format_value(key))
+}
@@ -0,0 +1,49 @@
+#![allow(clippy::cargo_common_metadata)]
+
+use mlua::prelude::*;
+
+use lune_utils::TableBuilder;
+
+mod diff;
+mod expectation;
+mod format;
+mod near;
+
+use self::diff::{deep_diff, format_differences};
+use self::expectation::Expectation;
+
+/**
+    Creates the `expect` standard library module.
+
+    The module itself is callable, creating a new expectation for the given value.
+
+    # Errors
+
+    Errors when out of memory.
+*/
+pub fn module(lua: &Lua) -> LuaResult<LuaTable> {
+    TableBuilder::new_named(lua, "expect")?
+        .with_function("deepEqual", expect_deep_equal)?
+        .with_metatable(
+            TableBuilder::new(lua)?
+                .with_function(LuaMetaMethod::Call.name(), expect_call)?
+                .build_readonly()?,
+        )?
+        .build_readonly()
+}
+
+fn expect_call(lua: &Lua, (_, value): (LuaValue, LuaValue)) -> LuaResult<Expectation> {
+    Expectation::new(lua, value)
+}
+
+fn expect_deep_equal(
+    _: &Lua,
+    (actual, expected): (LuaValue, LuaValue),
+) -> LuaResult<(bool, Option<String>)> {
+    let differences = deep_diff(&actual, &expected)?;
+    if differences.is_empty() {
+        Ok((true, None))
+    } else {
+        Ok((false, Some(format_differences(&differences, ""))))
+    }
+}
@@ -0,0 +1,78 @@
+use mlua::prelude::*;
+
+const VECTOR_COMPONENTS: &[&str] = &["X", "Y", "Z"];
+
+/**
+    Gets the numeric components of a value that can be approximately compared.
+
+    Numbers have a single component, and vectors have one component per axis.
+    Vector userdata such as `Vector3` and `Vector2` are supported by reading
+    their `X`, `Y`, and `Z` fields, stopping at the first missing field.
+
+    Returns `None` if the value is not a number or a vector.
+*/
+pub fn near_components(value: &LuaValue) -> LuaResult<Option<Vec<f64>>> {
+    Ok(match value {
+        LuaValue::Integer(i) => Some(vec![f64::from(*i)]),
+        LuaValue::Number(n) => Some(vec![*n]),
+        LuaValue::Vector(v) => Some(vec![f64::from(v.x()), f64::from(v.y()), f64::from(v.z())]),
+        LuaValue::UserData(ud) => {
+            let mut components = Vec::new();
+            for name in VECTOR_COMPONENTS {
+                match index_userdata(ud, name) {
+                    Some(LuaValue::Integer(i)) => components.push(f64::from(i)),
+                    Some(LuaValue::Number(n)) => components.push(n),
+                    _ => break,
+                }
+            }
+            if components.len() >= 2 {
+                Some(components)
+            } else {
+                None
+            }
+        }
+        _ => None,
+    })
+}
+
+/**
+    Indexes a userdata using its `__index` metamethod, returning
+    `None` if the userdata has no such field or indexing errors.
+*/
+fn index_userdata<'lua>(ud: &LuaAnyUserData<'lua>, key: &str) -> Option<LuaValue<'lua>> {
+    let index = ud
+        .get_metatable()
+        .ok()?
+        .get::<LuaValue>(LuaMetaMethod::Index)
+        .ok()?;
+    match index {
+        LuaValue::Function(f) => f.call((ud.clone(), key)).ok(),
+        LuaValue::Table(t) => t.get(key).ok(),
+        _ => None,
+    }
+}
+
+/**
+    Gets the largest difference between any two components, or `None`
+    if the values do not have the same number of components.
+
+    If any difference is `NaN`, the result will also be `NaN`.
+*/
+pub fn max_difference(actual: &[f64], expected: &[f64]) -> Option<f64> {
+    if actual.len() != expected.len() {
+        return None;
+    }
+    Some(
+        actual
+            .iter()
+            .zip(expected)
+            .map(|(a, e)| (a - e).abs())
+            .fold(0.0, |max, diff| {
+                if diff.is_nan() || diff > max {
+                    diff
+                } else {
+                    max
+                }
+            }),
+    )
+}
@@ -0,0 +1,49 @@
+use bstr::BString;
+use mlua::prelude::*;
+
+const WRITESTRING_KEY: &str = "__lune_ffi_buffer_writestring";
+
+/**
+    Stores the `buffer.writestring` function, so that we can write into buffers
+    later on, even if the `buffer` global has been modified by a script.
+*/
+pub fn store_buffer_functions(lua: &Lua) -> LuaResult<()> {
+    let writestring = lua
+        .globals()
+        .get::<_, LuaTable>("buffer")?
+        .get::<_, LuaFunction>("writestring")?;
+    lua.set_named_registry_value(WRITESTRING_KEY, writestring)
+}
+
+/**
+    Reads the given range of bytes from a buffer, erroring if it is out of bounds.
+*/
+pub fn read_buffer(
+    lua: &Lua,
+    buffer: &LuaAnyUserData,
+    offset: usize,
+    len: usize,
+) -> LuaResult<Vec<u8>> {
+    let bytes: Vec<u8> = BString::from_lua(LuaValue::UserData(buffer.clone()), lua)?.into();
+    if offset.checked_add(len).is_none_or(|end| end > bytes.len()) {
+        return Err(LuaError::runtime(format!(
+            "Range {offset}..{} is out of bounds for buffer of length {}",
+            offset.saturating_add(len),
+            bytes.len()
+        )));
+    }
+    Ok(bytes[offset..offset + len].to_vec())
+}
+
+/**
+    Writes the given bytes into a buffer, starting at the given offset.
+*/
+pub fn write_buffer(
+    lua: &Lua,
+    buffer: &LuaAnyUserData,
+    offset: usize,
+    bytes: &[u8],
+) -> LuaResult<()> {
+    let writestring: LuaFunction = lua.named_registry_value(WRITESTRING_KEY)?;
+    writestring.call((buffer.clone(), offset, lua.create_string(bytes)?))
+}
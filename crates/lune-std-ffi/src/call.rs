@@ -0,0 +1,121 @@
+use std::ffi::c_void;
+
+/**
+    Maximum number of integer and pointer arguments that can be passed to a native function.
+*/
+pub const MAX_INT_ARGS: usize = 6;
+
+/**
+    Maximum number of floating point arguments that can be passed to a native function.
+*/
+pub const MAX_FLOAT_ARGS: usize = 8;
+
+/**
+    If calling native functions is supported on the current platform.
+*/
+pub const IS_SUPPORTED: bool = cfg!(all(
+    unix,
+    any(target_arch = "x86_64", target_arch = "aarch64")
+));
+
+/**
+    The register class that a native function returns its value in.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReturnClass {
+    Int,
+    Float,
+}
+
+/**
+    The raw value returned from a native function.
+*/
+#[derive(Debug, Clone, Copy)]
+pub enum RawReturn {
+    Int(u64),
+    Float(f64),
+}
+
+type NativeFn = unsafe extern "C" fn(
+    u64,
+    u64,
+    u64,
+    u64,
+    u64,
+    u64,
+    f64,
+    f64,
+    f64,
+    f64,
+    f64,
+    f64,
+    f64,
+    f64,
+) -> u64;
+
+type NativeFloatFn = unsafe extern "C" fn(
+    u64,
+    u64,
+    u64,
+    u64,
+    u64,
+    u64,
+    f64,
+    f64,
+    f64,
+    f64,
+    f64,
+    f64,
+    f64,
+    f64,
+) -> f64;
+
+/**
+    Calls the native function at the given address.
+
+    Integer and floating point arguments are given separately, in the order they appear in
+    the signature of the function, and must not exceed [`MAX_INT_ARGS`] and [`MAX_FLOAT_ARGS`].
+
+    NOTE: Instead of generating code for each function signature, we rely on the calling
+    conventions of the supported platforms (System V on x86-64, and AAPCS64 on ARM64) passing
+    the first integer and floating point arguments in separate sets of registers, assigned
+    in order within each set. This lets us call any function that only takes register
+    arguments as one taking the maximum number of both, with any unused registers ignored.
+
+    # Safety
+
+    Native calls must be supported on the current platform, see [`IS_SUPPORTED`].
+
+    The address must point to a non-variadic native function with a signature that takes
+    the given arguments and returns a value in the given register class, and calling the
+    function must be safe, which can not be verified.
+*/
+pub unsafe fn call_native(
+    address: *const c_void,
+    ints: &[u64],
+    floats: &[f64],
+    ret: ReturnClass,
+) -> RawReturn {
+    assert!(ints.len() <= MAX_INT_ARGS, "too many integer arguments");
+    assert!(floats.len() <= MAX_FLOAT_ARGS, "too many float arguments");
+
+    let mut i = [0u64; MAX_INT_ARGS];
+    let mut f = [0f64; MAX_FLOAT_ARGS];
+    i[..ints.len()].copy_from_slice(ints);
+    f[..floats.len()].copy_from_slice(floats);
+
+    match ret {
+        ReturnClass::Int => {
+            let func = std::mem::transmute::<*const c_void, NativeFn>(address);
+            RawReturn::Int(func(
+                i[0], i[1], i[2], i[3], i[4], i[5], f[0], f[1], f[2], f[3], f[4], f[5], f[6], f[7],
+            ))
+        }
+        ReturnClass::Float => {
+            let func = std::mem::transmute::<*const c_void, NativeFloatFn>(address);
+            RawReturn::Float(func(
+                i[0], i[1], i[2], i[3], i[4], i[5], f[0], f[1], f[2], f[3], f[4], f[5], f[6], f[7],
+            ))
+        }
+    }
+}
@@ -0,0 +1,74 @@
+#![allow(clippy::cargo_common_metadata)]
+
+use std::ffi::c_void;
+
+use mlua::prelude::*;
+
+use lune_utils::TableBuilder;
+
+mod buffer;
+mod call;
+mod library;
+mod structs;
+mod types;
+
+use self::buffer::store_buffer_functions;
+use self::library::FfiLibrary;
+use self::structs::FfiStruct;
+use self::types::FfiType;
+
+/**
+    Creates the `ffi` standard library module.
+
+    # Errors
+
+    Errors when out of memory, or if the `buffer` global is missing.
+*/
+pub fn module(lua: &Lua) -> LuaResult<LuaTable> {
+    store_buffer_functions(lua)?;
+    TableBuilder::new_named(lua, "ffi")?
+        .with_value("supported", call::IS_SUPPORTED)?
+        .with_value("null", LuaLightUserData(std::ptr::null_mut()))?
+        .with_function("open", |_, path: Option<String>| FfiLibrary::open(path))?
+        .with_function("struct", |_, fields: LuaTable| FfiStruct::new(&fields))?
+        .with_function("sizeOf", |_, ty: FfiType| Ok(ty.size()))?
+        .with_function("isNull", ffi_is_null)?
+        .with_function("string", ffi_string)?
+        .build_readonly()
+}
+
+fn ffi_is_null(_: &Lua, ptr: LuaValue) -> LuaResult<bool> {
+    match ptr {
+        LuaValue::Nil => Ok(true),
+        LuaValue::LightUserData(ud) => Ok(ud.0.is_null()),
+        value => Err(LuaError::runtime(format!(
+            "Expected a pointer, got {}",
+            value.type_name()
+        ))),
+    }
+}
+
+fn ffi_string<'lua>(
+    lua: &'lua Lua,
+    (ptr, len): (LuaValue<'lua>, Option<usize>),
+) -> LuaResult<LuaValue<'lua>> {
+    let ptr = match ptr {
+        LuaValue::Nil => return Ok(LuaValue::Nil),
+        LuaValue::LightUserData(ud) if ud.0.is_null() => return Ok(LuaValue::Nil),
+        LuaValue::LightUserData(ud) => ud.0.cast_const(),
+        value => {
+            return Err(LuaError::runtime(format!(
+                "Expected a pointer, got {}",
+                value.type_name()
+            )))
+        }
+    };
+    match len {
+        // SAFETY: Reading from arbitrary pointers can not be verified, but using ffi is already unsafe
+        Some(len) => unsafe {
+            let bytes = std::slice::from_raw_parts(ptr.cast::<u8>(), len);
+            lua.create_string(bytes)?.into_lua(lua)
+        },
+        None => unsafe { types::read_c_string(lua, ptr.cast::<c_void>()) },
+    }
+}
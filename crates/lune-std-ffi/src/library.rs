@@ -0,0 +1,204 @@
+use std::{
+    ffi::{c_void, CString},
+    rc::Rc,
+};
+
+use bstr::BString;
+use mlua::prelude::*;
+
+use crate::{
+    buffer::write_buffer,
+    call::{call_native, RawReturn, ReturnClass, IS_SUPPORTED, MAX_FLOAT_ARGS, MAX_INT_ARGS},
+    types::FfiType,
+};
+
+/**
+    A native library that has been opened using `ffi.open`.
+*/
+pub struct FfiLibrary {
+    name: String,
+    inner: Rc<libloading::Library>,
+}
+
+impl FfiLibrary {
+    pub fn open(path: Option<String>) -> LuaResult<Self> {
+        let (name, inner) = match path {
+            // SAFETY: Loading a library runs its initialization routines,
+            // which can not be verified, but using ffi is already unsafe
+            Some(path) => {
+                let inner = unsafe { libloading::Library::new(&path) }.into_lua_err()?;
+                (path, inner)
+            }
+            None => ("<process>".to_string(), open_current_process()?),
+        };
+        Ok(Self {
+            name,
+            inner: Rc::new(inner),
+        })
+    }
+
+    fn symbol(&self, name: &str) -> LuaResult<*const c_void> {
+        let mut symbol_name = name.as_bytes().to_vec();
+        symbol_name.push(0);
+        // SAFETY: We only read the address of the symbol here
+        let symbol = unsafe { self.inner.get::<*const c_void>(&symbol_name) }
+            .map_err(|e| LuaError::runtime(format!("Failed to find symbol '{name}' - {e}")))?;
+        Ok(*symbol)
+    }
+
+    fn create_function<'lua>(
+        &self,
+        lua: &'lua Lua,
+        name: &str,
+        params: Vec<FfiType>,
+        ret: FfiType,
+    ) -> LuaResult<LuaFunction<'lua>> {
+        if !IS_SUPPORTED {
+            return Err(LuaError::runtime(
+                "Calling native functions is not supported on this platform",
+            ));
+        }
+        if let Some(param) = params.iter().find(|param| **param == FfiType::Void) {
+            return Err(LuaError::runtime(format!(
+                "Parameters of function '{name}' can not have the type '{param}'"
+            )));
+        }
+
+        let float_count = params.iter().filter(|param| param.is_float()).count();
+        let int_count = params.len() - float_count;
+        if int_count > MAX_INT_ARGS || float_count > MAX_FLOAT_ARGS {
+            return Err(LuaError::runtime(format!(
+                "Function '{name}' has too many parameters - at most {MAX_INT_ARGS} \
+                integer or pointer and {MAX_FLOAT_ARGS} floating point parameters are supported"
+            )));
+        }
+
+        let address = self.symbol(name)?;
+        let library = Rc::clone(&self.inner);
+        let name = name.to_string();
+
+        lua.create_function(move |lua, args: LuaMultiValue| {
+            // NOTE: Keep the library loaded for as long as the function exists
+            let _library = &library;
+
+            if args.len() != params.len() {
+                return Err(LuaError::runtime(format!(
+                    "Function '{name}' expected {} arguments, got {}",
+                    params.len(),
+                    args.len()
+                )));
+            }
+
+            let mut storage = CallStorage::default();
+            let mut ints = Vec::with_capacity(int_count);
+            let mut floats = Vec::with_capacity(float_count);
+            for (index, (param, arg)) in params.iter().zip(args.iter()).enumerate() {
+                let converted = if param.is_float() {
+                    param.float_bits(arg).map(|bits| floats.push(bits))
+                } else {
+                    storage
+                        .int_bits(lua, *param, arg)
+                        .map(|bits| ints.push(bits))
+                };
+                converted.map_err(|e| {
+                    e.context(format!("Invalid argument #{} to '{name}'", index + 1))
+                })?;
+            }
+
+            let class = if ret.is_float() {
+                ReturnClass::Float
+            } else {
+                ReturnClass::Int
+            };
+
+            // SAFETY: The signature was declared by the script, we can not verify it
+            let result = unsafe { call_native(address, &ints, &floats, class) };
+
+            // NOTE: Returned pointers may point into the argument storage,
+            // so the return value must be converted before it is dropped
+            let value = match result {
+                RawReturn::Int(bits) => ret.int_bits_to_lua(lua, bits)?,
+                RawReturn::Float(bits) => ret.float_bits_to_lua(bits),
+            };
+
+            storage.write_back(lua)?;
+
+            Ok(value)
+        })
+    }
+}
+
+impl LuaUserData for FfiLibrary {
+    fn add_fields<'lua, F: LuaUserDataFields<'lua, Self>>(fields: &mut F) {
+        fields.add_field_method_get("name", |_, this| Ok(this.name.clone()));
+    }
+
+    fn add_methods<'lua, M: LuaUserDataMethods<'lua, Self>>(methods: &mut M) {
+        methods.add_method(
+            "func",
+            |lua, this, (name, params, ret): (String, Vec<FfiType>, Option<FfiType>)| {
+                this.create_function(lua, &name, params, ret.unwrap_or(FfiType::Void))
+            },
+        );
+        methods.add_method("has", |_, this, name: String| {
+            Ok(this.symbol(&name).is_ok())
+        });
+        methods.add_meta_method(LuaMetaMethod::ToString, |_, this, ()| {
+            Ok(format!("FfiLibrary({})", this.name))
+        });
+    }
+}
+
+/**
+    Storage for values that must stay alive for the duration of a native call.
+
+    Strings are copied into nul-terminated C strings, and buffers are copied into
+    temporary memory, which is copied back into the buffers after the call returns.
+*/
+#[derive(Default)]
+struct CallStorage<'lua> {
+    strings: Vec<CString>,
+    buffers: Vec<(LuaAnyUserData<'lua>, Vec<u8>)>,
+}
+
+impl<'lua> CallStorage<'lua> {
+    fn int_bits(&mut self, lua: &'lua Lua, ty: FfiType, value: &LuaValue<'lua>) -> LuaResult<u64> {
+        match (ty, value) {
+            (FfiType::Pointer | FfiType::String, LuaValue::String(s)) => {
+                let s = CString::new(s.as_bytes()).map_err(|_| {
+                    LuaError::runtime("Strings passed to native functions can not contain nul")
+                })?;
+                let ptr = s.as_ptr();
+                self.strings.push(s);
+                Ok(ptr as usize as u64)
+            }
+            (FfiType::Pointer, LuaValue::UserData(ud)) if value.is_buffer() => {
+                let bytes = BString::from_lua(value.clone(), lua)?;
+                let mut bytes: Vec<u8> = bytes.into();
+                let ptr = bytes.as_mut_ptr();
+                self.buffers.push((ud.clone(), bytes));
+                Ok(ptr as usize as u64)
+            }
+            (ty, value) => ty.int_bits(value),
+        }
+    }
+
+    fn write_back(self, lua: &'lua Lua) -> LuaResult<()> {
+        for (buffer, bytes) in self.buffers {
+            write_buffer(lua, &buffer, 0, &bytes)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(unix)]
+fn open_current_process() -> LuaResult<libloading::Library> {
+    Ok(libloading::os::unix::Library::this().into())
+}
+
+#[cfg(windows)]
+fn open_current_process() -> LuaResult<libloading::Library> {
+    Ok(libloading::os::windows::Library::this()
+        .into_lua_err()?
+        .into())
+}
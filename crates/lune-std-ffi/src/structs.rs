@@ -0,0 +1,176 @@
+use mlua::prelude::*;
+
+use crate::{
+    buffer::{read_buffer, write_buffer},
+    types::FfiType,
+};
+
+struct StructField {
+    name: String,
+    ty: FfiType,
+    offset: usize,
+}
+
+/**
+    The layout of a C struct, created using `ffi.struct`.
+
+    Fields are laid out in order using the alignment rules of C, and
+    values are read from and written to buffers in native byte order.
+*/
+pub struct FfiStruct {
+    fields: Vec<StructField>,
+    size: usize,
+    alignment: usize,
+}
+
+impl FfiStruct {
+    pub fn new(definition: &LuaTable) -> LuaResult<Self> {
+        let mut fields: Vec<StructField> = Vec::new();
+        let mut size = 0usize;
+        let mut alignment = 1;
+
+        for entry in definition.clone().sequence_values::<LuaTable>() {
+            let entry = entry
+                .map_err(|e| e.context("Struct fields must be given as { name, type } pairs"))?;
+            let name: String = entry.get(1)?;
+            let ty: FfiType = entry.get(2)?;
+
+            if matches!(ty, FfiType::Void | FfiType::String) {
+                return Err(LuaError::runtime(format!(
+                    "Struct field '{name}' can not have the type '{ty}'"
+                )));
+            }
+            if fields.iter().any(|field| field.name == name) {
+                return Err(LuaError::runtime(format!(
+                    "Struct field '{name}' was given more than once"
+                )));
+            }
+
+            let offset = size.next_multiple_of(ty.alignment());
+            size = offset + ty.size();
+            alignment = alignment.max(ty.alignment());
+            fields.push(StructField { name, ty, offset });
+        }
+
+        if fields.is_empty() {
+            return Err(LuaError::runtime("Structs must have at least one field"));
+        }
+
+        Ok(Self {
+            fields,
+            size: size.next_multiple_of(alignment),
+            alignment,
+        })
+    }
+
+    fn encode(&self, values: Option<&LuaTable>) -> LuaResult<Vec<u8>> {
+        let mut bytes = vec![0; self.size];
+        let Some(values) = values else {
+            return Ok(bytes);
+        };
+
+        for field in &self.fields {
+            let value: LuaValue = values.get(field.name.as_str())?;
+            if value.is_nil() && field.ty != FfiType::Pointer {
+                continue;
+            }
+            let encoded = encode_value(field.ty, &value)
+                .map_err(|e| e.context(format!("Invalid value for field '{}'", field.name)))?;
+            bytes[field.offset..field.offset + encoded.len()].copy_from_slice(&encoded);
+        }
+
+        Ok(bytes)
+    }
+
+    fn decode<'lua>(&self, lua: &'lua Lua, bytes: &[u8]) -> LuaResult<LuaTable<'lua>> {
+        let table = lua.create_table_with_capacity(0, self.fields.len())?;
+        for field in &self.fields {
+            let bytes = &bytes[field.offset..field.offset + field.ty.size()];
+            table.set(field.name.as_str(), decode_value(lua, field.ty, bytes)?)?;
+        }
+        Ok(table)
+    }
+}
+
+impl LuaUserData for FfiStruct {
+    fn add_fields<'lua, F: LuaUserDataFields<'lua, Self>>(fields: &mut F) {
+        fields.add_field_method_get("size", |_, this| Ok(this.size));
+        fields.add_field_method_get("alignment", |_, this| Ok(this.alignment));
+    }
+
+    fn add_methods<'lua, M: LuaUserDataMethods<'lua, Self>>(methods: &mut M) {
+        methods.add_method("new", |lua, this, values: Option<LuaTable>| {
+            lua.create_buffer(this.encode(values.as_ref())?)
+        });
+        methods.add_method(
+            "read",
+            |lua, this, (buffer, offset): (LuaAnyUserData, Option<usize>)| {
+                let bytes = read_buffer(lua, &buffer, offset.unwrap_or_default(), this.size)?;
+                this.decode(lua, &bytes)
+            },
+        );
+        methods.add_method(
+            "write",
+            |lua, this, (buffer, offset, values): (LuaAnyUserData, Option<usize>, LuaTable)| {
+                let offset = offset.unwrap_or_default();
+                read_buffer(lua, &buffer, offset, this.size)?;
+                write_buffer(lua, &buffer, offset, &this.encode(Some(&values))?)
+            },
+        );
+        methods.add_method("offsetOf", |_, this, name: String| {
+            this.fields
+                .iter()
+                .find(|field| field.name == name)
+                .map(|field| field.offset)
+                .ok_or_else(|| LuaError::runtime(format!("Struct has no field named '{name}'")))
+        });
+        methods.add_meta_method(LuaMetaMethod::ToString, |_, this, ()| {
+            let fields = this
+                .fields
+                .iter()
+                .map(|field| format!("{}: {}", field.name, field.ty))
+                .collect::<Vec<_>>();
+            Ok(format!("FfiStruct({})", fields.join(", ")))
+        });
+    }
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn encode_value(ty: FfiType, value: &LuaValue) -> LuaResult<Vec<u8>> {
+    Ok(match ty {
+        // NOTE: 32-bit floats are stored in the lower half of the 64-bit register bits
+        FfiType::F32 => (ty.float_bits(value)?.to_bits() as u32)
+            .to_ne_bytes()
+            .to_vec(),
+        FfiType::F64 => ty.float_bits(value)?.to_ne_bytes().to_vec(),
+        _ => {
+            let bits = ty.int_bits(value)?;
+            match ty.size() {
+                1 => (bits as u8).to_ne_bytes().to_vec(),
+                2 => (bits as u16).to_ne_bytes().to_vec(),
+                4 => (bits as u32).to_ne_bytes().to_vec(),
+                _ => bits.to_ne_bytes().to_vec(),
+            }
+        }
+    })
+}
+
+fn decode_value<'lua>(lua: &'lua Lua, ty: FfiType, bytes: &[u8]) -> LuaResult<LuaValue<'lua>> {
+    let bits = match (ty, bytes.len()) {
+        (FfiType::F32, _) => {
+            let n = f32::from_ne_bytes(bytes.try_into().expect("f32 must be 4 bytes"));
+            return Ok(LuaValue::Number(f64::from(n)));
+        }
+        (FfiType::F64, _) => {
+            let n = f64::from_ne_bytes(bytes.try_into().expect("f64 must be 8 bytes"));
+            return Ok(LuaValue::Number(n));
+        }
+        (_, 1) => u64::from(bytes[0]),
+        (_, 2) => u64::from(u16::from_ne_bytes([bytes[0], bytes[1]])),
+        (_, 4) => u64::from(u32::from_ne_bytes(
+            bytes.try_into().expect("checked length"),
+        )),
+        _ => u64::from_ne_bytes(bytes.try_into().expect("checked length")),
+    };
+    ty.int_bits_to_lua(lua, bits)
+}
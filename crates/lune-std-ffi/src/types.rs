@@ -0,0 +1,283 @@
+use std::{ffi::c_void, fmt, mem, str::FromStr};
+
+use mlua::prelude::*;
+
+/**
+    A C type that can be used in function signatures and struct fields.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FfiType {
+    Void,
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    I64,
+    U64,
+    Isize,
+    Usize,
+    F32,
+    F64,
+    Pointer,
+    String,
+}
+
+impl FfiType {
+    pub const ALL: [Self; 15] = [
+        Self::Void,
+        Self::I8,
+        Self::U8,
+        Self::I16,
+        Self::U16,
+        Self::I32,
+        Self::U32,
+        Self::I64,
+        Self::U64,
+        Self::Isize,
+        Self::Usize,
+        Self::F32,
+        Self::F64,
+        Self::Pointer,
+        Self::String,
+    ];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Void => "void",
+            Self::I8 => "i8",
+            Self::U8 => "u8",
+            Self::I16 => "i16",
+            Self::U16 => "u16",
+            Self::I32 => "i32",
+            Self::U32 => "u32",
+            Self::I64 => "i64",
+            Self::U64 => "u64",
+            Self::Isize => "isize",
+            Self::Usize => "usize",
+            Self::F32 => "f32",
+            Self::F64 => "f64",
+            Self::Pointer => "pointer",
+            Self::String => "string",
+        }
+    }
+
+    /**
+        Returns the size of the type, in bytes.
+    */
+    pub const fn size(self) -> usize {
+        match self {
+            Self::Void => 0,
+            Self::I8 | Self::U8 => 1,
+            Self::I16 | Self::U16 => 2,
+            Self::I32 | Self::U32 | Self::F32 => 4,
+            Self::I64 | Self::U64 | Self::F64 => 8,
+            Self::Isize | Self::Usize | Self::Pointer | Self::String => mem::size_of::<usize>(),
+        }
+    }
+
+    /**
+        Returns the alignment of the type, in bytes.
+
+        All supported types are aligned to their own size.
+    */
+    pub const fn alignment(self) -> usize {
+        match self {
+            Self::Void => 1,
+            _ => self.size(),
+        }
+    }
+
+    /**
+        Returns `true` if values of this type are passed in floating point registers.
+    */
+    pub const fn is_float(self) -> bool {
+        matches!(self, Self::F32 | Self::F64)
+    }
+
+    /**
+        Converts a Lua value into the raw bits of an integer or pointer of this type.
+
+        Pointers may be given as `nil` or light userdata, other values such
+        as strings and buffers must be handled by the caller beforehand.
+    */
+    pub fn int_bits(self, value: &LuaValue) -> LuaResult<u64> {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let bits = match self {
+            Self::I8 => self.integer(value, f64::from(i8::MIN), f64::from(i8::MAX))? as i64 as u64,
+            Self::U8 => self.integer(value, 0.0, f64::from(u8::MAX))? as u64,
+            Self::I16 => {
+                self.integer(value, f64::from(i16::MIN), f64::from(i16::MAX))? as i64 as u64
+            }
+            Self::U16 => self.integer(value, 0.0, f64::from(u16::MAX))? as u64,
+            Self::I32 => {
+                self.integer(value, f64::from(i32::MIN), f64::from(i32::MAX))? as i64 as u64
+            }
+            Self::U32 => self.integer(value, 0.0, f64::from(u32::MAX))? as u64,
+            Self::I64 | Self::Isize => {
+                #[allow(clippy::cast_precision_loss)]
+                let n = self.integer(value, i64::MIN as f64, i64::MAX as f64)?;
+                n as i64 as u64
+            }
+            Self::U64 | Self::Usize => {
+                #[allow(clippy::cast_precision_loss)]
+                let n = self.integer(value, 0.0, u64::MAX as f64)?;
+                n as u64
+            }
+            Self::Pointer | Self::String => match value {
+                LuaValue::Nil => 0,
+                LuaValue::LightUserData(ud) => ud.0 as usize as u64,
+                _ => return Err(self.mismatch(value)),
+            },
+            Self::Void | Self::F32 | Self::F64 => {
+                unreachable!("int_bits called for non-integer type")
+            }
+        };
+        Ok(bits)
+    }
+
+    /**
+        Converts a Lua value into the raw bits of a floating point number of
+        this type, as passed in a 64-bit floating point register.
+
+        Note that 32-bit floats are passed in the lower half of the register.
+    */
+    pub fn float_bits(self, value: &LuaValue) -> LuaResult<f64> {
+        let n = match value {
+            LuaValue::Integer(i) => {
+                #[allow(clippy::cast_precision_loss)]
+                let n = *i as f64;
+                n
+            }
+            LuaValue::Number(n) => *n,
+            _ => return Err(self.mismatch(value)),
+        };
+        Ok(match self {
+            #[allow(clippy::cast_possible_truncation)]
+            Self::F32 => f64::from_bits(u64::from((n as f32).to_bits())),
+            Self::F64 => n,
+            _ => unreachable!("float_bits called for non-float type"),
+        })
+    }
+
+    /**
+        Converts the raw bits of an integer or pointer of this type into a Lua value.
+    */
+    #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+    pub fn int_bits_to_lua(self, lua: &Lua, bits: u64) -> LuaResult<LuaValue> {
+        #[allow(clippy::cast_precision_loss)]
+        let n = match self {
+            Self::Void => return Ok(LuaValue::Nil),
+            Self::I8 => f64::from(bits as i8),
+            Self::U8 => f64::from(bits as u8),
+            Self::I16 => f64::from(bits as i16),
+            Self::U16 => f64::from(bits as u16),
+            Self::I32 => f64::from(bits as i32),
+            Self::U32 => f64::from(bits as u32),
+            Self::I64 | Self::Isize => bits as i64 as f64,
+            Self::U64 | Self::Usize => bits as f64,
+            Self::Pointer => {
+                return Ok(match bits {
+                    0 => LuaValue::Nil,
+                    _ => LuaValue::LightUserData(LuaLightUserData(bits as usize as *mut c_void)),
+                })
+            }
+            Self::String => {
+                return match bits {
+                    0 => Ok(LuaValue::Nil),
+                    // SAFETY: The function signature declared that this is a C string
+                    _ => unsafe { read_c_string(lua, bits as usize as *const c_void) },
+                };
+            }
+            Self::F32 | Self::F64 => unreachable!("int_bits_to_lua called for float type"),
+        };
+        Ok(LuaValue::Number(n))
+    }
+
+    /**
+        Converts the raw bits of a floating point register into a Lua value.
+    */
+    pub fn float_bits_to_lua(self, bits: f64) -> LuaValue<'static> {
+        match self {
+            #[allow(clippy::cast_possible_truncation)]
+            Self::F32 => LuaValue::Number(f64::from(f32::from_bits(bits.to_bits() as u32))),
+            _ => LuaValue::Number(bits),
+        }
+    }
+
+    fn integer(self, value: &LuaValue, min: f64, max: f64) -> LuaResult<f64> {
+        let n = match value {
+            LuaValue::Integer(i) => {
+                #[allow(clippy::cast_precision_loss)]
+                let n = *i as f64;
+                n
+            }
+            LuaValue::Number(n) => *n,
+            LuaValue::Boolean(b) => f64::from(u8::from(*b)),
+            _ => return Err(self.mismatch(value)),
+        };
+        if n.fract() != 0.0 || n < min || n > max {
+            return Err(LuaError::runtime(format!(
+                "Number {n} is not a valid value for type '{}'",
+                self.name()
+            )));
+        }
+        Ok(n)
+    }
+
+    fn mismatch(self, value: &LuaValue) -> LuaError {
+        LuaError::runtime(format!(
+            "Expected a value for type '{}', got {}",
+            self.name(),
+            value.type_name()
+        ))
+    }
+}
+
+impl fmt::Display for FfiType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for FfiType {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|ty| ty.name() == s)
+            .ok_or_else(|| {
+                format!(
+                    "Invalid ffi type '{s}' - expected one of {}",
+                    Self::ALL.map(Self::name).join(", ")
+                )
+            })
+    }
+}
+
+impl<'lua> FromLua<'lua> for FfiType {
+    fn from_lua(value: LuaValue<'lua>, _: &'lua Lua) -> LuaResult<Self> {
+        match value {
+            LuaValue::String(s) => s.to_str()?.parse().map_err(LuaError::runtime),
+            _ => Err(LuaError::FromLuaConversionError {
+                from: value.type_name(),
+                to: "FfiType",
+                message: Some("expected a type name string".to_string()),
+            }),
+        }
+    }
+}
+
+/**
+    Reads a nul-terminated C string at the given address into a Lua string.
+
+    # Safety
+
+    The address must point to a valid nul-terminated string.
+*/
+pub unsafe fn read_c_string(lua: &Lua, ptr: *const c_void) -> LuaResult<LuaValue> {
+    let s = std::ffi::CStr::from_ptr(ptr.cast());
+    lua.create_string(s.to_bytes())?.into_lua(lua)
+}
@@ -33,7 +33,9 @@ async fn get_contents_at(root: PathBuf, _: FsWriteOptions) -> LuaResult<CopyCont
     // when we find any new descendant directories
     // FUTURE: Try to do async reading here concurrently to speed it up a bit
     while let Some((current_depth, current_path)) = queue.pop_front() {
-        let meta = fs::metadata(&current_path).await?;
+        // NOTE: Symlinks must not be followed here, since they could point outside
+        // of the source directory, and read permissions are only checked for it
+        let meta = fs::symlink_metadata(&current_path).await?;
         if meta.is_symlink() {
             return Err(LuaError::RuntimeError(format!(
                 "Symlinks are not yet supported, encountered at path '{}'",
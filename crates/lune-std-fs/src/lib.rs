@@ -5,17 +5,28 @@ use std::path::PathBuf;
 
 use bstr::{BString, ByteSlice};
 use mlua::prelude::*;
+use mlua_luau_scheduler::LuaSpawnExt;
 use tokio::fs;
+use tracing::instrument;
 
+use lune_std_serde::HashAlgorithm;
 use lune_utils::TableBuilder;
 
 mod copy;
 mod metadata;
 mod options;
+mod permissions;
+mod watch;
+
+pub use self::permissions::{
+    allow_read_paths, allow_write_paths, allowed_read_paths, allowed_write_paths, check_read,
+    check_write, is_restricted,
+};
 
 use self::copy::copy;
 use self::metadata::FsMetadata;
-use self::options::FsWriteOptions;
+use self::options::{FsWatchOptions, FsWriteOptions};
+use self::watch::watch;
 
 /**
     Creates the `fs` standard library module.
@@ -25,8 +36,9 @@ use self::options::FsWriteOptions;
     Errors when out of memory.
 */
 pub fn module(lua: &Lua) -> LuaResult<LuaTable> {
-    TableBuilder::new(lua)?
+    TableBuilder::new_named(lua, "fs")?
         .with_async_function("readFile", fs_read_file)?
+        .with_async_function("readFileBuffer", fs_read_file_buffer)?
         .with_async_function("readDir", fs_read_dir)?
         .with_async_function("writeFile", fs_write_file)?
         .with_async_function("writeDir", fs_write_dir)?
@@ -37,16 +49,30 @@ pub fn module(lua: &Lua) -> LuaResult<LuaTable> {
         .with_async_function("isDir", fs_is_dir)?
         .with_async_function("move", fs_move)?
         .with_async_function("copy", fs_copy)?
+        .with_async_function("hash", fs_hash)?
+        .with_function("watch", fs_watch)?
         .build_readonly()
 }
 
+#[instrument(level = "debug", name = "fs.readFile", skip_all, fields(path = %path))]
 async fn fs_read_file(lua: &Lua, path: String) -> LuaResult<LuaString> {
+    check_read(lua, &path)?;
     let bytes = fs::read(&path).await.into_lua_err()?;
 
     lua.create_string(bytes)
 }
 
-async fn fs_read_dir(_: &Lua, path: String) -> LuaResult<Vec<String>> {
+#[instrument(level = "debug", name = "fs.readFileBuffer", skip_all, fields(path = %path))]
+async fn fs_read_file_buffer(lua: &Lua, path: String) -> LuaResult<LuaAnyUserData> {
+    check_read(lua, &path)?;
+    let bytes = fs::read(&path).await.into_lua_err()?;
+
+    lua.create_buffer(bytes)
+}
+
+#[instrument(level = "debug", name = "fs.readDir", skip_all, fields(path = %path))]
+async fn fs_read_dir(lua: &Lua, path: String) -> LuaResult<Vec<String>> {
+    check_read(lua, &path)?;
     let mut dir_strings = Vec::new();
     let mut dir = fs::read_dir(&path).await.into_lua_err()?;
     while let Some(dir_entry) = dir.next_entry().await.into_lua_err()? {
@@ -62,23 +88,32 @@ async fn fs_read_dir(_: &Lua, path: String) -> LuaResult<Vec<String>> {
     Ok(dir_strings)
 }
 
-async fn fs_write_file(_: &Lua, (path, contents): (String, BString)) -> LuaResult<()> {
+#[instrument(level = "debug", name = "fs.writeFile", skip_all, fields(path = %path, bytes = contents.len()))]
+async fn fs_write_file(lua: &Lua, (path, contents): (String, BString)) -> LuaResult<()> {
+    check_write(lua, &path)?;
     fs::write(&path, contents.as_bytes()).await.into_lua_err()
 }
 
-async fn fs_write_dir(_: &Lua, path: String) -> LuaResult<()> {
+#[instrument(level = "debug", name = "fs.writeDir", skip_all, fields(path = %path))]
+async fn fs_write_dir(lua: &Lua, path: String) -> LuaResult<()> {
+    check_write(lua, &path)?;
     fs::create_dir_all(&path).await.into_lua_err()
 }
 
-async fn fs_remove_file(_: &Lua, path: String) -> LuaResult<()> {
+#[instrument(level = "debug", name = "fs.removeFile", skip_all, fields(path = %path))]
+async fn fs_remove_file(lua: &Lua, path: String) -> LuaResult<()> {
+    check_write(lua, &path)?;
     fs::remove_file(&path).await.into_lua_err()
 }
 
-async fn fs_remove_dir(_: &Lua, path: String) -> LuaResult<()> {
+#[instrument(level = "debug", name = "fs.removeDir", skip_all, fields(path = %path))]
+async fn fs_remove_dir(lua: &Lua, path: String) -> LuaResult<()> {
+    check_write(lua, &path)?;
     fs::remove_dir_all(&path).await.into_lua_err()
 }
 
-async fn fs_metadata(_: &Lua, path: String) -> LuaResult<FsMetadata> {
+async fn fs_metadata(lua: &Lua, path: String) -> LuaResult<FsMetadata> {
+    check_read(lua, &path)?;
     match fs::metadata(path).await {
         Err(e) if e.kind() == IoErrorKind::NotFound => Ok(FsMetadata::not_found()),
         Ok(meta) => Ok(FsMetadata::from(meta)),
@@ -86,7 +121,8 @@ async fn fs_metadata(_: &Lua, path: String) -> LuaResult<FsMetadata> {
     }
 }
 
-async fn fs_is_file(_: &Lua, path: String) -> LuaResult<bool> {
+async fn fs_is_file(lua: &Lua, path: String) -> LuaResult<bool> {
+    check_read(lua, &path)?;
     match fs::metadata(path).await {
         Err(e) if e.kind() == IoErrorKind::NotFound => Ok(false),
         Ok(meta) => Ok(meta.is_file()),
@@ -94,7 +130,8 @@ async fn fs_is_file(_: &Lua, path: String) -> LuaResult<bool> {
     }
 }
 
-async fn fs_is_dir(_: &Lua, path: String) -> LuaResult<bool> {
+async fn fs_is_dir(lua: &Lua, path: String) -> LuaResult<bool> {
+    check_read(lua, &path)?;
     match fs::metadata(path).await {
         Err(e) if e.kind() == IoErrorKind::NotFound => Ok(false),
         Ok(meta) => Ok(meta.is_dir()),
@@ -102,7 +139,13 @@ async fn fs_is_dir(_: &Lua, path: String) -> LuaResult<bool> {
     }
 }
 
-async fn fs_move(_: &Lua, (from, to, options): (String, String, FsWriteOptions)) -> LuaResult<()> {
+#[instrument(level = "debug", name = "fs.move", skip_all, fields(from = %from, to = %to))]
+async fn fs_move(
+    lua: &Lua,
+    (from, to, options): (String, String, FsWriteOptions),
+) -> LuaResult<()> {
+    check_write(lua, &from)?;
+    check_write(lua, &to)?;
     let path_from = PathBuf::from(from);
     if !path_from.exists() {
         return Err(LuaError::RuntimeError(format!(
@@ -117,10 +160,49 @@ async fn fs_move(_: &Lua, (from, to, options): (String, String, FsWriteOptions))
             path_to.display()
         )));
     }
-    fs::rename(path_from, path_to).await.into_lua_err()?;
-    Ok(())
+    match fs::rename(&path_from, &path_to).await {
+        // NOTE: Renaming does not work across filesystems or drives,
+        // so we fall back to copying and then removing the original
+        Err(e) if e.kind() == IoErrorKind::CrossesDevices => {
+            copy(&path_from, &path_to, options).await?;
+            if fs::metadata(&path_from).await?.is_dir() {
+                fs::remove_dir_all(path_from).await.into_lua_err()
+            } else {
+                fs::remove_file(path_from).await.into_lua_err()
+            }
+        }
+        res => res.into_lua_err(),
+    }
 }
 
-async fn fs_copy(_: &Lua, (from, to, options): (String, String, FsWriteOptions)) -> LuaResult<()> {
+#[instrument(level = "debug", name = "fs.copy", skip_all, fields(from = %from, to = %to))]
+async fn fs_copy(
+    lua: &Lua,
+    (from, to, options): (String, String, FsWriteOptions),
+) -> LuaResult<()> {
+    check_read(lua, &from)?;
+    check_write(lua, &to)?;
     copy(from, to, options).await
 }
+
+#[instrument(level = "debug", name = "fs.hash", skip_all, fields(path = %path))]
+async fn fs_hash(lua: &Lua, (path, algorithm): (String, HashAlgorithm)) -> LuaResult<String> {
+    check_read(lua, &path)?;
+    // NOTE: Files are streamed through the hasher on a background thread,
+    // so that hashing large files neither blocks nor needs to fit in memory
+    lua.spawn_blocking(move || {
+        let file = std::fs::File::open(&path)?;
+        algorithm.hash_reader(file)
+    })
+    .await
+    .into_lua_err()
+}
+
+#[instrument(level = "debug", name = "fs.watch", skip_all, fields(path = %path))]
+fn fs_watch<'lua>(
+    lua: &'lua Lua,
+    (path, handler, options): (String, LuaFunction<'lua>, FsWatchOptions),
+) -> LuaResult<LuaTable<'lua>> {
+    check_read(lua, &path)?;
+    watch(lua, path.as_ref(), handler, options)
+}
@@ -97,6 +97,7 @@ impl<'lua> IntoLua<'lua> for FsPermissions {
 pub struct FsMetadata {
     pub(crate) kind: FsMetadataKind,
     pub(crate) exists: bool,
+    pub(crate) size: Option<u64>,
     pub(crate) created_at: Option<DateTime>,
     pub(crate) modified_at: Option<DateTime>,
     pub(crate) accessed_at: Option<DateTime>,
@@ -108,6 +109,7 @@ impl FsMetadata {
         Self {
             kind: FsMetadataKind::None,
             exists: false,
+            size: None,
             created_at: None,
             modified_at: None,
             accessed_at: None,
@@ -118,9 +120,10 @@ impl FsMetadata {
 
 impl<'lua> IntoLua<'lua> for FsMetadata {
     fn into_lua(self, lua: &'lua Lua) -> LuaResult<LuaValue<'lua>> {
-        let tab = lua.create_table_with_capacity(0, 6)?;
+        let tab = lua.create_table_with_capacity(0, 7)?;
         tab.set("kind", self.kind)?;
         tab.set("exists", self.exists)?;
+        tab.set("size", self.size)?;
         tab.set("createdAt", self.created_at)?;
         tab.set("modifiedAt", self.modified_at)?;
         tab.set("accessedAt", self.accessed_at)?;
@@ -135,6 +138,7 @@ impl From<StdMetadata> for FsMetadata {
         Self {
             kind: value.file_type().into(),
             exists: true,
+            size: Some(value.len()),
             created_at: system_time_to_timestamp(value.created()),
             modified_at: system_time_to_timestamp(value.modified()),
             accessed_at: system_time_to_timestamp(value.accessed()),
@@ -29,3 +29,32 @@ impl<'lua> FromLua<'lua> for FsWriteOptions {
         })
     }
 }
+
+#[derive(Debug, Clone, Copy)]
+pub struct FsWatchOptions {
+    pub(crate) recursive: bool,
+}
+
+impl<'lua> FromLua<'lua> for FsWatchOptions {
+    fn from_lua(value: LuaValue<'lua>, _: &'lua Lua) -> LuaResult<Self> {
+        Ok(match value {
+            LuaValue::Nil => Self { recursive: true },
+            LuaValue::Table(t) => {
+                let recursive: Option<bool> = t.get("recursive")?;
+                Self {
+                    recursive: recursive.unwrap_or(true),
+                }
+            }
+            _ => {
+                return Err(LuaError::FromLuaConversionError {
+                    from: value.type_name(),
+                    to: "FsWatchOptions",
+                    message: Some(format!(
+                        "Invalid watch options - expected table, got {}",
+                        value.type_name()
+                    )),
+                })
+            }
+        })
+    }
+}
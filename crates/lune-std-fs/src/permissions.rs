@@ -0,0 +1,180 @@
+use std::path::{Path, PathBuf};
+
+use mlua::prelude::*;
+
+use lune_utils::{
+    path::{clean_path, get_current_dir},
+    CodedError,
+};
+
+/**
+    Paths that filesystem operations are restricted to.
+
+    A `None` list means that no restrictions have been set up,
+    and that any path may be accessed using that operation.
+*/
+#[derive(Debug, Default)]
+struct FsPermissions {
+    read: Option<Vec<PathBuf>>,
+    write: Option<Vec<PathBuf>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum FsAccess {
+    Read,
+    Write,
+}
+
+/**
+    Restricts reading from the filesystem to the given paths, and any paths inside of them.
+
+    This may be called multiple times to allow more paths to be read from.
+*/
+pub fn allow_read_paths<P: AsRef<Path>>(lua: &Lua, paths: impl IntoIterator<Item = P>) {
+    update_permissions(lua, |perms| {
+        let read = perms.read.get_or_insert_with(Vec::new);
+        read.extend(paths.into_iter().map(|p| resolve_path(p.as_ref())));
+    });
+}
+
+/**
+    Restricts writing to the filesystem to the given paths, and any paths inside of them.
+
+    This may be called multiple times to allow more paths to be written to.
+*/
+pub fn allow_write_paths<P: AsRef<Path>>(lua: &Lua, paths: impl IntoIterator<Item = P>) {
+    update_permissions(lua, |perms| {
+        let write = perms.write.get_or_insert_with(Vec::new);
+        write.extend(paths.into_iter().map(|p| resolve_path(p.as_ref())));
+    });
+}
+
+/**
+    Checks that the given path is allowed to be read from.
+
+    This is also used by other standard libraries that read
+    files, so that they respect the same read restrictions.
+
+    # Errors
+
+    Errors if reading from the given path has not been allowed.
+*/
+pub fn check_read(lua: &Lua, path: impl AsRef<Path>) -> LuaResult<()> {
+    check_access(lua, path.as_ref(), FsAccess::Read)
+}
+
+/**
+    Checks that the given path is allowed to be written to.
+
+    This is also used by other standard libraries that write
+    files, so that they respect the same write restrictions.
+
+    # Errors
+
+    Errors if writing to the given path has not been allowed.
+*/
+pub fn check_write(lua: &Lua, path: impl AsRef<Path>) -> LuaResult<()> {
+    check_access(lua, path.as_ref(), FsAccess::Write)
+}
+
+/**
+    Gets the paths that reading from the filesystem has been restricted to, if any.
+*/
+#[must_use]
+pub fn allowed_read_paths(lua: &Lua) -> Option<Vec<PathBuf>> {
+    lua.app_data_ref::<FsPermissions>()
+        .and_then(|perms| perms.read.clone())
+}
+
+/**
+    Gets the paths that writing to the filesystem has been restricted to, if any.
+*/
+#[must_use]
+pub fn allowed_write_paths(lua: &Lua) -> Option<Vec<PathBuf>> {
+    lua.app_data_ref::<FsPermissions>()
+        .and_then(|perms| perms.write.clone())
+}
+
+/**
+    Checks if filesystem access has been restricted to any paths, for reading or writing.
+
+    Other standard libraries may use this to disable features that would
+    let scripts access files without going through the usual path checks.
+*/
+#[must_use]
+pub fn is_restricted(lua: &Lua) -> bool {
+    lua.app_data_ref::<FsPermissions>()
+        .is_some_and(|perms| perms.read.is_some() || perms.write.is_some())
+}
+
+fn update_permissions(lua: &Lua, f: impl FnOnce(&mut FsPermissions)) {
+    let mut perms = lua.remove_app_data::<FsPermissions>().unwrap_or_default();
+    f(&mut perms);
+    lua.set_app_data(perms);
+}
+
+fn check_access(lua: &Lua, path: &Path, access: FsAccess) -> LuaResult<()> {
+    let Some(perms) = lua.app_data_ref::<FsPermissions>() else {
+        return Ok(());
+    };
+
+    let allowed = match access {
+        FsAccess::Read => perms.read.as_ref(),
+        FsAccess::Write => perms.write.as_ref(),
+    };
+    let Some(allowed) = allowed else {
+        return Ok(());
+    };
+
+    let resolved = resolve_path(path);
+    if allowed.iter().any(|allowed| resolved.starts_with(allowed)) {
+        Ok(())
+    } else {
+        let kind = match access {
+            FsAccess::Read => "Read",
+            FsAccess::Write => "Write",
+        };
+        Err(CodedError::new(
+            "PermissionDenied",
+            format!(
+                "{kind} access to the path '{}' is not allowed",
+                path.display()
+            ),
+        )
+        .into())
+    }
+}
+
+/**
+    Resolves a path into an absolute, canonical path, following any symlinks.
+
+    Paths that do not exist yet, such as files that are about to be written,
+    are resolved by canonicalizing the nearest ancestor that does exist, and
+    then appending the remaining, non-existent path components to it.
+*/
+fn resolve_path(path: &Path) -> PathBuf {
+    let absolute = if path.is_relative() {
+        get_current_dir().join(path)
+    } else {
+        path.to_path_buf()
+    };
+
+    let mut existing = absolute.as_path();
+    let mut remaining = Vec::new();
+    loop {
+        if let Ok(canonical) = dunce::canonicalize(existing) {
+            let joined = remaining
+                .iter()
+                .rev()
+                .fold(canonical, |acc, component| acc.join(component));
+            return clean_path(joined);
+        }
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                remaining.push(name.to_os_string());
+                existing = parent;
+            }
+            _ => return clean_path(absolute),
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use std::{
+    path::{Path, PathBuf},
+    rc::Weak,
+};
+
+use notify::{
+    event::{EventKind, ModifyKind},
+    Event, RecursiveMode, Watcher,
+};
+use tokio::sync::{mpsc, watch as watch_channel};
+
+use mlua::prelude::*;
+use mlua_luau_scheduler::{LuaSchedulerExt, LuaSpawnExt};
+
+use lune_utils::TableBuilder;
+
+use super::options::FsWatchOptions;
+
+/**
+    Starts watching the given path for changes, calling the
+    given handler in a new thread for every change event.
+
+    Returns a handle that can be used to stop watching. Watching
+    will otherwise continue for as long as the scheduler is running.
+*/
+pub fn watch<'lua>(
+    lua: &'lua Lua,
+    path: &Path,
+    handler: LuaFunction<'lua>,
+    options: FsWatchOptions,
+) -> LuaResult<LuaTable<'lua>> {
+    let (event_tx, mut event_rx) = mpsc::unbounded_channel();
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<Event>| {
+        // NOTE: Errors here mean that some events were lost, which is
+        // nothing that a handler could do anything about, so we skip them
+        if let Ok(event) = res {
+            event_tx.send(event).ok();
+        }
+    })
+    .into_lua_err()?;
+
+    let mode = if options.recursive {
+        RecursiveMode::Recursive
+    } else {
+        RecursiveMode::NonRecursive
+    };
+    watcher.watch(path, mode).into_lua_err()?;
+
+    let lua_inner = lua
+        .app_data_ref::<Weak<Lua>>()
+        .expect("Missing weak lua ref")
+        .upgrade()
+        .expect("Lua was dropped unexpectedly");
+    let handler_key = lua.create_registry_value(handler)?;
+
+    let (shutdown_tx, mut shutdown_rx) = watch_channel::channel(false);
+    lua.spawn_local(async move {
+        // NOTE: The watcher stops watching once it is dropped,
+        // so it needs to be kept alive for as long as we are
+        let _watcher = watcher;
+        let mut detached = false;
+        loop {
+            tokio::select! {
+                event = event_rx.recv() => {
+                    let Some(event) = event else {
+                        break;
+                    };
+                    let Some(kind) = event_kind_name(event.kind) else {
+                        continue;
+                    };
+                    let handler: LuaFunction = lua_inner
+                        .registry_value(&handler_key)
+                        .expect("Missing watch handler");
+                    let event = create_event_table(&lua_inner, kind, event.paths)
+                        .expect("Failed to create watch event");
+                    lua_inner
+                        .push_thread_back(handler, event)
+                        .expect("Failed to spawn watch handler");
+                }
+                res = shutdown_rx.changed(), if !detached => {
+                    // NOTE: We will only get an error here if the watch handle is dropped,
+                    // meaning that lua has garbage collected it, and the user does not want
+                    // to manually stop watching using the watch handle. Watch forever.
+                    if res.is_ok() {
+                        break;
+                    }
+                    detached = true;
+                }
+            }
+        }
+    });
+
+    TableBuilder::new(lua)?
+        .with_function("stop", move |_, (): ()| match shutdown_tx.send(true) {
+            Ok(()) => Ok(()),
+            Err(_) => Err(LuaError::runtime("Watcher already stopped")),
+        })?
+        .build_readonly()
+}
+
+fn event_kind_name(kind: EventKind) -> Option<&'static str> {
+    match kind {
+        EventKind::Create(_) => Some("created"),
+        EventKind::Modify(ModifyKind::Name(_)) => Some("renamed"),
+        EventKind::Modify(_) => Some("modified"),
+        EventKind::Remove(_) => Some("removed"),
+        _ => None,
+    }
+}
+
+fn create_event_table<'lua>(
+    lua: &'lua Lua,
+    kind: &'static str,
+    paths: Vec<PathBuf>,
+) -> LuaResult<LuaTable<'lua>> {
+    let paths = paths
+        .into_iter()
+        .map(|path| path.to_string_lossy().to_string())
+        .collect::<Vec<_>>();
+    TableBuilder::new(lua)?
+        .with_value("kind", kind)?
+        .with_value(
+            "paths",
+            TableBuilder::new(lua)?
+                .with_sequential_values(paths)?
+                .build_readonly()?,
+        )?
+        .build_readonly()
+}
@@ -0,0 +1,54 @@
+use image::imageops::FilterType;
+use mlua::prelude::*;
+
+/**
+    A sampling filter used when resizing images.
+
+    Defaults to lanczos, which gives the best quality for
+    most images, at the cost of being the slowest filter.
+*/
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ResizeFilter {
+    Nearest,
+    Linear,
+    Cubic,
+    #[default]
+    Lanczos,
+}
+
+impl ResizeFilter {
+    pub fn to_raw(self) -> FilterType {
+        match self {
+            Self::Nearest => FilterType::Nearest,
+            Self::Linear => FilterType::Triangle,
+            Self::Cubic => FilterType::CatmullRom,
+            Self::Lanczos => FilterType::Lanczos3,
+        }
+    }
+}
+
+impl<'lua> FromLua<'lua> for ResizeFilter {
+    fn from_lua(value: LuaValue<'lua>, _: &'lua Lua) -> LuaResult<Self> {
+        match &value {
+            LuaValue::Nil => Ok(Self::default()),
+            LuaValue::String(s) => match s.to_string_lossy().to_ascii_lowercase().as_str() {
+                "nearest" => Ok(Self::Nearest),
+                "linear" => Ok(Self::Linear),
+                "cubic" => Ok(Self::Cubic),
+                "lanczos" => Ok(Self::Lanczos),
+                kind => Err(LuaError::FromLuaConversionError {
+                    from: value.type_name(),
+                    to: "ResizeFilter",
+                    message: Some(format!(
+                        "Invalid resize filter '{kind}', valid filters are: nearest, linear, cubic, lanczos"
+                    )),
+                }),
+            },
+            _ => Err(LuaError::FromLuaConversionError {
+                from: value.type_name(),
+                to: "ResizeFilter",
+                message: None,
+            }),
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use std::fmt;
+
+use image::ImageFormat as RawImageFormat;
+use mlua::prelude::*;
+
+/**
+    A format of image that can be decoded and encoded.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+}
+
+impl ImageFormat {
+    /**
+        Gets the format from a format detected by the `image` crate,
+        returning `None` for any format that is not supported.
+    */
+    pub fn from_raw(format: RawImageFormat) -> Option<Self> {
+        match format {
+            RawImageFormat::Png => Some(Self::Png),
+            RawImageFormat::Jpeg => Some(Self::Jpeg),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Png => "png",
+            Self::Jpeg => "jpeg",
+        }
+    }
+}
+
+impl fmt::Display for ImageFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl<'lua> IntoLua<'lua> for ImageFormat {
+    fn into_lua(self, lua: &'lua Lua) -> LuaResult<LuaValue<'lua>> {
+        self.as_str().into_lua(lua)
+    }
+}
+
+impl<'lua> FromLua<'lua> for ImageFormat {
+    fn from_lua(value: LuaValue<'lua>, _: &'lua Lua) -> LuaResult<Self> {
+        if let LuaValue::String(s) = &value {
+            match s.to_string_lossy().to_ascii_lowercase().as_str() {
+                "png" => Ok(Self::Png),
+                "jpeg" | "jpg" => Ok(Self::Jpeg),
+                kind => Err(LuaError::FromLuaConversionError {
+                    from: value.type_name(),
+                    to: "ImageFormat",
+                    message: Some(format!(
+                        "Invalid image format '{kind}', valid formats are: png, jpeg"
+                    )),
+                }),
+            }
+        } else {
+            Err(LuaError::FromLuaConversionError {
+                from: value.type_name(),
+                to: "ImageFormat",
+                message: None,
+            })
+        }
+    }
+}
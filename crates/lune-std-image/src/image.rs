@@ -0,0 +1,129 @@
+use std::{io::Cursor, sync::Arc};
+
+use image::{
+    codecs::{jpeg::JpegEncoder, png::PngEncoder},
+    DynamicImage, ImageError,
+};
+use mlua::prelude::*;
+use mlua_luau_scheduler::LuaSpawnExt;
+
+use super::{filter::ResizeFilter, format::ImageFormat};
+
+const DEFAULT_JPEG_QUALITY: u8 = 90;
+
+/**
+    A decoded image, along with the format it was originally decoded from.
+
+    Images are immutable - any operation that changes the image,
+    such as resizing, will create and return a new image instead.
+*/
+#[derive(Debug, Clone)]
+pub struct LuaImage {
+    inner: Arc<DynamicImage>,
+    format: ImageFormat,
+}
+
+impl LuaImage {
+    pub fn new(image: DynamicImage, format: ImageFormat) -> Self {
+        Self {
+            inner: Arc::new(image),
+            format,
+        }
+    }
+
+    fn with_image(&self, image: DynamicImage) -> Self {
+        Self::new(image, self.format)
+    }
+}
+
+impl LuaUserData for LuaImage {
+    fn add_fields<'lua, F: LuaUserDataFields<'lua, Self>>(fields: &mut F) {
+        fields.add_meta_field(LuaMetaMethod::Type, "Image");
+        fields.add_field_method_get("width", |_, this| Ok(this.inner.width()));
+        fields.add_field_method_get("height", |_, this| Ok(this.inner.height()));
+        fields.add_field_method_get("format", |_, this| Ok(this.format));
+    }
+
+    fn add_methods<'lua, M: LuaUserDataMethods<'lua, Self>>(methods: &mut M) {
+        methods.add_async_method(
+            "resize",
+            |lua, this, (width, height, filter): (u32, u32, ResizeFilter)| async move {
+                check_dimensions(width, height)?;
+                let image = Arc::clone(&this.inner);
+                let resized = lua
+                    .spawn_blocking(move || image.resize_exact(width, height, filter.to_raw()))
+                    .await;
+                Ok(this.with_image(resized))
+            },
+        );
+        methods.add_async_method(
+            "thumbnail",
+            |lua, this, (max_width, max_height): (u32, u32)| async move {
+                check_dimensions(max_width, max_height)?;
+                let image = Arc::clone(&this.inner);
+                let resized = lua
+                    .spawn_blocking(move || image.thumbnail(max_width, max_height))
+                    .await;
+                Ok(this.with_image(resized))
+            },
+        );
+        methods.add_async_method(
+            "encode",
+            |lua, this, (format, quality): (ImageFormat, Option<u8>)| async move {
+                let quality = match quality {
+                    None => DEFAULT_JPEG_QUALITY,
+                    Some(q) if (1..=100).contains(&q) => q,
+                    Some(q) => {
+                        return Err(LuaError::runtime(format!(
+                            "Invalid quality {q} - must be between 1 and 100"
+                        )))
+                    }
+                };
+                let image = Arc::clone(&this.inner);
+                let bytes = lua
+                    .spawn_blocking(move || encode_image(&image, format, quality))
+                    .await
+                    .into_lua_err()?;
+                lua.create_buffer(bytes)
+            },
+        );
+        methods.add_meta_method(LuaMetaMethod::ToString, |_, this, ()| {
+            Ok(format!(
+                "Image({}x{}, {})",
+                this.inner.width(),
+                this.inner.height(),
+                this.format
+            ))
+        });
+    }
+}
+
+fn check_dimensions(width: u32, height: u32) -> LuaResult<()> {
+    if width == 0 || height == 0 {
+        Err(LuaError::runtime(format!(
+            "Invalid image size {width}x{height} - width and height must be greater than zero"
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+/**
+    Encodes the given image into the given format.
+
+    JPEG does not support transparency, so images are converted
+    to RGB before being encoded, discarding any alpha channel.
+*/
+fn encode_image(
+    image: &DynamicImage,
+    format: ImageFormat,
+    quality: u8,
+) -> Result<Vec<u8>, ImageError> {
+    let mut bytes = Cursor::new(Vec::new());
+    match format {
+        ImageFormat::Png => image.write_with_encoder(PngEncoder::new(&mut bytes))?,
+        ImageFormat::Jpeg => DynamicImage::ImageRgb8(image.to_rgb8())
+            .write_with_encoder(JpegEncoder::new_with_quality(&mut bytes, quality))?,
+    }
+    Ok(bytes.into_inner())
+}
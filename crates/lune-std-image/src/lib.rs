@@ -0,0 +1,66 @@
+#![allow(clippy::cargo_common_metadata)]
+
+use std::io::Cursor;
+
+use ::image::ImageReader;
+use bstr::BString;
+use mlua::prelude::*;
+use mlua_luau_scheduler::LuaSpawnExt;
+
+use lune_utils::TableBuilder;
+
+mod filter;
+mod format;
+mod image;
+
+use self::format::ImageFormat;
+use self::image::LuaImage;
+
+/**
+    Creates the `image` standard library module.
+
+    # Errors
+
+    Errors when out of memory.
+*/
+pub fn module(lua: &Lua) -> LuaResult<LuaTable> {
+    TableBuilder::new_named(lua, "image")?
+        .with_function("info", image_info)?
+        .with_async_function("decode", image_decode)?
+        .build_readonly()
+}
+
+fn image_info(lua: &Lua, contents: BString) -> LuaResult<LuaTable> {
+    let reader = create_reader(contents.as_slice())?;
+    let format = detect_format(&reader)?;
+    let (width, height) = reader.into_dimensions().into_lua_err()?;
+    TableBuilder::new(lua)?
+        .with_value("format", format)?
+        .with_value("width", width)?
+        .with_value("height", height)?
+        .build_readonly()
+}
+
+async fn image_decode(lua: &Lua, contents: BString) -> LuaResult<LuaImage> {
+    let contents = Vec::from(contents);
+    lua.spawn_blocking(move || {
+        let reader = create_reader(&contents)?;
+        let format = detect_format(&reader)?;
+        let image = reader.decode().into_lua_err()?;
+        Ok(LuaImage::new(image, format))
+    })
+    .await
+}
+
+fn create_reader(contents: &[u8]) -> LuaResult<ImageReader<Cursor<&[u8]>>> {
+    ImageReader::new(Cursor::new(contents))
+        .with_guessed_format()
+        .into_lua_err()
+}
+
+fn detect_format(reader: &ImageReader<Cursor<&[u8]>>) -> LuaResult<ImageFormat> {
+    reader
+        .format()
+        .and_then(ImageFormat::from_raw)
+        .ok_or_else(|| LuaError::runtime("Unknown image format - expected a png or jpeg image"))
+}
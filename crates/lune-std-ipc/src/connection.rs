@@ -0,0 +1,97 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use bstr::BString;
+use mlua::prelude::*;
+use tokio::{
+    io::{split, AsyncRead, AsyncWrite, AsyncWriteExt},
+    sync::Mutex as AsyncMutex,
+};
+
+use crate::frame::{read_message, write_message};
+
+type BoxedReader = Box<dyn AsyncRead + Send + Unpin>;
+type BoxedWriter = Box<dyn AsyncWrite + Send + Unpin>;
+
+/**
+    A connection to another process over an IPC channel.
+
+    Reading and writing are locked separately, so that
+    messages can be sent while waiting to receive one.
+*/
+#[derive(Clone)]
+pub struct LuaIpcConnection {
+    name: String,
+    closed: Arc<AtomicBool>,
+    reader: Arc<AsyncMutex<BoxedReader>>,
+    writer: Arc<AsyncMutex<BoxedWriter>>,
+}
+
+impl LuaIpcConnection {
+    pub fn new<S>(name: impl Into<String>, stream: S) -> Self
+    where
+        S: AsyncRead + AsyncWrite + Send + 'static,
+    {
+        let (reader, writer) = split(stream);
+        Self {
+            name: name.into(),
+            closed: Arc::new(AtomicBool::new(false)),
+            reader: Arc::new(AsyncMutex::new(Box::new(reader))),
+            writer: Arc::new(AsyncMutex::new(Box::new(writer))),
+        }
+    }
+
+    fn ensure_open(&self) -> LuaResult<()> {
+        if self.closed.load(Ordering::SeqCst) {
+            Err(LuaError::runtime("IPC connection has been closed"))
+        } else {
+            Ok(())
+        }
+    }
+
+    async fn send(&self, message: BString) -> LuaResult<()> {
+        self.ensure_open()?;
+        let mut writer = self.writer.lock().await;
+        write_message(&mut *writer, &message).await.into_lua_err()
+    }
+
+    async fn receive(&self) -> LuaResult<Option<Vec<u8>>> {
+        self.ensure_open()?;
+        let mut reader = self.reader.lock().await;
+        read_message(&mut *reader).await.into_lua_err()
+    }
+
+    async fn close(&self) -> LuaResult<()> {
+        if self.closed.swap(true, Ordering::SeqCst) {
+            return Ok(());
+        }
+        let mut writer = self.writer.lock().await;
+        writer.shutdown().await.into_lua_err()
+    }
+}
+
+impl LuaUserData for LuaIpcConnection {
+    fn add_fields<'lua, F: LuaUserDataFields<'lua, Self>>(fields: &mut F) {
+        fields.add_meta_field(LuaMetaMethod::Type, "IpcConnection");
+        fields.add_field_method_get("name", |_, this| Ok(this.name.clone()));
+        fields.add_field_method_get("closed", |_, this| Ok(this.closed.load(Ordering::SeqCst)));
+    }
+
+    fn add_methods<'lua, M: LuaUserDataMethods<'lua, Self>>(methods: &mut M) {
+        methods.add_async_method("send", |_, this, message: BString| async move {
+            this.send(message).await
+        });
+        methods.add_async_method("receive", |lua, this, (): ()| async move {
+            match this.receive().await? {
+                Some(message) => Ok(LuaValue::String(lua.create_string(message)?)),
+                None => Ok(LuaValue::Nil),
+            }
+        });
+        methods.add_async_method("close", |_, this, (): ()| async move { this.close().await });
+        methods.add_meta_method(LuaMetaMethod::ToString, |_, this, ()| {
+            Ok(format!("IpcConnection({})", this.name))
+        });
+    }
+}
@@ -0,0 +1,87 @@
+use std::io;
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/**
+    The maximum size of a single message, in bytes.
+
+    Messages are fully buffered in memory, so this prevents a misbehaving
+    peer from making us allocate an arbitrary amount of memory at once.
+*/
+pub const MAX_MESSAGE_SIZE: usize = 64 * 1024 * 1024;
+
+/**
+    Writes a single message, prefixed with its length as a big-endian `u32`.
+
+    # Errors
+
+    Errors if the message is larger than [`MAX_MESSAGE_SIZE`], or if writing fails.
+*/
+pub async fn write_message<W>(writer: &mut W, message: &[u8]) -> io::Result<()>
+where
+    W: AsyncWrite + Unpin + ?Sized,
+{
+    if message.len() > MAX_MESSAGE_SIZE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Message is too large - got {} bytes, maximum is {MAX_MESSAGE_SIZE} bytes",
+                message.len()
+            ),
+        ));
+    }
+    let len = u32::try_from(message.len()).expect("max message size fits in u32");
+    writer.write_all(&len.to_be_bytes()).await?;
+    writer.write_all(message).await?;
+    writer.flush().await
+}
+
+/**
+    Reads a single message that was written using [`write_message`].
+
+    Returns `None` if the other end closed the connection in between messages.
+
+    # Errors
+
+    Errors if the connection was closed in the middle of a message,
+    if the message is larger than [`MAX_MESSAGE_SIZE`], or if reading fails.
+*/
+pub async fn read_message<R>(reader: &mut R) -> io::Result<Option<Vec<u8>>>
+where
+    R: AsyncRead + Unpin + ?Sized,
+{
+    let mut len_bytes = [0u8; 4];
+    let mut filled = 0;
+    while filled < len_bytes.len() {
+        match reader.read(&mut len_bytes[filled..]).await? {
+            0 if filled == 0 => return Ok(None),
+            0 => return Err(unexpected_eof()),
+            n => filled += n,
+        }
+    }
+
+    let len = u32::from_be_bytes(len_bytes) as usize;
+    if len > MAX_MESSAGE_SIZE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Message is too large - got {len} bytes, maximum is {MAX_MESSAGE_SIZE} bytes"),
+        ));
+    }
+
+    let mut message = vec![0u8; len];
+    reader.read_exact(&mut message).await.map_err(|e| {
+        if e.kind() == io::ErrorKind::UnexpectedEof {
+            unexpected_eof()
+        } else {
+            e
+        }
+    })?;
+    Ok(Some(message))
+}
+
+fn unexpected_eof() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::UnexpectedEof,
+        "Connection was closed in the middle of a message",
+    )
+}
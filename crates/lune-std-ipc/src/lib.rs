@@ -0,0 +1,37 @@
+#![allow(clippy::cargo_common_metadata)]
+
+use mlua::prelude::*;
+
+use lune_utils::TableBuilder;
+
+mod connection;
+mod frame;
+mod name;
+mod platform;
+mod server;
+
+use self::connection::LuaIpcConnection;
+use self::name::IpcName;
+use self::server::LuaIpcServer;
+
+/**
+    Creates the `ipc` standard library module.
+
+    # Errors
+
+    Errors when out of memory.
+*/
+pub fn module(lua: &Lua) -> LuaResult<LuaTable> {
+    TableBuilder::new_named(lua, "ipc")?
+        .with_async_function("listen", ipc_listen)?
+        .with_async_function("connect", ipc_connect)?
+        .build_readonly()
+}
+
+async fn ipc_listen(_: &Lua, name: IpcName) -> LuaResult<LuaIpcServer> {
+    LuaIpcServer::listen(name).await.into_lua_err()
+}
+
+async fn ipc_connect(_: &Lua, name: IpcName) -> LuaResult<LuaIpcConnection> {
+    platform::connect(&name).await.into_lua_err()
+}
@@ -0,0 +1,161 @@
+use std::path::PathBuf;
+
+use mlua::prelude::*;
+
+/**
+    A validated name of an IPC channel.
+
+    Names may only contain ASCII letters, digits, dashes, underscores and periods,
+    so that they map to the same valid socket path or pipe name on all platforms.
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IpcName(String);
+
+impl IpcName {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /**
+        Gets the path of the Unix socket used for this channel.
+
+        Sockets are placed inside of a directory that only the current user may access,
+        which is created if it does not exist yet - see [`socket_dir`] for details.
+    */
+    #[cfg(unix)]
+    pub fn socket_path(&self) -> std::io::Result<PathBuf> {
+        Ok(socket_dir()?.join(format!("{}.sock", self.0)))
+    }
+
+    /**
+        Gets the path of the named pipe used for this channel.
+    */
+    #[cfg(windows)]
+    pub fn socket_path(&self) -> PathBuf {
+        PathBuf::from(format!(r"\\.\pipe\lune-ipc-{}", self.0))
+    }
+}
+
+/**
+    Gets the directory that Unix sockets for IPC channels are placed in, creating it if necessary.
+
+    This is `$XDG_RUNTIME_DIR/lune-ipc` if a runtime directory is available, which is private to
+    the current user, or a directory in the temporary directory that includes the id of the current
+    user otherwise. Since the temporary directory is usually writable by anyone, the directory must
+    be owned by the current user and not be accessible by anyone else, or it will not be used.
+*/
+#[cfg(unix)]
+fn socket_dir() -> std::io::Result<PathBuf> {
+    use std::env;
+
+    let dir = match env::var_os("XDG_RUNTIME_DIR").map(PathBuf::from) {
+        Some(runtime_dir) if runtime_dir.is_absolute() => runtime_dir.join("lune-ipc"),
+        _ => env::temp_dir().join(format!("lune-ipc-{}", current_uid())),
+    };
+
+    create_private_dir(&dir)?;
+    Ok(dir)
+}
+
+/**
+    Creates a directory that only the current user may access, if it does not exist yet,
+    and makes sure that an existing directory is owned by and private to the current user.
+*/
+#[cfg(unix)]
+fn create_private_dir(dir: &std::path::Path) -> std::io::Result<()> {
+    use std::{
+        fs,
+        io::{Error, ErrorKind},
+        os::unix::fs::{DirBuilderExt, MetadataExt},
+    };
+
+    match fs::DirBuilder::new().mode(0o700).create(dir) {
+        Ok(()) => {}
+        Err(e) if e.kind() == ErrorKind::AlreadyExists => {}
+        Err(e) => return Err(e),
+    }
+
+    // NOTE: We use symlink_metadata here so that a symlink placed
+    // by another user can never redirect us to a different directory
+    let meta = fs::symlink_metadata(dir)?;
+    if !meta.is_dir() || meta.uid() != current_uid() || meta.mode() & 0o077 != 0 {
+        return Err(Error::new(
+            ErrorKind::PermissionDenied,
+            format!(
+                "IPC socket directory '{}' must be a directory owned by \
+                the current user, and not be accessible by other users",
+                dir.display()
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+/**
+    Gets the id of the user that the current process is running as.
+*/
+#[cfg(unix)]
+pub(crate) fn current_uid() -> u32 {
+    // SAFETY: getuid has no preconditions and can never fail
+    unsafe { libc::getuid() }
+}
+
+impl<'lua> FromLua<'lua> for IpcName {
+    fn from_lua(value: LuaValue<'lua>, lua: &'lua Lua) -> LuaResult<Self> {
+        let name = String::from_lua(value, lua)?;
+        if name.is_empty() {
+            return Err(LuaError::runtime("IPC channel name must not be empty"));
+        }
+        if let Some(c) = name
+            .chars()
+            .find(|c| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')))
+        {
+            return Err(LuaError::runtime(format!(
+                "Invalid character '{c}' in IPC channel name '{name}' - \
+                expected only letters, digits, '-', '_' and '.'"
+            )));
+        }
+        Ok(Self(name))
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::{
+        fs,
+        os::unix::fs::{symlink, PermissionsExt},
+    };
+
+    use super::*;
+
+    #[test]
+    fn private_dir_is_created_for_current_user_only() {
+        let parent = tempfile::tempdir().unwrap();
+        let dir = parent.path().join("lune-ipc");
+        create_private_dir(&dir).unwrap();
+        let mode = fs::metadata(&dir).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o700);
+        // Creating it again should reuse the existing directory
+        create_private_dir(&dir).unwrap();
+    }
+
+    #[test]
+    fn private_dir_rejects_shared_directories() {
+        let parent = tempfile::tempdir().unwrap();
+        let dir = parent.path().join("lune-ipc");
+        fs::create_dir(&dir).unwrap();
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o777)).unwrap();
+        assert!(create_private_dir(&dir).is_err());
+    }
+
+    #[test]
+    fn private_dir_rejects_symlinks() {
+        let parent = tempfile::tempdir().unwrap();
+        let target = parent.path().join("target");
+        let dir = parent.path().join("lune-ipc");
+        create_private_dir(&target).unwrap();
+        symlink(&target, &dir).unwrap();
+        assert!(create_private_dir(&dir).is_err());
+    }
+}
@@ -0,0 +1,175 @@
+use std::io;
+
+use crate::{connection::LuaIpcConnection, name::IpcName};
+
+#[cfg(unix)]
+pub use self::unix::*;
+
+#[cfg(windows)]
+pub use self::windows::*;
+
+fn not_listening(name: &IpcName) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("No process is listening on IPC channel '{}'", name.as_str()),
+    )
+}
+
+fn already_listening(name: &IpcName) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::AddrInUse,
+        format!(
+            "Another process is already listening on IPC channel '{}'",
+            name.as_str()
+        ),
+    )
+}
+
+#[cfg(unix)]
+mod unix {
+    use std::{
+        fs,
+        os::unix::fs::{FileTypeExt, MetadataExt},
+        path::PathBuf,
+    };
+
+    use tokio::net::{UnixListener, UnixStream};
+
+    use crate::name::current_uid;
+
+    use super::{already_listening, io, not_listening, IpcName, LuaIpcConnection};
+
+    /**
+        A listener for an IPC channel, backed by a Unix domain socket.
+
+        The socket file is removed once the listener is dropped.
+    */
+    pub struct IpcListener {
+        path: PathBuf,
+        inner: UnixListener,
+    }
+
+    impl IpcListener {
+        pub async fn bind(name: &IpcName) -> io::Result<Self> {
+            let path = name.socket_path()?;
+            // NOTE: A socket file may be left behind if a previous listener
+            // was not shut down gracefully, so if nobody is accepting
+            // connections on it anymore, we can safely remove it, as
+            // long as it really is a socket that belongs to us
+            if let Ok(meta) = fs::symlink_metadata(&path) {
+                if UnixStream::connect(&path).await.is_ok() {
+                    return Err(already_listening(name));
+                }
+                if !meta.file_type().is_socket() || meta.uid() != current_uid() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::PermissionDenied,
+                        format!(
+                            "Refusing to remove '{}' for IPC channel '{}' - \
+                            it is not a socket owned by the current user",
+                            path.display(),
+                            name.as_str()
+                        ),
+                    ));
+                }
+                fs::remove_file(&path)?;
+            }
+            let inner = UnixListener::bind(&path)?;
+            Ok(Self { path, inner })
+        }
+
+        pub async fn accept(&mut self, name: &str) -> io::Result<LuaIpcConnection> {
+            let (stream, _) = self.inner.accept().await?;
+            Ok(LuaIpcConnection::new(name, stream))
+        }
+    }
+
+    impl Drop for IpcListener {
+        fn drop(&mut self) {
+            fs::remove_file(&self.path).ok();
+        }
+    }
+
+    pub async fn connect(name: &IpcName) -> io::Result<LuaIpcConnection> {
+        match UnixStream::connect(name.socket_path()?).await {
+            Ok(stream) => Ok(LuaIpcConnection::new(name.as_str(), stream)),
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    io::ErrorKind::NotFound | io::ErrorKind::ConnectionRefused
+                ) =>
+            {
+                Err(not_listening(name))
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+#[cfg(windows)]
+mod windows {
+    use std::time::Duration;
+
+    use tokio::{
+        net::windows::named_pipe::{ClientOptions, NamedPipeServer, ServerOptions},
+        time::sleep,
+    };
+
+    use super::{already_listening, io, not_listening, IpcName, LuaIpcConnection};
+
+    const ERROR_FILE_NOT_FOUND: i32 = 2;
+    const ERROR_ACCESS_DENIED: i32 = 5;
+    const ERROR_PIPE_BUSY: i32 = 231;
+
+    /**
+        A listener for an IPC channel, backed by a named pipe.
+
+        Named pipes accept a single client per pipe instance, so a
+        new instance is created every time a client has connected.
+    */
+    pub struct IpcListener {
+        name: IpcName,
+        next: NamedPipeServer,
+    }
+
+    impl IpcListener {
+        #[allow(clippy::unused_async)]
+        pub async fn bind(name: &IpcName) -> io::Result<Self> {
+            let next = ServerOptions::new()
+                .first_pipe_instance(true)
+                .create(name.socket_path())
+                .map_err(|e| match e.raw_os_error() {
+                    Some(ERROR_ACCESS_DENIED) => already_listening(name),
+                    _ => e,
+                })?;
+            Ok(Self {
+                name: name.clone(),
+                next,
+            })
+        }
+
+        pub async fn accept(&mut self, name: &str) -> io::Result<LuaIpcConnection> {
+            self.next.connect().await?;
+            let next = ServerOptions::new().create(self.name.socket_path())?;
+            let connected = std::mem::replace(&mut self.next, next);
+            Ok(LuaIpcConnection::new(name, connected))
+        }
+    }
+
+    pub async fn connect(name: &IpcName) -> io::Result<LuaIpcConnection> {
+        let path = name.socket_path();
+        // NOTE: All pipe instances may be busy if many clients connect at
+        // the same time, in which case we need to wait and try again
+        let client = loop {
+            match ClientOptions::new().open(&path) {
+                Ok(client) => break client,
+                Err(e) if e.raw_os_error() == Some(ERROR_PIPE_BUSY) => {}
+                Err(e) if e.raw_os_error() == Some(ERROR_FILE_NOT_FOUND) => {
+                    return Err(not_listening(name))
+                }
+                Err(e) => return Err(e),
+            }
+            sleep(Duration::from_millis(50)).await;
+        };
+        Ok(LuaIpcConnection::new(name.as_str(), client))
+    }
+}
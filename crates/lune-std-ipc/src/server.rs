@@ -0,0 +1,84 @@
+use std::{io, sync::Arc};
+
+use mlua::prelude::*;
+use tokio::sync::{watch, Mutex as AsyncMutex};
+
+use crate::{connection::LuaIpcConnection, name::IpcName, platform::IpcListener};
+
+/**
+    A server listening for connections on an IPC channel.
+*/
+#[derive(Clone)]
+pub struct LuaIpcServer {
+    name: IpcName,
+    closed: Arc<watch::Sender<bool>>,
+    listener: Arc<AsyncMutex<Option<IpcListener>>>,
+}
+
+impl LuaIpcServer {
+    /**
+        Starts listening on the IPC channel with the given name.
+
+        # Errors
+
+        Errors if another process is already listening on the channel.
+    */
+    pub async fn listen(name: IpcName) -> io::Result<Self> {
+        let listener = IpcListener::bind(&name).await?;
+        Ok(Self {
+            name,
+            closed: Arc::new(watch::Sender::new(false)),
+            listener: Arc::new(AsyncMutex::new(Some(listener))),
+        })
+    }
+
+    fn is_closed(&self) -> bool {
+        *self.closed.borrow()
+    }
+
+    async fn accept(&self) -> LuaResult<LuaIpcConnection> {
+        let mut closed = self.closed.subscribe();
+        let mut guard = self.listener.lock().await;
+        let Some(listener) = guard.as_mut().filter(|_| !*closed.borrow()) else {
+            return Err(LuaError::runtime("IPC server has been closed"));
+        };
+        tokio::select! {
+            res = listener.accept(self.name.as_str()) => res.into_lua_err(),
+            _ = closed.wait_for(|closed| *closed) => {
+                // NOTE: The server may have been closed while we were holding
+                // the listener, so we make sure to clean it up here instead
+                guard.take();
+                Err(LuaError::runtime("IPC server has been closed"))
+            }
+        }
+    }
+
+    fn close(&self) {
+        self.closed.send_replace(true);
+        if let Ok(mut guard) = self.listener.try_lock() {
+            guard.take();
+        }
+    }
+}
+
+impl LuaUserData for LuaIpcServer {
+    fn add_fields<'lua, F: LuaUserDataFields<'lua, Self>>(fields: &mut F) {
+        fields.add_meta_field(LuaMetaMethod::Type, "IpcServer");
+        fields.add_field_method_get("name", |_, this| Ok(this.name.as_str().to_string()));
+        fields.add_field_method_get("closed", |_, this| Ok(this.is_closed()));
+    }
+
+    fn add_methods<'lua, M: LuaUserDataMethods<'lua, Self>>(methods: &mut M) {
+        methods.add_async_method(
+            "accept",
+            |_, this, (): ()| async move { this.accept().await },
+        );
+        methods.add_method("close", |_, this, (): ()| {
+            this.close();
+            Ok(())
+        });
+        methods.add_meta_method(LuaMetaMethod::ToString, |_, this, ()| {
+            Ok(format!("IpcServer({})", this.name.as_str()))
+        });
+    }
+}
@@ -0,0 +1,207 @@
+use std::{env, fmt, str::FromStr};
+
+use mlua::prelude::*;
+
+/**
+    Environment variable that may be set to change the default log level.
+*/
+const LEVEL_ENV_VAR: &str = "LUNE_LOG_LEVEL";
+
+/**
+    Environment variable that may be set to change the default log format.
+*/
+const FORMAT_ENV_VAR: &str = "LUNE_LOG_FORMAT";
+
+/**
+    The level of a log record, ordered from least to most severe.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    /**
+        All available log levels, ordered from least to most severe.
+    */
+    pub const ALL: &'static [Self] = &[
+        Self::Trace,
+        Self::Debug,
+        Self::Info,
+        Self::Warn,
+        Self::Error,
+    ];
+
+    /**
+        Gets the name of the log level, such as `info` or `warn`.
+    */
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Trace => "trace",
+            Self::Debug => "debug",
+            Self::Info => "info",
+            Self::Warn => "warn",
+            Self::Error => "error",
+        }
+    }
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for LogLevel {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let low = s.trim().to_ascii_lowercase();
+        Ok(match low.as_str() {
+            "trace" => Self::Trace,
+            "debug" => Self::Debug,
+            "info" => Self::Info,
+            "warn" => Self::Warn,
+            "error" => Self::Error,
+            _ => {
+                return Err(format!(
+                    "Unknown log level '{low}'\nValid levels are: {}",
+                    Self::ALL
+                        .iter()
+                        .map(|level| level.name())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ))
+            }
+        })
+    }
+}
+
+impl<'lua> FromLua<'lua> for LogLevel {
+    fn from_lua(value: LuaValue<'lua>, _: &'lua Lua) -> LuaResult<Self> {
+        match value {
+            LuaValue::String(s) => s.to_str()?.parse().map_err(LuaError::runtime),
+            _ => Err(LuaError::FromLuaConversionError {
+                from: value.type_name(),
+                to: "LogLevel",
+                message: Some(String::from("expected a string")),
+            }),
+        }
+    }
+}
+
+/**
+    The output format for log records.
+*/
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LogFormat {
+    /// Human-readable, colored output.
+    #[default]
+    Pretty,
+    /// One JSON object per line.
+    Json,
+}
+
+impl LogFormat {
+    /**
+        Gets the name of the log format, such as `pretty` or `json`.
+    */
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Pretty => "pretty",
+            Self::Json => "json",
+        }
+    }
+}
+
+impl fmt::Display for LogFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let low = s.trim().to_ascii_lowercase();
+        Ok(match low.as_str() {
+            "pretty" => Self::Pretty,
+            "json" => Self::Json,
+            _ => {
+                return Err(format!(
+                    "Unknown log format '{low}'\nValid formats are: pretty, json"
+                ))
+            }
+        })
+    }
+}
+
+impl<'lua> FromLua<'lua> for LogFormat {
+    fn from_lua(value: LuaValue<'lua>, _: &'lua Lua) -> LuaResult<Self> {
+        match value {
+            LuaValue::String(s) => s.to_str()?.parse().map_err(LuaError::runtime),
+            _ => Err(LuaError::FromLuaConversionError {
+                from: value.type_name(),
+                to: "LogFormat",
+                message: Some(String::from("expected a string")),
+            }),
+        }
+    }
+}
+
+/**
+    Logging configuration, shared by all loggers in a Lua state.
+
+    Uses the `LUNE_LOG_LEVEL` and `LUNE_LOG_FORMAT` environment
+    variables as defaults, if they are set to valid values.
+*/
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct LogConfig {
+    pub(crate) level: LogLevel,
+    pub(crate) format: LogFormat,
+}
+
+impl LogConfig {
+    fn from_env() -> Self {
+        let level = env::var(LEVEL_ENV_VAR).ok().and_then(|s| s.parse().ok());
+        let format = env::var(FORMAT_ENV_VAR).ok().and_then(|s| s.parse().ok());
+        Self {
+            level: level.unwrap_or(LogLevel::Info),
+            format: format.unwrap_or_default(),
+        }
+    }
+
+    pub(crate) fn get(lua: &Lua) -> Self {
+        lua.app_data_ref::<Self>()
+            .map_or_else(Self::from_env, |config| *config)
+    }
+
+    pub(crate) fn update(lua: &Lua, f: impl FnOnce(&mut Self)) {
+        let mut config = Self::get(lua);
+        f(&mut config);
+        lua.set_app_data(config);
+    }
+}
+
+/**
+    Sets the minimum level of log records that will be written.
+
+    This overrides the `LUNE_LOG_LEVEL` environment variable.
+*/
+pub fn set_log_level(lua: &Lua, level: LogLevel) {
+    LogConfig::update(lua, |config| config.level = level);
+}
+
+/**
+    Sets the format that log records will be written in.
+
+    This overrides the `LUNE_LOG_FORMAT` environment variable.
+*/
+pub fn set_log_format(lua: &Lua, format: LogFormat) {
+    LogConfig::update(lua, |config| config.format = format);
+}
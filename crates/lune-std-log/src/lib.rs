@@ -0,0 +1,61 @@
+#![allow(clippy::cargo_common_metadata)]
+
+use std::rc::Rc;
+
+use mlua::prelude::*;
+
+use lune_utils::TableBuilder;
+
+mod config;
+mod record;
+
+use self::config::LogConfig;
+use self::record::{fields_from_table, merge_fields, write_record, Fields};
+
+pub use self::config::{set_log_format, set_log_level, LogFormat, LogLevel};
+
+/**
+    Creates the `log` standard library module.
+
+    # Errors
+
+    Errors when out of memory.
+*/
+pub fn module(lua: &Lua) -> LuaResult<LuaTable> {
+    create_logger(lua, Rc::new(Fields::new()))?
+        .with_function("setLevel", |lua, level: LogLevel| {
+            set_log_level(lua, level);
+            Ok(())
+        })?
+        .with_function("getLevel", |lua, ()| Ok(LogConfig::get(lua).level.name()))?
+        .with_function("setFormat", |lua, format: LogFormat| {
+            set_log_format(lua, format);
+            Ok(())
+        })?
+        .with_function("getFormat", |lua, ()| Ok(LogConfig::get(lua).format.name()))?
+        .build_readonly()
+}
+
+/**
+    Creates a logger with functions for each log level, as well
+    as a `child` function, all with the given fields bound to them.
+*/
+fn create_logger(lua: &Lua, bound: Rc<Fields>) -> LuaResult<TableBuilder> {
+    let mut builder = TableBuilder::new_named(lua, "log")?;
+
+    for level in LogLevel::ALL.iter().copied() {
+        let bound = Rc::clone(&bound);
+        builder = builder.with_function(
+            level.name(),
+            move |lua, (message, fields): (String, Option<LuaTable>)| {
+                write_record(lua, level, &message, &bound, fields_from_table(fields)?)
+            },
+        )?;
+    }
+
+    builder.with_function("child", move |lua, fields: LuaTable| {
+        let mut child_fields = bound.as_ref().clone();
+        merge_fields(&mut child_fields, fields_from_table(Some(fields))?);
+        create_logger(lua, Rc::new(child_fields))?.build_readonly()
+    })
+}
@@ -0,0 +1,180 @@
+use chrono::{SecondsFormat, Utc};
+use console::style;
+use mlua::prelude::*;
+use serde_json::{Map as JsonMap, Number as JsonNumber, Value as JsonValue};
+
+use lune_utils::write_stderr;
+
+use crate::config::{LogConfig, LogFormat, LogLevel};
+
+const MAX_FIELD_DEPTH: usize = 8;
+
+/**
+    Structured fields for a log record, in the order they were added.
+*/
+pub(crate) type Fields = Vec<(String, JsonValue)>;
+
+/**
+    Converts a Lua table of fields into structured log fields.
+
+    Fields are sorted by key, since table iteration order is not stable.
+*/
+pub(crate) fn fields_from_table(table: Option<LuaTable>) -> LuaResult<Fields> {
+    let Some(table) = table else {
+        return Ok(Fields::new());
+    };
+
+    let mut fields = Fields::new();
+    for pair in table.pairs::<LuaValue, LuaValue>() {
+        let (key, value) = pair?;
+        fields.push((field_key(&key)?, to_json_value(&value, 0)));
+    }
+
+    fields.sort_by(|(a, _), (b, _)| a.cmp(b));
+    Ok(fields)
+}
+
+/**
+    Merges the given fields into existing fields, replacing any with the same key.
+*/
+pub(crate) fn merge_fields(target: &mut Fields, fields: Fields) {
+    for (key, value) in fields {
+        match target.iter_mut().find(|(existing, _)| *existing == key) {
+            Some((_, existing)) => *existing = value,
+            None => target.push((key, value)),
+        }
+    }
+}
+
+/**
+    Writes a log record to stderr, if the level is enabled.
+*/
+pub(crate) fn write_record(
+    lua: &Lua,
+    level: LogLevel,
+    message: &str,
+    bound: &Fields,
+    fields: Fields,
+) -> LuaResult<()> {
+    let config = LogConfig::get(lua);
+    if level < config.level {
+        return Ok(());
+    }
+
+    let mut all_fields = bound.clone();
+    merge_fields(&mut all_fields, fields);
+
+    let timestamp = Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true);
+    let mut line = match config.format {
+        LogFormat::Pretty => format_pretty(&timestamp, level, message, &all_fields),
+        LogFormat::Json => format_json(&timestamp, level, message, all_fields),
+    };
+
+    line.push('\n');
+    write_stderr(lua, line.as_bytes())?;
+
+    Ok(())
+}
+
+fn format_pretty(timestamp: &str, level: LogLevel, message: &str, fields: &Fields) -> String {
+    let name = format!("{:<5}", level.name().to_ascii_uppercase());
+    let name = match level {
+        LogLevel::Trace => style(name).magenta(),
+        LogLevel::Debug => style(name).blue(),
+        LogLevel::Info => style(name).green(),
+        LogLevel::Warn => style(name).yellow(),
+        LogLevel::Error => style(name).red(),
+    };
+
+    let mut line = format!(
+        "{} {} {message}",
+        style(timestamp).dim().for_stderr(),
+        name.bold().for_stderr()
+    );
+    for (key, value) in fields {
+        let value = match value {
+            JsonValue::String(s) if is_bare_string(s) => s.clone(),
+            _ => value.to_string(),
+        };
+        line.push(' ');
+        line.push_str(&style(format!("{key}=")).dim().for_stderr().to_string());
+        line.push_str(&value);
+    }
+    line
+}
+
+fn format_json(timestamp: &str, level: LogLevel, message: &str, fields: Fields) -> String {
+    let mut map = JsonMap::new();
+    map.insert("timestamp".into(), timestamp.into());
+    map.insert("level".into(), level.name().into());
+    map.insert("message".into(), message.into());
+    // NOTE: Fields must never overwrite the
+    // keys above, so we only insert new ones
+    for (key, value) in fields {
+        map.entry(key).or_insert(value);
+    }
+    JsonValue::Object(map).to_string()
+}
+
+fn is_bare_string(s: &str) -> bool {
+    !s.is_empty()
+        && !s
+            .chars()
+            .any(|c| c.is_whitespace() || c.is_control() || matches!(c, '"' | '='))
+}
+
+fn field_key(key: &LuaValue) -> LuaResult<String> {
+    match key {
+        LuaValue::String(s) => Ok(s.to_string_lossy().to_string()),
+        LuaValue::Integer(i) => Ok(i.to_string()),
+        LuaValue::Number(n) => Ok(n.to_string()),
+        _ => Err(LuaError::runtime(format!(
+            "Log field keys must be strings, got '{}'",
+            key.type_name()
+        ))),
+    }
+}
+
+fn to_json_value(value: &LuaValue, depth: usize) -> JsonValue {
+    match value {
+        LuaValue::Nil => JsonValue::Null,
+        LuaValue::Boolean(b) => JsonValue::Bool(*b),
+        LuaValue::Integer(i) => JsonValue::Number((*i).into()),
+        LuaValue::Number(n) => JsonNumber::from_f64(*n).map_or(JsonValue::Null, JsonValue::Number),
+        LuaValue::String(s) => JsonValue::String(s.to_string_lossy().to_string()),
+        LuaValue::Table(t) if depth < MAX_FIELD_DEPTH => table_to_json_value(t, depth),
+        _ => JsonValue::String(
+            value
+                .to_string()
+                .unwrap_or_else(|_| format!("<{}>", value.type_name())),
+        ),
+    }
+}
+
+fn table_to_json_value(table: &LuaTable, depth: usize) -> JsonValue {
+    let len = table.raw_len();
+    let mut array = Vec::with_capacity(len);
+    let mut map = JsonMap::new();
+
+    for (key, value) in table.clone().pairs::<LuaValue, LuaValue>().flatten() {
+        let value = to_json_value(&value, depth + 1);
+        match key {
+            LuaValue::Integer(i) if i >= 1 && (i as usize) <= len => array.push((i, value)),
+            key => {
+                if let Ok(key) = field_key(&key) {
+                    map.insert(key, value);
+                }
+            }
+        }
+    }
+
+    if map.is_empty() && !array.is_empty() {
+        array.sort_by_key(|(i, _)| *i);
+        JsonValue::Array(array.into_iter().map(|(_, value)| value).collect())
+    } else {
+        for (i, value) in array {
+            map.insert(i.to_string(), value);
+        }
+        JsonValue::Object(map)
+    }
+}
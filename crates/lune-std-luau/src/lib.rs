@@ -10,6 +10,19 @@ use self::options::{LuauCompileOptions, LuauLoadOptions};
 
 const BYTECODE_ERROR_BYTE: u8 = 0;
 
+struct CodegenEnabled(bool);
+
+/**
+    Enables or disables native code generation for the given Lua state.
+
+    Functions loaded using `luau.load` may still choose to enable or disable
+    codegen for themselves, and this setting is restored once they are loaded.
+*/
+pub fn set_codegen_enabled(lua: &Lua, enabled: bool) {
+    lua.set_app_data(CodegenEnabled(enabled));
+    lua.enable_jit(enabled);
+}
+
 /**
     Creates the `luau` standard library module.
 
@@ -18,7 +31,7 @@ const BYTECODE_ERROR_BYTE: u8 = 0;
     Errors when out of memory.
 */
 pub fn module(lua: &Lua) -> LuaResult<LuaTable> {
-    TableBuilder::new(lua)?
+    TableBuilder::new_named(lua, "luau")?
         .with_function("compile", compile_source)?
         .with_function("load", load_source)?
         .build_readonly()
@@ -77,8 +90,13 @@ fn load_source<'lua>(
     // Enable JIT if codegen is enabled and the environment hasn't
     // changed, otherwise disable JIT since it'll fall back anyways
     lua.enable_jit(options.codegen_enabled && !env_changed);
-    let function = chunk.into_function()?;
-    lua.enable_jit(true);
+    let function = chunk.into_function();
+    let codegen_default = match lua.app_data_ref::<CodegenEnabled>() {
+        Some(codegen) => codegen.0,
+        None => true,
+    };
+    lua.enable_jit(codegen_default);
+    let function = function?;
 
     Ok(function)
 }
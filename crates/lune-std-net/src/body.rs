@@ -0,0 +1,171 @@
+use std::sync::Arc;
+
+use bstr::BString;
+use futures_util::stream;
+use reqwest::{Body, Response};
+use tokio::sync::{mpsc, Mutex as AsyncMutex};
+
+use mlua::prelude::*;
+
+/**
+    The default maximum size of a response body read using `readAll`, in bytes.
+*/
+const DEFAULT_READ_ALL_LIMIT: usize = 16 * 1024 * 1024;
+
+/**
+    A streaming response body for `net.request`.
+
+    The body is read from the connection on demand, one chunk at a
+    time, instead of being buffered in memory before the request returns.
+*/
+#[derive(Debug, Clone)]
+pub struct LuaResponseBody {
+    inner: Arc<AsyncMutex<LuaResponseBodyInner>>,
+}
+
+#[derive(Debug)]
+struct LuaResponseBodyInner {
+    response: Option<Response>,
+    pending: Vec<u8>,
+}
+
+impl LuaResponseBody {
+    pub fn new(response: Response) -> Self {
+        Self {
+            inner: Arc::new(AsyncMutex::new(LuaResponseBodyInner {
+                response: Some(response),
+                pending: Vec::new(),
+            })),
+        }
+    }
+
+    /**
+        Reads the next chunk of data from the body, containing at most `max` bytes if given.
+
+        Returns `None` once the entire body has been read.
+    */
+    async fn read(&self, max: Option<usize>) -> LuaResult<Option<Vec<u8>>> {
+        let mut guard = self.inner.lock().await;
+        let inner = &mut *guard;
+
+        let mut chunk = if inner.pending.is_empty() {
+            let Some(response) = inner.response.as_mut() else {
+                return Ok(None);
+            };
+            loop {
+                match response.chunk().await.into_lua_err()? {
+                    Some(data) if !data.is_empty() => break data.to_vec(),
+                    Some(_) => {}
+                    None => {
+                        inner.response.take();
+                        return Ok(None);
+                    }
+                }
+            }
+        } else {
+            std::mem::take(&mut inner.pending)
+        };
+
+        // NOTE: Anything past the maximum is kept around for the next read
+        if let Some(max) = max {
+            if chunk.len() > max {
+                inner.pending = chunk.split_off(max);
+            }
+        }
+        Ok(Some(chunk))
+    }
+
+    /**
+        Reads the rest of the body, erroring if it is larger than the given limit.
+    */
+    async fn read_all(&self, limit: usize) -> LuaResult<Vec<u8>> {
+        let mut all = Vec::new();
+        while let Some(chunk) = self.read(None).await? {
+            if all.len() + chunk.len() > limit {
+                // NOTE: Drop the rest of the body, there is no
+                // point in reading any more of it after this
+                self.inner.lock().await.response.take();
+                return Err(LuaError::runtime(format!(
+                    "Response body is larger than the limit of {limit} bytes"
+                )));
+            }
+            all.extend_from_slice(&chunk);
+        }
+        Ok(all)
+    }
+}
+
+impl LuaUserData for LuaResponseBody {
+    fn add_fields<'lua, F: LuaUserDataFields<'lua, Self>>(fields: &mut F) {
+        fields.add_meta_field(LuaMetaMethod::Type, "ResponseBody");
+    }
+
+    fn add_methods<'lua, M: LuaUserDataMethods<'lua, Self>>(methods: &mut M) {
+        methods.add_async_method("read", |lua, this, max: Option<usize>| async move {
+            if max == Some(0) {
+                return Err(LuaError::runtime(
+                    "Amount of bytes to read must be positive",
+                ));
+            }
+            match this.read(max).await? {
+                Some(chunk) => Ok(LuaValue::String(lua.create_string(chunk)?)),
+                None => Ok(LuaValue::Nil),
+            }
+        });
+        methods.add_async_method("readAll", |lua, this, limit: Option<usize>| async move {
+            let all = this
+                .read_all(limit.unwrap_or(DEFAULT_READ_ALL_LIMIT))
+                .await?;
+            lua.create_string(all)
+        });
+    }
+}
+
+/**
+    The sending half of a streaming request body for `net.request`.
+
+    Chunks are produced by calling a Lua function until it returns `nil`, and
+    are sent to the request body one at a time, so that only a single chunk
+    is ever held in memory while the request is being sent.
+*/
+pub struct RequestBodySender<'lua> {
+    producer: LuaFunction<'lua>,
+    sender: mpsc::Sender<Result<Vec<u8>, String>>,
+}
+
+impl<'lua> RequestBodySender<'lua> {
+    /**
+        Creates a new streaming request body, returning the sender
+        for it along with the body that should be sent in the request.
+    */
+    pub fn new(producer: LuaFunction<'lua>) -> (Self, Body) {
+        let (sender, receiver) = mpsc::channel(1);
+        let body = Body::wrap_stream(stream::unfold(receiver, |mut receiver| async move {
+            receiver.recv().await.map(|chunk| (chunk, receiver))
+        }));
+        (Self { producer, sender }, body)
+    }
+
+    /**
+        Produces and sends chunks until the producer returns `nil`,
+        or until the request no longer needs any more of the body.
+    */
+    pub async fn send_all(self) -> LuaResult<()> {
+        loop {
+            match self.producer.call_async::<_, Option<BString>>(()).await {
+                Ok(Some(chunk)) => {
+                    if self.sender.send(Ok(chunk.into())).await.is_err() {
+                        return Ok(());
+                    }
+                }
+                Ok(None) => return Ok(()),
+                Err(e) => {
+                    // NOTE: The error is also sent to the body, so that the
+                    // request gets aborted instead of sending a truncated body
+                    let _ = self.sender.send(Err(e.to_string())).await;
+                    return Err(e);
+                }
+            }
+        }
+    }
+}
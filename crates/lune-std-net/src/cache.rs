@@ -0,0 +1,282 @@
+use std::{
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use mlua::prelude::*;
+use reqwest::{
+    header::{
+        HeaderMap, HeaderName, HeaderValue, CACHE_CONTROL, DATE, ETAG, EXPIRES, LAST_MODIFIED,
+    },
+    Method, Url,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::fs;
+
+// NOTE: These are the headers that a 304 Not Modified response may
+// update on a stored response, everything else is kept as it was
+const REVALIDATION_HEADERS: &[HeaderName] = &[CACHE_CONTROL, DATE, ETAG, EXPIRES, LAST_MODIFIED];
+
+/**
+    The parts of a `Cache-Control` header that are relevant to a private client-side cache.
+*/
+#[derive(Debug, Clone, Copy, Default)]
+struct CacheControl {
+    no_store: bool,
+    no_cache: bool,
+    max_age: Option<u64>,
+}
+
+impl CacheControl {
+    fn from_headers(headers: &HeaderMap) -> Self {
+        let mut this = Self::default();
+        for value in headers.get_all(CACHE_CONTROL) {
+            let Ok(value) = value.to_str() else {
+                continue;
+            };
+            for directive in value.split(',') {
+                let (name, arg) = match directive.split_once('=') {
+                    Some((name, arg)) => (name.trim(), Some(arg.trim().trim_matches('"'))),
+                    None => (directive.trim(), None),
+                };
+                if name.eq_ignore_ascii_case("no-store") {
+                    this.no_store = true;
+                } else if name.eq_ignore_ascii_case("no-cache") {
+                    this.no_cache = true;
+                } else if name.eq_ignore_ascii_case("max-age") {
+                    this.max_age = arg.and_then(|arg| arg.parse().ok());
+                }
+            }
+        }
+        this
+    }
+}
+
+/**
+    A response stored in an on-disk response cache.
+
+    The body is stored in a separate file next to the metadata,
+    so that it does not need to be encoded in any special way.
+*/
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedResponse {
+    pub url: String,
+    pub status_code: u16,
+    pub headers: Vec<(String, String)>,
+    pub stored_at: u64,
+    #[serde(skip)]
+    pub body: Vec<u8>,
+}
+
+impl CachedResponse {
+    /**
+        Creates a new cached response, if the given response can be cached.
+
+        A response can be cached if it was successful, if it does not forbid storing it,
+        and if it has some way of either expiring or being revalidated with the server.
+    */
+    pub fn new(url: &Url, status_code: u16, headers: &HeaderMap, body: &[u8]) -> Option<Self> {
+        let control = CacheControl::from_headers(headers);
+        let revalidatable = headers.contains_key(ETAG) || headers.contains_key(LAST_MODIFIED);
+        let expirable = control.max_age.is_some_and(|age| age > 0);
+        if status_code != 200 || control.no_store || !(revalidatable || expirable) {
+            return None;
+        }
+        Some(Self {
+            url: url.to_string(),
+            status_code,
+            headers: headers
+                .iter()
+                .filter_map(|(name, value)| {
+                    let value = value.to_str().ok()?;
+                    Some((name.to_string(), value.to_string()))
+                })
+                .collect(),
+            stored_at: unix_now(),
+            body: body.to_vec(),
+        })
+    }
+
+    pub fn header_map(&self) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for (name, value) in &self.headers {
+            if let (Ok(name), Ok(value)) =
+                (HeaderName::from_str(name), HeaderValue::from_str(value))
+            {
+                map.append(name, value);
+            }
+        }
+        map
+    }
+
+    /**
+        Checks if the response is still fresh, meaning that
+        it can be used without revalidating it with the server.
+    */
+    pub fn is_fresh(&self) -> bool {
+        let control = CacheControl::from_headers(&self.header_map());
+        !control.no_cache
+            && control
+                .max_age
+                .is_some_and(|age| unix_now().saturating_sub(self.stored_at) < age)
+    }
+
+    /**
+        Adds headers to the given request headers that let the server
+        respond with `304 Not Modified` if the response has not changed.
+
+        Conditional headers that were already set are left untouched.
+    */
+    pub fn add_conditional_headers(&self, request_headers: &mut HeaderMap) {
+        let stored = self.header_map();
+        let pairs = [
+            (ETAG, HeaderName::from_static("if-none-match")),
+            (LAST_MODIFIED, HeaderName::from_static("if-modified-since")),
+        ];
+        for (stored_name, request_name) in pairs {
+            if let Some(value) = stored.get(stored_name) {
+                if !request_headers.contains_key(&request_name) {
+                    request_headers.insert(request_name, value.clone());
+                }
+            }
+        }
+    }
+
+    /**
+        Updates the response after the server said that it has not been modified.
+    */
+    pub fn revalidate(&mut self, not_modified_headers: &HeaderMap) {
+        for name in REVALIDATION_HEADERS {
+            if let Some(value) = not_modified_headers.get(name).and_then(|v| v.to_str().ok()) {
+                self.headers
+                    .retain(|(n, _)| !n.eq_ignore_ascii_case(name.as_str()));
+                self.headers.push((name.to_string(), value.to_string()));
+            }
+        }
+        self.stored_at = unix_now();
+    }
+}
+
+/**
+    An on-disk cache for responses to `net.request`.
+
+    Responses are keyed by the request url and headers, and only `GET` requests are cached.
+*/
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    dir: PathBuf,
+    key: String,
+}
+
+impl ResponseCache {
+    /**
+        Creates a cache handle for the given request, or `None` if the request can not be cached.
+    */
+    pub fn for_request(
+        dir: &Path,
+        method: &Method,
+        url: &Url,
+        headers: &HeaderMap,
+    ) -> Option<Self> {
+        if method != Method::GET {
+            return None;
+        }
+
+        let mut headers = headers
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_bytes()))
+            .collect::<Vec<_>>();
+        headers.sort_unstable();
+
+        let mut hasher = Sha256::new();
+        hasher.update(url.as_str());
+        for (name, value) in headers {
+            hasher.update(b"\n");
+            hasher.update(name);
+            hasher.update(b":");
+            hasher.update(value);
+        }
+
+        Some(Self {
+            dir: dir.to_path_buf(),
+            key: format!("{:x}", hasher.finalize()),
+        })
+    }
+
+    fn meta_path(&self) -> PathBuf {
+        self.dir.join(format!("{}.json", self.key))
+    }
+
+    fn body_path(&self) -> PathBuf {
+        self.dir.join(format!("{}.body", self.key))
+    }
+
+    /**
+        Reads the stored response, if any.
+
+        Missing or unreadable entries are treated as if nothing was stored.
+    */
+    pub async fn load(&self) -> Option<CachedResponse> {
+        let meta = fs::read(self.meta_path()).await.ok()?;
+        let mut response: CachedResponse = serde_json::from_slice(&meta).ok()?;
+        response.body = fs::read(self.body_path()).await.ok()?;
+        Some(response)
+    }
+
+    /**
+        Stores the given response, replacing any previously stored response.
+
+        # Errors
+
+        Errors if the cache directory or any of the entry files could not be written.
+    */
+    pub async fn store(&self, response: &CachedResponse) -> LuaResult<()> {
+        let meta = serde_json::to_vec(response).into_lua_err()?;
+        let context = || {
+            format!(
+                "Failed to write to cache directory '{}'",
+                self.dir.display()
+            )
+        };
+        fs::create_dir_all(&self.dir)
+            .await
+            .into_lua_err()
+            .with_context(|_| context())?;
+        fs::write(self.body_path(), &response.body)
+            .await
+            .into_lua_err()
+            .with_context(|_| context())?;
+        fs::write(self.meta_path(), meta)
+            .await
+            .into_lua_err()
+            .with_context(|_| context())
+    }
+
+    /**
+        Updates only the metadata of a stored response, keeping its body.
+
+        # Errors
+
+        Errors if the metadata file could not be written.
+    */
+    pub async fn store_meta(&self, response: &CachedResponse) -> LuaResult<()> {
+        let meta = serde_json::to_vec(response).into_lua_err()?;
+        fs::write(self.meta_path(), meta)
+            .await
+            .into_lua_err()
+            .with_context(|_| {
+                format!(
+                    "Failed to write to cache directory '{}'",
+                    self.dir.display()
+                )
+            })
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
@@ -1,24 +1,39 @@
-use std::str::FromStr;
+use std::{str::FromStr, sync::Arc};
 
 use mlua::prelude::*;
 
-use reqwest::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_ENCODING};
+use reqwest::{
+    header::{
+        HeaderMap, HeaderName, HeaderValue, AUTHORIZATION, CONTENT_ENCODING, CONTENT_LENGTH,
+        CONTENT_TYPE, COOKIE, LOCATION, PROXY_AUTHORIZATION, TRANSFER_ENCODING,
+    },
+    redirect::Policy,
+    Body, Method, Proxy, StatusCode, Url,
+};
 
 use lune_std_serde::{decompress, CompressDecompressFormat};
 use lune_utils::TableBuilder;
 
-use super::{config::RequestConfig, util::header_map_to_table};
+use super::{
+    body::LuaResponseBody,
+    cache::{CachedResponse, ResponseCache},
+    config::{RequestConfig, RequestConfigOptions},
+    permissions::{is_url_allowed, NetHost},
+    util::{create_body, header_map_to_table},
+};
 
 const REGISTRY_KEY: &str = "NetClient";
 
 pub struct NetClientBuilder {
-    builder: reqwest::ClientBuilder,
+    headers: HeaderMap,
+    allowed_hosts: Option<Vec<NetHost>>,
 }
 
 impl NetClientBuilder {
     pub fn new() -> NetClientBuilder {
         Self {
-            builder: reqwest::ClientBuilder::new(),
+            headers: HeaderMap::new(),
+            allowed_hosts: None,
         }
     }
 
@@ -27,25 +42,57 @@ impl NetClientBuilder {
         K: AsRef<str>,
         V: AsRef<[u8]>,
     {
-        let mut map = HeaderMap::new();
         for (key, val) in headers {
             let hkey = HeaderName::from_str(key.as_ref()).into_lua_err()?;
             let hval = HeaderValue::from_bytes(val.as_ref()).into_lua_err()?;
-            map.insert(hkey, hval);
+            self.headers.insert(hkey, hval);
         }
-        self.builder = self.builder.default_headers(map);
         Ok(self)
     }
 
+    pub fn allowed_hosts(mut self, hosts: Option<Vec<NetHost>>) -> Self {
+        self.allowed_hosts = hosts;
+        self
+    }
+
     pub fn build(self) -> LuaResult<NetClient> {
-        let client = self.builder.build().into_lua_err()?;
-        Ok(NetClient { inner: client })
+        let client = create_client(self.headers.clone(), None, true)?;
+        Ok(NetClient {
+            inner: client,
+            headers: Arc::new(self.headers),
+            allowed_hosts: self.allowed_hosts.map(Arc::new),
+        })
+    }
+}
+
+/**
+    Creates a new `reqwest` client with the given default headers and connection options.
+
+    Redirects are never followed by the client itself, since they are followed
+    by `NetClient` instead, which lets them be configured for each request.
+*/
+fn create_client(
+    headers: HeaderMap,
+    proxy: Option<&str>,
+    verify_tls: bool,
+) -> LuaResult<reqwest::Client> {
+    let mut builder = reqwest::ClientBuilder::new()
+        .default_headers(headers)
+        .redirect(Policy::none())
+        .danger_accept_invalid_certs(!verify_tls);
+    if let Some(proxy) = proxy {
+        let proxy = Proxy::all(proxy)
+            .map_err(|e| LuaError::RuntimeError(format!("Invalid proxy url '{proxy}' - {e}")))?;
+        builder = builder.proxy(proxy);
     }
+    builder.build().into_lua_err()
 }
 
 #[derive(Debug, Clone)]
 pub struct NetClient {
     inner: reqwest::Client,
+    headers: Arc<HeaderMap>,
+    allowed_hosts: Option<Arc<Vec<NetHost>>>,
 }
 
 impl NetClient {
@@ -59,9 +106,44 @@ impl NetClient {
             .expect("Failed to store NetClient in lua registry");
     }
 
-    pub async fn request(&self, config: RequestConfig) -> LuaResult<NetClientResponse> {
+    /**
+        Gets the client stored in the registry, if the `net` standard library has been created.
+    */
+    pub fn try_from_registry(lua: &Lua) -> Option<Self> {
+        lua.named_registry_value::<Option<Self>>(REGISTRY_KEY)
+            .expect("Failed to get NetClient from lua registry")
+    }
+
+    /**
+        Gets the `reqwest` client to send a request with, given its options.
+
+        The shared client is used unless the request needs a proxy or to skip TLS
+        verification, in which case a separate client is created just for that request.
+    */
+    fn client_for(&self, options: &RequestConfigOptions) -> LuaResult<reqwest::Client> {
+        if options.proxy.is_none() && options.verify_tls {
+            Ok(self.inner.clone())
+        } else {
+            create_client(
+                HeaderMap::clone(&self.headers),
+                options.proxy.as_deref(),
+                options.verify_tls,
+            )
+        }
+    }
+
+    /**
+        Sends a request using the given config and body.
+
+        The body is given separately from the config, since a streaming
+        body must first be taken out of the config while still in Lua.
+    */
+    pub async fn request(&self, config: RequestConfig, body: Body) -> LuaResult<NetClientResponse> {
+        let options = config.options;
+        let client = self.client_for(&options)?;
+
         // Create and send the request
-        let mut request = self.inner.request(config.method, config.url);
+        let mut request = client.request(config.method, config.url);
         for (query, values) in config.query {
             request = request.query(
                 &values
@@ -75,23 +157,34 @@ impl NetClient {
                 request = request.header(header.as_str(), value);
             }
         }
-        let res = request
-            .body(config.body.unwrap_or_default())
-            .send()
-            .await
-            .into_lua_err()?;
-
-        // Extract status, headers
-        let res_status = res.status().as_u16();
-        let res_status_text = res.status().canonical_reason();
-        let res_headers = res.headers().clone();
-
-        // Read response bytes
-        let mut res_bytes = res.bytes().await.into_lua_err()?.to_vec();
+        if let Some(timeout) = options.timeout {
+            request = request.timeout(timeout);
+        }
+        let request = request.body(body).build().into_lua_err()?;
+
+        // NOTE: Streamed responses are given to lua as they are, without
+        // caching or decompressing them, since that needs the whole body
+        if options.stream_body {
+            let (res, urls) = self.execute_following(&client, request, &options).await?;
+            return Ok(NetClientResponse::new(
+                res.status().as_u16(),
+                res.headers().clone(),
+                NetClientResponseBody::Stream(LuaResponseBody::new(res)),
+                false,
+                urls,
+            ));
+        }
+
+        let cache = options.cache_dir.as_deref().and_then(|dir| {
+            ResponseCache::for_request(dir, request.method(), request.url(), request.headers())
+        });
+        let (res_status, res_headers, mut res_bytes, res_urls) = self
+            .execute_cached(&client, request, cache, &options)
+            .await?;
         let mut res_decompressed = false;
 
         // Check for extra options, decompression
-        if config.options.decompress {
+        if options.decompress {
             let decompress_format = res_headers
                 .iter()
                 .find(|(name, _)| {
@@ -106,14 +199,172 @@ impl NetClient {
             }
         }
 
-        Ok(NetClientResponse {
-            ok: (200..300).contains(&res_status),
-            status_code: res_status,
-            status_message: res_status_text.unwrap_or_default().to_string(),
-            headers: res_headers,
-            body: res_bytes,
-            body_decompressed: res_decompressed,
-        })
+        Ok(NetClientResponse::new(
+            res_status,
+            res_headers,
+            NetClientResponseBody::Bytes(res_bytes),
+            res_decompressed,
+            res_urls,
+        ))
+    }
+
+    /**
+        Sends the given request, using the given response cache if there is one.
+
+        A cached response that is still fresh is used without sending the request at all,
+        otherwise the request is sent and the server may respond with `304 Not Modified`
+        to let us know that the cached response can still be used.
+    */
+    async fn execute_cached(
+        &self,
+        client: &reqwest::Client,
+        mut request: reqwest::Request,
+        cache: Option<ResponseCache>,
+        options: &RequestConfigOptions,
+    ) -> LuaResult<(u16, HeaderMap, Vec<u8>, Vec<Url>)> {
+        let cached = match &cache {
+            Some(cache) if !options.force_refresh => cache.load().await,
+            _ => None,
+        };
+        if let Some(cached) = &cached {
+            if cached.is_fresh() {
+                return Ok((
+                    cached.status_code,
+                    cached.header_map(),
+                    cached.body.clone(),
+                    vec![request.url().clone()],
+                ));
+            }
+            cached.add_conditional_headers(request.headers_mut());
+        }
+
+        let url = request.url().clone();
+        let (res, urls) = self.execute_following(client, request, options).await?;
+
+        if let (Some(cache), Some(mut cached)) = (&cache, cached) {
+            if res.status() == StatusCode::NOT_MODIFIED {
+                cached.revalidate(res.headers());
+                cache.store_meta(&cached).await?;
+                return Ok((cached.status_code, cached.header_map(), cached.body, urls));
+            }
+        }
+
+        let status = res.status().as_u16();
+        let headers = res.headers().clone();
+        let bytes = res.bytes().await.into_lua_err()?.to_vec();
+        if let Some(cache) = &cache {
+            if let Some(entry) = CachedResponse::new(&url, status, &headers, &bytes) {
+                cache.store(&entry).await?;
+            }
+        }
+
+        Ok((status, headers, bytes, urls))
+    }
+
+    /**
+        Sends the given request, following any redirects as allowed by the given options.
+
+        Returns the final response, along with every url that was requested in
+        order, the last of which is the url that the final response came from.
+
+        Redirects are followed the same way that browsers follow them - `303 See Other`
+        responses, as well as `301` and `302` responses, are followed using a `GET` request
+        without a body, while `307` and `308` responses are followed using the same method
+        and body. Credentials are never sent along when redirected to a different host.
+    */
+    async fn execute_following(
+        &self,
+        client: &reqwest::Client,
+        mut request: reqwest::Request,
+        options: &RequestConfigOptions,
+    ) -> LuaResult<(reqwest::Response, Vec<Url>)> {
+        let mut urls = vec![request.url().clone()];
+        loop {
+            // NOTE: Streamed request bodies can not be sent again, so any
+            // redirect that would need to resend one is not followed
+            let method = request.method().clone();
+            let mut headers = request.headers().clone();
+            let timeout = request.timeout().copied();
+            let replayable_body = match request.body() {
+                None => Some(None),
+                Some(body) => body.as_bytes().map(|bytes| Some(bytes.to_vec())),
+            };
+
+            let res = client.execute(request).await.into_lua_err()?;
+            if !options.follow_redirects {
+                return Ok((res, urls));
+            }
+
+            let (method, body) = match res.status() {
+                StatusCode::MOVED_PERMANENTLY | StatusCode::FOUND | StatusCode::SEE_OTHER => {
+                    for header in [
+                        TRANSFER_ENCODING,
+                        CONTENT_ENCODING,
+                        CONTENT_TYPE,
+                        CONTENT_LENGTH,
+                    ] {
+                        headers.remove(header);
+                    }
+                    let method = if method == Method::HEAD {
+                        method
+                    } else {
+                        Method::GET
+                    };
+                    (method, None)
+                }
+                StatusCode::TEMPORARY_REDIRECT | StatusCode::PERMANENT_REDIRECT => {
+                    match replayable_body {
+                        Some(body) => (method, body),
+                        None => return Ok((res, urls)),
+                    }
+                }
+                _ => return Ok((res, urls)),
+            };
+
+            let Some(next_url) = res
+                .headers()
+                .get(LOCATION)
+                .and_then(|location| location.to_str().ok())
+                .and_then(|location| res.url().join(location).ok())
+            else {
+                return Ok((res, urls));
+            };
+
+            if urls.len() > options.max_redirects {
+                return Err(LuaError::RuntimeError(format!(
+                    "Too many redirects - the maximum amount of redirects is {}",
+                    options.max_redirects
+                )));
+            }
+
+            // Redirects must also be checked, otherwise an allowed
+            // host could be used to redirect to a disallowed one
+            if let Some(hosts) = &self.allowed_hosts {
+                if !is_url_allowed(hosts, &next_url) {
+                    return Err(LuaError::RuntimeError(format!(
+                        "Network access to '{}' is not allowed",
+                        next_url.host_str().unwrap_or_default()
+                    )));
+                }
+            }
+
+            // NOTE: Credentials are only meant for the host they were
+            // given to, and must not be leaked to any other host
+            let previous_url = res.url();
+            if next_url.host_str() != previous_url.host_str()
+                || next_url.port_or_known_default() != previous_url.port_or_known_default()
+            {
+                for header in [AUTHORIZATION, COOKIE, PROXY_AUTHORIZATION] {
+                    headers.remove(header);
+                }
+            }
+
+            request = reqwest::Request::new(method, next_url.clone());
+            *request.headers_mut() = headers;
+            *request.body_mut() = body.map(Body::from);
+            *request.timeout_mut() = timeout;
+            urls.push(next_url);
+        }
     }
 }
 
@@ -138,17 +389,64 @@ impl From<&Lua> for NetClient {
     }
 }
 
+pub enum NetClientResponseBody {
+    Bytes(Vec<u8>),
+    Stream(LuaResponseBody),
+}
+
 pub struct NetClientResponse {
     ok: bool,
     status_code: u16,
     status_message: String,
     headers: HeaderMap,
-    body: Vec<u8>,
+    body: NetClientResponseBody,
     body_decompressed: bool,
+    url: String,
+    redirects: Vec<String>,
 }
 
 impl NetClientResponse {
-    pub fn into_lua_table(self, lua: &Lua) -> LuaResult<LuaTable> {
+    fn new(
+        status_code: u16,
+        headers: HeaderMap,
+        body: NetClientResponseBody,
+        body_decompressed: bool,
+        urls: Vec<Url>,
+    ) -> Self {
+        let status_message = StatusCode::from_u16(status_code)
+            .ok()
+            .and_then(|status| status.canonical_reason());
+        let mut redirects = urls.into_iter().map(String::from).collect::<Vec<_>>();
+        let url = redirects.pop().unwrap_or_default();
+        Self {
+            ok: (200..300).contains(&status_code),
+            status_code,
+            status_message: status_message.unwrap_or_default().to_string(),
+            headers,
+            body,
+            body_decompressed,
+            url,
+            redirects,
+        }
+    }
+
+    /**
+        Gets the status code and the full body of the response.
+
+        Streamed responses have no body here, since it has not been read yet.
+    */
+    pub fn into_status_and_bytes(self) -> (u16, Vec<u8>) {
+        match self.body {
+            NetClientResponseBody::Bytes(bytes) => (self.status_code, bytes),
+            NetClientResponseBody::Stream(_) => (self.status_code, Vec::new()),
+        }
+    }
+
+    pub fn into_lua_table(self, lua: &Lua, buffer_body: bool) -> LuaResult<LuaTable> {
+        let (body, stream) = match self.body {
+            NetClientResponseBody::Bytes(bytes) => (bytes, None),
+            NetClientResponseBody::Stream(stream) => (Vec::new(), Some(stream)),
+        };
         TableBuilder::new(lua)?
             .with_value("ok", self.ok)?
             .with_value("statusCode", self.status_code)?
@@ -157,7 +455,10 @@ impl NetClientResponse {
                 "headers",
                 header_map_to_table(lua, self.headers, self.body_decompressed)?,
             )?
-            .with_value("body", lua.create_string(&self.body)?)?
+            .with_value("body", create_body(lua, body, buffer_body)?)?
+            .with_value("stream", stream)?
+            .with_value("url", self.url)?
+            .with_value("redirects", self.redirects)?
             .build_readonly()
     }
 }
@@ -1,6 +1,8 @@
 use std::{
     collections::HashMap,
     net::{IpAddr, Ipv4Addr},
+    path::PathBuf,
+    time::Duration,
 };
 
 use bstr::{BString, ByteSlice};
@@ -12,6 +14,10 @@ use super::util::table_to_hash_map;
 
 const DEFAULT_IP_ADDRESS: IpAddr = IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1));
 
+const DEFAULT_PING_TIMEOUT: Duration = Duration::from_secs(10);
+
+const DEFAULT_MAX_REDIRECTS: usize = 10;
+
 const WEB_SOCKET_UPDGRADE_REQUEST_HANDLER: &str = r#"
 return {
     status = 426,
@@ -27,11 +33,31 @@ return {
 #[derive(Debug, Clone)]
 pub struct RequestConfigOptions {
     pub decompress: bool,
+    pub cache_dir: Option<PathBuf>,
+    pub force_refresh: bool,
+    pub stream_body: bool,
+    pub buffer_body: bool,
+    pub timeout: Option<Duration>,
+    pub follow_redirects: bool,
+    pub max_redirects: usize,
+    pub proxy: Option<String>,
+    pub verify_tls: bool,
 }
 
 impl Default for RequestConfigOptions {
     fn default() -> Self {
-        Self { decompress: true }
+        Self {
+            decompress: true,
+            cache_dir: None,
+            force_refresh: false,
+            stream_body: false,
+            buffer_body: false,
+            timeout: None,
+            follow_redirects: true,
+            max_redirects: DEFAULT_MAX_REDIRECTS,
+            proxy: None,
+            verify_tls: true,
+        }
     }
 }
 
@@ -48,7 +74,68 @@ impl<'lua> FromLua<'lua> for RequestConfigOptions {
                     "Invalid option value for 'decompress' in request config options".to_string(),
                 )),
             }?;
-            Ok(Self { decompress })
+            let cache_dir = match tab.get::<_, Option<String>>("cacheDir") {
+                Ok(dir) => Ok(dir.map(PathBuf::from)),
+                Err(_) => Err(LuaError::RuntimeError(
+                    "Invalid option value for 'cacheDir' in request config options".to_string(),
+                )),
+            }?;
+            let force_refresh = match tab.get::<_, Option<bool>>("forceRefresh") {
+                Ok(refresh) => Ok(refresh.unwrap_or_default()),
+                Err(_) => Err(LuaError::RuntimeError(
+                    "Invalid option value for 'forceRefresh' in request config options".to_string(),
+                )),
+            }?;
+            let stream_body = match tab.get::<_, Option<bool>>("streamBody") {
+                Ok(stream) => Ok(stream.unwrap_or_default()),
+                Err(_) => Err(LuaError::RuntimeError(
+                    "Invalid option value for 'streamBody' in request config options".to_string(),
+                )),
+            }?;
+            let buffer_body = match tab.get::<_, Option<bool>>("bufferBody") {
+                Ok(buffer) => Ok(buffer.unwrap_or_default()),
+                Err(_) => Err(LuaError::RuntimeError(
+                    "Invalid option value for 'bufferBody' in request config options".to_string(),
+                )),
+            }?;
+            let timeout = get_seconds_option(&tab, "timeout", "request config options")?;
+            let follow_redirects = match tab.get::<_, Option<bool>>("followRedirects") {
+                Ok(follow) => Ok(follow.unwrap_or(true)),
+                Err(_) => Err(LuaError::RuntimeError(
+                    "Invalid option value for 'followRedirects' in request config options"
+                        .to_string(),
+                )),
+            }?;
+            let max_redirects = match tab.get::<_, Option<usize>>("maxRedirects") {
+                Ok(max) => Ok(max.unwrap_or(DEFAULT_MAX_REDIRECTS)),
+                Err(_) => Err(LuaError::RuntimeError(
+                    "Invalid option value for 'maxRedirects' in request config options".to_string(),
+                )),
+            }?;
+            let proxy = match tab.get::<_, Option<String>>("proxy") {
+                Ok(proxy) => Ok(proxy),
+                Err(_) => Err(LuaError::RuntimeError(
+                    "Invalid option value for 'proxy' in request config options".to_string(),
+                )),
+            }?;
+            let verify_tls = match tab.get::<_, Option<bool>>("verifyTls") {
+                Ok(verify) => Ok(verify.unwrap_or(true)),
+                Err(_) => Err(LuaError::RuntimeError(
+                    "Invalid option value for 'verifyTls' in request config options".to_string(),
+                )),
+            }?;
+            Ok(Self {
+                decompress,
+                cache_dir,
+                force_refresh,
+                stream_body,
+                buffer_body,
+                timeout,
+                follow_redirects,
+                max_redirects,
+                proxy,
+                verify_tls,
+            })
         } else {
             // Anything else is invalid
             Err(LuaError::FromLuaConversionError {
@@ -63,13 +150,22 @@ impl<'lua> FromLua<'lua> for RequestConfigOptions {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug)]
+pub enum RequestConfigBody {
+    Bytes(Vec<u8>),
+    // NOTE: Stored in the registry since the config must be
+    // sendable, the function is taken back out of it before
+    // the request is sent, see `RequestBodySender`
+    Stream(LuaRegistryKey),
+}
+
+#[derive(Debug)]
 pub struct RequestConfig {
     pub url: String,
     pub method: Method,
     pub query: HashMap<String, Vec<String>>,
     pub headers: HashMap<String, Vec<String>>,
-    pub body: Option<Vec<u8>>,
+    pub body: Option<RequestConfigBody>,
     pub options: RequestConfigOptions,
 }
 
@@ -108,9 +204,14 @@ impl FromLua<'_> for RequestConfig {
                 Err(_) => HashMap::new(),
             };
             // Extract body
-            let body = match tab.get::<_, BString>("body") {
-                Ok(config_body) => Some(config_body.as_bytes().to_owned()),
-                Err(_) => None,
+            let body = match tab.get::<_, LuaValue>("body")? {
+                LuaValue::Function(f) => {
+                    Some(RequestConfigBody::Stream(lua.create_registry_value(f)?))
+                }
+                value => match BString::from_lua(value, lua) {
+                    Ok(config_body) => Some(RequestConfigBody::Bytes(config_body.into())),
+                    Err(_) => None,
+                },
             };
 
             // Convert method string into proper enum
@@ -156,6 +257,91 @@ impl FromLua<'_> for RequestConfig {
     }
 }
 
+// Net socket config
+
+#[derive(Debug, Clone, Copy)]
+pub struct SocketKeepalive {
+    pub interval: Duration,
+    pub timeout: Duration,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SocketConfigOptions {
+    pub keepalive: Option<SocketKeepalive>,
+}
+
+impl<'lua> FromLua<'lua> for SocketConfigOptions {
+    fn from_lua(value: LuaValue<'lua>, _: &'lua Lua) -> LuaResult<Self> {
+        if let LuaValue::Nil = value {
+            // Nil means default options
+            Ok(Self::default())
+        } else if let LuaValue::Table(tab) = value {
+            // Table means custom options, keepalive is only enabled given an interval
+            let interval = get_seconds_option(&tab, "pingInterval", "socket options")?;
+            let timeout = get_seconds_option(&tab, "pingTimeout", "socket options")?;
+            if timeout.is_some() && interval.is_none() {
+                return Err(LuaError::runtime(
+                    "Invalid socket options - 'pingTimeout' requires 'pingInterval' to be set",
+                ));
+            }
+            Ok(Self {
+                keepalive: interval.map(|interval| SocketKeepalive {
+                    interval,
+                    timeout: timeout.unwrap_or(DEFAULT_PING_TIMEOUT),
+                }),
+            })
+        } else {
+            // Anything else is invalid
+            Err(LuaError::FromLuaConversionError {
+                from: value.type_name(),
+                to: "SocketConfigOptions",
+                message: Some(format!(
+                    "Invalid socket options - expected table or nil, got {}",
+                    value.type_name()
+                )),
+            })
+        }
+    }
+}
+
+/**
+    Options for connecting to a web socket using `net.socket`.
+
+    These are the same as the socket options used by `net.serve`,
+    with the addition of headers to send with the connection request.
+*/
+#[derive(Debug, Clone, Default)]
+pub struct SocketConnectOptions {
+    pub headers: HashMap<String, Vec<String>>,
+    pub socket: SocketConfigOptions,
+}
+
+impl<'lua> FromLua<'lua> for SocketConnectOptions {
+    fn from_lua(value: LuaValue<'lua>, lua: &'lua Lua) -> LuaResult<Self> {
+        let headers = match &value {
+            LuaValue::Table(tab) => match tab.get::<_, Option<LuaTable>>("headers")? {
+                Some(headers) => table_to_hash_map(headers, "headers")?,
+                None => HashMap::new(),
+            },
+            _ => HashMap::new(),
+        };
+        Ok(Self {
+            headers,
+            socket: SocketConfigOptions::from_lua(value, lua)?,
+        })
+    }
+}
+
+fn get_seconds_option(tab: &LuaTable, key: &str, options: &str) -> LuaResult<Option<Duration>> {
+    match tab.get::<_, Option<f64>>(key) {
+        Ok(None) => Ok(None),
+        Ok(Some(secs)) if secs.is_finite() && secs > 0.0 => Ok(Some(Duration::from_secs_f64(secs))),
+        _ => Err(LuaError::runtime(format!(
+            "Invalid option value for '{key}' in {options} - expected a positive number"
+        ))),
+    }
+}
+
 // Net serve config
 
 #[derive(Debug)]
@@ -163,6 +349,9 @@ pub struct ServeConfig<'a> {
     pub address: IpAddr,
     pub handle_request: LuaFunction<'a>,
     pub handle_web_socket: Option<LuaFunction<'a>>,
+    pub stream_body: bool,
+    pub buffer_body: bool,
+    pub socket_options: SocketConfigOptions,
 }
 
 impl<'lua> FromLua<'lua> for ServeConfig<'lua> {
@@ -173,12 +362,18 @@ impl<'lua> FromLua<'lua> for ServeConfig<'lua> {
                 handle_request: f.clone(),
                 handle_web_socket: None,
                 address: DEFAULT_IP_ADDRESS,
+                stream_body: false,
+                buffer_body: false,
+                socket_options: SocketConfigOptions::default(),
             })
         } else if let LuaValue::Table(t) = &value {
             // Table means custom options
             let address: Option<LuaString> = t.get("address")?;
             let handle_request: Option<LuaFunction> = t.get("handleRequest")?;
             let handle_web_socket: Option<LuaFunction> = t.get("handleWebSocket")?;
+            let stream_body: Option<bool> = t.get("streamBody")?;
+            let buffer_body: Option<bool> = t.get("bufferBody")?;
+            let socket_options = SocketConfigOptions::from_lua(t.get("socketOptions")?, lua)?;
             if handle_request.is_some() || handle_web_socket.is_some() {
                 let address: IpAddr = match &address {
                     Some(addr) => {
@@ -209,6 +404,9 @@ impl<'lua> FromLua<'lua> for ServeConfig<'lua> {
                             .expect("Failed to create default http responder function")
                     }),
                     handle_web_socket,
+                    stream_body: stream_body.unwrap_or_default(),
+                    buffer_body: buffer_body.unwrap_or_default(),
+                    socket_options,
                 })
             } else {
                 Err(LuaError::FromLuaConversionError {
@@ -229,3 +427,39 @@ impl<'lua> FromLua<'lua> for ServeConfig<'lua> {
         }
     }
 }
+
+/**
+    Options for creating a mock server using `net.mockServer`.
+*/
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MockServerConfig {
+    pub buffer_body: bool,
+}
+
+impl<'lua> FromLua<'lua> for MockServerConfig {
+    fn from_lua(value: LuaValue<'lua>, _: &'lua Lua) -> LuaResult<Self> {
+        if let LuaValue::Nil = value {
+            // Nil means default options
+            Ok(Self::default())
+        } else if let LuaValue::Table(tab) = value {
+            // Table means custom options
+            let buffer_body = match tab.get::<_, Option<bool>>("bufferBody") {
+                Ok(buffer) => Ok(buffer.unwrap_or_default()),
+                Err(_) => Err(LuaError::RuntimeError(
+                    "Invalid option value for 'bufferBody' in mock server config".to_string(),
+                )),
+            }?;
+            Ok(Self { buffer_body })
+        } else {
+            // Anything else is invalid
+            Err(LuaError::FromLuaConversionError {
+                from: value.type_name(),
+                to: "MockServerConfig",
+                message: Some(format!(
+                    "Invalid mock server config - expected table or nil, got {}",
+                    value.type_name()
+                )),
+            })
+        }
+    }
+}
@@ -1,11 +1,22 @@
 #![allow(clippy::cargo_common_metadata)]
 
+use std::collections::HashMap;
+
 use bstr::BString;
 use mlua::prelude::*;
 use mlua_luau_scheduler::LuaSpawnExt;
+use reqwest::{Body, Method};
+use tokio_tungstenite::tungstenite::{
+    client::IntoClientRequest,
+    http::{HeaderName, HeaderValue},
+};
+use tracing::instrument;
 
+mod body;
+mod cache;
 mod client;
 mod config;
+mod permissions;
 mod server;
 mod util;
 mod websocket;
@@ -13,13 +24,22 @@ mod websocket;
 use lune_utils::TableBuilder;
 
 use self::{
+    body::RequestBodySender,
     client::{NetClient, NetClientBuilder},
-    config::{RequestConfig, ServeConfig},
-    server::serve,
+    config::{
+        MockServerConfig, RequestConfig, RequestConfigBody, RequestConfigOptions, ServeConfig,
+        SocketConnectOptions,
+    },
+    server::{serve, serve_mock, LuaMockServer},
     util::create_user_agent_header,
     websocket::NetWebSocket,
 };
 
+pub use self::permissions::{allow_net_hosts, allowed_net_hosts, check_url};
+
+use self::permissions::{allowed_hosts, check_serve};
+
+use lune_std_fs::{check_read, check_write};
 use lune_std_serde::{decode, encode, EncodeDecodeConfig, EncodeDecodeFormat};
 
 /**
@@ -30,21 +50,65 @@ use lune_std_serde::{decode, encode, EncodeDecodeConfig, EncodeDecodeFormat};
     Errors when out of memory.
 */
 pub fn module(lua: &Lua) -> LuaResult<LuaTable> {
-    NetClientBuilder::new()
-        .headers(&[("User-Agent", create_user_agent_header(lua)?)])?
-        .build()?
-        .into_registry(lua);
-    TableBuilder::new(lua)?
+    create_client(lua)?.into_registry(lua);
+    TableBuilder::new_named(lua, "net")?
         .with_function("jsonEncode", net_json_encode)?
         .with_function("jsonDecode", net_json_decode)?
         .with_async_function("request", net_request)?
         .with_async_function("socket", net_socket)?
         .with_async_function("serve", net_serve)?
+        .with_async_function("mockServer", net_mock_server)?
         .with_function("urlEncode", net_url_encode)?
         .with_function("urlDecode", net_url_decode)?
         .build_readonly()
 }
 
+/**
+    Sends a `GET` request to the given url, with the given headers, returning
+    the status code and the decompressed body of the response.
+
+    This uses the same client as `net.request`, meaning that redirects are followed
+    and checked against network permissions in the same way, and that credentials,
+    such as cookies, are never sent along when redirected to a different host.
+
+    # Errors
+
+    Errors if network access to the url is not allowed, or if the request fails.
+*/
+pub async fn fetch_bytes(
+    lua: &Lua,
+    url: String,
+    headers: Vec<(String, String)>,
+) -> LuaResult<(u16, Vec<u8>)> {
+    check_url(lua, &url)?;
+    let client = match NetClient::try_from_registry(lua) {
+        Some(client) => client,
+        None => create_client(lua)?,
+    };
+    let config = RequestConfig {
+        url,
+        method: Method::GET,
+        query: HashMap::new(),
+        headers: headers
+            .into_iter()
+            .map(|(name, value)| (name, vec![value]))
+            .collect(),
+        body: None,
+        options: RequestConfigOptions::default(),
+    };
+    let res = lua
+        .spawn(async move { client.request(config, Body::from(Vec::new())).await })
+        .await?;
+    Ok(res.into_status_and_bytes())
+}
+
+fn create_client(lua: &Lua) -> LuaResult<NetClient> {
+    NetClientBuilder::new()
+        .headers(&[("User-Agent", create_user_agent_header(lua)?)])?
+        .allowed_hosts(allowed_hosts(lua))
+        .build()
+}
+
 fn net_json_encode<'lua>(
     lua: &'lua Lua,
     (val, pretty): (LuaValue<'lua>, Option<bool>),
@@ -58,25 +122,73 @@ fn net_json_decode(lua: &Lua, json: BString) -> LuaResult<LuaValue> {
     decode(json, lua, config)
 }
 
-async fn net_request(lua: &Lua, config: RequestConfig) -> LuaResult<LuaTable> {
+#[instrument(level = "debug", name = "net.request", skip_all, fields(method = %config.method, url = %config.url))]
+async fn net_request(lua: &Lua, mut config: RequestConfig) -> LuaResult<LuaTable> {
+    check_url(lua, &config.url)?;
+    if let Some(proxy) = &config.options.proxy {
+        check_url(lua, proxy)?;
+    }
+    if let Some(dir) = &config.options.cache_dir {
+        check_read(lua, dir)?;
+        check_write(lua, dir)?;
+    }
+    let (sender, body) = match config.body.take() {
+        Some(RequestConfigBody::Stream(key)) => {
+            let producer = lua.registry_value::<LuaFunction>(&key)?;
+            lua.remove_registry_value(key)?;
+            let (sender, body) = RequestBodySender::new(producer);
+            (Some(sender), body)
+        }
+        Some(RequestConfigBody::Bytes(bytes)) => (None, Body::from(bytes)),
+        None => (None, Body::from(Vec::new())),
+    };
+    let buffer_body = config.options.buffer_body;
     let client = NetClient::from_registry(lua);
-    // NOTE: We spawn the request as a background task to free up resources in lua
-    let res = lua.spawn(async move { client.request(config).await });
-    res.await?.into_lua_table(lua)
+    // NOTE: We spawn the request as a background task to free up resources in lua,
+    // any streaming body is then produced here in lua while the request is sent
+    let res = lua.spawn(async move { client.request(config, body).await });
+    if let Some(sender) = sender {
+        sender.send_all().await?;
+    }
+    res.await?.into_lua_table(lua, buffer_body)
 }
 
-async fn net_socket(lua: &Lua, url: String) -> LuaResult<LuaValue> {
-    let (ws, _) = tokio_tungstenite::connect_async(url).await.into_lua_err()?;
-    NetWebSocket::new(ws).into_lua(lua)
+#[instrument(level = "debug", name = "net.socket", skip_all, fields(url = %url))]
+async fn net_socket(
+    lua: &Lua,
+    (url, options): (String, SocketConnectOptions),
+) -> LuaResult<LuaValue> {
+    check_url(lua, &url)?;
+    let mut request = url.into_client_request().into_lua_err()?;
+    for (name, values) in options.headers {
+        let name = HeaderName::from_bytes(name.as_bytes()).into_lua_err()?;
+        for value in values {
+            let value = HeaderValue::from_str(&value).into_lua_err()?;
+            request.headers_mut().append(&name, value);
+        }
+    }
+    let (ws, _) = tokio_tungstenite::connect_async(request)
+        .await
+        .into_lua_err()?;
+    let socket = NetWebSocket::new(ws);
+    if let Some(keepalive) = options.socket.keepalive {
+        socket.start_keepalive(lua, keepalive);
+    }
+    socket.into_lua(lua)
 }
 
 async fn net_serve<'lua>(
     lua: &'lua Lua,
     (port, config): (u16, ServeConfig<'lua>),
 ) -> LuaResult<LuaTable<'lua>> {
+    check_serve(lua, config.address, port)?;
     serve(lua, port, config).await
 }
 
+async fn net_mock_server(lua: &Lua, config: MockServerConfig) -> LuaResult<LuaMockServer> {
+    serve_mock(lua, config).await
+}
+
 fn net_url_encode<'lua>(
     lua: &'lua Lua,
     (lua_string, as_binary): (LuaString<'lua>, Option<bool>),
@@ -0,0 +1,169 @@
+use std::{fmt, net::IpAddr, str::FromStr};
+
+use mlua::prelude::*;
+use reqwest::Url;
+
+use lune_utils::CodedError;
+
+/**
+    A host, and optionally a port, that network operations are allowed to use.
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetHost {
+    host: String,
+    port: Option<u16>,
+}
+
+impl NetHost {
+    fn matches(&self, host: &str, port: Option<u16>) -> bool {
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        self.host.eq_ignore_ascii_case(host) && self.port.map_or(true, |p| Some(p) == port)
+    }
+}
+
+impl FromStr for NetHost {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        // Parse using the url crate, which handles both ipv6
+        // addresses in brackets, as well as validating ports
+        let url = Url::parse(&format!("unknown://{s}"))
+            .map_err(|e| format!("Invalid network host '{s}' - {e}"))?;
+        let host = match url.host_str() {
+            Some(host) if !host.is_empty() && url.path().is_empty() => host,
+            _ => return Err(format!("Invalid network host '{s}'")),
+        };
+        Ok(Self {
+            host: host
+                .trim_start_matches('[')
+                .trim_end_matches(']')
+                .to_ascii_lowercase(),
+            port: url.port(),
+        })
+    }
+}
+
+impl fmt::Display for NetHost {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.host.contains(':') {
+            write!(f, "[{}]", self.host)?;
+        } else {
+            write!(f, "{}", self.host)?;
+        }
+        if let Some(port) = self.port {
+            write!(f, ":{port}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct NetPermissions {
+    hosts: Option<Vec<NetHost>>,
+}
+
+/**
+    Restricts all network operations to the given hosts, in `host[:port]` format.
+
+    Hosts without a port may be accessed using any port.
+
+    This may be called multiple times to allow more hosts to be accessed.
+
+    # Errors
+
+    Errors if any of the given hosts are not valid.
+*/
+pub fn allow_net_hosts<S: AsRef<str>>(
+    lua: &Lua,
+    hosts: impl IntoIterator<Item = S>,
+) -> LuaResult<()> {
+    let hosts = hosts
+        .into_iter()
+        .map(|host| host.as_ref().parse())
+        .collect::<Result<Vec<NetHost>, _>>()
+        .map_err(LuaError::runtime)?;
+    let mut perms = lua.remove_app_data::<NetPermissions>().unwrap_or_default();
+    perms.hosts.get_or_insert_with(Vec::new).extend(hosts);
+    lua.set_app_data(perms);
+    Ok(())
+}
+
+/**
+    Gets the hosts that network operations have been restricted to, if any,
+    in the same `host[:port]` format as given to [`allow_net_hosts`].
+*/
+#[must_use]
+pub fn allowed_net_hosts(lua: &Lua) -> Option<Vec<String>> {
+    allowed_hosts(lua).map(|hosts| hosts.iter().map(ToString::to_string).collect())
+}
+
+/**
+    Gets the list of allowed hosts, if network operations have been restricted.
+*/
+pub(crate) fn allowed_hosts(lua: &Lua) -> Option<Vec<NetHost>> {
+    lua.app_data_ref::<NetPermissions>()
+        .and_then(|perms| perms.hosts.clone())
+}
+
+/**
+    Checks if the given url is allowed by the given list of allowed hosts.
+*/
+pub(crate) fn is_url_allowed(allowed: &[NetHost], url: &Url) -> bool {
+    let Some(host) = url.host_str() else {
+        return false;
+    };
+    let port = url.port_or_known_default();
+    allowed.iter().any(|allowed| allowed.matches(host, port))
+}
+
+/**
+    Checks that the given url is allowed to be connected to.
+
+    This is also used by other standard libraries that make network
+    requests, so that they respect the same network restrictions.
+
+    # Errors
+
+    Errors if the url is not valid, or if connecting to its host has not been allowed.
+*/
+pub fn check_url(lua: &Lua, url: &str) -> LuaResult<()> {
+    let Some(allowed) = allowed_hosts(lua) else {
+        return Ok(());
+    };
+    let parsed = Url::parse(url).into_lua_err()?;
+    if is_url_allowed(&allowed, &parsed) {
+        Ok(())
+    } else {
+        Err(CodedError::new(
+            "PermissionDenied",
+            format!(
+                "Network access to '{}' is not allowed",
+                parsed.host_str().unwrap_or(url)
+            ),
+        )
+        .into())
+    }
+}
+
+/**
+    Checks that a server is allowed to listen on the given address and port.
+*/
+pub(crate) fn check_serve(lua: &Lua, address: IpAddr, port: u16) -> LuaResult<()> {
+    let Some(allowed) = allowed_hosts(lua) else {
+        return Ok(());
+    };
+    let host = address.to_string();
+    let is_allowed = allowed.iter().any(|allowed| {
+        allowed.matches(&host, Some(port))
+            || (address.is_loopback() && allowed.matches("localhost", Some(port)))
+    });
+    if is_allowed {
+        Ok(())
+    } else {
+        Err(CodedError::new(
+            "PermissionDenied",
+            format!("Network access to '{host}:{port}' is not allowed"),
+        )
+        .into())
+    }
+}
@@ -0,0 +1,92 @@
+use std::rc::Rc;
+
+use http_body_util::BodyExt;
+use hyper::body::Incoming;
+use tokio::sync::Mutex as AsyncMutex;
+
+use mlua::prelude::*;
+
+/**
+    The default maximum size of a request body read using `readAll`, in bytes.
+*/
+const DEFAULT_READ_ALL_LIMIT: usize = 16 * 1024 * 1024;
+
+/**
+    A streaming request body for `net.serve` handlers.
+
+    The body is read from the connection on demand, one chunk at a
+    time, instead of being buffered in memory before the handler runs.
+*/
+#[derive(Debug, Clone)]
+pub(super) struct LuaRequestBody {
+    inner: Rc<AsyncMutex<Option<Incoming>>>,
+}
+
+impl LuaRequestBody {
+    pub(super) fn new(body: Incoming) -> Self {
+        Self {
+            inner: Rc::new(AsyncMutex::new(Some(body))),
+        }
+    }
+
+    /**
+        Reads the next chunk of data from the body, skipping any trailers.
+
+        Returns `None` once the entire body has been read.
+    */
+    async fn read(&self) -> LuaResult<Option<Vec<u8>>> {
+        let mut guard = self.inner.lock().await;
+        let Some(body) = guard.as_mut() else {
+            return Ok(None);
+        };
+        while let Some(frame) = body.frame().await {
+            if let Ok(data) = frame.into_lua_err()?.into_data() {
+                if !data.is_empty() {
+                    return Ok(Some(data.to_vec()));
+                }
+            }
+        }
+        guard.take();
+        Ok(None)
+    }
+
+    /**
+        Reads the rest of the body, erroring if it is larger than the given limit.
+    */
+    async fn read_all(&self, limit: usize) -> LuaResult<Vec<u8>> {
+        let mut all = Vec::new();
+        while let Some(chunk) = self.read().await? {
+            if all.len() + chunk.len() > limit {
+                // NOTE: Drop the rest of the body, there is no
+                // point in reading any more of it after this
+                self.inner.lock().await.take();
+                return Err(LuaError::runtime(format!(
+                    "Request body is larger than the limit of {limit} bytes"
+                )));
+            }
+            all.extend_from_slice(&chunk);
+        }
+        Ok(all)
+    }
+}
+
+impl LuaUserData for LuaRequestBody {
+    fn add_fields<'lua, F: LuaUserDataFields<'lua, Self>>(fields: &mut F) {
+        fields.add_meta_field(LuaMetaMethod::Type, "RequestBody");
+    }
+
+    fn add_methods<'lua, M: LuaUserDataMethods<'lua, Self>>(methods: &mut M) {
+        methods.add_async_method("read", |lua, this, (): ()| async move {
+            match this.read().await? {
+                Some(chunk) => Ok(LuaValue::String(lua.create_string(chunk)?)),
+                None => Ok(LuaValue::Nil),
+            }
+        });
+        methods.add_async_method("readAll", |lua, this, limit: Option<usize>| async move {
+            let all = this
+                .read_all(limit.unwrap_or(DEFAULT_READ_ALL_LIMIT))
+                .await?;
+            lua.create_string(all)
+        });
+    }
+}
@@ -0,0 +1,255 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    future::Future,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    pin::Pin,
+    rc::{Rc, Weak},
+    str::FromStr,
+};
+
+use http_body_util::{BodyExt, Full};
+use hyper::{
+    body::{Bytes, Incoming},
+    server::conn::http1,
+    service::Service,
+    Method, Request, Response,
+};
+use hyper_util::rt::TokioIo;
+use tokio::{net::TcpListener, pin, sync::watch};
+
+use mlua::prelude::*;
+use mlua_luau_scheduler::LuaSpawnExt;
+
+use crate::{config::MockServerConfig, permissions::check_serve};
+
+use super::{request::LuaRequest, response::LuaResponse};
+
+const MOCK_IP_ADDRESS: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
+
+/**
+    A route that a mock server can respond to, given as
+    either `"METHOD /path"`, or `"/path"` for any method.
+*/
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct MockRoute {
+    method: Option<Method>,
+    path: String,
+}
+
+impl FromStr for MockRoute {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (method, path) = match s.trim().split_once(' ') {
+            Some((method, path)) => {
+                let method = Method::from_bytes(method.to_ascii_uppercase().as_bytes())
+                    .map_err(|_| format!("Invalid mock route '{s}' - unknown method"))?;
+                (Some(method), path.trim())
+            }
+            None => (None, s.trim()),
+        };
+        if !path.starts_with('/') {
+            return Err(format!(
+                "Invalid mock route '{s}' - expected a path starting with '/'"
+            ));
+        }
+        Ok(Self {
+            method,
+            path: path.to_string(),
+        })
+    }
+}
+
+/**
+    State shared between a mock server and its connections.
+*/
+#[derive(Default)]
+struct MockState {
+    routes: HashMap<MockRoute, LuaResponse>,
+    requests: Vec<LuaRequest>,
+}
+
+impl MockState {
+    /**
+        Finds the response for the given method and path, preferring routes
+        with a matching method over routes that match any method.
+    */
+    fn find_response(&self, method: &Method, path: &str) -> Option<&LuaResponse> {
+        let route = MockRoute {
+            method: Some(method.clone()),
+            path: path.to_string(),
+        };
+        self.routes.get(&route).or_else(|| {
+            self.routes.get(&MockRoute {
+                method: None,
+                path: route.path,
+            })
+        })
+    }
+}
+
+#[derive(Clone)]
+struct MockSvc {
+    addr: SocketAddr,
+    buffer_body: bool,
+    state: Rc<RefCell<MockState>>,
+}
+
+impl Service<Request<Incoming>> for MockSvc {
+    type Response = Response<Full<Bytes>>;
+    type Error = LuaError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    fn call(&self, req: Request<Incoming>) -> Self::Future {
+        let addr = self.addr;
+        let buffer_body = self.buffer_body;
+        let state = Rc::clone(&self.state);
+
+        Box::pin(async move {
+            let (head, body) = req.into_parts();
+            let body = body.collect().await.into_lua_err()?;
+            let body = body.to_bytes().to_vec();
+
+            let mut state = state.borrow_mut();
+            let response = match state.find_response(&head.method, head.uri.path()) {
+                Some(response) => response.clone().into_response()?,
+                None => Response::builder()
+                    .status(404)
+                    .header("Content-Type", "text/plain")
+                    .body(Full::new(Bytes::from(format!(
+                        "No mock response for {} {}",
+                        head.method,
+                        head.uri.path()
+                    ))))
+                    .into_lua_err()?,
+            };
+
+            state.requests.push(LuaRequest {
+                _remote_addr: addr,
+                head,
+                body,
+                buffer_body,
+                stream: None,
+            });
+
+            Ok(response)
+        })
+    }
+}
+
+/**
+    A mock server, listening on an ephemeral port, that records all requests
+    it receives and responds to them using responses given for each route.
+*/
+pub struct LuaMockServer {
+    addr: SocketAddr,
+    state: Rc<RefCell<MockState>>,
+    shutdown_tx: watch::Sender<bool>,
+}
+
+impl LuaUserData for LuaMockServer {
+    fn add_fields<'lua, F: LuaUserDataFields<'lua, Self>>(fields: &mut F) {
+        fields.add_meta_field(LuaMetaMethod::Type, "MockServer");
+        fields.add_field_method_get("url", |_, this| Ok(format!("http://{}", this.addr)));
+        fields.add_field_method_get("ip", |_, this| Ok(this.addr.ip().to_string()));
+        fields.add_field_method_get("port", |_, this| Ok(this.addr.port()));
+    }
+
+    fn add_methods<'lua, M: LuaUserDataMethods<'lua, Self>>(methods: &mut M) {
+        methods.add_method(
+            "respond",
+            |_, this, (route, response): (String, LuaResponse)| {
+                let route = route.parse::<MockRoute>().map_err(LuaError::runtime)?;
+                this.state.borrow_mut().routes.insert(route, response);
+                Ok(())
+            },
+        );
+        methods.add_method("requests", |lua, this, ()| {
+            let state = this.state.borrow();
+            let table = lua.create_table_with_capacity(state.requests.len(), 0)?;
+            for request in &state.requests {
+                table.raw_push(request.to_lua_table(lua)?)?;
+            }
+            Ok(table)
+        });
+        methods.add_method("reset", |_, this, ()| {
+            let mut state = this.state.borrow_mut();
+            state.routes.clear();
+            state.requests.clear();
+            Ok(())
+        });
+        methods.add_method("stop", |_, this, ()| match this.shutdown_tx.send(true) {
+            Ok(()) => Ok(()),
+            Err(_) => Err(LuaError::runtime("Server already stopped")),
+        });
+    }
+}
+
+/**
+    Starts a new mock server on the loopback address, using a port chosen by the system.
+
+    Since the port is not known until the server has started listening, network
+    permissions are checked after binding, but before accepting any connections.
+*/
+pub async fn serve_mock(lua: &Lua, config: MockServerConfig) -> LuaResult<LuaMockServer> {
+    let listener = TcpListener::bind((MOCK_IP_ADDRESS, 0)).await?;
+    let addr = listener.local_addr()?;
+    check_serve(lua, addr.ip(), addr.port())?;
+
+    let state = Rc::new(RefCell::new(MockState::default()));
+    let svc = MockSvc {
+        addr,
+        buffer_body: config.buffer_body,
+        state: Rc::clone(&state),
+    };
+
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    let lua_inner = lua
+        .app_data_ref::<Weak<Lua>>()
+        .expect("Missing weak lua ref")
+        .upgrade()
+        .expect("Lua was dropped unexpectedly");
+    lua.spawn_local(async move {
+        let mut shutdown_rx_outer = shutdown_rx.clone();
+        loop {
+            let fut_shutdown = shutdown_rx_outer.changed();
+            let fut_accept = async {
+                let Ok((stream, _)) = listener.accept().await else {
+                    return;
+                };
+
+                let io = TokioIo::new(stream);
+                let svc = svc.clone();
+                let mut shutdown_rx_inner = shutdown_rx.clone();
+
+                lua_inner.spawn_local(async move {
+                    let conn = http1::Builder::new().serve_connection(io, svc);
+                    pin!(conn);
+                    tokio::select! {
+                        _ = conn.as_mut() => {}
+                        _ = shutdown_rx_inner.changed() => {
+                            conn.as_mut().graceful_shutdown();
+                        }
+                    }
+                });
+            };
+
+            tokio::select! {
+                () = fut_accept => {}
+                res = fut_shutdown => {
+                    // NOTE: Same as for normal servers, a RecvError here means that
+                    // the mock server was garbage collected without being stopped
+                    if res.is_ok() {
+                        break;
+                    }
+                }
+            }
+        }
+    });
+
+    Ok(LuaMockServer {
+        addr,
+        state,
+        shutdown_tx,
+    })
+}
@@ -8,13 +8,15 @@ use hyper_util::rt::TokioIo;
 use tokio::{net::TcpListener, pin};
 
 use mlua::prelude::*;
-use mlua_luau_scheduler::LuaSpawnExt;
+use mlua_luau_scheduler::{LuaSchedulerExt, LuaSpawnExt};
 
 use lune_utils::TableBuilder;
 
 use super::config::ServeConfig;
 
+mod body;
 mod keys;
+mod mock;
 mod request;
 mod response;
 mod service;
@@ -22,11 +24,17 @@ mod service;
 use keys::SvcKeys;
 use service::Svc;
 
+pub use mock::{serve_mock, LuaMockServer};
+
 pub async fn serve<'lua>(
     lua: &'lua Lua,
     port: u16,
     config: ServeConfig<'lua>,
 ) -> LuaResult<LuaTable<'lua>> {
+    // NOTE: The origin must be captured before anything is awaited,
+    // since the lua call stack is only available during the first poll
+    let origin = lua.capture_thread_origin("serve");
+
     let addr: SocketAddr = (config.address, port).into();
     let listener = TcpListener::bind(addr).await?;
 
@@ -44,6 +52,10 @@ pub async fn serve<'lua>(
         lua: lua_svc,
         addr,
         keys,
+        origin,
+        stream_body: config.stream_body,
+        buffer_body: config.buffer_body,
+        socket_options: config.socket_options,
     };
 
     let (shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
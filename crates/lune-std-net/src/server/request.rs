@@ -6,17 +6,23 @@ use mlua::prelude::*;
 
 use lune_utils::TableBuilder;
 
+use crate::util::create_body;
+
+use super::body::LuaRequestBody;
+
 pub(super) struct LuaRequest {
     pub(super) _remote_addr: SocketAddr,
     pub(super) head: Parts,
     pub(super) body: Vec<u8>,
+    pub(super) buffer_body: bool,
+    pub(super) stream: Option<LuaRequestBody>,
 }
 
 impl LuaRequest {
-    pub fn into_lua_table(self, lua: &Lua) -> LuaResult<LuaTable> {
+    pub fn to_lua_table<'lua>(&self, lua: &'lua Lua) -> LuaResult<LuaTable<'lua>> {
         let method = self.head.method.as_str().to_string();
         let path = self.head.uri.path().to_string();
-        let body = lua.create_string(&self.body)?;
+        let body = create_body(lua, &self.body, self.buffer_body)?;
 
         #[allow(clippy::mutable_key_type)]
         let query: HashMap<LuaString, LuaString> = self
@@ -25,10 +31,11 @@ impl LuaRequest {
             .query()
             .unwrap_or_default()
             .split('&')
-            .filter_map(|q| q.split_once('='))
+            .filter(|q| !q.is_empty())
+            .map(|q| q.split_once('=').unwrap_or((q, "")))
             .map(|(k, v)| {
-                let k = lua.create_string(k)?;
-                let v = lua.create_string(v)?;
+                let k = lua.create_string(decode_query_component(k))?;
+                let v = lua.create_string(decode_query_component(v))?;
                 Ok((k, v))
             })
             .collect::<LuaResult<_>>()?;
@@ -51,6 +58,15 @@ impl LuaRequest {
             .with_value("query", query)?
             .with_value("headers", headers)?
             .with_value("body", body)?
+            .with_value("stream", self.stream.clone())?
             .build()
     }
 }
+
+/**
+    Decodes a single key or value in a query string, where
+    spaces may be encoded either as `+` or percent-encoded.
+*/
+fn decode_query_component(component: &str) -> Vec<u8> {
+    urlencoding::decode_binary(component.replace('+', " ").as_bytes()).into_owned()
+}
@@ -13,9 +13,11 @@ use mlua::prelude::*;
 #[derive(Debug, Clone, Copy)]
 pub(super) enum LuaResponseKind {
     PlainText,
+    Binary,
     Table,
 }
 
+#[derive(Debug, Clone)]
 pub(super) struct LuaResponse {
     pub(super) kind: LuaResponseKind,
     pub(super) status: u16,
@@ -31,6 +33,11 @@ impl LuaResponse {
                 .header("Content-Type", "text/plain")
                 .body(Full::new(Bytes::from(self.body.unwrap())))
                 .into_lua_err()?,
+            LuaResponseKind::Binary => Response::builder()
+                .status(200)
+                .header("Content-Type", "application/octet-stream")
+                .body(Full::new(Bytes::from(self.body.unwrap())))
+                .into_lua_err()?,
             LuaResponseKind::Table => {
                 let mut response = Response::builder()
                     .status(self.status)
@@ -44,7 +51,7 @@ impl LuaResponse {
 }
 
 impl FromLua<'_> for LuaResponse {
-    fn from_lua(value: LuaValue, _: &Lua) -> LuaResult<Self> {
+    fn from_lua(value: LuaValue, lua: &Lua) -> LuaResult<Self> {
         match value {
             // Plain strings from the handler are plaintext responses
             LuaValue::String(s) => Ok(Self {
@@ -53,6 +60,13 @@ impl FromLua<'_> for LuaResponse {
                 headers: HeaderMap::new(),
                 body: Some(s.as_bytes().to_vec()),
             }),
+            // Buffers from the handler are binary responses
+            value if value.is_buffer() => Ok(Self {
+                kind: LuaResponseKind::Binary,
+                status: 200,
+                headers: HeaderMap::new(),
+                body: Some(BString::from_lua(value, lua)?.into()),
+            }),
             // Tables are more detailed responses with potential status, headers, body
             LuaValue::Table(t) => {
                 let status: Option<u16> = t.get("status")?;
@@ -69,7 +83,7 @@ impl FromLua<'_> for LuaResponse {
                     }
                 }
 
-                let body_bytes = body.map(|s| s.as_bytes().to_vec());
+                let body_bytes = body.map(Vec::from);
 
                 Ok(Self {
                     kind: LuaResponseKind::Table,
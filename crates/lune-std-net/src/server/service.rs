@@ -9,10 +9,14 @@ use hyper::{
 use hyper_tungstenite::{is_upgrade_request, upgrade};
 
 use mlua::prelude::*;
-use mlua_luau_scheduler::{LuaSchedulerExt, LuaSpawnExt};
+use mlua_luau_scheduler::{LuaSchedulerExt, LuaSpawnExt, ThreadOrigin};
 
 use super::{
-    super::websocket::NetWebSocket, keys::SvcKeys, request::LuaRequest, response::LuaResponse,
+    super::{config::SocketConfigOptions, websocket::NetWebSocket},
+    body::LuaRequestBody,
+    keys::SvcKeys,
+    request::LuaRequest,
+    response::LuaResponse,
 };
 
 #[derive(Debug, Clone)]
@@ -20,6 +24,10 @@ pub(super) struct Svc {
     pub(super) lua: Rc<Lua>,
     pub(super) addr: SocketAddr,
     pub(super) keys: SvcKeys,
+    pub(super) origin: Option<ThreadOrigin>,
+    pub(super) stream_body: bool,
+    pub(super) buffer_body: bool,
+    pub(super) socket_options: SocketConfigOptions,
 }
 
 impl Service<Request<Incoming>> for Svc {
@@ -31,6 +39,10 @@ impl Service<Request<Incoming>> for Svc {
         let lua = self.lua.clone();
         let addr = self.addr;
         let keys = self.keys;
+        let origin = self.origin.clone();
+        let stream_body = self.stream_body;
+        let buffer_body = self.buffer_body;
+        let socket_options = self.socket_options;
 
         if keys.has_websocket_handler() && is_upgrade_request(&req) {
             Box::pin(async move {
@@ -40,14 +52,19 @@ impl Service<Request<Incoming>> for Svc {
                 lua.spawn_local(async move {
                     let sock = sock.await.unwrap();
                     let lua_sock = NetWebSocket::new(sock);
+                    if let Some(keepalive) = socket_options.keepalive {
+                        lua_sock.start_keepalive(&lua_inner, keepalive);
+                    }
                     let lua_val = lua_sock.into_lua(&lua_inner).unwrap();
 
                     let handler_websocket: LuaFunction =
                         keys.websocket_handler(&lua_inner).unwrap().unwrap();
 
-                    lua_inner
-                        .push_thread_back(handler_websocket, lua_val)
-                        .unwrap();
+                    let thread = lua_inner.create_thread(handler_websocket).unwrap();
+                    if let Some(origin) = &origin {
+                        lua_inner.set_thread_origin(&thread, origin);
+                    }
+                    lua_inner.push_thread_back(thread, lua_val).unwrap();
                 });
 
                 Ok(res)
@@ -58,17 +75,29 @@ impl Service<Request<Incoming>> for Svc {
             Box::pin(async move {
                 let handler_request: LuaFunction = keys.request_handler(&lua).unwrap();
 
-                let body = body.collect().await.into_lua_err()?;
-                let body = body.to_bytes().to_vec();
+                // NOTE: When streaming, the body is read on demand by the handler,
+                // so that large bodies never need to be fully buffered in memory
+                let (body, stream) = if stream_body {
+                    (Vec::new(), Some(LuaRequestBody::new(body)))
+                } else {
+                    let body = body.collect().await.into_lua_err()?;
+                    (body.to_bytes().to_vec(), None)
+                };
 
                 let lua_req = LuaRequest {
                     _remote_addr: addr,
                     head,
                     body,
+                    buffer_body,
+                    stream,
                 };
-                let lua_req_table = lua_req.into_lua_table(&lua)?;
+                let lua_req_table = lua_req.to_lua_table(&lua)?;
 
-                let thread_id = lua.push_thread_back(handler_request, lua_req_table)?;
+                let thread = lua.create_thread(handler_request)?;
+                if let Some(origin) = &origin {
+                    lua.set_thread_origin(&thread, origin);
+                }
+                let thread_id = lua.push_thread_back(thread, lua_req_table)?;
                 lua.track_thread(thread_id);
                 lua.wait_for_thread(thread_id).await;
                 let thread_res = lua
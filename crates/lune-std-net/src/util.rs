@@ -22,6 +22,18 @@ pub fn create_user_agent_header(lua: &Lua) -> LuaResult<String> {
     Ok(format!("{}/{}", package_name.to_lowercase(), full_version))
 }
 
+/**
+    Creates a body value for a request or response, which is
+    a buffer if the `bufferBody` option was set, and a string otherwise.
+*/
+pub fn create_body(lua: &Lua, bytes: impl AsRef<[u8]>, as_buffer: bool) -> LuaResult<LuaValue> {
+    if as_buffer {
+        lua.create_buffer(bytes)?.into_lua(lua)
+    } else {
+        lua.create_string(bytes)?.into_lua(lua)
+    }
+}
+
 pub fn header_map_to_table(
     lua: &Lua,
     headers: HeaderMap,
@@ -1,18 +1,24 @@
-use std::sync::{
-    atomic::{AtomicBool, AtomicU16, Ordering},
-    Arc,
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicBool, AtomicU16, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
 };
 
 use bstr::{BString, ByteSlice};
 use mlua::prelude::*;
+use mlua_luau_scheduler::LuaSpawnExt;
 
 use futures_util::{
     stream::{SplitSink, SplitStream},
-    SinkExt, StreamExt,
+    FutureExt, SinkExt, StreamExt,
 };
 use tokio::{
     io::{AsyncRead, AsyncWrite},
-    sync::Mutex as AsyncMutex,
+    sync::{watch, Mutex as AsyncMutex},
+    time,
 };
 
 use hyper_tungstenite::{
@@ -23,11 +29,25 @@ use hyper_tungstenite::{
     WebSocketStream,
 };
 
+use crate::config::SocketKeepalive;
+
+// NOTE: This close code is reserved and never sent to the other end, it is
+// only used locally to signal that the connection was lost without a close
+const ABNORMAL_CLOSE_CODE: u16 = 1006;
+
+#[derive(Debug)]
+struct NetWebSocketReader<T> {
+    stream: SplitStream<WebSocketStream<T>>,
+    pending: VecDeque<WsMessage>,
+}
+
 #[derive(Debug)]
 pub struct NetWebSocket<T> {
     close_code_exists: Arc<AtomicBool>,
     close_code_value: Arc<AtomicU16>,
-    read_stream: Arc<AsyncMutex<SplitStream<WebSocketStream<T>>>>,
+    last_received: Arc<Mutex<Instant>>,
+    dead: Arc<watch::Sender<bool>>,
+    read_stream: Arc<AsyncMutex<NetWebSocketReader<T>>>,
     write_stream: Arc<AsyncMutex<SplitSink<WebSocketStream<T>, WsMessage>>>,
 }
 
@@ -36,6 +56,8 @@ impl<T> Clone for NetWebSocket<T> {
         Self {
             close_code_exists: Arc::clone(&self.close_code_exists),
             close_code_value: Arc::clone(&self.close_code_value),
+            last_received: Arc::clone(&self.last_received),
+            dead: Arc::clone(&self.dead),
             read_stream: Arc::clone(&self.read_stream),
             write_stream: Arc::clone(&self.write_stream),
         }
@@ -52,11 +74,88 @@ where
         Self {
             close_code_exists: Arc::new(AtomicBool::new(false)),
             close_code_value: Arc::new(AtomicU16::new(0)),
-            read_stream: Arc::new(AsyncMutex::new(read)),
+            last_received: Arc::new(Mutex::new(Instant::now())),
+            dead: Arc::new(watch::Sender::new(false)),
+            read_stream: Arc::new(AsyncMutex::new(NetWebSocketReader {
+                stream: read,
+                pending: VecDeque::new(),
+            })),
             write_stream: Arc::new(AsyncMutex::new(write)),
         }
     }
 
+    /**
+        Starts sending pings to the other end of the socket at the given interval.
+
+        If nothing is received within the timeout after sending a ping, the connection
+        is considered dead - the socket gets closed with code `1006`, and any current
+        or future calls to `next` will return `nil` once pending messages are consumed.
+    */
+    pub fn start_keepalive(&self, lua: &Lua, keepalive: SocketKeepalive) {
+        let socket = self.clone();
+        lua.spawn_local(async move {
+            loop {
+                time::sleep(keepalive.interval).await;
+                // NOTE: If we hold the only reference to the socket, it is no
+                // longer reachable from Lua, and we should stop keeping it alive
+                if Arc::strong_count(&socket.write_stream) <= 1 || socket.is_closed() {
+                    break;
+                }
+
+                let sent_at = Instant::now();
+                if socket.send(WsMessage::Ping(Vec::new())).await.is_err() {
+                    break;
+                }
+
+                time::sleep(keepalive.timeout).await;
+                if !socket.received_since(sent_at) {
+                    socket.mark_dead(keepalive.timeout).await;
+                    break;
+                }
+            }
+        });
+    }
+
+    fn is_closed(&self) -> bool {
+        self.close_code_exists.load(Ordering::Relaxed) || *self.dead.borrow()
+    }
+
+    fn record_received(&self) {
+        *self.last_received.lock().unwrap() = Instant::now();
+    }
+
+    fn received_since(&self, instant: Instant) -> bool {
+        if *self.last_received.lock().unwrap() >= instant {
+            return true;
+        }
+
+        // NOTE: If nothing is currently waiting for the next message, any pong
+        // we got would still be sitting unread in the stream, so we read whatever
+        // has arrived ourselves and keep messages around for the next call to next
+        if let Ok(mut reader) = self.read_stream.try_lock() {
+            while let Some(Some(Ok(msg))) = reader.stream.next().now_or_never() {
+                self.record_received();
+                if !is_control_message(&msg) {
+                    reader.pending.push_back(msg);
+                }
+            }
+        }
+
+        *self.last_received.lock().unwrap() >= instant
+    }
+
+    async fn mark_dead(&self, timeout: Duration) {
+        if !self.close_code_exists.load(Ordering::Relaxed) {
+            self.set_close_code(ABNORMAL_CLOSE_CODE);
+        }
+        self.dead.send_replace(true);
+
+        // NOTE: The other end is most likely gone, so we can't
+        // wait around forever for the close to be acknowledged
+        let mut ws = self.write_stream.lock().await;
+        time::timeout(timeout, ws.close()).await.ok();
+    }
+
     fn get_close_code(&self) -> Option<u16> {
         if self.close_code_exists.load(Ordering::Relaxed) {
             Some(self.close_code_value.load(Ordering::Relaxed))
@@ -76,8 +175,24 @@ where
     }
 
     pub async fn next(&self) -> LuaResult<Option<WsMessage>> {
-        let mut ws = self.read_stream.lock().await;
-        ws.next().await.transpose().into_lua_err()
+        let mut dead = self.dead.subscribe();
+        let mut reader = self.read_stream.lock().await;
+        if let Some(msg) = reader.pending.pop_front() {
+            return Ok(Some(msg));
+        }
+
+        loop {
+            let msg = tokio::select! {
+                msg = reader.stream.next() => msg.transpose().into_lua_err()?,
+                _ = dead.wait_for(|dead| *dead) => return Ok(None),
+            };
+            self.record_received();
+            // Ping/pong/frame messages are handled by tungstenite, we only
+            // need to know that they were received, for keepalive purposes
+            if !msg.as_ref().is_some_and(is_control_message) {
+                return Ok(msg);
+            }
+        }
     }
 
     pub async fn close(&self, code: Option<u16>) -> LuaResult<()> {
@@ -121,7 +236,7 @@ where
             "send",
             |_, this, (string, as_binary): (BString, Option<bool>)| async move {
                 this.send(if as_binary.unwrap_or_default() {
-                    WsMessage::Binary(string.as_bytes().to_vec())
+                    WsMessage::Binary(string.into())
                 } else {
                     let s = string.to_str().into_lua_err()?;
                     WsMessage::Text(s.to_string())
@@ -141,9 +256,15 @@ where
                 Some(WsMessage::Binary(bin)) => LuaValue::String(lua.create_string(bin)?),
                 Some(WsMessage::Text(txt)) => LuaValue::String(lua.create_string(txt)?),
                 Some(WsMessage::Close(_)) | None => LuaValue::Nil,
-                // Ignore ping/pong/frame messages, they are handled by tungstenite
                 msg => unreachable!("Unhandled message: {:?}", msg),
             })
         });
     }
 }
+
+fn is_control_message(msg: &WsMessage) -> bool {
+    matches!(
+        msg,
+        WsMessage::Ping(_) | WsMessage::Pong(_) | WsMessage::Frame(_)
+    )
+}
@@ -0,0 +1,71 @@
+#![allow(clippy::cargo_common_metadata)]
+
+use mlua::prelude::*;
+use mlua_luau_scheduler::LuaSpawnExt;
+use notify_rust::{Notification, Timeout};
+
+use lune_utils::TableBuilder;
+
+mod options;
+
+use self::options::{NotifyOptions, NotifyUrgency};
+
+const APP_NAME: &str = "Lune";
+
+/**
+    Creates the `notify` standard library module.
+
+    # Errors
+
+    Errors when out of memory.
+*/
+pub fn module(lua: &Lua) -> LuaResult<LuaTable> {
+    TableBuilder::new_named(lua, "notify")?
+        .with_async_function("send", notify_send)?
+        .build_readonly()
+}
+
+async fn notify_send(
+    lua: &Lua,
+    (title, body, options): (String, Option<String>, NotifyOptions),
+) -> LuaResult<()> {
+    let notification = create_notification(&title, body.as_deref(), &options);
+    // NOTE: Showing a notification talks to the notification server or system
+    // service, which may block for a little while, so we do it in the background
+    lua.spawn_blocking(move || notification.show().map(|_| ()).map_err(|e| e.to_string()))
+        .await
+        .map_err(|e| LuaError::runtime(format!("Failed to send notification - {e}")))
+}
+
+fn create_notification(title: &str, body: Option<&str>, options: &NotifyOptions) -> Notification {
+    let mut notification = Notification::new();
+    notification.appname(APP_NAME).summary(title);
+    if let Some(body) = body {
+        notification.body(body);
+    }
+    if let Some(icon) = &options.icon {
+        notification.icon(icon);
+    }
+    if let Some(sound) = &options.sound {
+        notification.sound_name(sound);
+    }
+    if let Some(timeout) = options.timeout {
+        let millis = u32::try_from(timeout.as_millis()).unwrap_or(u32::MAX);
+        notification.timeout(Timeout::Milliseconds(millis));
+    }
+    set_urgency(&mut notification, options.urgency);
+    notification
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn set_urgency(notification: &mut Notification, urgency: NotifyUrgency) {
+    use notify_rust::Urgency;
+    notification.urgency(match urgency {
+        NotifyUrgency::Low => Urgency::Low,
+        NotifyUrgency::Normal => Urgency::Normal,
+        NotifyUrgency::Critical => Urgency::Critical,
+    });
+}
+
+#[cfg(not(all(unix, not(target_os = "macos"))))]
+fn set_urgency(_: &mut Notification, _: NotifyUrgency) {}
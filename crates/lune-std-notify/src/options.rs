@@ -0,0 +1,86 @@
+use std::time::Duration;
+
+use mlua::prelude::*;
+
+/**
+    How urgent a notification is, which affects how it is displayed on Linux.
+*/
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NotifyUrgency {
+    Low,
+    #[default]
+    Normal,
+    Critical,
+}
+
+impl<'lua> FromLua<'lua> for NotifyUrgency {
+    fn from_lua(value: LuaValue<'lua>, _: &'lua Lua) -> LuaResult<Self> {
+        match &value {
+            LuaValue::String(s) => match s.to_str()? {
+                "low" => Ok(Self::Low),
+                "normal" => Ok(Self::Normal),
+                "critical" => Ok(Self::Critical),
+                other => Err(LuaError::runtime(format!(
+                    "Invalid urgency '{other}' - expected 'low', 'normal' or 'critical'"
+                ))),
+            },
+            _ => Err(LuaError::FromLuaConversionError {
+                from: value.type_name(),
+                to: "NotifyUrgency",
+                message: Some(format!(
+                    "Invalid urgency - expected string, got {}",
+                    value.type_name()
+                )),
+            }),
+        }
+    }
+}
+
+/**
+    Options for sending a desktop notification.
+*/
+#[derive(Debug, Clone, Default)]
+pub struct NotifyOptions {
+    pub icon: Option<String>,
+    pub sound: Option<String>,
+    pub timeout: Option<Duration>,
+    pub urgency: NotifyUrgency,
+}
+
+impl<'lua> FromLua<'lua> for NotifyOptions {
+    fn from_lua(value: LuaValue<'lua>, _: &'lua Lua) -> LuaResult<Self> {
+        let tab = match value {
+            LuaValue::Nil => return Ok(Self::default()),
+            LuaValue::Table(tab) => tab,
+            _ => {
+                return Err(LuaError::FromLuaConversionError {
+                    from: value.type_name(),
+                    to: "NotifyOptions",
+                    message: Some(format!(
+                        "Invalid notification options - expected table, got {}",
+                        value.type_name()
+                    )),
+                })
+            }
+        };
+
+        let timeout = match tab.get::<_, Option<f64>>("timeout")? {
+            None => None,
+            Some(secs) if secs.is_finite() && secs > 0.0 => Some(Duration::from_secs_f64(secs)),
+            Some(secs) => {
+                return Err(LuaError::runtime(format!(
+                    "Invalid timeout - expected a positive number of seconds, got {secs}"
+                )))
+            }
+        };
+
+        Ok(Self {
+            icon: tab.get("icon")?,
+            sound: tab.get("sound")?,
+            timeout,
+            urgency: tab
+                .get::<_, Option<NotifyUrgency>>("urgency")?
+                .unwrap_or_default(),
+        })
+    }
+}
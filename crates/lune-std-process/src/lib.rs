@@ -21,16 +21,24 @@ use os_str_bytes::RawOsString;
 use stream::{ChildProcessReader, ChildProcessWriter};
 use tokio::{io::AsyncWriteExt, process::Child, sync::RwLock};
 
+mod lune;
 mod options;
+mod signal;
 mod stream;
 mod tee_writer;
 mod wait_for_child;
 
+use self::lune::{create_result_table, run_lune_script, LuneSpawnOptions};
 use self::options::ProcessSpawnOptions;
+use self::signal::{exit_code, parse_signal, send_signal, SIGKILL};
 use self::wait_for_child::wait_for_child;
 
 use lune_utils::path::get_current_dir;
 
+pub use self::lune::{
+    set_lune_process_args, set_lune_script_runner, LuneScriptJob, LuneScriptResult,
+};
+
 /**
     Creates the `process` standard library module.
 
@@ -61,7 +69,7 @@ pub fn module(lua: &Lua) -> LuaResult<LuaTable> {
     // Create proxied table for env that gets & sets real env vars
     let env_tab = TableBuilder::new(lua)?
         .with_metatable(
-            TableBuilder::new(lua)?
+            TableBuilder::new_named(lua, "process.env")?
                 .with_function(LuaMetaMethod::Index.name(), process_env_get)?
                 .with_function(LuaMetaMethod::NewIndex.name(), process_env_set)?
                 .with_function(LuaMetaMethod::Iter.name(), process_env_iter)?
@@ -72,7 +80,7 @@ pub fn module(lua: &Lua) -> LuaResult<LuaTable> {
     let fns = Functions::new(lua)?;
     let process_exit = fns.exit;
     // Create the full process table
-    TableBuilder::new(lua)?
+    TableBuilder::new_named(lua, "process")?
         .with_value("os", os)?
         .with_value("arch", arch)?
         .with_value("args", args_tab)?
@@ -81,6 +89,7 @@ pub fn module(lua: &Lua) -> LuaResult<LuaTable> {
         .with_value("exit", process_exit)?
         .with_async_function("exec", process_exec)?
         .with_function("create", process_create)?
+        .with_async_function("spawnLune", process_spawn_lune)?
         .build_readonly()
 }
 
@@ -180,6 +189,23 @@ async fn process_exec(
         .build_readonly()
 }
 
+async fn process_spawn_lune(
+    lua: &Lua,
+    (script, args, options): (String, Option<Vec<String>>, LuneSpawnOptions),
+) -> LuaResult<LuaTable> {
+    let path = get_current_dir().join(script);
+    lune_std_fs::check_read(lua, &path)?;
+    if !path.is_file() {
+        return Err(LuaError::runtime(format!(
+            "Script '{}' does not exist",
+            path.display()
+        )));
+    }
+    let args = args.unwrap_or_default();
+    let result = run_lune_script(lua, path, args, options.isolation).await?;
+    create_result_table(lua, result)
+}
+
 #[allow(clippy::await_holding_refcell_ref)]
 fn process_create(
     lua: &Lua,
@@ -194,6 +220,7 @@ fn process_create(
     let code_rx_rc = Rc::new(RefCell::new(code_rx));
 
     let child = spawn_command(program, args, spawn_options)?;
+    let pid = child.id();
 
     let child_arc = Arc::new(RwLock::new(child));
 
@@ -211,7 +238,7 @@ fn process_create(
         let res = child_arc_inner.write().await.wait().await;
 
         if let Ok(output) = res {
-            let code = output.code().unwrap_or_default();
+            let code = exit_code(output);
 
             code_tx
                 .send(code)
@@ -220,23 +247,37 @@ fn process_create(
     });
 
     TableBuilder::new(lua)?
-        .with_value("stdout", ChildProcessReader(stdout))?
-        .with_value("stderr", ChildProcessReader(stderr))?
+        .with_value("stdout", ChildProcessReader::new(stdout))?
+        .with_value("stderr", ChildProcessReader::new(stderr))?
         .with_value("stdin", ChildProcessWriter(stdin))?
-        .with_async_function("kill", move |_, ()| {
-            // First, stop the status task so the RwLock is dropped
-            status_handle.abort();
+        .with_async_function("kill", move |lua, signal: Option<LuaValue>| {
+            let signal = signal.map_or(Ok(SIGKILL), |signal| parse_signal(lua, signal));
+
+            // First, stop the status task so the RwLock is dropped, but only when killing,
+            // since the child process may handle any other signal and then exit as usual
+            if signal.as_ref().is_ok_and(|signal| *signal == SIGKILL) {
+                status_handle.abort();
+            }
             let child_arc_clone = Arc::clone(&child_arc);
 
             // Then get another RwLock to write to the child process and kill it
-            async move { Ok(child_arc_clone.write().await.kill().await?) }
+            async move {
+                match signal? {
+                    SIGKILL => Ok(child_arc_clone.write().await.kill().await?),
+                    signal => send_signal(pid, signal),
+                }
+            }
         })?
         .with_async_function("status", move |lua, ()| {
             let code_rx_rc_clone = Rc::clone(&code_rx_rc);
             async move {
                 // Exit code of 9 corresponds to SIGKILL, which should be the only case where
                 // the receiver gets suddenly dropped
-                let code = code_rx_rc_clone.borrow_mut().recv().await.unwrap_or(9);
+                let code = code_rx_rc_clone
+                    .borrow_mut()
+                    .recv()
+                    .await
+                    .unwrap_or(SIGKILL);
 
                 TableBuilder::new(lua)?
                     .with_value("code", code)?
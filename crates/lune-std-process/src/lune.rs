@@ -0,0 +1,246 @@
+use std::{env, path::PathBuf, process::Stdio, rc::Rc};
+
+use mlua::prelude::*;
+use mlua_luau_scheduler::LuaSpawnExt;
+use serde::Deserialize;
+use serde_json::Value as JsonValue;
+use tokio::{fs, process::Command};
+
+use lune_utils::TableBuilder;
+
+/**
+    A job that runs a Lune script in a fresh Luau VM, given
+    the path to the script and the arguments to give it.
+*/
+pub type LuneScriptJob =
+    Box<dyn FnOnce(PathBuf, Vec<String>) -> Result<LuneScriptResult, String> + Send>;
+
+#[derive(Clone)]
+struct LuneScriptRunner(Rc<dyn Fn(&Lua) -> LuneScriptJob>);
+
+#[derive(Clone)]
+struct LuneProcessArgs(Rc<dyn Fn(&Lua) -> Vec<String>>);
+
+/**
+    The result of running a Lune script using `process.spawnLune`.
+*/
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct LuneScriptResult {
+    pub exit_code: u8,
+    pub stdout: String,
+    pub stderr: String,
+    pub values: Vec<JsonValue>,
+}
+
+/**
+    Sets the function used to run Lune scripts in a fresh Luau VM for `process.spawnLune`.
+
+    The function is called with the Luau VM of the script that is spawning a new
+    script, and should capture any of its restrictions and limits, so that the
+    new script is restricted the same way. The job that it returns is then called
+    on a background thread, and must block until the script has finished running,
+    with its output and returned values captured.
+
+    If no runner is set, only the `process` isolation mode will be available.
+*/
+pub fn set_lune_script_runner<F>(lua: &Lua, runner: F)
+where
+    F: Fn(&Lua) -> LuneScriptJob + 'static,
+{
+    lua.set_app_data(LuneScriptRunner(Rc::new(runner)));
+}
+
+/**
+    Sets the function used to get extra arguments for `lune run` when
+    running Lune scripts in a new process for `process.spawnLune`.
+
+    The function is called with the Luau VM of the script that is spawning a new
+    script, and should return arguments that apply any of its restrictions and
+    limits, so that the new script is restricted the same way.
+*/
+pub fn set_lune_process_args<F>(lua: &Lua, args: F)
+where
+    F: Fn(&Lua) -> Vec<String> + 'static,
+{
+    lua.set_app_data(LuneProcessArgs(Rc::new(args)));
+}
+
+/**
+    How a Lune script spawned using `process.spawnLune` is isolated from the current one.
+*/
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LuneIsolation {
+    #[default]
+    Vm,
+    Process,
+}
+
+impl<'lua> FromLua<'lua> for LuneIsolation {
+    fn from_lua(value: LuaValue<'lua>, _: &'lua Lua) -> LuaResult<Self> {
+        match &value {
+            LuaValue::Nil => Ok(Self::default()),
+            LuaValue::String(s) => match s.to_str()? {
+                "vm" => Ok(Self::Vm),
+                "process" => Ok(Self::Process),
+                other => Err(LuaError::runtime(format!(
+                    "Invalid isolation mode '{other}' - expected 'vm' or 'process'"
+                ))),
+            },
+            _ => Err(LuaError::FromLuaConversionError {
+                from: value.type_name(),
+                to: "LuneIsolation",
+                message: Some(format!(
+                    "Invalid isolation mode - expected string, got {}",
+                    value.type_name()
+                )),
+            }),
+        }
+    }
+}
+
+/**
+    Options for running a Lune script using `process.spawnLune`.
+*/
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LuneSpawnOptions {
+    pub isolation: LuneIsolation,
+}
+
+impl<'lua> FromLua<'lua> for LuneSpawnOptions {
+    fn from_lua(value: LuaValue<'lua>, lua: &'lua Lua) -> LuaResult<Self> {
+        match value {
+            LuaValue::Nil => Ok(Self::default()),
+            LuaValue::Table(t) => Ok(Self {
+                isolation: LuneIsolation::from_lua(t.get("isolation")?, lua)?,
+            }),
+            _ => Err(LuaError::FromLuaConversionError {
+                from: value.type_name(),
+                to: "LuneSpawnOptions",
+                message: Some(format!(
+                    "Invalid spawn options - expected table, got {}",
+                    value.type_name()
+                )),
+            }),
+        }
+    }
+}
+
+/**
+    Runs the Lune script at the given path using the given isolation mode,
+    waiting for it to finish and returning its output and returned values.
+
+    # Errors
+
+    Errors if the script could not be started.
+*/
+pub async fn run_lune_script(
+    lua: &Lua,
+    path: PathBuf,
+    args: Vec<String>,
+    isolation: LuneIsolation,
+) -> LuaResult<LuneScriptResult> {
+    match isolation {
+        LuneIsolation::Vm => run_in_vm(lua, path, args).await,
+        LuneIsolation::Process => run_in_process(lua, path, args).await,
+    }
+}
+
+async fn run_in_vm(lua: &Lua, path: PathBuf, args: Vec<String>) -> LuaResult<LuneScriptResult> {
+    let runner = lua
+        .app_data_ref::<LuneScriptRunner>()
+        .ok_or_else(|| {
+            LuaError::runtime(
+                "Running Lune scripts in a new VM is not supported here - use the 'process' isolation mode instead",
+            )
+        })?
+        .clone();
+    let job = (runner.0)(lua);
+    lua.spawn_blocking(move || job(path, args))
+        .await
+        .map_err(LuaError::runtime)
+}
+
+/**
+    Runs the script in a new Lune process, using the same executable as the current one.
+
+    The child process writes its result to a temporary file, so that returned values
+    can be passed back without having to be parsed out of anything it writes to stdout.
+*/
+async fn run_in_process(
+    lua: &Lua,
+    path: PathBuf,
+    args: Vec<String>,
+) -> LuaResult<LuneScriptResult> {
+    let exe = env::current_exe().into_lua_err()?;
+    let extra_args = lua
+        .app_data_ref::<LuneProcessArgs>()
+        .map(|args| (args.0)(lua))
+        .unwrap_or_default();
+
+    // NOTE: The result file is created here, only readable and writable by the
+    // current user, so that nobody else can swap it out before the child writes
+    // to it, and it is removed again once the temporary file handle is dropped
+    let result_file = tempfile::Builder::new()
+        .prefix("lune-spawn-")
+        .suffix(".json")
+        .tempfile()
+        .into_lua_err()?;
+
+    let output = Command::new(exe)
+        .arg("run")
+        .arg("--result-file")
+        .arg(result_file.path())
+        .args(extra_args)
+        .arg(&path)
+        .args(args)
+        .stdin(Stdio::null())
+        .output()
+        .await?;
+
+    // NOTE: The result file will be empty if the child process failed
+    // before running the script, for example if the script does not exist,
+    // in which case we fall back to whatever the child process wrote instead
+    let bytes = fs::read(result_file.path()).await.unwrap_or_default();
+    let result = if bytes.is_empty() {
+        LuneScriptResult {
+            exit_code: output
+                .status
+                .code()
+                .and_then(|code| u8::try_from(code).ok())
+                .unwrap_or(1),
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+            values: Vec::new(),
+        }
+    } else {
+        serde_json::from_slice(&bytes).into_lua_err()?
+    };
+
+    Ok(result)
+}
+
+/**
+    Converts the result of a Lune script into a readonly Lua table.
+
+    # Errors
+
+    Errors when out of memory.
+*/
+pub fn create_result_table(lua: &Lua, result: LuneScriptResult) -> LuaResult<LuaTable> {
+    let options = LuaSerializeOptions::new()
+        .serialize_none_to_null(false)
+        .serialize_unit_to_null(false);
+
+    let values = lua.create_table_with_capacity(result.values.len(), 0)?;
+    for (index, value) in result.values.iter().enumerate() {
+        values.raw_set(index + 1, lua.to_value_with(value, options)?)?;
+    }
+
+    TableBuilder::new(lua)?
+        .with_value("ok", result.exit_code == 0)?
+        .with_value("code", result.exit_code)?
+        .with_value("stdout", result.stdout)?
+        .with_value("stderr", result.stderr)?
+        .with_value("values", values)?
+        .build_readonly()
+}
@@ -0,0 +1,88 @@
+use std::process::ExitStatus;
+
+use mlua::prelude::*;
+
+/**
+    The signal used to forcefully kill a child process.
+
+    This is also the exit code given to child processes that were killed using `kill`.
+*/
+pub const SIGKILL: i32 = 9;
+
+/**
+    Parses a signal given to the `kill` function of a child process.
+
+    Signals may be given either as numbers, or as names such as `SIGTERM` or `TERM`.
+*/
+pub fn parse_signal(lua: &Lua, value: LuaValue) -> LuaResult<i32> {
+    let LuaValue::String(name) = value else {
+        return i32::from_lua(value, lua);
+    };
+    let name = name.to_str()?.trim().to_ascii_uppercase();
+    let name = name.strip_prefix("SIG").unwrap_or(&name);
+    signal_from_name(name).ok_or_else(|| LuaError::runtime(format!("Unknown signal name '{name}'")))
+}
+
+#[cfg(unix)]
+fn signal_from_name(name: &str) -> Option<i32> {
+    Some(match name {
+        "HUP" => libc::SIGHUP,
+        "INT" => libc::SIGINT,
+        "QUIT" => libc::SIGQUIT,
+        "KILL" => libc::SIGKILL,
+        "USR1" => libc::SIGUSR1,
+        "USR2" => libc::SIGUSR2,
+        "TERM" => libc::SIGTERM,
+        "CONT" => libc::SIGCONT,
+        "STOP" => libc::SIGSTOP,
+        _ => return None,
+    })
+}
+
+#[cfg(not(unix))]
+fn signal_from_name(name: &str) -> Option<i32> {
+    (name == "KILL").then_some(SIGKILL)
+}
+
+/**
+    Sends the given signal to the process with the given id.
+*/
+#[cfg(unix)]
+pub fn send_signal(pid: Option<u32>, signal: i32) -> LuaResult<()> {
+    let pid = pid
+        .and_then(|pid| libc::pid_t::try_from(pid).ok())
+        .ok_or_else(|| LuaError::runtime("Child process has already exited"))?;
+    // SAFETY: Sending a signal has no memory safety requirements,
+    // and any invalid signal or pid is reported through errno
+    if unsafe { libc::kill(pid, signal) } == 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error().into())
+    }
+}
+
+#[cfg(not(unix))]
+pub fn send_signal(_: Option<u32>, signal: i32) -> LuaResult<()> {
+    Err(LuaError::runtime(format!(
+        "Sending signal {signal} is not supported on this platform, only SIGKILL is"
+    )))
+}
+
+/**
+    Gets the exit code for the given exit status, using the signal that
+    terminated the process as the exit code if it did not exit by itself.
+*/
+pub fn exit_code(status: ExitStatus) -> i32 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        status
+            .code()
+            .or_else(|| status.signal())
+            .unwrap_or_default()
+    }
+    #[cfg(not(unix))]
+    {
+        status.code().unwrap_or_default()
+    }
+}
@@ -1,16 +1,20 @@
 use bstr::BString;
 use bytes::BytesMut;
 use mlua::prelude::*;
-use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
 
 const CHUNK_SIZE: usize = 8;
 
-#[derive(Debug, Clone)]
-pub struct ChildProcessReader<R: AsyncRead>(pub R);
+#[derive(Debug)]
+pub struct ChildProcessReader<R: AsyncRead>(BufReader<R>);
 #[derive(Debug, Clone)]
 pub struct ChildProcessWriter<W: AsyncWrite>(pub W);
 
 impl<R: AsyncRead + Unpin> ChildProcessReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self(BufReader::new(reader))
+    }
+
     pub async fn read(&mut self, chunk_size: Option<usize>) -> LuaResult<Vec<u8>> {
         let mut buf = BytesMut::with_capacity(chunk_size.unwrap_or(CHUNK_SIZE));
         self.0.read_buf(&mut buf).await?;
@@ -24,6 +28,21 @@ impl<R: AsyncRead + Unpin> ChildProcessReader<R> {
 
         Ok(buf)
     }
+
+    pub async fn read_line(&mut self) -> LuaResult<Option<Vec<u8>>> {
+        let mut buf = vec![];
+        if self.0.read_until(b'\n', &mut buf).await? == 0 {
+            return Ok(None);
+        }
+
+        if buf.ends_with(b"\n") {
+            buf.pop();
+            if buf.ends_with(b"\r") {
+                buf.pop();
+            }
+        }
+        Ok(Some(buf))
+    }
 }
 
 impl<R: AsyncRead + Unpin + 'static> LuaUserData for ChildProcessReader<R> {
@@ -38,6 +57,13 @@ impl<R: AsyncRead + Unpin + 'static> LuaUserData for ChildProcessReader<R> {
             Ok(LuaValue::String(lua.create_string(buf)?))
         });
 
+        methods.add_async_method_mut("readLine", |lua, this, ()| async move {
+            match this.read_line().await? {
+                Some(line) => Ok(LuaValue::String(lua.create_string(line)?)),
+                None => Ok(LuaValue::Nil),
+            }
+        });
+
         methods.add_async_method_mut("readToEnd", |lua, this, ()| async {
             Ok(lua.create_string(this.read_to_end().await?))
         });
@@ -18,7 +18,7 @@ use self::regex::LuaRegex;
     Errors when out of memory.
 */
 pub fn module(lua: &Lua) -> LuaResult<LuaTable> {
-    TableBuilder::new(lua)?
+    TableBuilder::new_named(lua, "regex")?
         .with_function("new", new_regex)?
         .build_readonly()
 }
@@ -1,7 +1,8 @@
 use std::{ops::Range, sync::Arc};
 
 use mlua::prelude::*;
-use regex::Match;
+use regex::{Match, Matches, Regex};
+use self_cell::self_cell;
 
 /**
     A wrapper over the `regex::Match` struct that can be used from Lua.
@@ -51,3 +52,54 @@ impl LuaUserData for LuaMatch {
         });
     }
 }
+
+type SameLifetimeMatches<'a> = Matches<'a, 'a>;
+
+struct LuaMatchesOwner {
+    pattern: Regex,
+    text: Arc<String>,
+}
+
+self_cell! {
+    struct LuaMatchesInner {
+        owner: LuaMatchesOwner,
+        #[not_covariant]
+        dependent: SameLifetimeMatches,
+    }
+}
+
+/**
+    An iterator over all successive non-overlapping matches in a text.
+
+    Matches are found lazily, one at a time, as the iterator is advanced.
+*/
+pub struct LuaMatches {
+    inner: LuaMatchesInner,
+}
+
+impl LuaMatches {
+    /**
+        Create a new `LuaMatches` iterator from a `Regex` pattern and a `String` text.
+    */
+    pub fn new(pattern: &Regex, text: String) -> Self {
+        let owner = LuaMatchesOwner {
+            pattern: pattern.clone(),
+            text: Arc::new(text),
+        };
+        let inner =
+            LuaMatchesInner::new(owner, |owner| owner.pattern.find_iter(owner.text.as_str()));
+        Self { inner }
+    }
+}
+
+impl Iterator for LuaMatches {
+    type Item = LuaMatch;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.with_dependent_mut(|owner, matches| {
+            matches
+                .next()
+                .map(|m| LuaMatch::new(Arc::clone(&owner.text), m))
+        })
+    }
+}
@@ -3,7 +3,10 @@ use std::sync::Arc;
 use mlua::prelude::*;
 use regex::Regex;
 
-use super::{captures::LuaCaptures, matches::LuaMatch};
+use super::{
+    captures::LuaCaptures,
+    matches::{LuaMatch, LuaMatches},
+};
 
 /**
     A wrapper over the `regex::Regex` struct that can be used from Lua.
@@ -38,6 +41,11 @@ impl LuaUserData for LuaRegex {
                 .map(|m| LuaMatch::new(Arc::clone(&arc), m)))
         });
 
+        methods.add_method("findAll", |lua, this, text: String| {
+            let mut matches = LuaMatches::new(&this.inner, text);
+            lua.create_function_mut(move |_, ()| Ok(matches.next()))
+        });
+
         methods.add_method("captures", |_, this, text: String| {
             Ok(LuaCaptures::new(&this.inner, text))
         });
@@ -0,0 +1,40 @@
+use mlua::prelude::*;
+
+use lune_std_net::fetch_bytes;
+
+const ASSET_DELIVERY_URL: &str = "https://assetdelivery.roblox.com/v1/asset/";
+
+/**
+    Downloads the asset with the given id from the asset delivery API, returning its contents.
+
+    The request is sent using the same client as `net.request`, meaning that every url
+    that gets requested, including redirects, is checked against network permissions,
+    and that the auth cookie, if any, is only ever sent to the asset delivery API
+    and not to whatever host we get redirected to.
+
+    # Errors
+
+    Errors if the asset could not be downloaded, or if any requested url is not allowed.
+*/
+pub async fn download_asset_contents(
+    lua: &Lua,
+    asset_id: u64,
+    auth: Option<String>,
+) -> LuaResult<Vec<u8>> {
+    let url = format!("{ASSET_DELIVERY_URL}?id={asset_id}");
+    let headers = auth
+        .map(|cookie| ("Cookie".to_string(), cookie))
+        .into_iter()
+        .collect();
+
+    let (status, bytes) = fetch_bytes(lua, url, headers).await?;
+    if (200..300).contains(&status) {
+        Ok(bytes)
+    } else {
+        let body = String::from_utf8_lossy(&bytes);
+        Err(LuaError::runtime(format!(
+            "Failed to download asset {asset_id} - {status} {}",
+            body.trim()
+        )))
+    }
+}
@@ -1,19 +1,36 @@
 #![allow(clippy::cargo_common_metadata)]
 
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    sync::Once,
+    thread,
+};
+
+use bstr::BString;
 use mlua::prelude::*;
 use mlua_luau_scheduler::LuaSpawnExt;
 use once_cell::sync::OnceCell;
 
 use lune_roblox::{
     document::{Document, DocumentError, DocumentFormat, DocumentKind},
-    instance::{registry::InstanceRegistry, Instance},
+    instance::{
+        raw_properties::{get_raw_property, set_raw_property},
+        registry::InstanceRegistry,
+        Instance,
+    },
     reflection::Database as ReflectionDatabase,
 };
 
 static REFLECTION_DATABASE: OnceCell<ReflectionDatabase> = OnceCell::new();
 
+use lune_std_fs::check_write;
 use lune_utils::TableBuilder;
 
+mod asset;
+
+use self::asset::download_asset_contents;
+
 /**
     Creates the `roblox` standard library module.
 
@@ -22,6 +39,8 @@ use lune_utils::TableBuilder;
     Errors when out of memory.
 */
 pub fn module(lua: &Lua) -> LuaResult<LuaTable> {
+    preload_reflection_database();
+
     let mut roblox_constants = Vec::new();
 
     let roblox_module = lune_roblox::module(lua)?;
@@ -29,24 +48,26 @@ pub fn module(lua: &Lua) -> LuaResult<LuaTable> {
         roblox_constants.push(pair?);
     }
 
-    TableBuilder::new(lua)?
+    TableBuilder::new_named(lua, "roblox")?
         .with_values(roblox_constants)?
         .with_async_function("deserializePlace", deserialize_place)?
         .with_async_function("deserializeModel", deserialize_model)?
         .with_async_function("serializePlace", serialize_place)?
         .with_async_function("serializeModel", serialize_model)?
+        .with_async_function("serializePlaceToFile", serialize_place_to_file)?
+        .with_async_function("serializeModelToFile", serialize_model_to_file)?
+        .with_async_function("downloadAsset", download_asset)?
         .with_function("getAuthCookie", get_auth_cookie)?
         .with_function("getReflectionDatabase", get_reflection_database)?
         .with_function("implementProperty", implement_property)?
         .with_function("implementMethod", implement_method)?
+        .with_function("getRawProperty", get_raw_property)?
+        .with_function("setRawProperty", set_raw_property)?
         .build_readonly()
 }
 
-async fn deserialize_place<'lua>(
-    lua: &'lua Lua,
-    contents: LuaString<'lua>,
-) -> LuaResult<LuaValue<'lua>> {
-    let bytes = contents.as_bytes().to_vec();
+async fn deserialize_place<'lua>(lua: &'lua Lua, contents: BString) -> LuaResult<LuaValue<'lua>> {
+    let bytes = Vec::from(contents);
     let fut = lua.spawn_blocking(move || {
         let doc = Document::from_bytes(bytes, DocumentKind::Place)?;
         let data_model = doc.into_data_model_instance()?;
@@ -55,11 +76,8 @@ async fn deserialize_place<'lua>(
     fut.await.into_lua_err()?.into_lua(lua)
 }
 
-async fn deserialize_model<'lua>(
-    lua: &'lua Lua,
-    contents: LuaString<'lua>,
-) -> LuaResult<LuaValue<'lua>> {
-    let bytes = contents.as_bytes().to_vec();
+async fn deserialize_model<'lua>(lua: &'lua Lua, contents: BString) -> LuaResult<LuaValue<'lua>> {
+    let bytes = Vec::from(contents);
     let fut = lua.spawn_blocking(move || {
         let doc = Document::from_bytes(bytes, DocumentKind::Model)?;
         let instance_array = doc.into_instance_array()?;
@@ -102,6 +120,66 @@ async fn serialize_model<'lua>(
     lua.create_string(bytes)
 }
 
+async fn serialize_place_to_file<'lua>(
+    lua: &'lua Lua,
+    (data_model, path, as_xml): (LuaUserDataRef<'lua, Instance>, String, Option<bool>),
+) -> LuaResult<()> {
+    check_write(lua, &path)?;
+    let data_model = (*data_model).clone();
+    let fut = lua.spawn_blocking(move || {
+        let doc = Document::from_data_model_instance(data_model)?;
+        write_document_to_file(&doc, &path, as_xml)
+    });
+    fut.await.into_lua_err()
+}
+
+async fn serialize_model_to_file<'lua>(
+    lua: &'lua Lua,
+    (instances, path, as_xml): (Vec<LuaUserDataRef<'lua, Instance>>, String, Option<bool>),
+) -> LuaResult<()> {
+    check_write(lua, &path)?;
+    let instances = instances.iter().map(|i| (*i).clone()).collect();
+    let fut = lua.spawn_blocking(move || {
+        let doc = Document::from_instance_array(instances)?;
+        write_document_to_file(&doc, &path, as_xml)
+    });
+    fut.await.into_lua_err()
+}
+
+async fn download_asset<'lua>(
+    lua: &'lua Lua,
+    (asset_id, auth): (u64, Option<String>),
+) -> LuaResult<LuaValue<'lua>> {
+    let bytes = download_asset_contents(lua, asset_id, auth).await?;
+    let fut = lua.spawn_blocking(move || {
+        let doc = Document::from_bytes(bytes, DocumentKind::Model)?;
+        let instance_array = doc.into_instance_array()?;
+        Ok::<_, DocumentError>(instance_array)
+    });
+    fut.await.into_lua_err()?.into_lua(lua)
+}
+
+/**
+    Encodes the document directly into a file at the given path, so that
+    the full encoded document never has to be kept in memory at once.
+*/
+fn write_document_to_file(
+    doc: &Document,
+    path: &str,
+    as_xml: Option<bool>,
+) -> Result<(), DocumentError> {
+    let write_error = |err: std::io::Error| DocumentError::WriteError(err.to_string());
+    let mut writer = BufWriter::new(File::create(path).map_err(write_error)?);
+    doc.to_writer_with_format(
+        &mut writer,
+        match as_xml {
+            Some(true) => DocumentFormat::Xml,
+            _ => DocumentFormat::Binary,
+        },
+    )?;
+    writer.flush().map_err(write_error)
+}
+
 fn get_auth_cookie(_: &Lua, raw: Option<bool>) -> LuaResult<Option<String>> {
     if matches!(raw, Some(true)) {
         Ok(rbx_cookie::get_value())
@@ -110,6 +188,28 @@ fn get_auth_cookie(_: &Lua, raw: Option<bool>) -> LuaResult<Option<String>> {
     }
 }
 
+/**
+    Starts decoding the bundled reflection database on a background thread.
+
+    The database is only decoded once it is first used, which is quite slow and
+    blocks whatever is using it, so we start decoding it as soon as the library
+    is required instead, but without blocking the script that required it.
+    Scripts that never require the library will never decode the database.
+*/
+fn preload_reflection_database() {
+    static PRELOAD: Once = Once::new();
+    PRELOAD.call_once(|| {
+        let spawned = thread::Builder::new()
+            .name("lune-reflection-database".to_string())
+            .spawn(|| {
+                REFLECTION_DATABASE.get_or_init(ReflectionDatabase::new);
+            });
+        // NOTE: If we could not spawn a thread, the database
+        // will instead be decoded whenever it is first used
+        drop(spawned);
+    });
+}
+
 fn get_reflection_database(_: &Lua, _: ()) -> LuaResult<ReflectionDatabase> {
     Ok(*REFLECTION_DATABASE.get_or_init(ReflectionDatabase::new))
 }
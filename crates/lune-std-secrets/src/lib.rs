@@ -0,0 +1,86 @@
+#![allow(clippy::cargo_common_metadata)]
+
+use keyring::{Entry, Error as KeyringError};
+use mlua::prelude::*;
+use mlua_luau_scheduler::LuaSpawnExt;
+
+use lune_utils::TableBuilder;
+
+/**
+    Creates the `secrets` standard library module.
+
+    # Errors
+
+    Errors when out of memory.
+*/
+pub fn module(lua: &Lua) -> LuaResult<LuaTable> {
+    TableBuilder::new_named(lua, "secrets")?
+        .with_async_function("get", secrets_get)?
+        .with_async_function("set", secrets_set)?
+        .with_async_function("delete", secrets_delete)?
+        .build_readonly()
+}
+
+/*
+    NOTE: Accessing the platform keychain may block for a long time, such as
+    when the user is asked to unlock it, so all of the below functions run
+    on a background thread to not block any other Lua threads meanwhile
+*/
+
+async fn secrets_get(lua: &Lua, (service, account): (String, String)) -> LuaResult<Option<String>> {
+    validate_names(&service, &account)?;
+    lua.spawn_blocking(
+        move || match open_entry(&service, &account)?.get_password() {
+            Ok(secret) => Ok(Some(secret)),
+            Err(KeyringError::NoEntry) => Ok(None),
+            Err(e) => Err(keyring_error("read", &service, &account, &e)),
+        },
+    )
+    .await
+    .map_err(LuaError::runtime)
+}
+
+async fn secrets_set(
+    lua: &Lua,
+    (service, account, secret): (String, String, String),
+) -> LuaResult<()> {
+    validate_names(&service, &account)?;
+    lua.spawn_blocking(move || {
+        open_entry(&service, &account)?
+            .set_password(&secret)
+            .map_err(|e| keyring_error("write", &service, &account, &e))
+    })
+    .await
+    .map_err(LuaError::runtime)
+}
+
+async fn secrets_delete(lua: &Lua, (service, account): (String, String)) -> LuaResult<bool> {
+    validate_names(&service, &account)?;
+    lua.spawn_blocking(
+        move || match open_entry(&service, &account)?.delete_credential() {
+            Ok(()) => Ok(true),
+            Err(KeyringError::NoEntry) => Ok(false),
+            Err(e) => Err(keyring_error("delete", &service, &account, &e)),
+        },
+    )
+    .await
+    .map_err(LuaError::runtime)
+}
+
+fn validate_names(service: &str, account: &str) -> LuaResult<()> {
+    if service.is_empty() {
+        Err(LuaError::runtime("Service name must not be empty"))
+    } else if account.is_empty() {
+        Err(LuaError::runtime("Account name must not be empty"))
+    } else {
+        Ok(())
+    }
+}
+
+fn open_entry(service: &str, account: &str) -> Result<Entry, String> {
+    Entry::new(service, account).map_err(|e| keyring_error("access", service, account, &e))
+}
+
+fn keyring_error(action: &str, service: &str, account: &str, e: &KeyringError) -> String {
+    format!("Failed to {action} secret for account '{account}' in service '{service}' - {e}")
+}
@@ -0,0 +1,45 @@
+#![allow(clippy::cargo_common_metadata)]
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use mlua::prelude::*;
+
+use lune_std_serde::{decode, encode, EncodeDecodeConfig, EncodeDecodeFormat};
+
+// NOTE: Roughly the shape of the telemetry events that
+// scripts usually process, with a mix of all JSON types
+fn create_document(events: usize) -> String {
+    let events = (0..events)
+        .map(|i| {
+            format!(
+                r#"{{"id":{i},"name":"event-{i}","timestamp":1700000000.{i},"success":{},"error":null,"tags":["alpha","beta","gamma"],"data":{{"x":{i},"y":-{i},"label":"point \"{i}\""}}}}"#,
+                i % 2 == 0
+            )
+        })
+        .collect::<Vec<_>>();
+    format!("[{}]", events.join(","))
+}
+
+// NOTE: CI runs this benchmark with and without the simd-json feature, and
+// fails if decoding with the feature enabled is any slower than without it
+fn bench_json(c: &mut Criterion) {
+    let lua = Lua::new();
+    let document = create_document(10_000);
+    let config = EncodeDecodeConfig::from(EncodeDecodeFormat::Json);
+
+    let mut group = c.benchmark_group("json");
+    group.throughput(Throughput::Bytes(document.len() as u64));
+
+    group.bench_function("decode", |b| {
+        b.iter(|| decode(black_box(&document), &lua, config).unwrap());
+    });
+
+    let value = decode(&document, &lua, config).unwrap();
+    group.bench_function("encode", |b| {
+        b.iter(|| encode(black_box(value.clone()), &lua, config).unwrap());
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_json);
+criterion_main!(benches);
@@ -113,18 +113,21 @@ impl<'lua> FromLua<'lua> for CompressDecompressFormat {
 /**
     Compresses the given bytes using the specified format.
 
+    Owned bytes such as a `Vec<u8>` or `BString` are moved instead
+    of copied when the compression needs to happen on another thread.
+
     # Errors
 
     Errors when the compression fails.
 */
 pub async fn compress<'lua>(
-    source: impl AsRef<[u8]>,
+    source: impl AsRef<[u8]> + Into<Vec<u8>>,
     format: CompressDecompressFormat,
     level: Option<i32>,
 ) -> LuaResult<Vec<u8>> {
     if let CompressDecompressFormat::LZ4 = format {
-        let source = source.as_ref().to_vec();
-        return spawn_blocking(move || compress_lz4(source))
+        let source = source.into();
+        return spawn_blocking(move || compress_lz4(source, level))
             .await
             .into_lua_err()?
             .into_lua_err();
@@ -159,16 +162,19 @@ pub async fn compress<'lua>(
 /**
     Decompresses the given bytes using the specified format.
 
+    Owned bytes such as a `Vec<u8>` or `BString` are moved instead
+    of copied when the decompression needs to happen on another thread.
+
     # Errors
 
     Errors when the decompression fails.
 */
 pub async fn decompress<'lua>(
-    source: impl AsRef<[u8]>,
+    source: impl AsRef<[u8]> + Into<Vec<u8>>,
     format: CompressDecompressFormat,
 ) -> LuaResult<Vec<u8>> {
     if let CompressDecompressFormat::LZ4 = format {
-        let source = source.as_ref().to_vec();
+        let source = source.into();
         return spawn_blocking(move || decompress_lz4(source))
             .await
             .into_lua_err()?
@@ -197,11 +203,13 @@ pub async fn decompress<'lua>(
     Ok(bytes)
 }
 
+const LZ4_MAX_LEVEL: u32 = 16;
+
 // TODO: Remove the compatibility layer. Prepending size is no longer
 // necessary, using lz4 create instead of lz4-flex, but we must remove
 // it in a major version to not unexpectedly break compatibility
 
-fn compress_lz4(input: Vec<u8>) -> LuaResult<Vec<u8>> {
+fn compress_lz4(input: Vec<u8>, level: Option<i32>) -> LuaResult<Vec<u8>> {
     let mut input = Cursor::new(input);
     let mut output = Cursor::new(Vec::new());
 
@@ -210,7 +218,7 @@ fn compress_lz4(input: Vec<u8>) -> LuaResult<Vec<u8>> {
     output.write_all(len.to_le_bytes().as_ref())?;
 
     let mut encoder = EncoderBuilder::new()
-        .level(16)
+        .level(level.map_or(LZ4_MAX_LEVEL, |l| l.clamp(0, LZ4_MAX_LEVEL as i32) as u32))
         .checksum(lz4::ContentChecksum::ChecksumEnabled)
         .block_mode(lz4::BlockMode::Independent)
         .build(output)?;
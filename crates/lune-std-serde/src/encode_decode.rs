@@ -4,6 +4,8 @@ use serde_json::Value as JsonValue;
 use serde_yaml::Value as YamlValue;
 use toml::Value as TomlValue;
 
+use crate::json::decode_json;
+
 // NOTE: These are options for going from other format -> lua ("serializing" lua values)
 const LUA_SERIALIZE_OPTIONS: LuaSerializeOptions = LuaSerializeOptions::new()
     .set_array_metatable(false)
@@ -136,10 +138,7 @@ pub fn decode(
 ) -> LuaResult<LuaValue> {
     let bytes = bytes.as_ref();
     match config.format {
-        EncodeDecodeFormat::Json => {
-            let value: JsonValue = serde_json::from_slice(bytes).into_lua_err()?;
-            lua.to_value_with(&value, LUA_SERIALIZE_OPTIONS)
-        }
+        EncodeDecodeFormat::Json => decode_json(lua, bytes),
         EncodeDecodeFormat::Yaml => {
             let value: YamlValue = serde_yaml::from_slice(bytes).into_lua_err()?;
             lua.to_value_with(&value, LUA_SERIALIZE_OPTIONS)
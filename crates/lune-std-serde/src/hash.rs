@@ -1,4 +1,4 @@
-use std::fmt::Write;
+use std::{fmt::Write, io::Read};
 
 use bstr::BString;
 use md5::Md5;
@@ -16,8 +16,13 @@ pub struct HashOptions {
     // seed: Option<BString>,
 }
 
+const READER_BUFFER_SIZE: usize = 64 * 1024;
+
+/**
+    A hashing algorithm supported by `serde.hash` and `serde.hmac`.
+*/
 #[derive(Debug, Clone, Copy)]
-enum HashAlgorithm {
+pub enum HashAlgorithm {
     Md5,
     Sha1,
     // SHA-2 variants
@@ -49,6 +54,7 @@ impl HashAlgorithm {
         Self::Blake3,
     ];
 
+    #[must_use]
     pub const fn name(self) -> &'static str {
         match self {
             Self::Md5 => "md5",
@@ -64,66 +70,84 @@ impl HashAlgorithm {
             Self::Blake3 => "blake3",
         }
     }
-}
 
-impl HashOptions {
     /**
-        Computes the hash for the `message` using whatever `algorithm` is
-        contained within this struct and returns it as a string of hex digits.
+        Computes the hash of everything read from the given reader, one chunk
+        at a time, and returns it as a string of hex digits.
+
+        This never needs to hold more than a single chunk in memory,
+        so it can be used to hash files of any size.
+
+        # Errors
+
+        Errors if reading from the reader fails.
+    */
+    pub fn hash_reader(self, reader: impl Read) -> std::io::Result<String> {
+        fn digest_reader<D: digest::Digest>(mut reader: impl Read) -> std::io::Result<Vec<u8>> {
+            let mut hasher = D::new();
+            let mut buffer = vec![0; READER_BUFFER_SIZE];
+            loop {
+                match reader.read(&mut buffer)? {
+                    0 => break,
+                    n => hasher.update(&buffer[..n]),
+                }
+            }
+            Ok(hasher.finalize().to_vec())
+        }
+
+        let bytes = match self {
+            Self::Md5 => digest_reader::<Md5>(reader)?,
+            Self::Sha1 => digest_reader::<Sha1>(reader)?,
+            Self::Sha2_224 => digest_reader::<Sha224>(reader)?,
+            Self::Sha2_256 => digest_reader::<Sha256>(reader)?,
+            Self::Sha2_384 => digest_reader::<Sha384>(reader)?,
+            Self::Sha2_512 => digest_reader::<Sha512>(reader)?,
+
+            Self::Sha3_224 => digest_reader::<Sha3_224>(reader)?,
+            Self::Sha3_256 => digest_reader::<Sha3_256>(reader)?,
+            Self::Sha3_384 => digest_reader::<Sha3_384>(reader)?,
+            Self::Sha3_512 => digest_reader::<Sha3_512>(reader)?,
+
+            Self::Blake3 => digest_reader::<Blake3>(reader)?,
+        };
+        Ok(hex_string(&bytes))
+    }
+
+    /**
+        Computes the hash of the given message, returning the raw bytes of the hash.
     */
-    #[inline]
     #[must_use = "hashing a message is useless without using the resulting hash"]
-    pub fn hash(self) -> String {
+    pub fn digest(self, message: &[u8]) -> Vec<u8> {
         use digest::Digest;
 
-        let message = self.message;
-        let bytes = match self.algorithm {
-            HashAlgorithm::Md5 => Md5::digest(message).to_vec(),
-            HashAlgorithm::Sha1 => Sha1::digest(message).to_vec(),
-            HashAlgorithm::Sha2_224 => Sha224::digest(message).to_vec(),
-            HashAlgorithm::Sha2_256 => Sha256::digest(message).to_vec(),
-            HashAlgorithm::Sha2_384 => Sha384::digest(message).to_vec(),
-            HashAlgorithm::Sha2_512 => Sha512::digest(message).to_vec(),
-
-            HashAlgorithm::Sha3_224 => Sha3_224::digest(message).to_vec(),
-            HashAlgorithm::Sha3_256 => Sha3_256::digest(message).to_vec(),
-            HashAlgorithm::Sha3_384 => Sha3_384::digest(message).to_vec(),
-            HashAlgorithm::Sha3_512 => Sha3_512::digest(message).to_vec(),
-
-            HashAlgorithm::Blake3 => Blake3::digest(message).to_vec(),
-        };
+        match self {
+            Self::Md5 => Md5::digest(message).to_vec(),
+            Self::Sha1 => Sha1::digest(message).to_vec(),
+            Self::Sha2_224 => Sha224::digest(message).to_vec(),
+            Self::Sha2_256 => Sha256::digest(message).to_vec(),
+            Self::Sha2_384 => Sha384::digest(message).to_vec(),
+            Self::Sha2_512 => Sha512::digest(message).to_vec(),
 
-        // We don't want to return raw binary data generally, since that's not
-        // what most people want a hash for. So we have to make a hex string.
-        bytes
-            .iter()
-            .fold(String::with_capacity(bytes.len() * 2), |mut output, b| {
-                let _ = write!(output, "{b:02x}");
-                output
-            })
+            Self::Sha3_224 => Sha3_224::digest(message).to_vec(),
+            Self::Sha3_256 => Sha3_256::digest(message).to_vec(),
+            Self::Sha3_384 => Sha3_384::digest(message).to_vec(),
+            Self::Sha3_512 => Sha3_512::digest(message).to_vec(),
+
+            Self::Blake3 => Blake3::digest(message).to_vec(),
+        }
     }
 
     /**
-        Computes the HMAC for the `message` using whatever `algorithm` and
-        `secret` are contained within this struct. The computed value is
-        returned as a string of hex digits.
+        Computes the HMAC of the given message using the given secret,
+        returning the raw bytes of the HMAC.
 
         # Errors
 
-        If the `secret` is not provided or is otherwise invalid.
+        If the `secret` is invalid for this algorithm.
     */
-    #[inline]
-    pub fn hmac(self) -> LuaResult<String> {
+    pub fn hmac(self, secret: &[u8], message: &[u8]) -> LuaResult<Vec<u8>> {
         use hmac::{Hmac, Mac, SimpleHmac};
 
-        let secret = self
-            .secret
-            .ok_or_else(|| LuaError::FromLuaConversionError {
-                from: "nil",
-                to: "string or buffer",
-                message: Some("Argument #3 missing or nil".to_string()),
-            })?;
-
         /*
             These macros exist to remove what would ultimately be dozens of
             repeating lines. Essentially, there's several step to processing
@@ -135,45 +159,87 @@ impl HashOptions {
         */
         macro_rules! hmac {
             ($Type:ty) => {{
-                let mut mac: Hmac<$Type> = Hmac::new_from_slice(&secret).into_lua_err()?;
-                mac.update(&self.message);
+                let mut mac: Hmac<$Type> = Hmac::new_from_slice(secret).into_lua_err()?;
+                mac.update(message);
                 mac.finalize().into_bytes().to_vec()
             }};
         }
         macro_rules! hmac_no_blocks {
             ($Type:ty) => {{
                 let mut mac: SimpleHmac<$Type> =
-                    SimpleHmac::new_from_slice(&secret).into_lua_err()?;
-                mac.update(&self.message);
+                    SimpleHmac::new_from_slice(secret).into_lua_err()?;
+                mac.update(message);
                 mac.finalize().into_bytes().to_vec()
             }};
         }
 
-        let bytes = match self.algorithm {
-            HashAlgorithm::Md5 => hmac!(Md5),
-            HashAlgorithm::Sha1 => hmac!(Sha1),
+        Ok(match self {
+            Self::Md5 => hmac!(Md5),
+            Self::Sha1 => hmac!(Sha1),
 
-            HashAlgorithm::Sha2_224 => hmac!(Sha224),
-            HashAlgorithm::Sha2_256 => hmac!(Sha256),
-            HashAlgorithm::Sha2_384 => hmac!(Sha384),
-            HashAlgorithm::Sha2_512 => hmac!(Sha512),
+            Self::Sha2_224 => hmac!(Sha224),
+            Self::Sha2_256 => hmac!(Sha256),
+            Self::Sha2_384 => hmac!(Sha384),
+            Self::Sha2_512 => hmac!(Sha512),
 
-            HashAlgorithm::Sha3_224 => hmac!(Sha3_224),
-            HashAlgorithm::Sha3_256 => hmac!(Sha3_256),
-            HashAlgorithm::Sha3_384 => hmac!(Sha3_384),
-            HashAlgorithm::Sha3_512 => hmac!(Sha3_512),
+            Self::Sha3_224 => hmac!(Sha3_224),
+            Self::Sha3_256 => hmac!(Sha3_256),
+            Self::Sha3_384 => hmac!(Sha3_384),
+            Self::Sha3_512 => hmac!(Sha3_512),
 
-            HashAlgorithm::Blake3 => hmac_no_blocks!(Blake3),
-        };
-        Ok(bytes
-            .iter()
-            .fold(String::with_capacity(bytes.len() * 2), |mut output, b| {
-                let _ = write!(output, "{b:02x}");
-                output
-            }))
+            Self::Blake3 => hmac_no_blocks!(Blake3),
+        })
     }
 }
 
+impl HashOptions {
+    /**
+        Computes the hash for the `message` using whatever `algorithm` is
+        contained within this struct and returns it as a string of hex digits.
+    */
+    #[inline]
+    #[must_use = "hashing a message is useless without using the resulting hash"]
+    pub fn hash(self) -> String {
+        let bytes = self.algorithm.digest(&self.message);
+
+        // We don't want to return raw binary data generally, since that's not
+        // what most people want a hash for. So we have to make a hex string.
+        hex_string(&bytes)
+    }
+
+    /**
+        Computes the HMAC for the `message` using whatever `algorithm` and
+        `secret` are contained within this struct. The computed value is
+        returned as a string of hex digits.
+
+        # Errors
+
+        If the `secret` is not provided or is otherwise invalid.
+    */
+    #[inline]
+    pub fn hmac(self) -> LuaResult<String> {
+        let secret = self
+            .secret
+            .ok_or_else(|| LuaError::FromLuaConversionError {
+                from: "nil",
+                to: "string or buffer",
+                message: Some("Argument #3 missing or nil".to_string()),
+            })?;
+
+        let bytes = self.algorithm.hmac(&secret, &self.message)?;
+        Ok(hex_string(&bytes))
+    }
+}
+
+fn hex_string(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .fold(String::with_capacity(bytes.len() * 2), |mut output, b| {
+            let _ = write!(output, "{b:02x}");
+            output
+        })
+}
+
 impl<'lua> FromLua<'lua> for HashAlgorithm {
     fn from_lua(value: LuaValue<'lua>, _lua: &'lua Lua) -> LuaResult<Self> {
         if let LuaValue::String(str) = value {
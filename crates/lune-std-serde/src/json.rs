@@ -0,0 +1,116 @@
+use std::fmt;
+
+use mlua::prelude::*;
+use serde::de::{DeserializeSeed, Deserializer, Error as _, MapAccess, SeqAccess, Visitor};
+
+/**
+    Decodes the given JSON bytes directly into a Lua value.
+
+    This produces the same values as first decoding into a [`serde_json::Value`]
+    and then converting that into a Lua value, but without ever allocating the
+    intermediate value, which is where most of the time would otherwise be spent.
+
+    When the `simd-json` feature is enabled, the bytes are parsed using
+    [`simd_json`] instead of [`serde_json`], which is much faster for larger
+    documents, and produces the same values, but different error messages.
+
+    # Errors
+
+    Errors when the bytes are not valid JSON, or when out of memory.
+*/
+#[cfg(not(feature = "simd-json"))]
+pub fn decode_json<'lua>(lua: &'lua Lua, bytes: &[u8]) -> LuaResult<LuaValue<'lua>> {
+    let mut deserializer = serde_json::Deserializer::from_slice(bytes);
+    let value = LuaValueSeed(lua)
+        .deserialize(&mut deserializer)
+        .into_lua_err()?;
+    deserializer.end().into_lua_err()?;
+    Ok(value)
+}
+
+#[cfg(feature = "simd-json")]
+pub fn decode_json<'lua>(lua: &'lua Lua, bytes: &[u8]) -> LuaResult<LuaValue<'lua>> {
+    // NOTE: simd-json parses in place and needs its own mutable copy of the
+    // bytes, it also validates the entire document before we get any values
+    let mut bytes = bytes.to_vec();
+    let mut deserializer = simd_json::Deserializer::from_slice(&mut bytes).into_lua_err()?;
+    LuaValueSeed(lua)
+        .deserialize(&mut deserializer)
+        .into_lua_err()
+}
+
+#[derive(Clone, Copy)]
+struct LuaValueSeed<'lua>(&'lua Lua);
+
+impl<'de, 'lua> DeserializeSeed<'de> for LuaValueSeed<'lua> {
+    type Value = LuaValue<'lua>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+}
+
+// NOTE: Numbers are converted using IntoLua, same as the Lua serializer
+// does, and null becomes nil, matching our serialize options in encode_decode
+impl<'de, 'lua> Visitor<'de> for LuaValueSeed<'lua> {
+    type Value = LuaValue<'lua>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("any valid JSON value")
+    }
+
+    fn visit_bool<E: serde::de::Error>(self, value: bool) -> Result<Self::Value, E> {
+        Ok(LuaValue::Boolean(value))
+    }
+
+    fn visit_i64<E: serde::de::Error>(self, value: i64) -> Result<Self::Value, E> {
+        value.into_lua(self.0).map_err(E::custom)
+    }
+
+    fn visit_u64<E: serde::de::Error>(self, value: u64) -> Result<Self::Value, E> {
+        value.into_lua(self.0).map_err(E::custom)
+    }
+
+    fn visit_f64<E: serde::de::Error>(self, value: f64) -> Result<Self::Value, E> {
+        Ok(LuaValue::Number(value))
+    }
+
+    fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        self.0
+            .create_string(value)
+            .map(LuaValue::String)
+            .map_err(E::custom)
+    }
+
+    fn visit_unit<E: serde::de::Error>(self) -> Result<Self::Value, E> {
+        Ok(LuaValue::Nil)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let table = self
+            .0
+            .create_table_with_capacity(seq.size_hint().unwrap_or(0), 0)
+            .map_err(A::Error::custom)?;
+        let mut index = 1;
+        while let Some(value) = seq.next_element_seed(self)? {
+            table.raw_set(index, value).map_err(A::Error::custom)?;
+            index += 1;
+        }
+        Ok(LuaValue::Table(table))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let table = self
+            .0
+            .create_table_with_capacity(0, map.size_hint().unwrap_or(0))
+            .map_err(A::Error::custom)?;
+        while let Some(key) = map.next_key_seed(self)? {
+            let value = map.next_value_seed(self)?;
+            table.raw_set(key, value).map_err(A::Error::custom)?;
+        }
+        Ok(LuaValue::Table(table))
+    }
+}
@@ -2,16 +2,18 @@
 
 use bstr::BString;
 use mlua::prelude::*;
+use tracing::instrument;
 
 use lune_utils::TableBuilder;
 
 mod compress_decompress;
 mod encode_decode;
 mod hash;
+mod json;
 
 pub use self::compress_decompress::{compress, decompress, CompressDecompressFormat};
 pub use self::encode_decode::{decode, encode, EncodeDecodeConfig, EncodeDecodeFormat};
-pub use self::hash::HashOptions;
+pub use self::hash::{HashAlgorithm, HashOptions};
 
 /**
     Creates the `serde` standard library module.
@@ -21,7 +23,7 @@ pub use self::hash::HashOptions;
     Errors when out of memory.
 */
 pub fn module(lua: &Lua) -> LuaResult<LuaTable> {
-    TableBuilder::new(lua)?
+    TableBuilder::new_named(lua, "serde")?
         .with_function("encode", serde_encode)?
         .with_function("decode", serde_decode)?
         .with_async_function("compress", serde_compress)?
@@ -31,6 +33,7 @@ pub fn module(lua: &Lua) -> LuaResult<LuaTable> {
         .build_readonly()
 }
 
+#[instrument(level = "debug", name = "serde.encode", skip_all, fields(format = ?format))]
 fn serde_encode<'lua>(
     lua: &'lua Lua,
     (format, value, pretty): (EncodeDecodeFormat, LuaValue<'lua>, Option<bool>),
@@ -39,25 +42,44 @@ fn serde_encode<'lua>(
     encode(value, lua, config)
 }
 
+#[instrument(level = "debug", name = "serde.decode", skip_all, fields(format = ?format, bytes = bs.len()))]
 fn serde_decode(lua: &Lua, (format, bs): (EncodeDecodeFormat, BString)) -> LuaResult<LuaValue> {
     let config = EncodeDecodeConfig::from(format);
     decode(bs, lua, config)
 }
 
-async fn serde_compress(
-    lua: &Lua,
-    (format, bs, level): (CompressDecompressFormat, BString, Option<i32>),
-) -> LuaResult<LuaString> {
+#[instrument(level = "debug", name = "serde.compress", skip_all, fields(format = ?format))]
+async fn serde_compress<'lua>(
+    lua: &'lua Lua,
+    (format, value, level): (CompressDecompressFormat, LuaValue<'lua>, Option<i32>),
+) -> LuaResult<LuaValue<'lua>> {
+    let as_buffer = value.is_buffer();
+    let bs = BString::from_lua(value, lua)?;
     let bytes = compress(bs, format, level).await?;
-    lua.create_string(bytes)
+    create_bytes(lua, bytes, as_buffer)
 }
 
-async fn serde_decompress(
-    lua: &Lua,
-    (format, bs): (CompressDecompressFormat, BString),
-) -> LuaResult<LuaString> {
+#[instrument(level = "debug", name = "serde.decompress", skip_all, fields(format = ?format))]
+async fn serde_decompress<'lua>(
+    lua: &'lua Lua,
+    (format, value): (CompressDecompressFormat, LuaValue<'lua>),
+) -> LuaResult<LuaValue<'lua>> {
+    let as_buffer = value.is_buffer();
+    let bs = BString::from_lua(value, lua)?;
     let bytes = decompress(bs, format).await?;
-    lua.create_string(bytes)
+    create_bytes(lua, bytes, as_buffer)
+}
+
+/**
+    Creates either a buffer or a string from the given bytes, so that
+    functions can return the same kind of value that they were given.
+*/
+fn create_bytes(lua: &Lua, bytes: Vec<u8>, as_buffer: bool) -> LuaResult<LuaValue> {
+    if as_buffer {
+        lua.create_buffer(bytes)?.into_lua(lua)
+    } else {
+        lua.create_string(bytes)?.into_lua(lua)
+    }
 }
 
 fn hash_message(lua: &Lua, options: HashOptions) -> LuaResult<LuaString> {
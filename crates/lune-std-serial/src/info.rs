@@ -0,0 +1,28 @@
+use mlua::prelude::*;
+use serialport::{SerialPortInfo, SerialPortType};
+
+use lune_utils::TableBuilder;
+
+/**
+    Converts information about an available serial port into a Lua table.
+
+    # Errors
+
+    Errors when out of memory.
+*/
+pub fn create_port_info_table(lua: &Lua, info: SerialPortInfo) -> LuaResult<LuaTable> {
+    let builder = TableBuilder::new(lua)?.with_value("name", info.port_name)?;
+    match info.port_type {
+        SerialPortType::UsbPort(usb) => builder
+            .with_value("kind", "usb")?
+            .with_value("vendorId", usb.vid)?
+            .with_value("productId", usb.pid)?
+            .with_value("serialNumber", usb.serial_number)?
+            .with_value("manufacturer", usb.manufacturer)?
+            .with_value("product", usb.product)?
+            .build_readonly(),
+        SerialPortType::PciPort => builder.with_value("kind", "pci")?.build_readonly(),
+        SerialPortType::BluetoothPort => builder.with_value("kind", "bluetooth")?.build_readonly(),
+        SerialPortType::Unknown => builder.with_value("kind", "unknown")?.build_readonly(),
+    }
+}
@@ -0,0 +1,54 @@
+#![allow(clippy::cargo_common_metadata)]
+
+use mlua::prelude::*;
+use mlua_luau_scheduler::LuaSpawnExt;
+
+use lune_utils::TableBuilder;
+
+mod info;
+mod options;
+mod port;
+
+use self::info::create_port_info_table;
+use self::options::SerialOpenOptions;
+use self::port::LuaSerialPort;
+
+/**
+    Creates the `serial` standard library module.
+
+    # Errors
+
+    Errors when out of memory.
+*/
+pub fn module(lua: &Lua) -> LuaResult<LuaTable> {
+    TableBuilder::new_named(lua, "serial")?
+        .with_async_function("list", serial_list)?
+        .with_async_function("open", serial_open)?
+        .build_readonly()
+}
+
+async fn serial_list(lua: &Lua, (): ()) -> LuaResult<LuaTable> {
+    let ports = lua
+        .spawn_blocking(serialport::available_ports)
+        .await
+        .map_err(|e| LuaError::runtime(format!("Failed to list serial ports - {e}")))?;
+    let table = lua.create_table_with_capacity(ports.len(), 0)?;
+    for info in ports {
+        table.raw_push(create_port_info_table(lua, info)?)?;
+    }
+    Ok(table)
+}
+
+async fn serial_open(
+    lua: &Lua,
+    (path, options): (String, SerialOpenOptions),
+) -> LuaResult<LuaSerialPort> {
+    let builder = options.into_builder(&path);
+    let name = path.clone();
+    lua.spawn_blocking(move || {
+        let port = builder.open()?;
+        LuaSerialPort::new(name, port)
+    })
+    .await
+    .map_err(|e| LuaError::runtime(format!("Failed to open serial port '{path}' - {e}")))
+}
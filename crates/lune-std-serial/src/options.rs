@@ -0,0 +1,137 @@
+use std::time::Duration;
+
+use mlua::prelude::*;
+use serialport::{DataBits, FlowControl, Parity, SerialPortBuilder, StopBits};
+
+const DEFAULT_BAUD_RATE: u32 = 9600;
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/**
+    Options for opening a serial port.
+*/
+#[derive(Debug, Clone)]
+pub struct SerialOpenOptions {
+    pub baud_rate: u32,
+    pub data_bits: DataBits,
+    pub parity: Parity,
+    pub stop_bits: StopBits,
+    pub flow_control: FlowControl,
+    pub timeout: Duration,
+}
+
+impl SerialOpenOptions {
+    pub fn into_builder(self, path: &str) -> SerialPortBuilder {
+        serialport::new(path, self.baud_rate)
+            .data_bits(self.data_bits)
+            .parity(self.parity)
+            .stop_bits(self.stop_bits)
+            .flow_control(self.flow_control)
+            .timeout(self.timeout)
+    }
+}
+
+impl Default for SerialOpenOptions {
+    fn default() -> Self {
+        Self {
+            baud_rate: DEFAULT_BAUD_RATE,
+            data_bits: DataBits::Eight,
+            parity: Parity::None,
+            stop_bits: StopBits::One,
+            flow_control: FlowControl::None,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+}
+
+impl<'lua> FromLua<'lua> for SerialOpenOptions {
+    fn from_lua(value: LuaValue<'lua>, _: &'lua Lua) -> LuaResult<Self> {
+        let tab = match value {
+            LuaValue::Nil => return Ok(Self::default()),
+            LuaValue::Table(tab) => tab,
+            _ => {
+                return Err(LuaError::FromLuaConversionError {
+                    from: value.type_name(),
+                    to: "SerialOpenOptions",
+                    message: Some(format!(
+                        "Invalid serial port options - expected table, got {}",
+                        value.type_name()
+                    )),
+                })
+            }
+        };
+
+        let mut this = Self::default();
+
+        if let Some(baud_rate) = tab.get::<_, Option<u32>>("baudRate")? {
+            if baud_rate == 0 {
+                return Err(LuaError::runtime(
+                    "Invalid baud rate - must be greater than 0",
+                ));
+            }
+            this.baud_rate = baud_rate;
+        }
+
+        if let Some(data_bits) = tab.get::<_, Option<u8>>("dataBits")? {
+            this.data_bits = match data_bits {
+                5 => DataBits::Five,
+                6 => DataBits::Six,
+                7 => DataBits::Seven,
+                8 => DataBits::Eight,
+                _ => {
+                    return Err(LuaError::runtime(format!(
+                        "Invalid data bits '{data_bits}' - expected 5, 6, 7 or 8"
+                    )))
+                }
+            };
+        }
+
+        if let Some(parity) = tab.get::<_, Option<String>>("parity")? {
+            this.parity = match parity.as_str() {
+                "none" => Parity::None,
+                "odd" => Parity::Odd,
+                "even" => Parity::Even,
+                _ => {
+                    return Err(LuaError::runtime(format!(
+                        "Invalid parity '{parity}' - expected 'none', 'odd' or 'even'"
+                    )))
+                }
+            };
+        }
+
+        if let Some(stop_bits) = tab.get::<_, Option<u8>>("stopBits")? {
+            this.stop_bits = match stop_bits {
+                1 => StopBits::One,
+                2 => StopBits::Two,
+                _ => {
+                    return Err(LuaError::runtime(format!(
+                        "Invalid stop bits '{stop_bits}' - expected 1 or 2"
+                    )))
+                }
+            };
+        }
+
+        if let Some(flow_control) = tab.get::<_, Option<String>>("flowControl")? {
+            this.flow_control = match flow_control.as_str() {
+                "none" => FlowControl::None,
+                "software" => FlowControl::Software,
+                "hardware" => FlowControl::Hardware,
+                _ => {
+                    return Err(LuaError::runtime(format!(
+                        "Invalid flow control '{flow_control}' - expected 'none', 'software' or 'hardware'"
+                    )))
+                }
+            };
+        }
+
+        if let Some(timeout) = tab.get::<_, Option<f64>>("timeout")? {
+            if !timeout.is_finite() || timeout < 0.0 {
+                return Err(LuaError::runtime(format!(
+                    "Invalid timeout - expected a non-negative number of seconds, got {timeout}"
+                )));
+            }
+            this.timeout = Duration::from_secs_f64(timeout);
+        }
+
+        Ok(this)
+    }
+}
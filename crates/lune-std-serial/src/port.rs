@@ -0,0 +1,145 @@
+use std::{
+    io::{self, Read, Write},
+    sync::{Arc, Mutex},
+};
+
+use bstr::BString;
+use mlua::prelude::*;
+use mlua_luau_scheduler::LuaSpawnExt;
+use serialport::SerialPort;
+
+const DEFAULT_READ_SIZE: usize = 1024;
+
+type SharedPort = Arc<Mutex<Option<Box<dyn SerialPort>>>>;
+
+/**
+    An open serial port.
+
+    Reading and writing use separate handles to the same port, so that
+    data can be written while another thread is waiting to read data.
+
+    All blocking operations happen on background threads, and the
+    port is closed once both handles have been dropped or closed.
+*/
+#[derive(Clone)]
+pub struct LuaSerialPort {
+    name: String,
+    baud_rate: u32,
+    reader: SharedPort,
+    writer: SharedPort,
+}
+
+impl LuaSerialPort {
+    /**
+        Creates a new serial port from an already opened port.
+
+        # Errors
+
+        Errors if a second handle to the port could not be created.
+    */
+    pub fn new(name: String, port: Box<dyn SerialPort>) -> Result<Self, serialport::Error> {
+        let baud_rate = port.baud_rate()?;
+        let writer = port.try_clone()?;
+        Ok(Self {
+            name,
+            baud_rate,
+            reader: Arc::new(Mutex::new(Some(port))),
+            writer: Arc::new(Mutex::new(Some(writer))),
+        })
+    }
+
+    async fn read(&self, lua: &Lua, size: usize) -> LuaResult<Option<Vec<u8>>> {
+        let reader = Arc::clone(&self.reader);
+        lua.spawn_blocking(move || {
+            with_port(&reader, |port| {
+                let mut buf = vec![0u8; size];
+                match port.read(&mut buf) {
+                    Ok(n) => {
+                        buf.truncate(n);
+                        Ok(Some(buf))
+                    }
+                    Err(e) if e.kind() == io::ErrorKind::TimedOut => Ok(None),
+                    Err(e) => Err(e),
+                }
+            })
+        })
+        .await
+        .into_lua_err()
+    }
+
+    async fn write(&self, lua: &Lua, data: Vec<u8>) -> LuaResult<()> {
+        let writer = Arc::clone(&self.writer);
+        lua.spawn_blocking(move || {
+            with_port(&writer, |port| {
+                port.write_all(&data)?;
+                port.flush()
+            })
+        })
+        .await
+        .into_lua_err()
+    }
+
+    async fn close(&self, lua: &Lua) {
+        let reader = Arc::clone(&self.reader);
+        let writer = Arc::clone(&self.writer);
+        // NOTE: A read may currently be waiting for data while holding the
+        // reader lock, so closing may need to wait for it to time out
+        lua.spawn_blocking(move || {
+            for port in [writer, reader] {
+                if let Ok(mut port) = port.lock() {
+                    port.take();
+                }
+            }
+        })
+        .await;
+    }
+
+    fn is_closed(&self) -> bool {
+        self.writer.lock().map_or(true, |port| port.is_none())
+    }
+}
+
+fn with_port<R>(
+    port: &SharedPort,
+    f: impl FnOnce(&mut Box<dyn SerialPort>) -> io::Result<R>,
+) -> io::Result<R> {
+    let mut guard = port
+        .lock()
+        .map_err(|_| io::Error::other("Serial port is no longer usable"))?;
+    match guard.as_mut() {
+        Some(port) => f(port),
+        None => Err(io::Error::other("Serial port has been closed")),
+    }
+}
+
+impl LuaUserData for LuaSerialPort {
+    fn add_fields<'lua, F: LuaUserDataFields<'lua, Self>>(fields: &mut F) {
+        fields.add_meta_field(LuaMetaMethod::Type, "SerialPort");
+        fields.add_field_method_get("name", |_, this| Ok(this.name.clone()));
+        fields.add_field_method_get("baudRate", |_, this| Ok(this.baud_rate));
+        fields.add_field_method_get("closed", |_, this| Ok(this.is_closed()));
+    }
+
+    fn add_methods<'lua, M: LuaUserDataMethods<'lua, Self>>(methods: &mut M) {
+        methods.add_async_method("read", |lua, this, size: Option<usize>| async move {
+            let size = size.unwrap_or(DEFAULT_READ_SIZE);
+            if size == 0 {
+                return Err(LuaError::runtime("Read size must be greater than 0"));
+            }
+            match this.read(lua, size).await? {
+                Some(data) => Ok(LuaValue::String(lua.create_string(data)?)),
+                None => Ok(LuaValue::Nil),
+            }
+        });
+        methods.add_async_method("write", |lua, this, data: BString| async move {
+            this.write(lua, data.into()).await
+        });
+        methods.add_async_method("close", |lua, this, (): ()| async move {
+            this.close(lua).await;
+            Ok(())
+        });
+        methods.add_meta_method(LuaMetaMethod::ToString, |_, this, ()| {
+            Ok(format!("SerialPort({})", this.name))
+        });
+    }
+}
@@ -0,0 +1,223 @@
+use std::{cell::RefCell, path::Path, rc::Rc};
+
+use mlua::prelude::*;
+use rusqlite::{
+    ffi,
+    hooks::{AuthAction, AuthContext, Authorization},
+    OpenFlags,
+};
+
+use super::{
+    options::SqliteOpenOptions,
+    statement::{execute, query, query_row, LuaStatement},
+    value::SqlParams,
+};
+
+// NOTE: The default capacity of the statement cache is quite small, and
+// since prepared statements are stored in it, we make it a bit larger
+const STATEMENT_CACHE_CAPACITY: usize = 64;
+
+// NOTE: Savepoints are used for transactions instead of BEGIN and COMMIT,
+// since they work both on their own and when nested inside of each other
+const TRANSACTION_BEGIN: &str = "SAVEPOINT lune_transaction";
+const TRANSACTION_COMMIT: &str = "RELEASE lune_transaction";
+const TRANSACTION_ROLLBACK: &str = "ROLLBACK TO lune_transaction; RELEASE lune_transaction";
+
+/**
+    A database connection shared between a database and its prepared statements.
+
+    The connection is removed when the database is closed, after which
+    any use of the database or its statements will result in an error.
+*/
+#[derive(Debug, Clone)]
+pub struct Connection(Rc<RefCell<Option<rusqlite::Connection>>>);
+
+impl Connection {
+    fn new(conn: rusqlite::Connection, restricted: bool) -> Self {
+        conn.set_prepared_statement_cache_capacity(STATEMENT_CACHE_CAPACITY);
+        if restricted {
+            conn.authorizer(Some(deny_attach));
+        }
+        Self(Rc::new(RefCell::new(Some(conn))))
+    }
+
+    /**
+        Calls the given function with the connection.
+
+        # Errors
+
+        Errors if the connection has been closed, or if the given function errors.
+    */
+    pub fn with<R>(&self, f: impl FnOnce(&rusqlite::Connection) -> LuaResult<R>) -> LuaResult<R> {
+        match self.0.borrow().as_ref() {
+            Some(conn) => f(conn),
+            None => Err(LuaError::runtime("Database is closed")),
+        }
+    }
+
+    fn close(&self) -> LuaResult<()> {
+        match self.0.borrow_mut().take() {
+            Some(conn) => conn.close().map_err(|(_, e)| e).into_lua_err(),
+            None => Ok(()),
+        }
+    }
+}
+
+/**
+    A connection to a sqlite database that can be used from Lua.
+*/
+#[derive(Debug, Clone)]
+pub struct LuaDatabase {
+    conn: Connection,
+}
+
+impl LuaDatabase {
+    /**
+        Opens the database at the given path, creating it if it does not exist,
+        unless the database is being opened as read-only.
+
+        Restricted databases may not attach any other database files,
+        which should be used when filesystem access is restricted.
+
+        # Errors
+
+        Errors if the database could not be opened.
+    */
+    pub fn open(
+        path: impl AsRef<Path>,
+        options: SqliteOpenOptions,
+        restricted: bool,
+    ) -> LuaResult<Self> {
+        // NOTE: The bundled SQLite always accepts URI filenames, even without the
+        // URI open flag, so relative paths are prefixed to never be read as URIs
+        let path = path.as_ref();
+        let path = if path.is_relative() {
+            Path::new(".").join(path)
+        } else {
+            path.to_path_buf()
+        };
+        let mut flags = open_flags();
+        if options.read_only {
+            flags.remove(OpenFlags::SQLITE_OPEN_READ_WRITE | OpenFlags::SQLITE_OPEN_CREATE);
+            flags.insert(OpenFlags::SQLITE_OPEN_READ_ONLY);
+        }
+        let conn = rusqlite::Connection::open_with_flags(path, flags).into_lua_err()?;
+        Ok(Self {
+            conn: Connection::new(conn, restricted),
+        })
+    }
+
+    /**
+        Opens a new database that only exists in memory.
+
+        Restricted databases may not attach any other database files,
+        which should be used when filesystem access is restricted.
+
+        # Errors
+
+        Errors if the database could not be opened.
+    */
+    pub fn open_in_memory(restricted: bool) -> LuaResult<Self> {
+        let conn = rusqlite::Connection::open_in_memory_with_flags(open_flags()).into_lua_err()?;
+        Ok(Self {
+            conn: Connection::new(conn, restricted),
+        })
+    }
+}
+
+/**
+    Gets the default flags for opening a database.
+
+    URI filenames are not allowed, since a URI could point to a
+    different file than the path that filesystem permissions checked.
+*/
+fn open_flags() -> OpenFlags {
+    let mut flags = OpenFlags::default();
+    flags.remove(OpenFlags::SQLITE_OPEN_URI);
+    flags
+}
+
+/**
+    Authorizer that denies attaching database files to a connection.
+
+    Temporary and in-memory databases may still be attached, which
+    also lets `VACUUM` work, since it attaches a temporary database.
+*/
+fn deny_attach(ctx: AuthContext<'_>) -> Authorization {
+    match ctx.action {
+        AuthAction::Attach { filename } if filename.is_empty() || filename == ":memory:" => {
+            Authorization::Allow
+        }
+        AuthAction::Attach { .. } => Authorization::Deny,
+        // NOTE: Attaching a database using anything other than a string
+        // literal, such as a bound parameter, gives us no filename at all
+        AuthAction::Unknown { code, .. } if code == ffi::SQLITE_ATTACH => Authorization::Deny,
+        _ => Authorization::Allow,
+    }
+}
+
+impl LuaUserData for LuaDatabase {
+    fn add_methods<'lua, M: LuaUserDataMethods<'lua, Self>>(methods: &mut M) {
+        methods.add_method("execute", |_, this, (sql, params): (String, SqlParams)| {
+            this.conn.with(|c| {
+                let mut stmt = c.prepare_cached(&sql).into_lua_err()?;
+                execute(&mut stmt, params)
+            })
+        });
+
+        methods.add_method("executeBatch", |_, this, sql: String| {
+            this.conn.with(|c| c.execute_batch(&sql).into_lua_err())
+        });
+
+        methods.add_method("query", |lua, this, (sql, params): (String, SqlParams)| {
+            this.conn.with(|c| {
+                let mut stmt = c.prepare_cached(&sql).into_lua_err()?;
+                query(lua, &mut stmt, params)
+            })
+        });
+
+        methods.add_method(
+            "queryRow",
+            |lua, this, (sql, params): (String, SqlParams)| {
+                this.conn.with(|c| {
+                    let mut stmt = c.prepare_cached(&sql).into_lua_err()?;
+                    query_row(lua, &mut stmt, params)
+                })
+            },
+        );
+
+        methods.add_method("prepare", |_, this, sql: String| {
+            LuaStatement::new(this.conn.clone(), sql)
+        });
+
+        methods.add_method("transaction", |_, this, callback: LuaFunction| {
+            this.conn
+                .with(|c| c.execute_batch(TRANSACTION_BEGIN).into_lua_err())?;
+            match callback.call::<_, LuaMultiValue>(()) {
+                Ok(values) => {
+                    this.conn
+                        .with(|c| c.execute_batch(TRANSACTION_COMMIT).into_lua_err())?;
+                    Ok(values)
+                }
+                Err(e) => {
+                    // NOTE: The callback error is more useful than any error from rolling
+                    // back, which may also fail if the callback closed the database
+                    let _ = this
+                        .conn
+                        .with(|c| c.execute_batch(TRANSACTION_ROLLBACK).into_lua_err());
+                    Err(e)
+                }
+            }
+        });
+
+        methods.add_method("lastInsertRowId", |_, this, ()| {
+            this.conn.with(|c| Ok(c.last_insert_rowid()))
+        });
+
+        methods.add_method("close", |_, this, ()| this.conn.close());
+    }
+
+    fn add_fields<'lua, F: LuaUserDataFields<'lua, Self>>(fields: &mut F) {
+        fields.add_meta_field(LuaMetaMethod::Type, "Database");
+    }
+}
@@ -0,0 +1,43 @@
+#![allow(clippy::cargo_common_metadata)]
+
+use mlua::prelude::*;
+
+use lune_utils::TableBuilder;
+
+mod database;
+mod options;
+mod statement;
+mod value;
+
+use self::database::LuaDatabase;
+use self::options::SqliteOpenOptions;
+
+/**
+    Creates the `sqlite` standard library module.
+
+    # Errors
+
+    Errors when out of memory.
+*/
+pub fn module(lua: &Lua) -> LuaResult<LuaTable> {
+    TableBuilder::new_named(lua, "sqlite")?
+        .with_function("open", sqlite_open)?
+        .build_readonly()
+}
+
+fn sqlite_open(
+    lua: &Lua,
+    (path, options): (Option<String>, SqliteOpenOptions),
+) -> LuaResult<LuaDatabase> {
+    let restricted = lune_std_fs::is_restricted(lua);
+    match path {
+        Some(path) if path != ":memory:" => {
+            lune_std_fs::check_read(lua, &path)?;
+            if !options.read_only {
+                lune_std_fs::check_write(lua, &path)?;
+            }
+            LuaDatabase::open(path, options, restricted)
+        }
+        _ => LuaDatabase::open_in_memory(restricted),
+    }
+}
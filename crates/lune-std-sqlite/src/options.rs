@@ -0,0 +1,30 @@
+use mlua::prelude::*;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SqliteOpenOptions {
+    pub(crate) read_only: bool,
+}
+
+impl<'lua> FromLua<'lua> for SqliteOpenOptions {
+    fn from_lua(value: LuaValue<'lua>, _: &'lua Lua) -> LuaResult<Self> {
+        Ok(match value {
+            LuaValue::Nil => Self::default(),
+            LuaValue::Table(t) => {
+                let read_only: Option<bool> = t.get("readOnly")?;
+                Self {
+                    read_only: read_only.unwrap_or(false),
+                }
+            }
+            _ => {
+                return Err(LuaError::FromLuaConversionError {
+                    from: value.type_name(),
+                    to: "SqliteOpenOptions",
+                    message: Some(format!(
+                        "Invalid open options - expected table, got {}",
+                        value.type_name()
+                    )),
+                })
+            }
+        })
+    }
+}
@@ -0,0 +1,116 @@
+use mlua::prelude::*;
+use rusqlite::Statement;
+
+use super::{
+    database::Connection,
+    value::{row_to_table, SqlParams},
+};
+
+/**
+    A prepared SQL statement, which can be executed many times with different parameters.
+
+    Prepared statements are kept in the statement cache of their database connection,
+    so that their SQL does not need to be compiled again every time they are executed.
+*/
+#[derive(Debug, Clone)]
+pub struct LuaStatement {
+    conn: Connection,
+    sql: String,
+}
+
+impl LuaStatement {
+    /**
+        Prepares a new statement for the given connection.
+
+        # Errors
+
+        Errors if the connection is closed, or if the SQL is invalid.
+    */
+    pub fn new(conn: Connection, sql: String) -> LuaResult<Self> {
+        conn.with(|c| c.prepare_cached(&sql).map(drop).into_lua_err())?;
+        Ok(Self { conn, sql })
+    }
+}
+
+impl LuaUserData for LuaStatement {
+    fn add_methods<'lua, M: LuaUserDataMethods<'lua, Self>>(methods: &mut M) {
+        methods.add_method("execute", |_, this, params: SqlParams| {
+            this.conn.with(|c| {
+                let mut stmt = c.prepare_cached(&this.sql).into_lua_err()?;
+                execute(&mut stmt, params)
+            })
+        });
+
+        methods.add_method("query", |lua, this, params: SqlParams| {
+            this.conn.with(|c| {
+                let mut stmt = c.prepare_cached(&this.sql).into_lua_err()?;
+                query(lua, &mut stmt, params)
+            })
+        });
+
+        methods.add_method("queryRow", |lua, this, params: SqlParams| {
+            this.conn.with(|c| {
+                let mut stmt = c.prepare_cached(&this.sql).into_lua_err()?;
+                query_row(lua, &mut stmt, params)
+            })
+        });
+
+        methods.add_meta_method(LuaMetaMethod::ToString, |_, this, ()| Ok(this.sql.clone()));
+    }
+
+    fn add_fields<'lua, F: LuaUserDataFields<'lua, Self>>(fields: &mut F) {
+        fields.add_meta_field(LuaMetaMethod::Type, "Statement");
+    }
+}
+
+/**
+    Executes the given statement, returning the number of rows that were changed.
+*/
+pub fn execute(stmt: &mut Statement, params: SqlParams) -> LuaResult<usize> {
+    params.bind(stmt)?;
+    stmt.raw_execute().into_lua_err()
+}
+
+/**
+    Runs the given statement, returning all of the resulting rows as tables.
+*/
+pub fn query<'lua>(
+    lua: &'lua Lua,
+    stmt: &mut Statement,
+    params: SqlParams,
+) -> LuaResult<LuaTable<'lua>> {
+    params.bind(stmt)?;
+    let columns = column_names(lua, stmt)?;
+
+    let table = lua.create_table()?;
+    let mut rows = stmt.raw_query();
+    while let Some(row) = rows.next().into_lua_err()? {
+        table.raw_push(row_to_table(lua, &columns, row)?)?;
+    }
+    Ok(table)
+}
+
+/**
+    Runs the given statement, returning only the first resulting row, if any.
+*/
+pub fn query_row<'lua>(
+    lua: &'lua Lua,
+    stmt: &mut Statement,
+    params: SqlParams,
+) -> LuaResult<Option<LuaTable<'lua>>> {
+    params.bind(stmt)?;
+    let columns = column_names(lua, stmt)?;
+
+    let mut rows = stmt.raw_query();
+    match rows.next().into_lua_err()? {
+        Some(row) => row_to_table(lua, &columns, row).map(Some),
+        None => Ok(None),
+    }
+}
+
+fn column_names<'lua>(lua: &'lua Lua, stmt: &Statement) -> LuaResult<Vec<LuaString<'lua>>> {
+    stmt.column_names()
+        .into_iter()
+        .map(|name| lua.create_string(name))
+        .collect()
+}
@@ -0,0 +1,218 @@
+use bstr::BString;
+use mlua::prelude::*;
+use rusqlite::{
+    types::{Value, ValueRef},
+    Row, Statement,
+};
+
+const NAMED_PARAMETER_PREFIXES: [char; 3] = [':', '@', '$'];
+
+/**
+    A value that can be bound as a parameter to an SQL statement.
+
+    Strings are bound as text if they are valid UTF-8, and as blobs otherwise.
+    Buffers are always bound as blobs, and booleans as the integers `0` or `1`.
+*/
+#[derive(Debug, Clone)]
+pub struct SqlValue(Value);
+
+impl<'lua> FromLua<'lua> for SqlValue {
+    fn from_lua(value: LuaValue<'lua>, lua: &'lua Lua) -> LuaResult<Self> {
+        Ok(Self(match value {
+            LuaValue::Nil => Value::Null,
+            LuaValue::Boolean(b) => Value::Integer(i64::from(b)),
+            LuaValue::Integer(i) => Value::Integer(i64::from(i)),
+            LuaValue::Number(n) => number_to_value(n),
+            LuaValue::String(s) => match s.to_str() {
+                Ok(s) => Value::Text(s.to_string()),
+                Err(_) => Value::Blob(s.as_bytes().to_vec()),
+            },
+            value if value.is_buffer() => Value::Blob(BString::from_lua(value, lua)?.into()),
+            value => {
+                return Err(LuaError::FromLuaConversionError {
+                    from: value.type_name(),
+                    to: "SqlValue",
+                    message: Some(format!(
+                        "Invalid parameter - {} values can not be bound",
+                        value.type_name()
+                    )),
+                })
+            }
+        }))
+    }
+}
+
+fn number_to_value(n: f64) -> Value {
+    // NOTE: Luau only has one number type, so whole numbers are bound as
+    // integers, which lets them be used for integer primary keys and such
+    if n.fract() == 0.0 && n >= i64::MIN as f64 && n < i64::MAX as f64 {
+        Value::Integer(n as i64)
+    } else {
+        Value::Real(n)
+    }
+}
+
+/**
+    Parameters to bind to an SQL statement, given either as an array
+    of positional parameters, or as a table of named parameters.
+*/
+#[derive(Debug, Clone, Default)]
+pub enum SqlParams {
+    #[default]
+    None,
+    Positional(Vec<SqlValue>),
+    Named(Vec<(String, SqlValue)>),
+}
+
+impl SqlParams {
+    /**
+        Binds these parameters to the given statement.
+
+        Positional parameters that were not given are bound as `NULL`, since
+        there is no way to tell a trailing `nil` apart from a missing value.
+
+        # Errors
+
+        Errors if there are more positional parameters than the
+        statement accepts, or if a named parameter does not exist.
+    */
+    pub fn bind(self, stmt: &mut Statement) -> LuaResult<()> {
+        match self {
+            Self::None => {}
+            Self::Positional(values) => {
+                let count = stmt.parameter_count();
+                if values.len() > count {
+                    return Err(LuaError::runtime(format!(
+                        "Statement accepts {count} parameter(s), but {} were given",
+                        values.len()
+                    )));
+                }
+                for (index, value) in values.into_iter().enumerate() {
+                    stmt.raw_bind_parameter(index + 1, value.0).into_lua_err()?;
+                }
+            }
+            Self::Named(values) => {
+                for (name, value) in values {
+                    let index = named_parameter_index(stmt, &name)?.ok_or_else(|| {
+                        LuaError::runtime(format!("Statement has no parameter named '{name}'"))
+                    })?;
+                    stmt.raw_bind_parameter(index, value.0).into_lua_err()?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'lua> FromLua<'lua> for SqlParams {
+    fn from_lua(value: LuaValue<'lua>, lua: &'lua Lua) -> LuaResult<Self> {
+        let table = match value {
+            LuaValue::Nil => return Ok(Self::None),
+            LuaValue::Table(t) => t,
+            value => {
+                return Err(LuaError::FromLuaConversionError {
+                    from: value.type_name(),
+                    to: "SqlParams",
+                    message: Some(format!(
+                        "Invalid parameters - expected table, got {}",
+                        value.type_name()
+                    )),
+                })
+            }
+        };
+
+        let mut positional = Vec::new();
+        let mut named = Vec::new();
+        for pair in table.clone().pairs::<LuaValue, LuaValue>() {
+            let (key, value) = pair?;
+            match key {
+                LuaValue::String(s) => named.push((s.to_str()?.to_string(), value)),
+                LuaValue::Integer(_) | LuaValue::Number(_) => positional.push(value),
+                key => {
+                    return Err(LuaError::runtime(format!(
+                        "Invalid parameters - expected string or number keys, got {}",
+                        key.type_name()
+                    )))
+                }
+            }
+        }
+
+        match (positional.is_empty(), named.is_empty()) {
+            (true, true) => Ok(Self::None),
+            (false, true) => {
+                // NOTE: Iteration order of pairs is not guaranteed to be sequential,
+                // so we read the array part of the table again, in order, here
+                let values = table
+                    .sequence_values::<LuaValue>()
+                    .map(|value| SqlValue::from_lua(value?, lua))
+                    .collect::<LuaResult<Vec<_>>>()?;
+                if values.len() != positional.len() {
+                    return Err(LuaError::runtime(
+                        "Invalid parameters - positional parameters must be an array",
+                    ));
+                }
+                Ok(Self::Positional(values))
+            }
+            (true, false) => Ok(Self::Named(
+                named
+                    .into_iter()
+                    .map(|(name, value)| Ok((name, SqlValue::from_lua(value, lua)?)))
+                    .collect::<LuaResult<Vec<_>>>()?,
+            )),
+            (false, false) => Err(LuaError::runtime(
+                "Invalid parameters - can not mix positional and named parameters",
+            )),
+        }
+    }
+}
+
+/**
+    Finds the index of a named parameter, which may be given either
+    with its prefix (`:name`, `@name`, `$name`) or without it (`name`).
+*/
+fn named_parameter_index(stmt: &Statement, name: &str) -> LuaResult<Option<usize>> {
+    if name.starts_with(NAMED_PARAMETER_PREFIXES) {
+        return stmt.parameter_index(name).into_lua_err();
+    }
+    for prefix in NAMED_PARAMETER_PREFIXES {
+        if let Some(index) = stmt
+            .parameter_index(&format!("{prefix}{name}"))
+            .into_lua_err()?
+        {
+            return Ok(Some(index));
+        }
+    }
+    Ok(None)
+}
+
+/**
+    Converts a single SQL value into a Lua value.
+
+    Integers and reals become numbers, text becomes a string,
+    blobs become buffers, and `NULL` becomes `nil`.
+*/
+fn value_to_lua<'lua>(lua: &'lua Lua, value: ValueRef) -> LuaResult<LuaValue<'lua>> {
+    match value {
+        ValueRef::Null => Ok(LuaValue::Nil),
+        ValueRef::Integer(i) => i.into_lua(lua),
+        ValueRef::Real(n) => Ok(LuaValue::Number(n)),
+        ValueRef::Text(s) => lua.create_string(s)?.into_lua(lua),
+        ValueRef::Blob(b) => lua.create_buffer(b)?.into_lua(lua),
+    }
+}
+
+/**
+    Converts a row into a table, keyed by the given column names.
+*/
+pub fn row_to_table<'lua>(
+    lua: &'lua Lua,
+    columns: &[LuaString<'lua>],
+    row: &Row,
+) -> LuaResult<LuaTable<'lua>> {
+    let table = lua.create_table_with_capacity(0, columns.len())?;
+    for (index, column) in columns.iter().enumerate() {
+        let value = row.get_ref(index).into_lua_err()?;
+        table.raw_set(column.clone(), value_to_lua(lua, value)?)?;
+    }
+    Ok(table)
+}
@@ -0,0 +1,39 @@
+use mlua::prelude::*;
+
+use lune_utils::fmt::ValueFormatConfig;
+
+pub const DEFAULT_FORMAT_DEPTH: usize = 4;
+
+/**
+    Options for formatting values using `stdio.formatWith`.
+*/
+#[derive(Debug, Clone, Copy)]
+pub struct FormatOptions {
+    pub colors: bool,
+    pub depth: usize,
+}
+
+impl FormatOptions {
+    pub fn to_config(self) -> ValueFormatConfig {
+        ValueFormatConfig::new()
+            .with_max_depth(self.depth)
+            .with_colors_enabled(self.colors)
+    }
+}
+
+impl<'lua> FromLua<'lua> for FormatOptions {
+    fn from_lua(value: LuaValue<'lua>, _: &'lua Lua) -> LuaResult<Self> {
+        let LuaValue::Table(options) = value else {
+            return Err(LuaError::FromLuaConversionError {
+                from: value.type_name(),
+                to: "FormatOptions",
+                message: Some("Format options must be a table".to_string()),
+            });
+        };
+        let colors = options.get::<_, Option<bool>>("colors")?.unwrap_or(false);
+        let depth = options
+            .get::<_, Option<usize>>("depth")?
+            .unwrap_or(DEFAULT_FORMAT_DEPTH);
+        Ok(Self { colors, depth })
+    }
+}
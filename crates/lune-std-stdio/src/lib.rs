@@ -4,18 +4,38 @@ use lune_utils::fmt::{pretty_format_multi_value, ValueFormatConfig};
 use mlua::prelude::*;
 use mlua_luau_scheduler::LuaSpawnExt;
 
-use tokio::io::{stderr, stdin, stdout, AsyncReadExt, AsyncWriteExt};
+use once_cell::sync::Lazy;
+use tokio::{
+    io::{stderr, stdin, stdout, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader, Stdin},
+    sync::Mutex as AsyncMutex,
+};
 
-use lune_utils::TableBuilder;
+use lune_utils::{CapturedOutput, TableBuilder};
 
+mod format;
 mod prompt;
 mod style_and_color;
 
+use self::format::{FormatOptions, DEFAULT_FORMAT_DEPTH};
 use self::prompt::{prompt, PromptOptions, PromptResult};
 use self::style_and_color::{ColorKind, StyleKind};
 
+const DEFAULT_READ_SIZE: usize = 8192;
+
+// NOTE: A single read never returns more than this, so that scripts can not
+// make us allocate an arbitrarily large buffer by passing in a huge count
+const MAX_READ_SIZE: usize = 64 * 1024;
+
+/*
+    NOTE: All reads from stdin go through this single buffered reader, this
+    lets scripts mix and match readLine, read, and readToEnd without losing
+    any input that was buffered by a previous call to a different function.
+*/
+static STDIN: Lazy<AsyncMutex<BufReader<Stdin>>> =
+    Lazy::new(|| AsyncMutex::new(BufReader::new(stdin())));
+
 const FORMAT_CONFIG: ValueFormatConfig = ValueFormatConfig::new()
-    .with_max_depth(4)
+    .with_max_depth(DEFAULT_FORMAT_DEPTH)
     .with_colors_enabled(false);
 
 /**
@@ -26,12 +46,15 @@ const FORMAT_CONFIG: ValueFormatConfig = ValueFormatConfig::new()
     Errors when out of memory.
 */
 pub fn module(lua: &Lua) -> LuaResult<LuaTable> {
-    TableBuilder::new(lua)?
+    TableBuilder::new_named(lua, "stdio")?
         .with_function("color", stdio_color)?
         .with_function("style", stdio_style)?
         .with_function("format", stdio_format)?
+        .with_function("formatWith", stdio_format_with)?
         .with_async_function("write", stdio_write)?
         .with_async_function("ewrite", stdio_ewrite)?
+        .with_async_function("read", stdio_read)?
+        .with_async_function("readLine", stdio_read_line)?
         .with_async_function("readToEnd", stdio_read_to_end)?
         .with_async_function("prompt", stdio_prompt)?
         .build_readonly()
@@ -49,32 +72,63 @@ fn stdio_format(_: &Lua, args: LuaMultiValue) -> LuaResult<String> {
     Ok(pretty_format_multi_value(&args, &FORMAT_CONFIG))
 }
 
-async fn stdio_write(_: &Lua, s: LuaString<'_>) -> LuaResult<()> {
+fn stdio_format_with<'lua>(
+    _: &'lua Lua,
+    (options, args): (FormatOptions, LuaMultiValue<'lua>),
+) -> LuaResult<String> {
+    Ok(pretty_format_multi_value(&args, &options.to_config()))
+}
+
+async fn stdio_write(lua: &Lua, s: LuaString<'_>) -> LuaResult<()> {
+    if let Some(output) = CapturedOutput::get(lua) {
+        output.write_stdout(s.as_bytes());
+        return Ok(());
+    }
     let mut stdout = stdout();
     stdout.write_all(s.as_bytes()).await?;
     stdout.flush().await?;
     Ok(())
 }
 
-async fn stdio_ewrite(_: &Lua, s: LuaString<'_>) -> LuaResult<()> {
+async fn stdio_ewrite(lua: &Lua, s: LuaString<'_>) -> LuaResult<()> {
+    if let Some(output) = CapturedOutput::get(lua) {
+        output.write_stderr(s.as_bytes());
+        return Ok(());
+    }
     let mut stderr = stderr();
     stderr.write_all(s.as_bytes()).await?;
     stderr.flush().await?;
     Ok(())
 }
 
-/*
-    FUTURE: Figure out how to expose some kind of "readLine" function using a buffered reader.
+async fn stdio_read(lua: &Lua, count: Option<usize>) -> LuaResult<Option<LuaString>> {
+    let mut input = vec![0; count.unwrap_or(DEFAULT_READ_SIZE).min(MAX_READ_SIZE)];
+    let read = STDIN.lock().await.read(&mut input).await?;
+    if read == 0 && !input.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(lua.create_string(&input[..read])?))
+    }
+}
 
-    This is a bit tricky since we would want to be able to use **both** readLine and readToEnd
-    in the same script, doing something like readLine, readLine, readToEnd from lua, and
-    having that capture the first two lines and then read the rest of the input.
-*/
+async fn stdio_read_line(lua: &Lua, (): ()) -> LuaResult<Option<LuaString>> {
+    let mut input = Vec::new();
+    let read = STDIN.lock().await.read_until(b'\n', &mut input).await?;
+    if read == 0 {
+        return Ok(None);
+    }
+    if input.ends_with(b"\n") {
+        input.pop();
+        if input.ends_with(b"\r") {
+            input.pop();
+        }
+    }
+    Ok(Some(lua.create_string(&input)?))
+}
 
 async fn stdio_read_to_end(lua: &Lua, (): ()) -> LuaResult<LuaString> {
     let mut input = Vec::new();
-    let mut stdin = stdin();
-    stdin.read_to_end(&mut input).await?;
+    STDIN.lock().await.read_to_end(&mut input).await?;
     lua.create_string(&input)
 }
 
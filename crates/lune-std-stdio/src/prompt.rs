@@ -1,4 +1,8 @@
-use std::{fmt, str::FromStr};
+use std::{
+    fmt,
+    io::{stderr, stdin, BufRead, IsTerminal, Write},
+    str::FromStr,
+};
 
 use dialoguer::{theme::ColorfulTheme, Confirm, Input, MultiSelect, Select};
 use mlua::prelude::*;
@@ -177,6 +181,11 @@ impl<'lua> IntoLua<'lua> for PromptResult {
 }
 
 pub fn prompt(options: PromptOptions) -> LuaResult<PromptResult> {
+    // NOTE: Interactive prompts need a terminal, so when input is piped
+    // or redirected we fall back to reading plain lines from stdin instead
+    if !stdin().is_terminal() || !stderr().is_terminal() {
+        return prompt_plain(options);
+    }
     let theme = ColorfulTheme::default();
     match options.kind {
         PromptKind::Text => {
@@ -225,3 +234,84 @@ pub fn prompt(options: PromptOptions) -> LuaResult<PromptResult> {
         }
     }
 }
+
+/**
+    Prompts for user input without using any interactive terminal features.
+
+    The prompt message is written to stderr, and a single line is then read
+    from stdin, with an empty line or the end of input choosing the default.
+    Options for selection prompts may be chosen using either their
+    numbers or their text, with multiple options separated by commas.
+*/
+fn prompt_plain(options: PromptOptions) -> LuaResult<PromptResult> {
+    let mut message = options.text.clone().unwrap_or_default();
+    match (&options.kind, &options.options) {
+        (PromptKind::Confirm, _) => message.push_str(match options.default_bool {
+            Some(true) => " [Y/n]",
+            Some(false) => " [y/N]",
+            None => " [y/n]",
+        }),
+        (PromptKind::Select | PromptKind::MultiSelect, Some(items)) => {
+            use std::fmt::Write as _;
+            for (index, item) in items.iter().enumerate() {
+                write!(message, "\n  {}) {item}", index + 1).ok();
+            }
+            message.push('\n');
+        }
+        _ => {}
+    }
+
+    let mut err = stderr().lock();
+    write!(err, "{message}: ")?;
+    err.flush()?;
+
+    let mut line = String::new();
+    stdin().lock().read_line(&mut line)?;
+    writeln!(err)?;
+
+    parse_plain_answer(&options, line.trim()).map_err(LuaError::runtime)
+}
+
+fn parse_plain_answer(options: &PromptOptions, answer: &str) -> Result<PromptResult, String> {
+    match options.kind {
+        PromptKind::Text => Ok(PromptResult::String(if answer.is_empty() {
+            options.default_string.clone().unwrap_or_default()
+        } else {
+            answer.to_string()
+        })),
+        PromptKind::Confirm => match answer.to_ascii_lowercase().as_str() {
+            "" => options
+                .default_bool
+                .map(PromptResult::Boolean)
+                .ok_or_else(|| "Expected an answer of 'y' or 'n'".to_string()),
+            "y" | "yes" => Ok(PromptResult::Boolean(true)),
+            "n" | "no" => Ok(PromptResult::Boolean(false)),
+            _ => Err(format!("Invalid answer '{answer}', expected 'y' or 'n'")),
+        },
+        PromptKind::Select => {
+            if answer.is_empty() {
+                return Ok(PromptResult::None);
+            }
+            find_plain_option(options, answer).map(PromptResult::Index)
+        }
+        PromptKind::MultiSelect => answer
+            .split(',')
+            .map(str::trim)
+            .filter(|choice| !choice.is_empty())
+            .map(|choice| find_plain_option(options, choice))
+            .collect::<Result<_, _>>()
+            .map(PromptResult::Indices),
+    }
+}
+
+fn find_plain_option(options: &PromptOptions, choice: &str) -> Result<usize, String> {
+    let items = options.options.as_deref().unwrap_or_default();
+    let index = match choice.parse::<usize>() {
+        Ok(number) => (1..=items.len()).contains(&number).then_some(number),
+        Err(_) => items
+            .iter()
+            .position(|item| item == choice)
+            .map(|index| index + 1),
+    };
+    index.ok_or_else(|| format!("Invalid option '{choice}'"))
+}
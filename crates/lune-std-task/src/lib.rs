@@ -5,10 +5,16 @@ use std::time::Duration;
 use mlua::prelude::*;
 use mlua_luau_scheduler::Functions;
 
-use tokio::time::{sleep, Instant};
+use tokio::time::Instant;
 
 use lune_utils::TableBuilder;
 
+mod timer;
+
+use self::timer::sleep_until_deadline;
+
+pub use self::timer::{set_timer_resolution, TimerResolution};
+
 /**
     Creates the `task` standard library module.
 
@@ -42,6 +48,10 @@ pub fn module(lua: &Lua) -> LuaResult<LuaTable> {
         .build_readonly()
 }
 
+// NOTE: Adding too large of a duration to an instant will overflow and panic,
+// so we use a deadline far enough into the future instead, same as tokio does
+const FOREVER: Duration = Duration::from_hours(24 * 365 * 30);
+
 const DELAY_IMPL_LUA: &str = r"
 return defer(function(...)
     wait(select(1, ...))
@@ -49,12 +59,26 @@ return defer(function(...)
 end, ...)
 ";
 
-async fn wait(_: &Lua, secs: Option<f64>) -> LuaResult<f64> {
-    let duration = Duration::from_secs_f64(secs.unwrap_or_default());
+async fn wait(lua: &Lua, secs: Option<f64>) -> LuaResult<f64> {
+    let resolution = TimerResolution::get(lua);
 
     let before = Instant::now();
-    sleep(duration).await;
+    sleep_until_deadline(wait_deadline(before, secs), resolution).await;
     let after = Instant::now();
 
     Ok((after - before).as_secs_f64())
 }
+
+/**
+    Gets the deadline for waiting the given amount of seconds, starting at the given instant.
+
+    Matches Roblox in that negative and NaN durations wait for as little time as possible,
+    instead of erroring, and that infinite durations wait for practically forever.
+*/
+fn wait_deadline(start: Instant, secs: Option<f64>) -> Instant {
+    let secs = secs.filter(|secs| *secs > 0.0).unwrap_or_default();
+    Duration::try_from_secs_f64(secs)
+        .ok()
+        .and_then(|duration| start.checked_add(duration))
+        .unwrap_or_else(|| start + FOREVER)
+}
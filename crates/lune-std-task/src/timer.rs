@@ -0,0 +1,204 @@
+use std::{env, fmt, str::FromStr, time::Duration};
+
+use futures_lite::future::yield_now;
+use mlua::prelude::*;
+use tokio::time::{sleep_until, Instant};
+
+/**
+    Environment variable that may be set to change the default timer resolution.
+*/
+const RESOLUTION_ENV_VAR: &str = "LUNE_TIMER_RESOLUTION";
+
+/**
+    The tick of the underlying timer, which wakes up sleeping tasks
+    at most once per tick, and may wake them up to one tick late.
+*/
+const TIMER_TICK: Duration = Duration::from_millis(1);
+
+/**
+    The resolution of the timer used by `task.wait` and `task.delay`.
+
+    Waits always last for at least the requested amount of time, and the resolution
+    is how much longer than that they may last, not counting time spent running
+    other Lua threads. The default resolution of one millisecond is the minimum
+    resolution of the underlying timer, and is reached without using any extra CPU.
+
+    Resolutions finer than one millisecond are reached by sleeping until just before
+    the end of each wait, and then repeatedly yielding to the scheduler until the
+    wait is over, which trades CPU time for timer precision.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimerResolution(Duration);
+
+impl TimerResolution {
+    /**
+        Creates a new timer resolution from the given duration.
+    */
+    #[must_use]
+    pub const fn new(duration: Duration) -> Self {
+        Self(duration)
+    }
+
+    /**
+        Gets the duration of the timer resolution.
+    */
+    #[must_use]
+    pub const fn as_duration(self) -> Duration {
+        self.0
+    }
+
+    fn from_env() -> Self {
+        env::var(RESOLUTION_ENV_VAR)
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn get(lua: &Lua) -> Self {
+        lua.app_data_ref::<Self>()
+            .map_or_else(Self::from_env, |resolution| *resolution)
+    }
+
+    fn is_precise(self) -> bool {
+        self.0 < TIMER_TICK
+    }
+}
+
+impl Default for TimerResolution {
+    fn default() -> Self {
+        Self(TIMER_TICK)
+    }
+}
+
+impl fmt::Display for TimerResolution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}us", self.0.as_micros())
+    }
+}
+
+impl FromStr for TimerResolution {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let split = s
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(s.len());
+        let (number, unit) = s.split_at(split);
+
+        let number = number.parse::<f64>().map_err(|_| {
+            format!("Invalid timer resolution '{s}', expected a value such as `1ms`")
+        })?;
+        let seconds = match unit.trim().to_ascii_lowercase().as_str() {
+            "us" | "µs" => number / 1_000_000.0,
+            "" | "ms" => number / 1_000.0,
+            "s" => number,
+            _ => {
+                return Err(format!(
+                    "Invalid timer resolution unit '{unit}', expected one of us, ms, s"
+                ))
+            }
+        };
+
+        Duration::try_from_secs_f64(seconds)
+            .map(Self)
+            .map_err(|_| format!("Invalid timer resolution '{s}'"))
+    }
+}
+
+/**
+    Sets the resolution of the timer used by `task.wait` and `task.delay`.
+
+    This overrides the `LUNE_TIMER_RESOLUTION` environment variable.
+*/
+pub fn set_timer_resolution(lua: &Lua, resolution: TimerResolution) {
+    lua.set_app_data(resolution);
+}
+
+/**
+    Sleeps until the given deadline, using the given timer resolution.
+
+    Always yields to the scheduler at least once, even if the deadline has already passed.
+*/
+pub(crate) async fn sleep_until_deadline(deadline: Instant, resolution: TimerResolution) {
+    if !resolution.is_precise() {
+        sleep_until(deadline).await;
+        return;
+    }
+
+    // NOTE: The timer may wake us up to one tick late, and rounds the
+    // deadline up to the next tick, so we stop sleeping two ticks early
+    // and yield until the actual deadline has passed for the remainder
+    if let Some(coarse) = deadline.checked_sub(TIMER_TICK * 2) {
+        if coarse > Instant::now() {
+            sleep_until(coarse).await;
+        }
+    }
+
+    yield_now().await;
+    while Instant::now() < deadline {
+        yield_now().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Result<Duration, String> {
+        s.parse::<TimerResolution>()
+            .map(TimerResolution::as_duration)
+    }
+
+    #[test]
+    fn resolutions_parse_units() {
+        assert_eq!(parse("2"), Ok(Duration::from_millis(2)));
+        assert_eq!(parse("2ms"), Ok(Duration::from_millis(2)));
+        assert_eq!(parse("0.5ms"), Ok(Duration::from_micros(500)));
+        assert_eq!(parse("100us"), Ok(Duration::from_micros(100)));
+        assert_eq!(parse("100µs"), Ok(Duration::from_micros(100)));
+        assert_eq!(parse("100US"), Ok(Duration::from_micros(100)));
+        assert_eq!(parse("1s"), Ok(Duration::from_secs(1)));
+        assert_eq!(parse("0"), Ok(Duration::ZERO));
+    }
+
+    #[test]
+    fn resolutions_allow_whitespace() {
+        assert_eq!(parse(" 250us "), Ok(Duration::from_micros(250)));
+        assert_eq!(parse("250 us"), Ok(Duration::from_micros(250)));
+        assert_eq!(parse("\t1 ms\n"), Ok(Duration::from_millis(1)));
+    }
+
+    #[test]
+    fn resolutions_reject_invalid_input() {
+        assert!(parse("").is_err());
+        assert!(parse("ms").is_err());
+        assert!(parse("-1ms").is_err());
+        assert!(parse("1.2.3ms").is_err());
+        assert!(parse("1 ms extra").is_err());
+        assert!(parse("5m").unwrap_err().contains("unit"));
+        assert!(parse("5ns").unwrap_err().contains("unit"));
+        assert!(parse("inf").is_err());
+    }
+
+    #[test]
+    fn resolutions_reject_overflow() {
+        assert!(parse(&format!("{}s", u64::MAX)).is_err());
+        assert!(parse(&format!("{}00000ms", u64::MAX)).is_err());
+        assert!(parse(&"9".repeat(400)).is_err());
+    }
+
+    #[test]
+    fn resolutions_roundtrip_through_strings() {
+        for resolution in ["1ms", "50us", "2s"] {
+            let parsed = resolution.parse::<TimerResolution>().unwrap();
+            assert_eq!(parsed.to_string().parse(), Ok(parsed));
+        }
+    }
+
+    #[test]
+    fn resolutions_below_one_tick_are_precise() {
+        assert!(!TimerResolution::default().is_precise());
+        assert!(TimerResolution::new(Duration::from_micros(999)).is_precise());
+        assert!(!TimerResolution::new(Duration::from_secs(1)).is_precise());
+    }
+}
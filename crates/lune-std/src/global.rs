@@ -7,6 +7,8 @@ use mlua::prelude::*;
 */
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
 pub enum LuneStandardGlobal {
+    CollectGarbage,
+    Error,
     GTable,
     Print,
     Require,
@@ -19,6 +21,8 @@ impl LuneStandardGlobal {
         All available standard globals.
     */
     pub const ALL: &'static [Self] = &[
+        Self::CollectGarbage,
+        Self::Error,
         Self::GTable,
         Self::Print,
         Self::Require,
@@ -32,6 +36,8 @@ impl LuneStandardGlobal {
     #[must_use]
     pub fn name(&self) -> &'static str {
         match self {
+            Self::CollectGarbage => "collectgarbage",
+            Self::Error => "Error",
             Self::GTable => "_G",
             Self::Print => "print",
             Self::Require => "require",
@@ -51,6 +57,8 @@ impl LuneStandardGlobal {
     #[allow(unreachable_patterns)]
     pub fn create<'lua>(&self, lua: &'lua Lua) -> LuaResult<LuaValue<'lua>> {
         let res = match self {
+            Self::CollectGarbage => crate::globals::collect_garbage::create(lua),
+            Self::Error => crate::globals::error::create(lua),
             Self::GTable => crate::globals::g_table::create(lua),
             Self::Print => crate::globals::print::create(lua),
             Self::Require => crate::globals::require::create(lua),
@@ -72,6 +80,8 @@ impl FromStr for LuneStandardGlobal {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let low = s.trim().to_ascii_lowercase();
         Ok(match low.as_str() {
+            "collectgarbage" => Self::CollectGarbage,
+            "error" => Self::Error,
             "_g" => Self::GTable,
             "print" => Self::Print,
             "require" => Self::Require,
@@ -0,0 +1,101 @@
+use mlua::prelude::*;
+
+use lune_utils::TableBuilder;
+
+/**
+    Default step size of the Luau garbage collector, in kilobytes.
+
+    Matches `LUAI_GCSTEPSIZE` in the Luau configuration.
+*/
+const DEFAULT_STEP_SIZE_KB: i32 = 1;
+
+/**
+    The most recently set step size for the garbage collector.
+
+    Luau does not let us read this value back without also changing it,
+    so we keep track of it ourselves to be able to return previous values.
+*/
+#[derive(Debug, Clone, Copy)]
+struct StepSize(i32);
+
+pub fn create(lua: &Lua) -> LuaResult<LuaValue> {
+    let f = lua.create_function(|lua, (opt, arg): (Option<String>, Option<i32>)| {
+        let opt = opt.unwrap_or_else(|| String::from("collect"));
+        match opt.as_str() {
+            "collect" => {
+                lua.gc_collect()?;
+                0.into_lua(lua)
+            }
+            "count" => count_kbytes(lua).into_lua(lua),
+            "step" => lua.gc_step_kbytes(arg.unwrap_or_default())?.into_lua(lua),
+            "stop" => {
+                lua.gc_stop();
+                0.into_lua(lua)
+            }
+            "restart" => {
+                lua.gc_restart();
+                0.into_lua(lua)
+            }
+            "isrunning" => lua.gc_is_running().into_lua(lua),
+            "setpause" | "setgoal" => {
+                let value = expect_positive(&opt, arg)?;
+                lua.gc_set_pause(value).into_lua(lua)
+            }
+            "setstepmul" => {
+                let value = expect_positive(&opt, arg)?;
+                lua.gc_set_step_multiplier(value).into_lua(lua)
+            }
+            "setstepsize" => {
+                let value = expect_positive(&opt, arg)?;
+                let previous = current_step_size(lua);
+                lua.gc_inc(0, 0, value);
+                lua.set_app_data(StepSize(value));
+                previous.into_lua(lua)
+            }
+            "stats" => create_stats(lua)?.into_lua(lua),
+            _ => Err(LuaError::runtime(format!(
+                "collectgarbage called with invalid option '{opt}'\
+                \nValid options are: collect, count, step, stop, restart, isrunning, \
+                setpause, setstepmul, setstepsize, stats"
+            ))),
+        }
+    })?;
+    f.into_lua(lua)
+}
+
+fn create_stats(lua: &Lua) -> LuaResult<LuaTable> {
+    // NOTE: Setting the pause and step multiplier returns
+    // the previous value, which we immediately restore here
+    let pause = lua.gc_set_pause(0);
+    lua.gc_set_pause(pause);
+    let step_multiplier = lua.gc_set_step_multiplier(0);
+    lua.gc_set_step_multiplier(step_multiplier);
+
+    TableBuilder::new(lua)?
+        .with_value("heapSize", lua.used_memory())?
+        .with_value("heapSizeKb", count_kbytes(lua))?
+        .with_value("isRunning", lua.gc_is_running())?
+        .with_value("pause", pause)?
+        .with_value("stepMultiplier", step_multiplier)?
+        .with_value("stepSize", current_step_size(lua))?
+        .build_readonly()
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn count_kbytes(lua: &Lua) -> f64 {
+    lua.used_memory() as f64 / 1024.0
+}
+
+fn current_step_size(lua: &Lua) -> i32 {
+    lua.app_data_ref::<StepSize>()
+        .map_or(DEFAULT_STEP_SIZE_KB, |size| size.0)
+}
+
+fn expect_positive(opt: &str, arg: Option<i32>) -> LuaResult<i32> {
+    match arg {
+        Some(value) if value > 0 => Ok(value),
+        _ => Err(LuaError::runtime(format!(
+            "collectgarbage option '{opt}' requires a positive integer argument"
+        ))),
+    }
+}
@@ -0,0 +1,279 @@
+use std::fmt::Write as _;
+
+use mlua::prelude::*;
+
+use lune_utils::{
+    error_code,
+    fmt::{ErrorComponents, StackTrace},
+    TableBuilder,
+};
+
+/**
+    Maximum number of causes to follow when formatting
+    or searching an error chain, to prevent runaway recursion.
+*/
+const MAX_CAUSE_DEPTH: usize = 32;
+
+const RAISE_WRAPPER_KEY: &str = "__lune_error_raise_wrapper";
+
+/**
+    Wraps a builtin function so that any error it raises is
+    converted into an error object before reaching the script.
+
+    This needs to be a Luau function, since Rust functions can only
+    raise opaque errors, and so that async functions can still yield.
+*/
+const RAISE_WRAPPER_SOURCE: &str = r"
+local pcall, error, convert = pcall, error, ...
+
+local function rethrow(ok, ...)
+    if ok then
+        return ...
+    end
+    error(convert((...)), 0)
+end
+
+return function(f)
+    return function(...)
+        return rethrow(pcall(f, ...))
+    end
+end
+";
+
+/**
+    A rich error object, with a message, an optional code,
+    an optional cause, and the stack where it was created.
+*/
+pub struct ErrorObject {
+    message: String,
+    code: Option<String>,
+    cause: Option<LuaRegistryKey>,
+    stack: String,
+}
+
+impl ErrorObject {
+    fn new(
+        lua: &Lua,
+        message: String,
+        code: Option<String>,
+        cause: Option<LuaValue>,
+        stack: StackTrace,
+    ) -> LuaResult<Self> {
+        let cause = match cause {
+            None | Some(LuaValue::Nil) => None,
+            Some(value) => Some(lua.create_registry_value(into_cause(lua, value)?)?),
+        };
+        let stack = stack
+            .lines()
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("\n");
+        Ok(Self {
+            message,
+            code,
+            cause,
+            stack,
+        })
+    }
+
+    /**
+        Creates an error object from an opaque error raised by a builtin,
+        keeping its code and the stack trace from where it was raised.
+    */
+    fn from_lua_error(lua: &Lua, error: &LuaError) -> LuaResult<Self> {
+        let code = error_code(error);
+        let components = ErrorComponents::from(error.clone());
+        let message = components.messages().join("\n");
+        let stack = components.trace().cloned().unwrap_or_default();
+        Self::new(lua, message, code, None, stack)
+    }
+
+    fn cause<'lua>(&self, lua: &'lua Lua) -> LuaResult<LuaValue<'lua>> {
+        match &self.cause {
+            Some(key) => lua.registry_value(key),
+            None => Ok(LuaValue::Nil),
+        }
+    }
+
+    fn format_chain(&self, lua: &Lua) -> LuaResult<String> {
+        let mut formatted = self.to_string();
+        let mut cause = self.cause(lua)?;
+        for _ in 0..MAX_CAUSE_DEPTH {
+            let next = match &cause {
+                LuaValue::Nil => break,
+                LuaValue::UserData(ud) if ud.is::<Self>() => {
+                    let inner = ud.borrow::<Self>()?;
+                    write!(formatted, "\nCaused by: {inner}").ok();
+                    inner.cause(lua)?
+                }
+                value => {
+                    let s = value.to_string()?;
+                    write!(formatted, "\nCaused by: {s}").ok();
+                    break;
+                }
+            };
+            cause = next;
+        }
+        Ok(formatted)
+    }
+
+    fn chain_has_code(&self, lua: &Lua, code: &str) -> LuaResult<bool> {
+        if self.code.as_deref() == Some(code) {
+            return Ok(true);
+        }
+        let mut cause = self.cause(lua)?;
+        for _ in 0..MAX_CAUSE_DEPTH {
+            let LuaValue::UserData(ud) = &cause else {
+                break;
+            };
+            let Ok(inner) = ud.borrow::<Self>() else {
+                break;
+            };
+            if inner.code.as_deref() == Some(code) {
+                return Ok(true);
+            }
+            let next = inner.cause(lua)?;
+            drop(inner);
+            cause = next;
+        }
+        Ok(false)
+    }
+}
+
+impl std::fmt::Display for ErrorObject {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.code {
+            Some(code) => write!(f, "[{code}] {}", self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl LuaUserData for ErrorObject {
+    fn add_fields<'lua, F: LuaUserDataFields<'lua, Self>>(fields: &mut F) {
+        fields.add_field_method_get("message", |_, this| Ok(this.message.clone()));
+        fields.add_field_method_get("code", |_, this| Ok(this.code.clone()));
+        fields.add_field_method_get("cause", |lua, this| this.cause(lua));
+        fields.add_field_method_get("stack", |_, this| Ok(this.stack.clone()));
+
+        fields.add_meta_field(LuaMetaMethod::Type, "Error");
+    }
+
+    fn add_methods<'lua, M: LuaUserDataMethods<'lua, Self>>(methods: &mut M) {
+        methods.add_meta_method(LuaMetaMethod::ToString, |lua, this, ()| {
+            this.format_chain(lua)
+        });
+    }
+}
+
+/**
+    Converts a value into a cause for an error object.
+
+    Opaque errors raised by builtins are converted into error
+    objects so that their messages and codes can be inspected,
+    any other values are kept as they are.
+*/
+fn into_cause<'lua>(lua: &'lua Lua, value: LuaValue<'lua>) -> LuaResult<LuaValue<'lua>> {
+    match value {
+        LuaValue::Error(e) => ErrorObject::from_lua_error(lua, &e)?.into_lua(lua),
+        value => Ok(value),
+    }
+}
+
+/**
+    Makes all functions in the given standard library module raise error
+    objects instead of opaque errors, so that scripts can inspect them
+    using the `Error` global, without having to match on their messages.
+*/
+pub(crate) fn raise_error_objects(lua: &Lua, module: &LuaTable) -> LuaResult<()> {
+    let wrapper = match lua.named_registry_value::<Option<LuaFunction>>(RAISE_WRAPPER_KEY)? {
+        Some(wrapper) => wrapper,
+        None => {
+            let wrapper: LuaFunction = lua
+                .load(RAISE_WRAPPER_SOURCE)
+                .set_name("__lune_error")
+                .call(lua.create_function(into_cause)?)?;
+            lua.set_named_registry_value(RAISE_WRAPPER_KEY, wrapper.clone())?;
+            wrapper
+        }
+    };
+
+    let functions = module
+        .clone()
+        .pairs::<LuaValue, LuaValue>()
+        .filter_map(|pair| match pair {
+            Ok((key, LuaValue::Function(f))) => Some(Ok((key, f))),
+            Ok(_) => None,
+            Err(e) => Some(Err(e)),
+        })
+        .collect::<LuaResult<Vec<_>>>()?;
+
+    let readonly = module.is_readonly();
+    module.set_readonly(false);
+    for (key, f) in functions {
+        module.raw_set(key, wrapper.call::<_, LuaFunction>(f)?)?;
+    }
+    module.set_readonly(readonly);
+
+    Ok(())
+}
+
+fn error_new<'lua>(
+    lua: &'lua Lua,
+    (message, options): (String, Option<LuaTable<'lua>>),
+) -> LuaResult<ErrorObject> {
+    let (code, cause) = match options {
+        Some(options) => (options.get("code")?, options.get("cause")?),
+        None => (None, None),
+    };
+    ErrorObject::new(lua, message, code, cause, StackTrace::capture(lua, 1))
+}
+
+fn error_wrap<'lua>(
+    lua: &'lua Lua,
+    (cause, message, code): (LuaValue<'lua>, String, Option<String>),
+) -> LuaResult<ErrorObject> {
+    ErrorObject::new(lua, message, code, Some(cause), StackTrace::capture(lua, 1))
+}
+
+fn error_from<'lua>(lua: &'lua Lua, value: LuaValue<'lua>) -> LuaResult<LuaValue<'lua>> {
+    match value {
+        LuaValue::UserData(ud) if ud.is::<ErrorObject>() => Ok(LuaValue::UserData(ud)),
+        LuaValue::Error(e) => ErrorObject::from_lua_error(lua, &e)?.into_lua(lua),
+        LuaValue::String(s) => {
+            let message = s.to_str()?.to_string();
+            ErrorObject::new(lua, message, None, None, StackTrace::capture(lua, 1))?.into_lua(lua)
+        }
+        value => {
+            let message = value.to_string()?;
+            ErrorObject::new(lua, message, None, Some(value), StackTrace::capture(lua, 1))?
+                .into_lua(lua)
+        }
+    }
+}
+
+fn error_is<'lua>(
+    lua: &'lua Lua,
+    (value, code): (LuaValue<'lua>, Option<String>),
+) -> LuaResult<bool> {
+    match (value, code) {
+        (LuaValue::UserData(ud), code) if ud.is::<ErrorObject>() => match code {
+            Some(code) => ud.borrow::<ErrorObject>()?.chain_has_code(lua, &code),
+            None => Ok(true),
+        },
+        (LuaValue::Error(e), Some(code)) => Ok(error_code(&e).as_deref() == Some(code.as_str())),
+        (LuaValue::Error(_), None) => Ok(true),
+        _ => Ok(false),
+    }
+}
+
+pub fn create(lua: &Lua) -> LuaResult<LuaValue> {
+    TableBuilder::new(lua)?
+        .with_function("new", error_new)?
+        .with_function("wrap", error_wrap)?
+        .with_function("from", error_from)?
+        .with_function("is", error_is)?
+        .build_readonly()?
+        .into_lua(lua)
+}
@@ -1,3 +1,5 @@
+pub mod collect_garbage;
+pub mod error;
 pub mod g_table;
 pub mod print;
 pub mod require;
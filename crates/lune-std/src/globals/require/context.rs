@@ -7,6 +7,8 @@ use std::{
 use mlua::prelude::*;
 use mlua_luau_scheduler::LuaSchedulerExt;
 
+use tracing::debug;
+
 use tokio::{
     fs::read,
     sync::{
@@ -15,10 +17,15 @@ use tokio::{
     },
 };
 
-use lune_utils::path::{clean_path, clean_path_and_make_absolute};
+use lune_utils::{
+    path::{clean_path, clean_path_and_make_absolute},
+    CodedError,
+};
 
 use crate::library::LuneStandardLibrary;
 
+use super::resolver::RequireResolver;
+
 /**
     Context containing cached results for all `require` operations.
 
@@ -159,9 +166,13 @@ impl RequireContext {
         let abs_path = abs_path.as_ref();
         let rel_path = rel_path.as_ref();
 
-        // Read the file at the given path, try to parse and
-        // load it into a new lua thread that we can schedule
-        let file_contents = read(&abs_path).await?;
+        // Read the file at the given path, or get it from the host resolver,
+        // try to parse and load it into a new lua thread that we can schedule
+        let file_contents = match RequireResolver::resolve(lua, rel_path) {
+            Some(contents) => contents,
+            None => read(&abs_path).await?,
+        };
+        debug!(path = %abs_path.display(), "resolved require");
         let file_thread = lua
             .load(file_contents)
             .set_name(rel_path.to_string_lossy().to_string());
@@ -251,6 +262,14 @@ impl RequireContext {
             Ok(b) => b,
         };
 
+        if library.is_disabled(lua) {
+            return Err(CodedError::new(
+                "PermissionDenied",
+                format!("Standard library '{}' has been disabled", library.name()),
+            )
+            .into());
+        }
+
         let mut cache = self
             .libraries
             .try_lock()
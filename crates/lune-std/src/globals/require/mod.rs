@@ -1,4 +1,6 @@
-use mlua::prelude::*;
+use mlua::{prelude::*, ChunkMode, Compiler};
+use once_cell::sync::Lazy;
+use tracing::{instrument, Span};
 
 use lune_utils::TableBuilder;
 
@@ -8,11 +10,21 @@ use context::RequireContext;
 mod alias;
 mod library;
 mod path;
+mod plugin;
+mod resolver;
+
+pub use plugin::register_plugin_module;
+pub use resolver::set_require_resolver;
 
 const REQUIRE_IMPL: &str = r"
 return require(source(), ...)
 ";
 
+// NOTE: Compiling is the most expensive part of creating the require
+// function, and would otherwise happen for every new Lua state, so we
+// compile the implementation just once and then load it as bytecode
+static REQUIRE_IMPL_BYTECODE: Lazy<Vec<u8>> = Lazy::new(|| Compiler::new().compile(REQUIRE_IMPL));
+
 pub fn create(lua: &Lua) -> LuaResult<LuaValue> {
     lua.set_app_data(RequireContext::new());
 
@@ -35,7 +47,6 @@ pub fn create(lua: &Lua) -> LuaResult<LuaValue> {
         3. The lua chunk we are require-ing from
     */
 
-    let require_fn = lua.create_async_function(require)?;
     let get_source_fn = lua.create_function(move |lua, (): ()| match lua.inspect_stack(2) {
         None => Err(LuaError::runtime(
             "Failed to get stack info for require source",
@@ -48,18 +59,38 @@ pub fn create(lua: &Lua) -> LuaResult<LuaValue> {
         },
     })?;
 
+    // NOTE: Creating async functions is fairly expensive, and many scripts
+    // never call require, so we create the async function on first use
     let require_env = TableBuilder::new(lua)?
         .with_value("source", get_source_fn)?
-        .with_value("require", require_fn)?
-        .build_readonly()?;
-
-    lua.load(REQUIRE_IMPL)
+        .with_metatable(
+            TableBuilder::new(lua)?
+                .with_function(LuaMetaMethod::Index.name(), require_env_get)?
+                .build_readonly()?,
+        )?
+        .build()?;
+
+    lua.load(REQUIRE_IMPL_BYTECODE.as_slice())
         .set_name("require")
+        .set_mode(ChunkMode::Binary)
         .set_environment(require_env)
         .into_function()?
         .into_lua(lua)
 }
 
+fn require_env_get<'lua>(
+    lua: &'lua Lua,
+    (env, key): (LuaTable<'lua>, LuaString<'lua>),
+) -> LuaResult<LuaValue<'lua>> {
+    if key != "require" {
+        return Ok(LuaValue::Nil);
+    }
+    let require_fn = lua.create_async_function(require)?;
+    env.raw_set("require", require_fn.clone())?;
+    Ok(LuaValue::Function(require_fn))
+}
+
+#[instrument(level = "debug", name = "require", skip_all, fields(source, path))]
 async fn require<'lua>(
     lua: &'lua Lua,
     (source, path): (LuaString<'lua>, LuaString<'lua>),
@@ -76,12 +107,18 @@ async fn require<'lua>(
         .context("Failed to parse require path as string")?
         .to_string();
 
+    Span::current()
+        .record("source", source.as_str())
+        .record("path", path.as_str());
+
     let context = lua
         .app_data_ref()
         .expect("Failed to get RequireContext from app data");
 
     if let Some(builtin_name) = path.strip_prefix("@lune/").map(str::to_ascii_lowercase) {
         library::require(lua, &context, &builtin_name)
+    } else if let Some(plugin_name) = path.strip_prefix("@plugin/") {
+        plugin::require(lua, plugin_name)
     } else if let Some(aliased_path) = path.strip_prefix('@') {
         let (alias, path) = aliased_path.split_once('/').ok_or(LuaError::runtime(
             "Require with custom alias must contain '/' delimiter",
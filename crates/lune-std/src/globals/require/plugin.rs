@@ -0,0 +1,58 @@
+use std::collections::HashMap;
+
+use mlua::prelude::*;
+
+/**
+    Modules registered by native plugins, keyed by plugin name.
+*/
+#[derive(Debug, Default)]
+struct PluginModules(HashMap<String, LuaRegistryKey>);
+
+pub(super) fn require<'lua>(lua: &'lua Lua, name: &str) -> LuaResult<LuaMultiValue<'lua>> {
+    let modules = lua.app_data_ref::<PluginModules>();
+    match modules.as_ref().and_then(|modules| modules.0.get(name)) {
+        Some(key) => lua.registry_value::<LuaValue>(key)?.into_lua_multi(lua),
+        None => Err(LuaError::runtime(format!(
+            "No plugin named '{name}' has been loaded"
+        ))),
+    }
+}
+
+/**
+    Registers a module provided by a native plugin, making it
+    available to scripts using `require("@plugin/name")`.
+
+    # Errors
+
+    Errors if the name is empty or contains characters other than ASCII
+    letters, digits, `-` and `_`, or if a plugin with the same name
+    has already been registered.
+*/
+pub fn register_plugin_module<'lua>(
+    lua: &'lua Lua,
+    name: impl Into<String>,
+    module: LuaValue<'lua>,
+) -> LuaResult<()> {
+    let name = name.into();
+    let is_valid_char = |c: char| c.is_ascii_alphanumeric() || c == '-' || c == '_';
+    if name.is_empty() || !name.chars().all(is_valid_char) {
+        return Err(LuaError::runtime(format!(
+            "Invalid plugin name '{name}' - names may only contain letters, digits, '-' and '_'"
+        )));
+    }
+
+    let key = lua.create_registry_value(module)?;
+
+    let Some(mut modules) = lua.app_data_mut::<PluginModules>() else {
+        lua.set_app_data(PluginModules(HashMap::from([(name, key)])));
+        return Ok(());
+    };
+    if modules.0.contains_key(&name) {
+        return Err(LuaError::runtime(format!(
+            "A plugin named '{name}' has already been loaded"
+        )));
+    }
+    modules.0.insert(name, key);
+
+    Ok(())
+}
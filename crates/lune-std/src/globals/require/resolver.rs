@@ -0,0 +1,49 @@
+use std::{path::Path, rc::Rc};
+
+use mlua::prelude::*;
+
+type ResolverFn = dyn Fn(&str) -> Option<Vec<u8>>;
+
+/**
+    A host-provided function used to resolve the contents of required modules.
+*/
+#[derive(Clone)]
+pub(super) struct RequireResolver(Rc<ResolverFn>);
+
+impl RequireResolver {
+    /**
+        Resolves the contents of the module at the given path, relative to
+        the current working directory, using the resolver set for the given
+        Lua state, if any.
+
+        Returns `None` if there is no resolver, or if the
+        resolver did not know of a module at the given path.
+    */
+    pub fn resolve(lua: &Lua, rel_path: &Path) -> Option<Vec<u8>> {
+        let resolver = lua.app_data_ref::<Self>()?.clone();
+        let path = rel_path
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        (resolver.0)(&path)
+    }
+}
+
+/**
+    Sets a function used to resolve the contents of required modules, letting
+    scripts be loaded from databases, embedded assets, or virtual filesystems.
+
+    The function receives the path of the module being required, relative to
+    the current working directory and using `/` as the separator, such as
+    `modules/utils.luau`, and should return either the source code or the
+    compiled bytecode of the module, or `None` if it does not know of it.
+
+    Paths are resolved the same way as for modules on the filesystem, meaning
+    the function will also be called for paths with `.luau`, `.lua`, and
+    `/init.luau` or `/init.lua` appended, until a module is found. Any path
+    that the function returns `None` for is read from the filesystem instead.
+*/
+pub fn set_require_resolver(lua: &Lua, resolver: impl Fn(&str) -> Option<Vec<u8>> + 'static) {
+    lua.set_app_data(RequireResolver(Rc::new(resolver)));
+}
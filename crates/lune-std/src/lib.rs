@@ -8,8 +8,31 @@ mod library;
 mod luaurc;
 
 pub use self::global::LuneStandardGlobal;
+pub use self::globals::require::{register_plugin_module, set_require_resolver};
 pub use self::globals::version::set_global_version;
-pub use self::library::LuneStandardLibrary;
+pub use self::library::{disable_libraries, LuneStandardLibrary};
+
+#[cfg(feature = "fs")]
+pub use lune_std_fs::{
+    allow_read_paths, allow_write_paths, allowed_read_paths, allowed_write_paths,
+};
+
+#[cfg(feature = "log")]
+pub use lune_std_log::{set_log_format, set_log_level, LogFormat, LogLevel};
+
+#[cfg(feature = "luau")]
+pub use lune_std_luau::set_codegen_enabled;
+
+#[cfg(feature = "net")]
+pub use lune_std_net::{allow_net_hosts, allowed_net_hosts};
+
+#[cfg(feature = "process")]
+pub use lune_std_process::{
+    set_lune_process_args, set_lune_script_runner, LuneScriptJob, LuneScriptResult,
+};
+
+#[cfg(feature = "task")]
+pub use lune_std_task::{set_timer_resolution, TimerResolution};
 
 /**
     Injects all standard globals into the given Lua state / VM.
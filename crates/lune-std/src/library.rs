@@ -1,23 +1,61 @@
-use std::str::FromStr;
+use std::{collections::HashSet, str::FromStr};
 
 use mlua::prelude::*;
 
+#[derive(Debug, Default)]
+struct DisabledLibraries(HashSet<LuneStandardLibrary>);
+
+/**
+    Disables the given standard libraries, making any
+    attempt to `require` them result in an error instead.
+
+    This may be called multiple times, and all given libraries will stay disabled.
+
+    Disabling the `net` library also denies network access to all other
+    libraries that use the network, such as `roblox` downloading assets.
+*/
+pub fn disable_libraries(lua: &Lua, libraries: impl IntoIterator<Item = LuneStandardLibrary>) {
+    let mut disabled = lua
+        .remove_app_data::<DisabledLibraries>()
+        .unwrap_or_default();
+    disabled.0.extend(libraries);
+    #[cfg(feature = "net")]
+    if disabled.0.contains(&LuneStandardLibrary::Net) {
+        lune_std_net::allow_net_hosts(lua, std::iter::empty::<&str>())
+            .expect("an empty list of hosts is always valid");
+    }
+    lua.set_app_data(disabled);
+}
+
 /**
     A standard library provided by Lune.
 */
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
 #[rustfmt::skip]
 pub enum LuneStandardLibrary {
-    #[cfg(feature = "datetime")] DateTime,
-    #[cfg(feature = "fs")]       Fs,
-    #[cfg(feature = "luau")]     Luau,
-    #[cfg(feature = "net")]      Net,
-    #[cfg(feature = "task")]     Task,
-    #[cfg(feature = "process")]  Process,
-    #[cfg(feature = "regex")]    Regex,
-    #[cfg(feature = "serde")]    Serde,
-    #[cfg(feature = "stdio")]    Stdio,
-    #[cfg(feature = "roblox")]   Roblox,
+    #[cfg(feature = "archive")]   Archive,
+    #[cfg(feature = "bench")]     Bench,
+    #[cfg(feature = "clipboard")] Clipboard,
+    #[cfg(feature = "crypto")]    Crypto,
+    #[cfg(feature = "datetime")]  DateTime,
+    #[cfg(feature = "expect")]    Expect,
+    #[cfg(feature = "ffi")]       Ffi,
+    #[cfg(feature = "fs")]        Fs,
+    #[cfg(feature = "image")]     Image,
+    #[cfg(feature = "ipc")]       Ipc,
+    #[cfg(feature = "log")]       Log,
+    #[cfg(feature = "luau")]      Luau,
+    #[cfg(feature = "net")]       Net,
+    #[cfg(feature = "notify")]    Notify,
+    #[cfg(feature = "task")]      Task,
+    #[cfg(feature = "process")]   Process,
+    #[cfg(feature = "regex")]     Regex,
+    #[cfg(feature = "secrets")]   Secrets,
+    #[cfg(feature = "serde")]     Serde,
+    #[cfg(feature = "serial")]    Serial,
+    #[cfg(feature = "sqlite")]    Sqlite,
+    #[cfg(feature = "stdio")]     Stdio,
+    #[cfg(feature = "roblox")]    Roblox,
 }
 
 impl LuneStandardLibrary {
@@ -26,16 +64,29 @@ impl LuneStandardLibrary {
     */
     #[rustfmt::skip]
     pub const ALL: &'static [Self] = &[
-        #[cfg(feature = "datetime")] Self::DateTime,
-        #[cfg(feature = "fs")]       Self::Fs,
-        #[cfg(feature = "luau")]     Self::Luau,
-        #[cfg(feature = "net")]      Self::Net,
-        #[cfg(feature = "task")]     Self::Task,
-        #[cfg(feature = "process")]  Self::Process,
-        #[cfg(feature = "regex")]    Self::Regex,
-        #[cfg(feature = "serde")]    Self::Serde,
-        #[cfg(feature = "stdio")]    Self::Stdio,
-        #[cfg(feature = "roblox")]   Self::Roblox,
+        #[cfg(feature = "archive")]   Self::Archive,
+        #[cfg(feature = "bench")]     Self::Bench,
+        #[cfg(feature = "clipboard")] Self::Clipboard,
+        #[cfg(feature = "crypto")]    Self::Crypto,
+        #[cfg(feature = "datetime")]  Self::DateTime,
+        #[cfg(feature = "expect")]    Self::Expect,
+        #[cfg(feature = "ffi")]       Self::Ffi,
+        #[cfg(feature = "fs")]        Self::Fs,
+        #[cfg(feature = "image")]     Self::Image,
+        #[cfg(feature = "ipc")]       Self::Ipc,
+        #[cfg(feature = "log")]       Self::Log,
+        #[cfg(feature = "luau")]      Self::Luau,
+        #[cfg(feature = "net")]       Self::Net,
+        #[cfg(feature = "notify")]    Self::Notify,
+        #[cfg(feature = "task")]      Self::Task,
+        #[cfg(feature = "process")]   Self::Process,
+        #[cfg(feature = "regex")]     Self::Regex,
+        #[cfg(feature = "secrets")]   Self::Secrets,
+        #[cfg(feature = "serde")]     Self::Serde,
+        #[cfg(feature = "serial")]    Self::Serial,
+        #[cfg(feature = "sqlite")]    Self::Sqlite,
+        #[cfg(feature = "stdio")]     Self::Stdio,
+        #[cfg(feature = "roblox")]    Self::Roblox,
     ];
 
     /**
@@ -46,21 +97,43 @@ impl LuneStandardLibrary {
     #[allow(unreachable_patterns)]
     pub fn name(&self) -> &'static str {
         match self {
-            #[cfg(feature = "datetime")] Self::DateTime => "datetime",
-            #[cfg(feature = "fs")]       Self::Fs       => "fs",
-            #[cfg(feature = "luau")]     Self::Luau     => "luau",
-            #[cfg(feature = "net")]      Self::Net      => "net",
-            #[cfg(feature = "task")]     Self::Task     => "task",
-            #[cfg(feature = "process")]  Self::Process  => "process",
-            #[cfg(feature = "regex")]    Self::Regex    => "regex",
-            #[cfg(feature = "serde")]    Self::Serde    => "serde",
-            #[cfg(feature = "stdio")]    Self::Stdio    => "stdio",
-            #[cfg(feature = "roblox")]   Self::Roblox   => "roblox",
+            #[cfg(feature = "archive")]   Self::Archive   => "archive",
+            #[cfg(feature = "bench")]     Self::Bench     => "bench",
+            #[cfg(feature = "clipboard")] Self::Clipboard => "clipboard",
+            #[cfg(feature = "crypto")]    Self::Crypto    => "crypto",
+            #[cfg(feature = "datetime")]  Self::DateTime  => "datetime",
+            #[cfg(feature = "expect")]    Self::Expect    => "expect",
+            #[cfg(feature = "ffi")]       Self::Ffi       => "ffi",
+            #[cfg(feature = "fs")]        Self::Fs        => "fs",
+            #[cfg(feature = "image")]     Self::Image     => "image",
+            #[cfg(feature = "ipc")]       Self::Ipc       => "ipc",
+            #[cfg(feature = "log")]       Self::Log       => "log",
+            #[cfg(feature = "luau")]      Self::Luau      => "luau",
+            #[cfg(feature = "net")]       Self::Net       => "net",
+            #[cfg(feature = "notify")]    Self::Notify    => "notify",
+            #[cfg(feature = "task")]      Self::Task      => "task",
+            #[cfg(feature = "process")]   Self::Process   => "process",
+            #[cfg(feature = "regex")]     Self::Regex     => "regex",
+            #[cfg(feature = "secrets")]   Self::Secrets   => "secrets",
+            #[cfg(feature = "serde")]     Self::Serde     => "serde",
+            #[cfg(feature = "serial")]    Self::Serial    => "serial",
+            #[cfg(feature = "sqlite")]    Self::Sqlite    => "sqlite",
+            #[cfg(feature = "stdio")]     Self::Stdio     => "stdio",
+            #[cfg(feature = "roblox")]    Self::Roblox    => "roblox",
 
             _ => unreachable!("no standard library enabled"),
         }
     }
 
+    /**
+        Checks if the library has been disabled using [`disable_libraries`].
+    */
+    #[must_use]
+    pub fn is_disabled(&self, lua: &Lua) -> bool {
+        lua.app_data_ref::<DisabledLibraries>()
+            .is_some_and(|disabled| disabled.0.contains(self))
+    }
+
     /**
         Creates the Lua module for the library.
 
@@ -72,20 +145,36 @@ impl LuneStandardLibrary {
     #[allow(unreachable_patterns)]
     pub fn module<'lua>(&self, lua: &'lua Lua) -> LuaResult<LuaMultiValue<'lua>> {
         let res: LuaResult<LuaTable> = match self {
-            #[cfg(feature = "datetime")] Self::DateTime => lune_std_datetime::module(lua),
-            #[cfg(feature = "fs")]       Self::Fs       => lune_std_fs::module(lua),
-            #[cfg(feature = "luau")]     Self::Luau     => lune_std_luau::module(lua),
-            #[cfg(feature = "net")]      Self::Net      => lune_std_net::module(lua),
-            #[cfg(feature = "task")]     Self::Task     => lune_std_task::module(lua),
-            #[cfg(feature = "process")]  Self::Process  => lune_std_process::module(lua),
-            #[cfg(feature = "regex")]    Self::Regex    => lune_std_regex::module(lua),
-            #[cfg(feature = "serde")]    Self::Serde    => lune_std_serde::module(lua),
-            #[cfg(feature = "stdio")]    Self::Stdio    => lune_std_stdio::module(lua),
-            #[cfg(feature = "roblox")]   Self::Roblox   => lune_std_roblox::module(lua),
+            #[cfg(feature = "archive")]   Self::Archive   => lune_std_archive::module(lua),
+            #[cfg(feature = "bench")]     Self::Bench     => lune_std_bench::module(lua),
+            #[cfg(feature = "clipboard")] Self::Clipboard => lune_std_clipboard::module(lua),
+            #[cfg(feature = "crypto")]    Self::Crypto    => lune_std_crypto::module(lua),
+            #[cfg(feature = "datetime")]  Self::DateTime  => lune_std_datetime::module(lua),
+            #[cfg(feature = "expect")]    Self::Expect    => lune_std_expect::module(lua),
+            #[cfg(feature = "ffi")]       Self::Ffi       => lune_std_ffi::module(lua),
+            #[cfg(feature = "fs")]        Self::Fs        => lune_std_fs::module(lua),
+            #[cfg(feature = "image")]     Self::Image     => lune_std_image::module(lua),
+            #[cfg(feature = "ipc")]       Self::Ipc       => lune_std_ipc::module(lua),
+            #[cfg(feature = "log")]       Self::Log       => lune_std_log::module(lua),
+            #[cfg(feature = "luau")]      Self::Luau      => lune_std_luau::module(lua),
+            #[cfg(feature = "net")]       Self::Net       => lune_std_net::module(lua),
+            #[cfg(feature = "notify")]    Self::Notify    => lune_std_notify::module(lua),
+            #[cfg(feature = "task")]      Self::Task      => lune_std_task::module(lua),
+            #[cfg(feature = "process")]   Self::Process   => lune_std_process::module(lua),
+            #[cfg(feature = "regex")]     Self::Regex     => lune_std_regex::module(lua),
+            #[cfg(feature = "secrets")]   Self::Secrets   => lune_std_secrets::module(lua),
+            #[cfg(feature = "serde")]     Self::Serde     => lune_std_serde::module(lua),
+            #[cfg(feature = "serial")]    Self::Serial    => lune_std_serial::module(lua),
+            #[cfg(feature = "sqlite")]    Self::Sqlite    => lune_std_sqlite::module(lua),
+            #[cfg(feature = "stdio")]     Self::Stdio     => lune_std_stdio::module(lua),
+            #[cfg(feature = "roblox")]    Self::Roblox    => lune_std_roblox::module(lua),
 
             _ => unreachable!("no standard library enabled"),
         };
-        match res {
+        match res.and_then(|module| {
+            crate::globals::error::raise_error_objects(lua, &module)?;
+            Ok(module)
+        }) {
             Ok(v) => v.into_lua_multi(lua),
             Err(e) => Err(e.context(format!(
                 "Failed to create standard library '{}'",
@@ -101,16 +190,29 @@ impl FromStr for LuneStandardLibrary {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let low = s.trim().to_ascii_lowercase();
         Ok(match low.as_str() {
-            #[cfg(feature = "datetime")] "datetime" => Self::DateTime,
-            #[cfg(feature = "fs")]       "fs"       => Self::Fs,
-            #[cfg(feature = "luau")]     "luau"     => Self::Luau,
-            #[cfg(feature = "net")]      "net"      => Self::Net,
-            #[cfg(feature = "task")]     "task"     => Self::Task,
-            #[cfg(feature = "process")]  "process"  => Self::Process,
-            #[cfg(feature = "regex")]    "regex"    => Self::Regex,
-            #[cfg(feature = "serde")]    "serde"    => Self::Serde,
-            #[cfg(feature = "stdio")]    "stdio"    => Self::Stdio,
-            #[cfg(feature = "roblox")]   "roblox"   => Self::Roblox,
+            #[cfg(feature = "archive")]   "archive"   => Self::Archive,
+            #[cfg(feature = "bench")]     "bench"     => Self::Bench,
+            #[cfg(feature = "clipboard")] "clipboard" => Self::Clipboard,
+            #[cfg(feature = "crypto")]    "crypto"    => Self::Crypto,
+            #[cfg(feature = "datetime")]  "datetime"  => Self::DateTime,
+            #[cfg(feature = "expect")]    "expect"    => Self::Expect,
+            #[cfg(feature = "ffi")]       "ffi"       => Self::Ffi,
+            #[cfg(feature = "fs")]        "fs"        => Self::Fs,
+            #[cfg(feature = "image")]     "image"     => Self::Image,
+            #[cfg(feature = "ipc")]       "ipc"       => Self::Ipc,
+            #[cfg(feature = "log")]       "log"       => Self::Log,
+            #[cfg(feature = "luau")]      "luau"      => Self::Luau,
+            #[cfg(feature = "net")]       "net"       => Self::Net,
+            #[cfg(feature = "notify")]    "notify"    => Self::Notify,
+            #[cfg(feature = "task")]      "task"      => Self::Task,
+            #[cfg(feature = "process")]   "process"   => Self::Process,
+            #[cfg(feature = "regex")]     "regex"     => Self::Regex,
+            #[cfg(feature = "secrets")]   "secrets"   => Self::Secrets,
+            #[cfg(feature = "serde")]     "serde"     => Self::Serde,
+            #[cfg(feature = "serial")]    "serial"    => Self::Serial,
+            #[cfg(feature = "sqlite")]    "sqlite"    => Self::Sqlite,
+            #[cfg(feature = "stdio")]     "stdio"     => Self::Stdio,
+            #[cfg(feature = "roblox")]    "roblox"    => Self::Roblox,
 
             _ => {
                 return Err(format!(
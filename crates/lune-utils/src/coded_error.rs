@@ -0,0 +1,83 @@
+use std::{error::Error, fmt, io};
+
+use mlua::prelude::*;
+
+/**
+    An error with a machine-readable code, such as `NotFound`, in addition to its message.
+
+    Builtins may return this error to let scripts react to specific
+    kinds of errors without having to match against error messages.
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodedError {
+    code: String,
+    message: String,
+}
+
+impl CodedError {
+    /**
+        Creates a new error with the given code and message.
+    */
+    pub fn new(code: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            code: code.into(),
+            message: message.into(),
+        }
+    }
+
+    /**
+        Returns the code of the error.
+    */
+    #[must_use]
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    /**
+        Returns the message of the error.
+    */
+    #[must_use]
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for CodedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Error for CodedError {}
+
+impl From<CodedError> for LuaError {
+    fn from(value: CodedError) -> Self {
+        LuaError::external(value)
+    }
+}
+
+/**
+    Extracts a machine-readable code from the given error, if it has one.
+
+    This includes codes from [`CodedError`], the kind of any I/O
+    errors, and the most common errors raised by the Luau VM.
+*/
+#[must_use]
+pub fn error_code(error: &LuaError) -> Option<String> {
+    match error {
+        LuaError::CallbackError { cause, .. } | LuaError::WithContext { cause, .. } => {
+            error_code(cause)
+        }
+        LuaError::ExternalError(e) => {
+            if let Some(coded) = e.downcast_ref::<CodedError>() {
+                Some(coded.code.clone())
+            } else {
+                e.downcast_ref::<io::Error>()
+                    .map(|io| format!("{:?}", io.kind()))
+            }
+        }
+        LuaError::MemoryError(_) => Some(String::from("OutOfMemory")),
+        LuaError::SyntaxError { .. } => Some(String::from("SyntaxError")),
+        _ => None,
+    }
+}
@@ -115,17 +115,41 @@ impl From<LuaError> for ErrorComponents {
             StackTrace::from_str(source).ok()
         }
 
+        fn has_own_trace(e: &LuaError) -> bool {
+            match e {
+                LuaError::CallbackError { .. } => true,
+                LuaError::WithContext { cause, .. } => has_own_trace(cause),
+                LuaError::RuntimeError(s) => s.contains("stack traceback:"),
+                _ => false,
+            }
+        }
+
         // Extract any additional "context" messages before the actual error(s)
         // The Arc is necessary here because mlua wraps all inner errors in an Arc
+        // We also extract traces from any outer callbacks whose cause has a trace of
+        // its own - these are callers of the inner error, such as the `require` call
+        // that loaded a failing module, and will be appended to its stack trace
         let mut error = Arc::new(error);
         let mut messages = Vec::new();
-        while let LuaError::WithContext {
-            ref context,
-            ref cause,
-        } = *error
-        {
-            messages.push(context.to_string());
-            error = cause.clone();
+        let mut callers = Vec::new();
+        loop {
+            match *error {
+                LuaError::WithContext {
+                    ref context,
+                    ref cause,
+                } => {
+                    messages.push(context.to_string());
+                    error = cause.clone();
+                }
+                LuaError::CallbackError {
+                    ref traceback,
+                    ref cause,
+                } if has_own_trace(cause) => {
+                    callers.extend(lua_stack_trace(traceback));
+                    error = cause.clone();
+                }
+                _ => break,
+            }
         }
 
         // We will then try to extract any stack trace
@@ -152,6 +176,15 @@ impl From<LuaError> for ErrorComponents {
             None
         };
 
+        // Append any caller traces, innermost caller first, so
+        // that the full trace reads like a single call stack
+        for caller in callers.into_iter().rev() {
+            trace
+                .get_or_insert_with(StackTrace::default)
+                .lines_mut()
+                .extend(caller.lines().iter().cloned());
+        }
+
         // Sometimes, we can get duplicate stack trace lines that only
         // mention "[C]", without a function name or path, and these can
         // be safely ignored / removed if the following line has more info
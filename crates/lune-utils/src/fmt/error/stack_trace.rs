@@ -1,6 +1,8 @@
 use std::fmt;
 use std::str::FromStr;
 
+use mlua::prelude::*;
+
 fn parse_path(s: &str) -> Option<(&str, &str)> {
     let path = s.strip_prefix("[string \"")?;
     let (path, after) = path.split_once("\"]:")?;
@@ -185,6 +187,49 @@ impl StackTrace {
     pub fn lines_mut(&mut self) -> &mut Vec<StackTraceLine> {
         &mut self.lines
     }
+
+    /**
+        Captures the current Lua call stack, starting at the given level.
+
+        Level 0 is the currently running function, which is usually the Rust
+        function calling this, and level 1 is the Lua function that called it.
+    */
+    #[must_use]
+    pub fn capture(lua: &Lua, level: usize) -> Self {
+        let mut lines = Vec::new();
+        let mut level = level;
+        while let Some(debug) = lua.inspect_stack(level) {
+            level += 1;
+            let source = debug.source();
+            let short_src = source.short_src.as_deref().unwrap_or("?");
+            // Skip internal frames used by mlua to implement async functions
+            if short_src.contains("__mlua") {
+                continue;
+            }
+            let function_name = debug.names().name.map(|name| name.to_string());
+            if source.what == "C" {
+                lines.push(StackTraceLine {
+                    source: StackTraceSource::C,
+                    path: None,
+                    line_number: None,
+                    function_name,
+                });
+            } else {
+                let path = short_src
+                    .strip_prefix("[string \"")
+                    .and_then(|path| path.strip_suffix("\"]"))
+                    .unwrap_or(short_src);
+                let line = debug.curr_line();
+                lines.push(StackTraceLine {
+                    source: StackTraceSource::Lua,
+                    path: Some(path.to_string()),
+                    line_number: usize::try_from(line).ok().filter(|line| *line > 0),
+                    function_name,
+                });
+            }
+        }
+        Self { lines }
+    }
 }
 
 impl FromStr for StackTrace {
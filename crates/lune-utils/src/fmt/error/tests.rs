@@ -36,6 +36,32 @@ fn new_lua_script_error() -> LuaResult<()> {
     .eval()
 }
 
+fn new_lua_nested_error() -> LuaResult<()> {
+    let lua = Lua::new();
+
+    // Emulates requiring a module that errors, which
+    // is a rust function running another lua chunk
+    lua.globals()
+        .set(
+            "load_module",
+            LuaFunction::wrap(|lua, (): ()| {
+                lua.load(
+                    "local function inner()\
+                    \n    error(\"oh no, a module error\")\
+                    \nend\
+                    \n\
+                    \ninner()\
+                    ",
+                )
+                .set_name("module_name")
+                .exec()
+            }),
+        )
+        .unwrap();
+
+    lua.load("load_module()").set_name("chunk_name").eval()
+}
+
 // Tests for error context stack
 mod context {
     use super::*;
@@ -148,3 +174,46 @@ mod general {
         assert_eq!(c_stack_lines.len(), 1); // Just the "error" call
     }
 }
+
+// Tests for errors that pass through rust callbacks, such as require
+mod nested {
+    use super::*;
+
+    #[test]
+    fn message_is_innermost() {
+        let lua_error = new_lua_nested_error().unwrap_err();
+        let components = ErrorComponents::from(lua_error);
+
+        assert_eq!(components.messages(), &["oh no, a module error"]);
+    }
+
+    #[test]
+    fn trace_includes_callers() {
+        let lua_error = new_lua_nested_error().unwrap_err();
+        let components = ErrorComponents::from(lua_error);
+
+        let lines = components
+            .trace()
+            .unwrap()
+            .lines()
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+
+        let module_pos = lines
+            .iter()
+            .position(|line| line.starts_with("Script 'module_name', Line 2"))
+            .expect("trace should contain the module");
+        let caller_pos = lines
+            .iter()
+            .position(|line| line == "Script '[C]' - function 'load_module'")
+            .expect("trace should contain the calling function");
+        let chunk_pos = lines
+            .iter()
+            .position(|line| line == "Script 'chunk_name', Line 1")
+            .expect("trace should contain the calling chunk");
+
+        assert!(module_pos < caller_pos);
+        assert!(caller_pos < chunk_pos);
+    }
+}
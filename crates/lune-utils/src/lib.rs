@@ -1,10 +1,16 @@
 #![allow(clippy::cargo_common_metadata)]
 
+mod coded_error;
+mod output;
+mod panic;
 mod table_builder;
 mod version_string;
 
 pub mod fmt;
 pub mod path;
 
+pub use self::coded_error::{error_code, CodedError};
+pub use self::output::{write_stderr, write_stdout, CapturedOutput};
+pub use self::panic::{catch_panic, catch_panic_async, is_catching_panic};
 pub use self::table_builder::TableBuilder;
 pub use self::version_string::get_version_string;
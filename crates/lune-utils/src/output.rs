@@ -0,0 +1,128 @@
+use std::{
+    io::{self, Write},
+    sync::{Arc, Mutex},
+};
+
+use mlua::prelude::*;
+
+/**
+    Buffers for capturing output written by builtins, such as `print` and `stdio.write`.
+
+    Once installed in a Luau VM using [`CapturedOutput::install`], any output written
+    using [`write_stdout`] and [`write_stderr`] goes to these buffers instead of the
+    standard output and error streams of the current process.
+
+    Note that output written directly by child processes, such as
+    when using `process.spawn` with inherited stdio, is not captured.
+*/
+#[derive(Debug, Clone, Default)]
+pub struct CapturedOutput {
+    stdout: Arc<Mutex<Vec<u8>>>,
+    stderr: Arc<Mutex<Vec<u8>>>,
+}
+
+impl CapturedOutput {
+    /**
+        Creates a new set of empty output buffers.
+    */
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /**
+        Redirects all output in the given Luau VM to these buffers.
+    */
+    pub fn install(&self, lua: &Lua) {
+        lua.set_app_data(self.clone());
+    }
+
+    /**
+        Stops redirecting output in the given Luau VM, if it was being redirected.
+    */
+    pub fn uninstall(lua: &Lua) {
+        lua.remove_app_data::<Self>();
+    }
+
+    /**
+        Gets the output buffers installed in the given Luau VM, if any.
+    */
+    #[must_use]
+    pub fn get(lua: &Lua) -> Option<Self> {
+        lua.app_data_ref::<Self>().map(|output| output.clone())
+    }
+
+    /**
+        Appends the given bytes to the captured standard output.
+    */
+    pub fn write_stdout(&self, bytes: &[u8]) {
+        lock(&self.stdout).extend_from_slice(bytes);
+    }
+
+    /**
+        Appends the given bytes to the captured standard error.
+    */
+    pub fn write_stderr(&self, bytes: &[u8]) {
+        lock(&self.stderr).extend_from_slice(bytes);
+    }
+
+    /**
+        Takes all standard output captured so far, leaving the buffer empty.
+    */
+    #[must_use]
+    pub fn take_stdout(&self) -> Vec<u8> {
+        std::mem::take(&mut *lock(&self.stdout))
+    }
+
+    /**
+        Takes all standard error captured so far, leaving the buffer empty.
+    */
+    #[must_use]
+    pub fn take_stderr(&self) -> Vec<u8> {
+        std::mem::take(&mut *lock(&self.stderr))
+    }
+}
+
+fn lock(buffer: &Mutex<Vec<u8>>) -> std::sync::MutexGuard<'_, Vec<u8>> {
+    // NOTE: Appending bytes can not leave the buffer in an
+    // inconsistent state, so a poisoned lock is safe to use
+    buffer
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+/**
+    Writes the given bytes to standard output, or to the
+    captured output if one is installed in the given Luau VM.
+
+    # Errors
+
+    Errors if writing to standard output fails.
+*/
+pub fn write_stdout(lua: &Lua, bytes: &[u8]) -> io::Result<()> {
+    if let Some(output) = CapturedOutput::get(lua) {
+        output.write_stdout(bytes);
+        return Ok(());
+    }
+    let mut stdout = io::stdout().lock();
+    stdout.write_all(bytes)?;
+    stdout.flush()
+}
+
+/**
+    Writes the given bytes to standard error, or to the
+    captured output if one is installed in the given Luau VM.
+
+    # Errors
+
+    Errors if writing to standard error fails.
+*/
+pub fn write_stderr(lua: &Lua, bytes: &[u8]) -> io::Result<()> {
+    if let Some(output) = CapturedOutput::get(lua) {
+        output.write_stderr(bytes);
+        return Ok(());
+    }
+    let mut stderr = io::stderr().lock();
+    stderr.write_all(bytes)?;
+    stderr.flush()
+}
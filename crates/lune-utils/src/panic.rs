@@ -0,0 +1,177 @@
+use std::{
+    any::Any,
+    cell::Cell,
+    future::{poll_fn, Future},
+    panic::{catch_unwind, AssertUnwindSafe},
+    pin::pin,
+    task::Poll,
+};
+
+use mlua::prelude::*;
+
+thread_local! {
+    static CATCHING_DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+/**
+    Returns `true` if the current thread is running code that is
+    guarded by [`catch_panic`] or [`catch_panic_async`].
+
+    Panic hooks may use this to skip any reporting that should
+    only happen for panics that would abort the whole process.
+*/
+#[must_use]
+pub fn is_catching_panic() -> bool {
+    CATCHING_DEPTH.with(|depth| depth.get() > 0)
+}
+
+/**
+    Runs the given function, converting any Rust panic
+    that happens inside of it into a regular Lua error.
+
+    The given name is included in the error message, and should
+    identify the builtin that panicked, such as `roblox.deserializePlace`.
+
+    # Errors
+
+    Errors if the function returns an error, or if it panics.
+*/
+pub fn catch_panic<R>(name: &str, f: impl FnOnce() -> LuaResult<R>) -> LuaResult<R> {
+    guarded(name, f)?
+}
+
+/**
+    Runs the given future, converting any Rust panic that
+    happens while polling it into a regular Lua error.
+
+    See [`catch_panic`] for more details.
+
+    # Errors
+
+    Errors if the future resolves to an error, or if it panics.
+*/
+pub async fn catch_panic_async<R>(
+    name: &str,
+    fut: impl Future<Output = LuaResult<R>>,
+) -> LuaResult<R> {
+    let mut fut = pin!(fut);
+    poll_fn(|cx| match guarded(name, || fut.as_mut().poll(cx)) {
+        Ok(poll) => poll,
+        Err(e) => Poll::Ready(Err(e)),
+    })
+    .await
+}
+
+fn guarded<T>(name: &str, f: impl FnOnce() -> T) -> LuaResult<T> {
+    struct DepthGuard;
+    impl Drop for DepthGuard {
+        fn drop(&mut self) {
+            CATCHING_DEPTH.with(|depth| depth.set(depth.get() - 1));
+        }
+    }
+
+    CATCHING_DEPTH.with(|depth| depth.set(depth.get() + 1));
+    let _guard = DepthGuard;
+
+    catch_unwind(AssertUnwindSafe(f)).map_err(|payload| {
+        LuaError::runtime(format!(
+            "Internal error in '{name}': {}\
+            \nThis is a bug in Lune, please consider reporting it",
+            panic_message(payload.as_ref())
+        ))
+    })
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.as_str()
+    } else {
+        "unknown panic"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mlua::prelude::*;
+
+    use crate::TableBuilder;
+
+    async fn panic_async() -> LuaResult<()> {
+        // NOTE: Yielding first makes sure that we also catch panics when
+        // the future is polled again later, and not just in the first poll
+        tokio::task::yield_now().await;
+        panic!("oh no, an async panic")
+    }
+
+    fn new_lua_with_panicking_lib() -> Lua {
+        let lua = Lua::new();
+        let lib = TableBuilder::new_named(&lua, "lib")
+            .unwrap()
+            .with_function("panic", |_, (): ()| -> LuaResult<()> {
+                panic!("oh no, a panic")
+            })
+            .unwrap()
+            .with_async_function("panicAsync", |_, (): ()| panic_async())
+            .unwrap()
+            .build_readonly()
+            .unwrap();
+        lua.globals().set("lib", lib).unwrap();
+        lua
+    }
+
+    #[test]
+    fn panics_are_catchable() {
+        let lua = new_lua_with_panicking_lib();
+        lua.load(
+            "local ok, err = pcall(lib.panic)\
+            \nassert(not ok, \"Panicking function should fail\")\
+            \nlocal message = tostring(err)\
+            \nassert(string.find(message, \"Internal error in 'lib.panic'\", 1, true), message)\
+            \nassert(string.find(message, \"oh no, a panic\", 1, true), message)",
+        )
+        .set_name("chunk_name")
+        .exec()
+        .unwrap();
+    }
+
+    #[test]
+    fn async_panics_are_catchable() {
+        let lua = new_lua_with_panicking_lib();
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        rt.block_on(
+            lua.load(
+                "local ok, err = pcall(lib.panicAsync)\
+                \nassert(not ok, \"Panicking function should fail\")\
+                \nlocal message = tostring(err)\
+                \nassert(string.find(message, \"Internal error in 'lib.panicAsync'\", 1, true), message)\
+                \nassert(string.find(message, \"oh no, an async panic\", 1, true), message)",
+            )
+            .set_name("chunk_name")
+            .exec_async(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn unnamed_tables_use_keys() {
+        let lua = Lua::new();
+        let lib = TableBuilder::new(&lua)
+            .unwrap()
+            .with_function("panic", |_, (): ()| -> LuaResult<()> {
+                panic!("oh no, a panic")
+            })
+            .unwrap()
+            .build()
+            .unwrap();
+        let err = lib
+            .get::<_, LuaFunction>("panic")
+            .unwrap()
+            .call::<_, ()>(())
+            .unwrap_err();
+        assert!(err.to_string().contains("Internal error in 'panic'"));
+    }
+}
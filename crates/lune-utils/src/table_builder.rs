@@ -4,12 +4,15 @@ use std::future::Future;
 
 use mlua::prelude::*;
 
+use crate::panic::{catch_panic, catch_panic_async};
+
 /**
     Utility struct for building Lua tables.
 */
 pub struct TableBuilder<'lua> {
     lua: &'lua Lua,
     tab: LuaTable<'lua>,
+    name: Option<String>,
 }
 
 impl<'lua> TableBuilder<'lua> {
@@ -18,7 +21,23 @@ impl<'lua> TableBuilder<'lua> {
     */
     pub fn new(lua: &'lua Lua) -> LuaResult<Self> {
         let tab = lua.create_table()?;
-        Ok(Self { lua, tab })
+        Ok(Self {
+            lua,
+            tab,
+            name: None,
+        })
+    }
+
+    /**
+        Creates a new table builder for a table with the given name, such as `fs`.
+
+        The name is used to identify functions in the table when they panic,
+        meaning a panic in `readFile` will be reported as coming from `fs.readFile`.
+    */
+    pub fn new_named(lua: &'lua Lua, name: impl Into<String>) -> LuaResult<Self> {
+        let mut builder = Self::new(lua)?;
+        builder.name = Some(name.into());
+        Ok(builder)
     }
 
     /**
@@ -85,6 +104,9 @@ impl<'lua> TableBuilder<'lua> {
         Adds a new key-value pair to the table, with a function value.
 
         This will overwrite any value that already exists.
+
+        Any panic that happens inside of the function will be
+        converted into a Lua error instead of aborting the process.
     */
     pub fn with_function<K, A, R, F>(self, key: K, func: F) -> LuaResult<Self>
     where
@@ -93,7 +115,11 @@ impl<'lua> TableBuilder<'lua> {
         R: IntoLuaMulti<'lua>,
         F: Fn(&'lua Lua, A) -> LuaResult<R> + 'static,
     {
-        let f = self.lua.create_function(func)?;
+        let key = key.into_lua(self.lua)?;
+        let name = self.function_name(&key);
+        let f = self
+            .lua
+            .create_function(move |lua, args| catch_panic(&name, || func(lua, args)))?;
         self.with_value(key, LuaValue::Function(f))
     }
 
@@ -101,6 +127,9 @@ impl<'lua> TableBuilder<'lua> {
         Adds a new key-value pair to the table, with an async function value.
 
         This will overwrite any value that already exists.
+
+        Any panic that happens while running the function will be
+        converted into a Lua error instead of aborting the process.
     */
     pub fn with_async_function<K, A, R, F, FR>(self, key: K, func: F) -> LuaResult<Self>
     where
@@ -110,7 +139,13 @@ impl<'lua> TableBuilder<'lua> {
         F: Fn(&'lua Lua, A) -> FR + 'static,
         FR: Future<Output = LuaResult<R>> + 'lua,
     {
-        let f = self.lua.create_async_function(func)?;
+        let key = key.into_lua(self.lua)?;
+        let name = self.function_name(&key);
+        let f = self.lua.create_async_function(move |lua, args| {
+            let fut = catch_panic(&name, || Ok(func(lua, args)));
+            let name = name.clone();
+            async move { catch_panic_async(&name, fut?).await }
+        })?;
         self.with_value(key, LuaValue::Function(f))
     }
 
@@ -140,4 +175,12 @@ impl<'lua> TableBuilder<'lua> {
     pub fn build(self) -> LuaResult<LuaTable<'lua>> {
         Ok(self.tab)
     }
+
+    fn function_name(&self, key: &LuaValue) -> String {
+        let key = key.as_str().unwrap_or("<function>");
+        match &self.name {
+            Some(name) => format!("{name}.{key}"),
+            None => key.to_string(),
+        }
+    }
 }
@@ -1,45 +1,74 @@
 use std::{
     io::{Cursor, Read},
-    path::PathBuf,
+    path::{Path, PathBuf},
 };
 
+use minisign_verify::{PublicKey, Signature};
+use sha2::{Digest, Sha256};
 use tokio::{fs, task};
 
-use crate::standalone::metadata::CURRENT_EXE;
+use crate::standalone::metadata::{CURRENT_EXE, MIN_BASE_VERSION};
 
 use super::{
     files::write_executable_file_to,
     result::{BuildError, BuildResult},
-    target::{BuildTarget, CACHE_DIR},
+    target::BuildTarget,
 };
 
+/**
+    The minisign public key that `SHA256SUMS` files in GitHub releases are signed with.
+
+    This is embedded at compile time using the `LUNE_RELEASE_PUBLIC_KEY` environment
+    variable, which is set for release builds - other builds can not verify signatures.
+*/
+const RELEASE_PUBLIC_KEY: Option<&str> = option_env!("LUNE_RELEASE_PUBLIC_KEY");
+
 /**
     Discovers the path to the base executable to use for cross-compilation.
 
-    If the target is the same as the current system, the current executable is used.
+    If the target is the same as the current system, and the requested version is
+    the same as the version of the current executable, the current executable is used.
+
+    Other versions must be at least [`MIN_BASE_VERSION`], since older versions can not
+    read the metadata that gets appended to the base executable to create a standalone binary.
 
     If no binary exists at the target path, it will attempt to download it from the internet.
+    Downloaded binaries must have a published checksum and signature, unless `allow_unverified`
+    is set, in which case a missing checksum or signature is only warned about - a checksum
+    or signature that does not match is never allowed.
 */
-pub async fn get_or_download_base_executable(target: BuildTarget) -> BuildResult<PathBuf> {
-    if target.is_current_system() {
+pub async fn get_or_download_base_executable(
+    target: BuildTarget,
+    version: &str,
+    allow_unverified: bool,
+) -> BuildResult<PathBuf> {
+    if target.is_current_system() && version == env!("CARGO_PKG_VERSION") {
         return Ok(CURRENT_EXE.to_path_buf());
     }
-    if target.cache_path().exists() {
-        return Ok(target.cache_path());
+    if !is_supported_base_version(version) {
+        return Err(BuildError::UnsupportedBaseVersion {
+            version: version.to_string(),
+            minimum: MIN_BASE_VERSION,
+        });
+    }
+    let cache_path = target.cache_path(version);
+    if is_cached_binary_intact(&cache_path).await {
+        return Ok(cache_path);
     }
 
     // The target is not cached, we must download it
-    println!("Requested target '{target}' does not exist in cache");
-    let version = env!("CARGO_PKG_VERSION");
+    eprintln!("Requested target '{target}' for version {version} does not exist in cache");
     let target_triple = format!("lune-{version}-{target}");
 
-    let release_url = format!(
-        "{base_url}/v{version}/{target_triple}.zip",
+    let release_base_url = format!(
+        "{base_url}/v{version}",
         base_url = "https://github.com/lune-org/lune/releases/download",
     );
+    let release_zip_name = format!("{target_triple}.zip");
+    let release_url = format!("{release_base_url}/{release_zip_name}");
 
     // NOTE: This is not entirely accurate, but it is clearer for a user
-    println!("Downloading {target_triple}{}...", target.exe_suffix());
+    eprintln!("Downloading {target_triple}{}...", target.exe_suffix());
 
     // Try to request to download the zip file from the target url,
     // making sure transient errors are handled gracefully and
@@ -54,8 +83,16 @@ pub async fn get_or_download_base_executable(target: BuildTarget) -> BuildResult
         ));
     }
 
-    // Receive the full zip file
+    // Receive the full zip file, and make sure it matches the checksum
+    // and signature published alongside it before we unpack anything
     let zip_bytes = response.bytes().await?.to_vec();
+    verify_release_file(
+        &release_base_url,
+        &release_zip_name,
+        &zip_bytes,
+        allow_unverified,
+    )
+    .await?;
     let zip_file = Cursor::new(zip_bytes);
 
     // Look for and extract the binary file from the zip file
@@ -75,12 +112,260 @@ pub async fn get_or_download_base_executable(target: BuildTarget) -> BuildResult
     });
     let binary_file_contents = binary_file_handle.await??;
 
-    // Finally, write the extracted binary to the cache
-    if !CACHE_DIR.exists() {
-        fs::create_dir_all(CACHE_DIR.as_path()).await?;
+    // Finally, write the extracted binary to the cache, along with its digest,
+    // which makes sure that it has not been modified when it is used again later
+    let digest = format!("{:x}", Sha256::digest(&binary_file_contents));
+    if let Some(cache_dir) = cache_path.parent() {
+        fs::create_dir_all(cache_dir).await?;
+    }
+    write_executable_file_to(&cache_path, binary_file_contents).await?;
+    fs::write(digest_path(&cache_path), digest).await?;
+    eprintln!("Downloaded successfully and added to cache");
+
+    Ok(cache_path)
+}
+
+/**
+    Checks if a base executable exists at the given cache path, and that it
+    still matches the digest that was stored next to it when it was cached.
+
+    Cached binaries without a stored digest, or that have been
+    modified since they were cached, must be downloaded again.
+*/
+async fn is_cached_binary_intact(cache_path: &Path) -> bool {
+    let Ok(contents) = fs::read(cache_path).await else {
+        return false;
+    };
+    let Ok(expected) = fs::read_to_string(digest_path(cache_path)).await else {
+        eprintln!("Cached base executable has no stored checksum, downloading it again");
+        return false;
+    };
+    let actual = format!("{:x}", Sha256::digest(contents));
+    if expected.trim().eq_ignore_ascii_case(&actual) {
+        true
+    } else {
+        eprintln!(
+            "Cached base executable does not match its stored checksum, downloading it again"
+        );
+        false
+    }
+}
+
+/**
+    Gets the path that the digest of a cached base executable is stored at.
+*/
+fn digest_path(cache_path: &Path) -> PathBuf {
+    let mut file_name = cache_path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".sha256");
+    cache_path.with_file_name(file_name)
+}
+
+/**
+    Checks if the given version of Lune can be used as a base executable.
+
+    Only the major, minor and patch numbers are compared, meaning
+    that prereleases of the minimum version are also supported.
+*/
+fn is_supported_base_version(version: &str) -> bool {
+    match (
+        parse_version_core(version),
+        parse_version_core(MIN_BASE_VERSION),
+    ) {
+        (Some(version), Some(minimum)) => version >= minimum,
+        _ => false,
+    }
+}
+
+/**
+    Parses the major, minor and patch numbers of a semantic version,
+    ignoring any prerelease or build metadata that follows them.
+*/
+fn parse_version_core(version: &str) -> Option<(u64, u64, u64)> {
+    let core = version.split(['-', '+']).next()?;
+    let mut numbers = core.split('.').map(|number| number.parse::<u64>().ok());
+    let parsed = (numbers.next()??, numbers.next()??, numbers.next()??);
+    numbers.next().is_none().then_some(parsed)
+}
+
+/**
+    Verifies the given release file contents against the `SHA256SUMS` file published
+    in the same GitHub release, and the `SHA256SUMS` file against its minisign signature.
+
+    Releases published before checksums and signatures were introduced do not have
+    these files, and are rejected unless `allow_unverified` is set.
+*/
+async fn verify_release_file(
+    release_base_url: &str,
+    file_name: &str,
+    file_contents: &[u8],
+    allow_unverified: bool,
+) -> BuildResult<()> {
+    let Some(checksums) = download_release_text(release_base_url, "SHA256SUMS").await? else {
+        return skip_verification(BuildError::ChecksumsNotPublished, allow_unverified);
+    };
+
+    verify_checksums_signature(release_base_url, &checksums, allow_unverified).await?;
+
+    let expected = parse_checksum_for_file(&checksums, file_name)
+        .ok_or_else(|| BuildError::ChecksumNotFound(file_name.to_string()))?;
+    let actual = format!("{:x}", Sha256::digest(file_contents));
+
+    if expected.eq_ignore_ascii_case(&actual) {
+        Ok(())
+    } else {
+        Err(BuildError::ChecksumMismatch {
+            file_name: file_name.to_string(),
+            expected,
+            actual,
+        })
     }
-    write_executable_file_to(target.cache_path(), binary_file_contents).await?;
-    println!("Downloaded successfully and added to cache");
+}
+
+/**
+    Verifies the contents of a `SHA256SUMS` file against the
+    `SHA256SUMS.minisig` file published in the same GitHub release.
+*/
+async fn verify_checksums_signature(
+    release_base_url: &str,
+    checksums: &str,
+    allow_unverified: bool,
+) -> BuildResult<()> {
+    let Some(public_key) = RELEASE_PUBLIC_KEY else {
+        return skip_verification(BuildError::PublicKeyMissing, allow_unverified);
+    };
+    let Some(signature) = download_release_text(release_base_url, "SHA256SUMS.minisig").await?
+    else {
+        return skip_verification(BuildError::SignatureNotPublished, allow_unverified);
+    };
+
+    let public_key = PublicKey::from_base64(public_key).map_err(BuildError::Signature)?;
+    let signature = Signature::decode(&signature).map_err(BuildError::Signature)?;
+    public_key
+        .verify(checksums.as_bytes(), &signature, false)
+        .map_err(BuildError::Signature)
+}
+
+/**
+    Downloads a text file from a GitHub release, returning `None` if it does not exist.
+*/
+async fn download_release_text(
+    release_base_url: &str,
+    file_name: &str,
+) -> BuildResult<Option<String>> {
+    let response = reqwest::get(format!("{release_base_url}/{file_name}")).await?;
+    if response.status().as_u16() == 404 {
+        return Ok(None);
+    }
+    Ok(Some(response.error_for_status()?.text().await?))
+}
+
+/**
+    Skips a verification step that can not be performed with a warning if
+    unverified releases have been explicitly allowed, or errors otherwise.
+*/
+fn skip_verification(reason: BuildError, allow_unverified: bool) -> BuildResult<()> {
+    if allow_unverified {
+        eprintln!("Warning: {reason}, skipping verification");
+        Ok(())
+    } else {
+        Err(reason)
+    }
+}
+
+/**
+    Finds the checksum for a file in the contents of a `SHA256SUMS` file.
 
-    Ok(target.cache_path())
+    Each line is expected to be in the format output by `sha256sum`,
+    meaning a hex digest, whitespace, and then an optional `*` binary
+    marker directly followed by the file name.
+*/
+fn parse_checksum_for_file(checksums: &str, file_name: &str) -> Option<String> {
+    checksums.lines().find_map(|line| {
+        let (digest, name) = line.trim().split_once(char::is_whitespace)?;
+        let name = name.trim_start().trim_start_matches('*');
+        if name == file_name {
+            Some(digest.to_string())
+        } else {
+            None
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base_versions_older_than_the_metadata_format_are_rejected() {
+        assert!(is_supported_base_version(MIN_BASE_VERSION));
+        assert!(is_supported_base_version("0.9.0-rc.1"));
+        assert!(is_supported_base_version("0.9.1"));
+        assert!(is_supported_base_version("0.10.0"));
+        assert!(is_supported_base_version("1.0.0+build.5"));
+
+        assert!(!is_supported_base_version("0.8.9"));
+        assert!(!is_supported_base_version("0.7.11"));
+        assert!(!is_supported_base_version("0.9"));
+        assert!(!is_supported_base_version("0.9.0.1"));
+        assert!(!is_supported_base_version("0.9.x"));
+    }
+
+    #[tokio::test]
+    async fn cached_binaries_are_checked_against_their_digest() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache_path = dir.path().join("linux-x86_64");
+        assert!(!is_cached_binary_intact(&cache_path).await);
+
+        fs::write(&cache_path, b"binary").await.unwrap();
+        assert!(!is_cached_binary_intact(&cache_path).await);
+
+        let digest = format!("{:X}\n", Sha256::digest(b"binary"));
+        fs::write(digest_path(&cache_path), digest).await.unwrap();
+        assert!(is_cached_binary_intact(&cache_path).await);
+
+        fs::write(&cache_path, b"modified binary").await.unwrap();
+        assert!(!is_cached_binary_intact(&cache_path).await);
+    }
+
+    #[test]
+    fn digests_are_stored_next_to_cached_binaries() {
+        assert_eq!(
+            digest_path(Path::new("cache/0.9.0/windows-x86_64.exe")),
+            Path::new("cache/0.9.0/windows-x86_64.exe.sha256")
+        );
+    }
+
+    #[test]
+    fn checksums_are_parsed_for_files() {
+        let checksums = concat!(
+            "aaaa  lune-0.9.0-linux-x86_64.zip\n",
+            "bbbb *lune-0.9.0-windows-x86_64.zip\n",
+            "\n",
+            "  cccc \t  lune-0.9.0-macos-aarch64.zip  \n",
+            "dddd  lune-0.9.0-linux-x86_64.zip.sig\n",
+        );
+        let parse = |file_name| parse_checksum_for_file(checksums, file_name);
+
+        assert_eq!(
+            parse("lune-0.9.0-linux-x86_64.zip").as_deref(),
+            Some("aaaa")
+        );
+        assert_eq!(
+            parse("lune-0.9.0-windows-x86_64.zip").as_deref(),
+            Some("bbbb")
+        );
+        assert_eq!(
+            parse("lune-0.9.0-macos-aarch64.zip").as_deref(),
+            Some("cccc")
+        );
+        assert_eq!(
+            parse("lune-0.9.0-linux-x86_64.zip.sig").as_deref(),
+            Some("dddd")
+        );
+
+        assert_eq!(parse("lune-0.9.0-linux-aarch64.zip"), None);
+        assert_eq!(parse("lune-0.9.0-linux-x86_64"), None);
+        assert_eq!(parse("*lune-0.9.0-windows-x86_64.zip"), None);
+        assert_eq!(parse(""), None);
+    }
 }
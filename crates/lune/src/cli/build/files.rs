@@ -22,11 +22,21 @@ pub fn remove_source_file_ext(path: &Path) -> PathBuf {
 /**
     Writes the given bytes to a file at the specified path,
     and makes sure it has permissions to be executed.
+
+    The bytes are first written to a temporary file next to the target path,
+    which is then renamed over the target, so that an interrupted write can
+    never leave behind a partially written executable.
 */
 pub async fn write_executable_file_to(
     path: impl AsRef<Path>,
     bytes: impl AsRef<[u8]>,
 ) -> Result<(), std::io::Error> {
+    let path = path.as_ref();
+
+    let mut temp_file_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_file_name.push(format!(".{}.tmp", std::process::id()));
+    let temp_path = path.with_file_name(temp_file_name);
+
     let mut options = fs::OpenOptions::new();
     options.write(true).create(true).truncate(true);
 
@@ -35,8 +45,18 @@ pub async fn write_executable_file_to(
         options.mode(0o755); // Read & execute for all, write for owner
     }
 
-    let mut file = options.open(path).await?;
-    file.write_all(bytes.as_ref()).await?;
+    let res = async {
+        let mut file = options.open(&temp_path).await?;
+        file.write_all(bytes.as_ref()).await?;
+        file.sync_all().await?;
+        drop(file);
+        fs::rename(&temp_path, path).await
+    }
+    .await;
+
+    if res.is_err() {
+        fs::remove_file(&temp_path).await.ok();
+    }
 
-    Ok(())
+    res
 }
@@ -5,7 +5,7 @@ use clap::Parser;
 use console::style;
 use tokio::fs;
 
-use crate::standalone::metadata::Metadata;
+use crate::standalone::{metadata::Metadata, tracer::trace_requires};
 
 mod base_exe;
 mod files;
@@ -31,6 +31,17 @@ pub struct BuildCommand {
     /// defaults to the os and arch of the current system
     #[clap(short, long)]
     pub target: Option<BuildTarget>,
+
+    /// The version of Lune to use as the base executable, such as `0.9.0` -
+    /// defaults to the version of the current executable, and may not be
+    /// older than the first version that supports the standalone binary format
+    #[clap(long, value_name = "VERSION", value_parser = parse_base_version)]
+    pub base_version: Option<String>,
+
+    /// Allow downloading base executables from releases that do not
+    /// publish checksums or signatures, which can not be verified
+    #[clap(long)]
+    pub allow_unverified: bool,
 }
 
 impl BuildCommand {
@@ -52,22 +63,31 @@ impl BuildCommand {
             bail!("output path cannot be the same as input path, please specify a different output path");
         }
 
-        // Try to read the given input file
-        // FUTURE: We should try and resolve a full require file graph using the input
-        // path here instead, see the notes in the `standalone` module for more details
+        // Try to read the given input file, and any modules that it requires
         let source_code = fs::read(&self.input)
             .await
             .context("failed to read input file")?;
+        let modules = trace_requires(&self.input, &source_code)
+            .await
+            .context("failed to trace required modules")?;
 
         // Derive the base executable path based on the arguments provided
-        let base_exe_path = get_or_download_base_executable(target).await?;
+        let base_version = self
+            .base_version
+            .as_deref()
+            .unwrap_or(env!("CARGO_PKG_VERSION"));
+        let base_exe_path =
+            get_or_download_base_executable(target, base_version, self.allow_unverified).await?;
 
         // Read the contents of the lune interpreter as our starting point
         println!(
             "Compiling standalone binary from {}",
             style(self.input.display()).green()
         );
-        let patched_bin = Metadata::create_env_patched_bin(base_exe_path, source_code)
+        for module_path in modules.keys() {
+            println!("Bundling required module {}", style(module_path).dim());
+        }
+        let patched_bin = Metadata::create_env_patched_bin(base_exe_path, source_code, modules)
             .await
             .context("failed to create patched binary")?;
 
@@ -81,3 +101,24 @@ impl BuildCommand {
         Ok(ExitCode::SUCCESS)
     }
 }
+
+/**
+    Parses a Lune version to download a base executable for, with an optional `v` prefix.
+
+    Only characters valid in a semantic version are allowed, since
+    the version is used in both download urls and cache paths.
+*/
+fn parse_base_version(s: &str) -> Result<String, String> {
+    let version = s.trim().trim_start_matches('v');
+    let is_valid = version.starts_with(|c: char| c.is_ascii_digit())
+        && version
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '+'));
+    if is_valid {
+        Ok(version.to_string())
+    } else {
+        Err(format!(
+            "invalid version '{s}' - expected a version such as '0.9.0'"
+        ))
+    }
+}
@@ -7,10 +7,31 @@ use super::target::BuildTarget;
 */
 #[derive(Debug, Error)]
 pub enum BuildError {
+    #[error("lune {version} can not be used as a base executable - the earliest supported version is {minimum}")]
+    UnsupportedBaseVersion {
+        version: String,
+        minimum: &'static str,
+    },
     #[error("failed to find lune target '{0}' in GitHub release")]
     ReleaseTargetNotFound(BuildTarget),
     #[error("failed to find lune binary '{0}' in downloaded zip file")]
     ZippedBinaryNotFound(String),
+    #[error("GitHub release does not publish checksums - pass --allow-unverified to build anyway")]
+    ChecksumsNotPublished,
+    #[error("GitHub release does not publish a signature for its checksums - pass --allow-unverified to build anyway")]
+    SignatureNotPublished,
+    #[error("this build of lune has no public key to verify release signatures with - pass --allow-unverified to build anyway")]
+    PublicKeyMissing,
+    #[error("failed to verify signature of GitHub release checksums: {0}")]
+    Signature(minisign_verify::Error),
+    #[error("failed to find checksum for '{0}' in GitHub release")]
+    ChecksumNotFound(String),
+    #[error("checksum mismatch for '{file_name}' - expected {expected}, got {actual}")]
+    ChecksumMismatch {
+        file_name: String,
+        expected: String,
+        actual: String,
+    },
     #[error("failed to download lune binary: {0}")]
     Download(#[from] reqwest::Error),
     #[error("failed to unzip lune binary: {0}")]
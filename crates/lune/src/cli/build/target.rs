@@ -151,8 +151,10 @@ impl BuildTarget {
         self.os.exe_suffix()
     }
 
-    pub fn cache_path(&self) -> PathBuf {
-        CACHE_DIR.join(format!("{self}{}", self.os.exe_extension()))
+    pub fn cache_path(&self, version: &str) -> PathBuf {
+        CACHE_DIR
+            .join(version)
+            .join(format!("{self}{}", self.os.exe_extension()))
     }
 }
 
@@ -0,0 +1,254 @@
+use std::{
+    path::{Path, PathBuf},
+    process::{ExitCode, Stdio},
+};
+
+use anyhow::{bail, Context, Result};
+use clap::Parser;
+use console::style;
+use tokio::{fs, process::Command};
+
+use super::setup::{generate_typedef_files_from_definitions, lune_version, TYPEDEFS_DIR};
+
+const TEMPLATE_NAME_DEFAULT: &str = "default";
+
+const TEMPLATE_HELLO_SCRIPT: &str = r#"--> Prints a greeting to the given name, or the world
+
+local process = require("@lune/process")
+
+local name = process.args[1] or "world"
+
+print(`Hello, {name}!`)
+"#;
+
+const TEMPLATE_EXAMPLE_TEST: &str = r#"local process = require("@lune/process")
+
+assert(type(process.args) == "table", "process.args should be a table")
+assert(#process.os > 0, "process.os should not be empty")
+
+print("All tests passed!")
+"#;
+
+const TEMPLATE_GITIGNORE: &str = "# Standalone binaries created using `lune build`
+/bin
+
+# Local environment files, these may contain secrets
+.env
+.env.local
+";
+
+/// Create a new project
+#[derive(Debug, Clone, Parser)]
+pub struct InitCommand {
+    /// The template to use - either `default`, or the url of a git repository
+    template: Option<String>,
+    /// The directory to create the project in - defaults to the current directory
+    #[clap(long, short)]
+    dir: Option<PathBuf>,
+}
+
+impl InitCommand {
+    pub async fn run(self) -> Result<ExitCode> {
+        let dir = self.dir.unwrap_or_else(|| PathBuf::from("."));
+        let template = self.template.as_deref().unwrap_or(TEMPLATE_NAME_DEFAULT);
+
+        if template == TEMPLATE_NAME_DEFAULT {
+            write_default_template(&dir).await?;
+        } else {
+            clone_git_template(template, &dir).await?;
+        }
+
+        // The .luaurc aliases point to the typedefs
+        // directory, so we make sure that it exists
+        generate_typedef_files_from_definitions(&TYPEDEFS_DIR).await?;
+
+        println!(
+            "Created a new Lune project in {}\
+            \nRun the example script using {}",
+            style(dir.display()).green(),
+            style("lune run hello").blue(),
+        );
+
+        Ok(ExitCode::SUCCESS)
+    }
+}
+
+async fn write_default_template(dir: &Path) -> Result<()> {
+    let luaurc = format!(
+        "{{\
+        \n\t\"languageMode\": \"strict\",\
+        \n\t\"aliases\": {{\
+        \n\t\t\"lune\": \"~/.lune/.typedefs/{}/\"\
+        \n\t}}\
+        \n}}\n",
+        lune_version()
+    );
+
+    let files = [
+        (dir.join(".luaurc"), luaurc.as_str()),
+        (dir.join(".gitignore"), TEMPLATE_GITIGNORE),
+        (dir.join(".lune").join("hello.luau"), TEMPLATE_HELLO_SCRIPT),
+        (
+            dir.join("tests").join("example.luau"),
+            TEMPLATE_EXAMPLE_TEST,
+        ),
+    ];
+
+    for (path, contents) in files {
+        // NOTE: We never want to overwrite anything the user has
+        // already written, so we skip files that already exist
+        if fs::try_exists(&path).await? {
+            println!("Skipping existing file {}", style(path.display()).yellow());
+            continue;
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+        fs::write(&path, contents)
+            .await
+            .with_context(|| format!("Failed to write file at '{}'", path.display()))?;
+    }
+
+    Ok(())
+}
+
+async fn clone_git_template(url: &str, dir: &Path) -> Result<()> {
+    // NOTE: We clone into a temporary directory next to the project files,
+    // so that the project directory does not need to be empty, and so that
+    // any cloned files can be moved into it without having to copy them
+    fs::create_dir_all(dir)
+        .await
+        .with_context(|| format!("Failed to create directory at '{}'", dir.display()))?;
+    let clone_dir = tempfile::Builder::new()
+        .prefix(".lune-template-")
+        .tempdir_in(dir)
+        .context("Failed to create temporary directory for template")?;
+
+    let status = git_clone_command(url, clone_dir.path())
+        .stdin(Stdio::null())
+        .status()
+        .await
+        .context("Failed to run git, make sure it is installed")?;
+    if !status.success() {
+        bail!("Failed to clone template repository '{url}'");
+    }
+
+    // The template history is not useful for the new project,
+    // and gets removed together with the temporary directory
+    let mut entries = Vec::new();
+    let mut reader = fs::read_dir(clone_dir.path()).await?;
+    while let Some(entry) = reader.next_entry().await? {
+        if entry.file_name() != ".git" {
+            entries.push(entry.file_name());
+        }
+    }
+
+    // We never want to overwrite anything the user has already written,
+    // and checking all of the files first means we either move all or none
+    let mut existing = Vec::new();
+    for name in &entries {
+        if fs::try_exists(dir.join(name)).await? {
+            existing.push(name.to_string_lossy().to_string());
+        }
+    }
+    if !existing.is_empty() {
+        existing.sort();
+        bail!(
+            "The directory '{}' already contains files that are part of the template: {}\
+            \nRemove them first, or use an empty directory using --dir",
+            dir.display(),
+            existing.join(", ")
+        );
+    }
+
+    for name in entries {
+        fs::rename(clone_dir.path().join(&name), dir.join(&name))
+            .await
+            .with_context(|| {
+                format!("Failed to move template file '{}'", name.to_string_lossy())
+            })?;
+    }
+
+    Ok(())
+}
+
+fn git_clone_command(url: &str, dir: &Path) -> Command {
+    // NOTE: The url comes after a "--" so that it can never be parsed as
+    // an option for git, even if it starts with a dash, such as "--upload-pack"
+    let mut command = Command::new("git");
+    command
+        .arg("clone")
+        .arg("--depth")
+        .arg("1")
+        .arg("--")
+        .arg(url)
+        .arg(dir);
+    command
+}
+
+#[cfg(test)]
+mod tests {
+    use std::process::Command as StdCommand;
+
+    use super::*;
+
+    fn create_template_repo() -> tempfile::TempDir {
+        let repo = tempfile::tempdir().unwrap();
+        std::fs::write(repo.path().join("hello.luau"), "print(\"Hello!\")").unwrap();
+        let git = |args: &[&str]| {
+            let status = StdCommand::new("git")
+                .args(["-c", "user.name=lune", "-c", "user.email=lune@localhost"])
+                .args(args)
+                .current_dir(repo.path())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()
+                .unwrap();
+            assert!(status.success(), "git {args:?} failed");
+        };
+        git(&["init", "--quiet"]);
+        git(&["add", "."]);
+        git(&["commit", "--quiet", "-m", "template"]);
+        repo
+    }
+
+    #[tokio::test]
+    async fn templates_are_cloned_into_non_empty_dirs() {
+        let repo = create_template_repo();
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("existing.luau"), "").unwrap();
+
+        let url = repo.path().to_str().unwrap();
+        clone_git_template(url, dir.path()).await.unwrap();
+
+        let mut names = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, ["existing.luau", "hello.luau"]);
+    }
+
+    #[tokio::test]
+    async fn templates_never_overwrite_files() {
+        let repo = create_template_repo();
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("hello.luau"), "mine").unwrap();
+
+        let url = repo.path().to_str().unwrap();
+        let err = clone_git_template(url, dir.path()).await.unwrap_err();
+        assert!(err.to_string().contains("hello.luau"));
+
+        let contents = std::fs::read_to_string(dir.path().join("hello.luau")).unwrap();
+        assert_eq!(contents, "mine");
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn template_urls_are_not_options() {
+        let url = "--upload-pack=touch /tmp/pwned";
+        let command = git_clone_command(url, Path::new("dir"));
+        let args = command.as_std().get_args().collect::<Vec<_>>();
+        assert_eq!(args, ["clone", "--depth", "1", "--", url, "dir"]);
+    }
+}
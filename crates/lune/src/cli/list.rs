@@ -2,12 +2,27 @@ use std::{fmt::Write as _, process::ExitCode};
 
 use anyhow::Result;
 use clap::Parser;
+use serde::Serialize;
 
-use super::utils::listing::{find_lune_scripts, sort_lune_scripts, write_lune_scripts_list};
+use super::utils::{
+    listing::{find_lune_scripts, sort_lune_scripts, write_lune_scripts_list},
+    output::{print_json, OutputFormat},
+};
 
 /// List scripts available to run
 #[derive(Debug, Clone, Parser)]
-pub struct ListCommand {}
+pub struct ListCommand {
+    /// Output format - either `text` or `json`
+    #[clap(long, default_value = "text")]
+    output: OutputFormat,
+}
+
+#[derive(Debug, Serialize)]
+struct ListedScript {
+    name: String,
+    description: String,
+    global: bool,
+}
 
 impl ListCommand {
     pub async fn run(self) -> Result<ExitCode> {
@@ -15,13 +30,38 @@ impl ListCommand {
 
         let sorted_home_dir = find_lune_scripts(true).await.map(sort_lune_scripts);
         if sorted_relative.is_err() && sorted_home_dir.is_err() {
-            eprintln!("{}", sorted_relative.unwrap_err());
+            if self.output.is_json() {
+                #[derive(Serialize)]
+                struct ListError {
+                    error: String,
+                }
+                print_json(&ListError {
+                    error: sorted_relative.unwrap_err().to_string(),
+                })?;
+            } else {
+                eprintln!("{}", sorted_relative.unwrap_err());
+            }
             return Ok(ExitCode::FAILURE);
         }
 
         let sorted_relative = sorted_relative.unwrap_or(Vec::new());
         let sorted_home_dir = sorted_home_dir.unwrap_or(Vec::new());
 
+        if self.output.is_json() {
+            let scripts = sorted_relative
+                .into_iter()
+                .map(|script| (script, false))
+                .chain(sorted_home_dir.into_iter().map(|script| (script, true)))
+                .map(|((name, description), global)| ListedScript {
+                    name,
+                    description,
+                    global,
+                })
+                .collect::<Vec<_>>();
+            print_json(&scripts)?;
+            return Ok(ExitCode::SUCCESS);
+        }
+
         let mut buffer = String::new();
         if !sorted_relative.is_empty() {
             if sorted_home_dir.is_empty() {
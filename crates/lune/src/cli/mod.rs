@@ -2,8 +2,10 @@ use std::process::ExitCode;
 
 use anyhow::Result;
 use clap::{Parser, Subcommand};
+use tracing::Level;
 
 pub(crate) mod build;
+pub(crate) mod init;
 pub(crate) mod list;
 pub(crate) mod repl;
 pub(crate) mod run;
@@ -11,7 +13,8 @@ pub(crate) mod setup;
 pub(crate) mod utils;
 
 pub use self::{
-    build::BuildCommand, list::ListCommand, repl::ReplCommand, run::RunCommand, setup::SetupCommand,
+    build::BuildCommand, init::InitCommand, list::ListCommand, repl::ReplCommand, run::RunCommand,
+    setup::SetupCommand,
 };
 
 #[derive(Debug, Clone, Subcommand)]
@@ -20,6 +23,7 @@ pub enum CliSubcommand {
     List(ListCommand),
     Setup(SetupCommand),
     Build(BuildCommand),
+    Init(InitCommand),
     Repl(ReplCommand),
 }
 
@@ -35,6 +39,20 @@ impl Default for CliSubcommand {
 pub struct Cli {
     #[clap(subcommand)]
     subcommand: Option<CliSubcommand>,
+    /// Emit debug logs from the runtime, same as `--log-level debug`
+    #[clap(long, short, global = true)]
+    verbose: bool,
+    /// Emit logs from the runtime at the given level or above -
+    /// one of `trace`, `debug`, `info`, `warn`, or `error`
+    #[clap(long, global = true)]
+    log_level: Option<Level>,
+    /// Emit logs as JSON, with one object per line
+    #[clap(long, global = true)]
+    log_json: bool,
+    /// Write a crash report file if Lune itself crashes,
+    /// same as setting the `LUNE_CRASH_REPORTS` environment variable
+    #[clap(long, global = true)]
+    crash_reports: bool,
 }
 
 impl Cli {
@@ -42,13 +60,85 @@ impl Cli {
         Self::parse()
     }
 
+    pub fn log_level(&self) -> Option<Level> {
+        match self.log_level {
+            Some(level) => Some(level),
+            None if self.verbose => Some(Level::DEBUG),
+            None => None,
+        }
+    }
+
+    pub fn log_json(&self) -> bool {
+        self.log_json
+    }
+
+    pub fn crash_reports(&self) -> bool {
+        self.crash_reports
+    }
+
+    /**
+        Loads any env files requested by the subcommand into the current process.
+
+        This must be called before any other threads have been started.
+    */
+    pub fn load_env_files(&self) -> Result<()> {
+        match &self.subcommand {
+            Some(CliSubcommand::Run(cmd)) => cmd.load_env_files(),
+            _ => Ok(()),
+        }
+    }
+
     pub async fn run(self) -> Result<ExitCode> {
         match self.subcommand.unwrap_or_default() {
             CliSubcommand::Run(cmd) => cmd.run().await,
             CliSubcommand::List(cmd) => cmd.run().await,
             CliSubcommand::Setup(cmd) => cmd.run().await,
             CliSubcommand::Build(cmd) => cmd.run().await,
+            CliSubcommand::Init(cmd) => cmd.run().await,
             CliSubcommand::Repl(cmd) => cmd.run().await,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use clap::CommandFactory;
+
+    use super::*;
+
+    #[test]
+    fn cli_command_is_valid() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn global_log_level_parses_after_subcommand() {
+        let cli = Cli::try_parse_from(["lune", "run", "--log-level", "debug", "script"])
+            .expect("failed to parse run command with --log-level");
+        assert_eq!(cli.log_level(), Some(Level::DEBUG));
+        assert!(matches!(cli.subcommand, Some(CliSubcommand::Run(_))));
+    }
+
+    #[test]
+    fn script_log_level_is_separate_from_global_log_level() {
+        let cli = Cli::try_parse_from(["lune", "run", "--script-log-level", "warn", "script"])
+            .expect("failed to parse run command with --script-log-level");
+        assert_eq!(cli.log_level(), None);
+    }
+
+    #[test]
+    fn build_base_version_is_validated() {
+        let cli = Cli::try_parse_from(["lune", "build", "--base-version", "v0.9.0", "script"])
+            .expect("failed to parse build command with --base-version");
+        let Some(CliSubcommand::Build(build)) = cli.subcommand else {
+            panic!("expected build subcommand");
+        };
+        assert_eq!(build.base_version.as_deref(), Some("0.9.0"));
+        assert!(!build.allow_unverified);
+
+        for invalid in ["", "latest", "../0.8.9", "0.8.9/../../x"] {
+            let result = Cli::try_parse_from(["lune", "build", "--base-version", invalid, "s"]);
+            assert!(result.is_err(), "version '{invalid}' should be rejected");
+        }
+    }
+}
@@ -3,13 +3,19 @@ use std::{path::PathBuf, process::ExitCode};
 use anyhow::{Context, Result};
 use clap::Parser;
 use directories::UserDirs;
+use mlua::prelude::*;
 use rustyline::{error::ReadlineError, DefaultEditor};
 
 use lune::Runtime;
+use lune_utils::fmt::{pretty_format_multi_value, ValueFormatConfig};
 
 const MESSAGE_WELCOME: &str = concat!("Lune v", env!("CARGO_PKG_VERSION"));
 const MESSAGE_INTERRUPT: &str = "Interrupt: ^C again to exit";
 
+const FORMAT_CONFIG: ValueFormatConfig = ValueFormatConfig::new()
+    .with_max_depth(4)
+    .with_colors_enabled(true);
+
 enum PromptState {
     Regular,
     Continuation,
@@ -70,6 +76,10 @@ impl ReplCommand {
                 Err(ReadlineError::Interrupted) => {
                     interrupt_counter += 1;
 
+                    // Any incomplete input is discarded, same as in most other shells
+                    prompt_state = PromptState::Regular;
+                    source_code.clear();
+
                     // NOTE: We actually want the user to do ^C twice to exit,
                     // and if we get an interrupt we should continue to the next
                     // readline loop iteration so we don't run input code twice
@@ -87,18 +97,40 @@ impl ReplCommand {
                 }
             };
 
+            // NOTE: We first try to evaluate the input as an expression, so that
+            // values of expressions such as `1 + 2` can be printed, and then fall
+            // back to running it as statements if it was not a valid expression,
+            // which is fine to do since syntax errors happen before anything runs
             // TODO: Preserve context here somehow?
-            let eval_result = lune_instance.run("REPL", &source_code).await;
+            let eval_result = match lune_instance
+                .run("REPL", format!("return {source_code}"))
+                .await
+                .map(|(_, values)| format_values(values))
+            {
+                Err(err) if err.is_syntax_error() => lune_instance
+                    .run("REPL", &source_code)
+                    .await
+                    .map(|(_, values)| format_values(values)),
+                result => result,
+            };
 
             match eval_result {
-                Ok(_) => prompt_state = PromptState::Regular,
+                Ok(formatted) => {
+                    prompt_state = PromptState::Regular;
+                    if !formatted.is_empty() {
+                        println!("{formatted}");
+                    }
+                }
 
                 Err(err) => {
                     if err.is_incomplete_input() {
                         prompt_state = PromptState::Continuation;
                         source_code.push('\n');
                     } else {
-                        eprintln!("{err}");
+                        prompt_state = PromptState::Regular;
+                        if !err.is_reported() {
+                            eprintln!("{err}");
+                        }
                     }
                 }
             };
@@ -109,3 +141,11 @@ impl ReplCommand {
         Ok(ExitCode::SUCCESS)
     }
 }
+
+fn format_values(values: Vec<LuaValue>) -> String {
+    if values.is_empty() {
+        String::new()
+    } else {
+        pretty_format_multi_value(&LuaMultiValue::from_vec(values), &FORMAT_CONFIG)
+    }
+}
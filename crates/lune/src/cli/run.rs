@@ -1,15 +1,45 @@
-use std::process::ExitCode;
+use std::{
+    env::current_exe,
+    iter::once,
+    path::{Path, PathBuf},
+    process::{ExitCode, Stdio},
+};
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use clap::Parser;
+use console::{style, Style, Term};
+use once_cell::sync::Lazy;
 use tokio::{
     fs::read as read_to_vec,
-    io::{stdin, AsyncReadExt as _},
+    io::{stdin, AsyncBufReadExt as _, AsyncRead, AsyncReadExt as _, BufReader},
+    process::Command,
+    task::JoinSet,
 };
 
-use lune::Runtime;
+use lune::{strip_shebang, Runtime};
+
+use super::utils::{
+    dotenv::load_env_files,
+    files::discover_script_path_including_lune_dirs,
+    limits::LimitArgs,
+    logging::LogArgs,
+    remote::{fetch_remote_script, is_remote_script_url},
+    sandbox::SandboxArgs,
+    stdin::{StdinFormat, StdinValue},
+    timer::TimerArgs,
+    watch::ScriptWatcher,
+};
 
-use super::utils::files::{discover_script_path_including_lune_dirs, strip_shebang};
+static PARALLEL_PREFIX_STYLES: Lazy<[Style; 6]> = Lazy::new(|| {
+    [
+        Style::new().cyan(),
+        Style::new().magenta(),
+        Style::new().yellow(),
+        Style::new().green(),
+        Style::new().blue(),
+        Style::new().red(),
+    ]
+});
 
 /// Run a script
 #[derive(Debug, Clone, Parser)]
@@ -19,12 +49,70 @@ pub struct RunCommand {
     /// If native codegen should be disabled. This is useful for benchmarking.
     #[clap(long)]
     disable_codegen: bool,
-    /// Arguments to pass to the script, stored in process.args
+    /// Run all given scripts concurrently, instead of passing arguments to a single script
+    #[clap(long)]
+    parallel: bool,
+    /// Watch the script and any modules it requires for changes, and restart it when they change
+    #[clap(long)]
+    watch: bool,
+    /// Clear the terminal every time the script is restarted using `--watch`
+    #[clap(long, requires = "watch")]
+    clear: bool,
+    /// Skip confirmation when running a script from a remote url
+    #[clap(long, short)]
+    yes: bool,
+    /// Allow running a script from an insecure `http://` url
+    #[clap(long)]
+    allow_http: bool,
+    /// Read all of stdin before running the script, and store it in a `stdin`
+    /// global - one of `text` (string), `bytes` (buffer), or `json` (decoded value)
+    #[clap(long, value_name = "FORMAT")]
+    stdin: Option<StdinFormat>,
+    /// Load environment variables from `.env.local` and `.env` files in the current directory
+    #[clap(long)]
+    dotenv: bool,
+    /// Load environment variables from the given file, may be given multiple times
+    #[clap(long = "env-file", value_name = "PATH")]
+    env_files: Vec<PathBuf>,
+    /// Load a native plugin from the given dynamic library, may be given multiple times
+    #[clap(long = "plugin", value_name = "PATH")]
+    plugins: Vec<PathBuf>,
+    /// Capture output and write it along with returned values to the given file as JSON,
+    /// used internally when running scripts using `process.spawnLune`
+    #[clap(long, value_name = "PATH", hide = true)]
+    result_file: Option<PathBuf>,
+    #[clap(flatten)]
+    sandbox: SandboxArgs,
+    #[clap(flatten)]
+    limits: LimitArgs,
+    #[clap(flatten)]
+    log: LogArgs,
+    #[clap(flatten)]
+    timer: TimerArgs,
+    /// Arguments to pass to the script, stored in process.args - flags that
+    /// Lune itself also accepts must be given after a `--` to be passed along
+    #[clap(trailing_var_arg = true, allow_hyphen_values = true)]
     script_args: Vec<String>,
 }
 
 impl RunCommand {
+    /**
+        Loads the env files given using `--env-file` and `--dotenv`.
+
+        This must be called before any other threads have been started.
+    */
+    pub fn load_env_files(&self) -> Result<()> {
+        load_env_files(&self.env_files, self.dotenv)
+    }
+
     pub async fn run(self) -> Result<ExitCode> {
+        if self.parallel {
+            return self.run_parallel().await;
+        }
+        if self.watch {
+            return self.run_watch().await;
+        }
+
         // Figure out if we should read from stdin or from a file,
         // reading from stdin is marked by passing a single "-"
         // (dash) as the script name to run to the cli
@@ -35,6 +123,10 @@ impl RunCommand {
                 .await
                 .context("Failed to read script contents from stdin")?;
             ("stdin".to_string(), stdin_contents)
+        } else if is_remote_script_url(&self.script_path) {
+            let contents =
+                fetch_remote_script(&self.script_path, self.yes, self.allow_http).await?;
+            (self.script_path.clone(), contents)
         } else {
             let file_path = discover_script_path_including_lune_dirs(&self.script_path)?;
             let file_contents = read_to_vec(&file_path).await?;
@@ -45,6 +137,32 @@ impl RunCommand {
 
         // Create a new lune runtime with all globals & run the script
         let mut rt = Runtime::new(!self.disable_codegen).with_args(self.script_args);
+        self.sandbox.apply(&mut rt)?;
+        self.limits.apply(&mut rt)?;
+        self.log.apply(&mut rt)?;
+        self.timer.apply(&mut rt)?;
+        for plugin in &self.plugins {
+            rt.load_plugin(plugin)?;
+        }
+
+        // Pre-parse stdin for filter-style scripts, if requested
+        if let Some(format) = self.stdin {
+            if &self.script_path == "-" {
+                bail!("Stdin can not be pre-parsed when the script itself is read from stdin");
+            }
+            rt.set_global("stdin", StdinValue::read(format).await?)?;
+        }
+
+        if let Some(result_file) = &self.result_file {
+            let run = rt
+                .run_captured(&script_display_name, strip_shebang(script_contents))
+                .await?;
+            let json = serde_json::to_vec(&run).context("Failed to serialize script result")?;
+            tokio::fs::write(result_file, json)
+                .await
+                .context("Failed to write script result")?;
+            return Ok(ExitCode::from(run.exit_code));
+        }
 
         let result = rt
             .run(&script_display_name, strip_shebang(script_contents))
@@ -52,10 +170,170 @@ impl RunCommand {
 
         Ok(match result {
             Err(err) => {
-                eprintln!("{err}");
+                if !err.is_reported() {
+                    eprintln!("{err}");
+                }
                 ExitCode::FAILURE
             }
             Ok((code, _)) => ExitCode::from(code),
         })
     }
+
+    /**
+        Runs all of the given scripts concurrently, each one in its own Lune process.
+
+        Output from each script is prefixed with the name of the script it came
+        from, and the resulting exit code is the exit code of the first script
+        that failed, or success if all of the scripts succeeded.
+    */
+    async fn run_parallel(self) -> Result<ExitCode> {
+        let scripts = once(self.script_path.clone())
+            .chain(self.script_args.iter().cloned())
+            .collect::<Vec<_>>();
+        if scripts.iter().any(|script| script == "-") {
+            bail!("Reading a script from stdin is not supported when running in parallel");
+        }
+
+        let exe = current_exe().context("Failed to find current executable")?;
+        let longest_name_len = scripts.iter().map(String::len).max().unwrap_or_default();
+
+        let mut children = JoinSet::new();
+        for (index, script) in scripts.into_iter().enumerate() {
+            // Make sure the script exists before spawning anything,
+            // this gives a much nicer error message for simple typos
+            discover_script_path_including_lune_dirs(&script)?;
+
+            let mut child = self
+                .child_command(&exe)
+                .arg(&script)
+                .stdin(Stdio::null())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .kill_on_drop(true)
+                .spawn()
+                .with_context(|| format!("Failed to spawn process for script '{script}'"))?;
+
+            let style = &PARALLEL_PREFIX_STYLES[index % PARALLEL_PREFIX_STYLES.len()];
+            let prefix = style
+                .apply_to(format!("[{script:<longest_name_len$}]"))
+                .to_string();
+
+            let stdout = child.stdout.take().expect("stdout is piped");
+            let stderr = child.stderr.take().expect("stderr is piped");
+            children.spawn(async move {
+                let (status, (), ()) = tokio::join!(
+                    child.wait(),
+                    write_prefixed_lines(stdout, &prefix, false),
+                    write_prefixed_lines(stderr, &prefix, true),
+                );
+                status
+            });
+        }
+
+        let mut exit_code = 0;
+        while let Some(res) = children.join_next().await {
+            let status = res?.context("Failed to wait for script process")?;
+            if exit_code == 0 && !status.success() {
+                exit_code = status
+                    .code()
+                    .and_then(|code| u8::try_from(code).ok())
+                    .filter(|code| *code != 0)
+                    .unwrap_or(1);
+            }
+        }
+
+        Ok(ExitCode::from(exit_code))
+    }
+
+    /**
+        Runs the given script in a separate Lune process, restarting it
+        whenever the script or any of the modules it requires change.
+
+        The script keeps being watched after it exits, until Lune itself is stopped.
+    */
+    async fn run_watch(self) -> Result<ExitCode> {
+        if &self.script_path == "-" || is_remote_script_url(&self.script_path) {
+            bail!("Only script files on the filesystem can be watched for changes");
+        }
+        if self.stdin.is_some() {
+            bail!("Stdin can not be pre-parsed when watching a script for changes");
+        }
+
+        let script_path = discover_script_path_including_lune_dirs(&self.script_path)?;
+        let exe = current_exe().context("Failed to find current executable")?;
+        let mut watcher = ScriptWatcher::new()?;
+
+        loop {
+            if self.clear {
+                Term::stdout().clear_screen().ok();
+            }
+
+            // NOTE: Requires may have been added or removed since the last
+            // run, so we need to figure out which files to watch every time
+            watcher.watch_script(&script_path).await?;
+
+            let mut child = self
+                .child_command(&exe)
+                .arg(&script_path)
+                .arg("--")
+                .args(&self.script_args)
+                .kill_on_drop(true)
+                .spawn()
+                .context("Failed to spawn process for script")?;
+
+            tokio::select! {
+                status = child.wait() => {
+                    let status = status.context("Failed to wait for script process")?;
+                    let message = match status.code() {
+                        Some(code) => format!("Script exited with code {code}"),
+                        None => "Script was terminated".to_string(),
+                    };
+                    eprintln!("{}", style(format!("{message} - waiting for changes...")).dim());
+                    watcher.wait_for_change().await;
+                }
+                () = watcher.wait_for_change() => {
+                    child.kill().await.ok();
+                }
+            }
+
+            eprintln!("{}", style("Change detected - restarting script...").dim());
+        }
+    }
+
+    /**
+        Creates a command for running a script in a child Lune
+        process, using the same options as this command.
+    */
+    fn child_command(&self, exe: &Path) -> Command {
+        let mut command = Command::new(exe);
+        command.arg("run");
+        if self.disable_codegen {
+            command.arg("--disable-codegen");
+        }
+        for plugin in &self.plugins {
+            command.arg("--plugin").arg(plugin);
+        }
+        command.args(self.sandbox.to_args());
+        command.args(self.limits.to_args());
+        command.args(self.log.to_args());
+        command.args(self.timer.to_args());
+        command
+    }
+}
+
+/**
+    Reads lines from the given reader until it is closed, writing
+    each one to either stdout or stderr with the given prefix.
+*/
+async fn write_prefixed_lines(reader: impl AsyncRead + Unpin, prefix: &str, to_stderr: bool) {
+    let mut lines = BufReader::new(reader).split(b'\n');
+    while let Ok(Some(line)) = lines.next_segment().await {
+        let line = String::from_utf8_lossy(&line);
+        let line = line.trim_end_matches('\r');
+        if to_stderr {
+            eprintln!("{prefix} {line}");
+        } else {
+            println!("{prefix} {line}");
+        }
+    }
 }
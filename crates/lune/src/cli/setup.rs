@@ -8,26 +8,46 @@ use clap::Parser;
 use directories::UserDirs;
 use futures_util::future::try_join_all;
 use include_dir::{include_dir, Dir};
+use serde::Serialize;
 use thiserror::Error;
 use tokio::fs;
 
 // TODO: Use a library that supports json with comments since VSCode settings may contain comments
 use serde_json::Value as JsonValue;
 
+use super::utils::output::{print_json, OutputFormat};
+
 pub(crate) static TYPEDEFS_DIR: Dir<'_> = include_dir!("types");
 
 pub(crate) static SETTING_NAME_MODE: &str = "luau-lsp.require.mode";
 pub(crate) static SETTING_NAME_ALIASES: &str = "luau-lsp.require.directoryAliases";
 
+pub(crate) static LUAURC_NAME_ALIASES: &str = "aliases";
+pub(crate) static LUAURC_ALIAS_LUNE: &str = "lune";
+
 /// Set up type definitions for your editor
 #[derive(Debug, Clone, Parser)]
-pub struct SetupCommand {}
+pub struct SetupCommand {
+    /// Output format - either `text` or `json`
+    #[clap(long, default_value = "text")]
+    output: OutputFormat,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SetupOutput {
+    version: &'static str,
+    typedefs_dir: PathBuf,
+    settings_written: bool,
+    settings: JsonValue,
+    luaurc_updated: bool,
+}
 
 impl SetupCommand {
     pub async fn run(self) -> Result<ExitCode> {
         generate_typedef_files_from_definitions(&TYPEDEFS_DIR)
             .await
-            .expect("Failed to generate typedef files");
+            .context("Failed to generate typedef files")?;
 
         // TODO: Let the user interactively choose what editor to set up
         let res = async {
@@ -38,6 +58,23 @@ impl SetupCommand {
         }
         .await;
 
+        // NOTE: Aliases in an existing .luaurc would keep pointing to the typedefs
+        // of an older Lune version after upgrading, so we update them here as well
+        let luaurc_updated = update_existing_luaurc().await.unwrap_or(false);
+
+        if self.output.is_json() {
+            let settings =
+                add_values_to_vscode_settings_json(JsonValue::Object(Default::default()));
+            print_json(&SetupOutput {
+                version: lune_version(),
+                typedefs_dir: typedefs_dir()?,
+                settings_written: res.is_ok(),
+                settings,
+                luaurc_updated,
+            })?;
+            return Ok(ExitCode::SUCCESS);
+        }
+
         let message = match res {
             Ok(()) => "These settings have been added to your workspace for Visual Studio Code:",
             Err(_) => "To finish setting up your editor, add these settings to your workspace:",
@@ -53,6 +90,11 @@ impl SetupCommand {
             \n    \"@lune/\": \"~/.lune/.typedefs/{version_string}/\"\
             \n}}",
         );
+        if luaurc_updated {
+            println!(
+                "\nThe \"{LUAURC_ALIAS_LUNE}\" alias in your .luaurc file has also been updated."
+            );
+        }
 
         Ok(ExitCode::SUCCESS)
     }
@@ -70,10 +112,19 @@ enum SetupError {
     Serialize,
 }
 
-fn lune_version() -> &'static str {
+pub(crate) fn lune_version() -> &'static str {
     env!("CARGO_PKG_VERSION")
 }
 
+fn typedefs_dir() -> Result<PathBuf> {
+    Ok(UserDirs::new()
+        .context("Failed to find user home directory")?
+        .home_dir()
+        .join(".lune")
+        .join(".typedefs")
+        .join(lune_version()))
+}
+
 fn vscode_path() -> PathBuf {
     current_dir()
         .expect("No current dir")
@@ -115,6 +166,55 @@ async fn write_vscode_settings_json(value: JsonValue) -> Result<(), SetupError>
     }
 }
 
+fn luaurc_path() -> PathBuf {
+    current_dir().expect("No current dir").join(".luaurc")
+}
+
+/**
+    Updates the `lune` alias in the `.luaurc` file in the current directory
+    to point to the current typedefs, if there is such a file.
+
+    Returns `true` if the file exists and the alias needed to be updated.
+*/
+async fn update_existing_luaurc() -> Result<bool, SetupError> {
+    let path = luaurc_path();
+    let contents = match fs::read(&path).await {
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(false),
+        Err(_) => return Err(SetupError::Read),
+        Ok(contents) => contents,
+    };
+    let Ok(luaurc) = serde_json::from_slice::<JsonValue>(&contents) else {
+        return Err(SetupError::Deserialize);
+    };
+    let modified = add_values_to_luaurc(luaurc.clone());
+    if modified == luaurc {
+        return Ok(false);
+    }
+    let Ok(json) = serde_json::to_vec_pretty(&modified) else {
+        return Err(SetupError::Serialize);
+    };
+    match fs::write(path, json).await {
+        Err(_) => Err(SetupError::Write),
+        Ok(()) => Ok(true),
+    }
+}
+
+fn add_values_to_luaurc(value: JsonValue) -> JsonValue {
+    let mut luaurc = value;
+    if let JsonValue::Object(config) = luaurc.borrow_mut() {
+        let alias_key = LUAURC_ALIAS_LUNE.to_string();
+        let alias_val = JsonValue::String(format!("~/.lune/.typedefs/{}/", lune_version()));
+        if let Some(JsonValue::Object(aliases)) = config.get_mut(LUAURC_NAME_ALIASES) {
+            aliases.insert(alias_key, alias_val);
+        } else {
+            let mut map = serde_json::Map::new();
+            map.insert(alias_key, alias_val);
+            config.insert(LUAURC_NAME_ALIASES.to_string(), JsonValue::Object(map));
+        }
+    }
+    luaurc
+}
+
 fn add_values_to_vscode_settings_json(value: JsonValue) -> JsonValue {
     let mut settings_json = value;
     if let JsonValue::Object(settings) = settings_json.borrow_mut() {
@@ -142,7 +242,7 @@ fn add_values_to_vscode_settings_json(value: JsonValue) -> JsonValue {
     settings_json
 }
 
-async fn generate_typedef_files_from_definitions(dir: &Dir<'_>) -> Result<String> {
+pub(crate) async fn generate_typedef_files_from_definitions(dir: &Dir<'_>) -> Result<String> {
     let contents = read_typedefs_dir_contents(dir);
     write_typedef_files(contents).await
 }
@@ -164,16 +264,11 @@ fn read_typedefs_dir_contents(dir: &Dir<'_>) -> HashMap<String, Vec<u8>> {
 }
 
 async fn write_typedef_files(typedef_files: HashMap<String, Vec<u8>>) -> Result<String> {
-    let version_string = env!("CARGO_PKG_VERSION");
+    let version_string = lune_version();
     let mut dirs_to_write = Vec::new();
     let mut files_to_write = Vec::new();
     // Create the typedefs dir in the users cache dir
-    let cache_dir = UserDirs::new()
-        .context("Failed to find user home directory")?
-        .home_dir()
-        .join(".lune")
-        .join(".typedefs")
-        .join(version_string);
+    let cache_dir = typedefs_dir()?;
     dirs_to_write.push(cache_dir.clone());
     // Make typedef files
     for (builtin_name, builtin_typedef) in typedef_files {
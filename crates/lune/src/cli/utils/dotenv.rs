@@ -0,0 +1,188 @@
+use std::{
+    collections::HashSet,
+    env,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+
+const DEFAULT_ENV_FILES: &[&str] = &[".env.local", ".env"];
+
+/**
+    Loads environment variables from the given env files into the current process.
+
+    If `include_defaults` is `true`, the `.env.local` and `.env` files in the
+    current directory are also loaded, if they exist, after any explicit files.
+
+    Variables that are already set are never overwritten, meaning that the
+    real environment takes precedence over any file, and files that are
+    loaded earlier take precedence over files that are loaded later.
+
+    Setting environment variables is not thread-safe, so this must
+    be called before the async runtime or any other threads are started.
+
+    # Errors
+
+    Errors if any explicitly given file does not exist, or if any file fails to parse.
+*/
+pub fn load_env_files(explicit_files: &[PathBuf], include_defaults: bool) -> Result<()> {
+    let cwd = env::current_dir().context("Failed to get current directory")?;
+    for (key, value) in read_env_files(&cwd, explicit_files, include_defaults)? {
+        env::set_var(key, value);
+    }
+    Ok(())
+}
+
+/**
+    Reads all variables from the given env files, and the default env files in the given
+    directory, that should be set in the current process, without setting any of them.
+
+    See [`load_env_files`] for the order in which files take precedence.
+*/
+fn read_env_files(
+    dir: &Path,
+    explicit_files: &[PathBuf],
+    include_defaults: bool,
+) -> Result<Vec<(String, String)>> {
+    let mut paths = explicit_files.to_vec();
+    if include_defaults {
+        paths.extend(
+            DEFAULT_ENV_FILES
+                .iter()
+                .map(|name| dir.join(name))
+                .filter(|path| path.is_file()),
+        );
+    }
+
+    let mut seen = HashSet::new();
+    let mut vars = Vec::new();
+    for path in paths {
+        let iter = dotenvy::from_path_iter(&path)
+            .with_context(|| format!("Failed to load env file at '{}'", path.display()))?;
+        for item in iter {
+            let (key, value) =
+                item.with_context(|| format!("Failed to parse env file at '{}'", path.display()))?;
+            if env::var_os(&key).is_none() && seen.insert(key.clone()) {
+                vars.push((key, value));
+            }
+        }
+    }
+    Ok(vars)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn read(
+        dir: &Path,
+        explicit_files: &[&str],
+        include_defaults: bool,
+    ) -> Result<HashMap<String, String>> {
+        let explicit_files = explicit_files
+            .iter()
+            .map(|name| dir.join(name))
+            .collect::<Vec<_>>();
+        Ok(read_env_files(dir, &explicit_files, include_defaults)?
+            .into_iter()
+            .collect())
+    }
+
+    fn write(dir: &Path, name: &str, contents: &str) {
+        std::fs::write(dir.join(name), contents).unwrap();
+    }
+
+    #[test]
+    fn explicit_files_take_precedence_over_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            dir.path(),
+            ".env",
+            "LUNE_TEST_A=default\nLUNE_TEST_B=default\n",
+        );
+        write(
+            dir.path(),
+            ".env.local",
+            "LUNE_TEST_B=local\nLUNE_TEST_C=local\n",
+        );
+        write(dir.path(), "first.env", "LUNE_TEST_A=first\n");
+        write(
+            dir.path(),
+            "second.env",
+            "LUNE_TEST_A=second\nLUNE_TEST_D=second\n",
+        );
+
+        let vars = read(dir.path(), &["first.env", "second.env"], true).unwrap();
+        assert_eq!(vars["LUNE_TEST_A"], "first");
+        assert_eq!(vars["LUNE_TEST_B"], "local");
+        assert_eq!(vars["LUNE_TEST_C"], "local");
+        assert_eq!(vars["LUNE_TEST_D"], "second");
+    }
+
+    #[test]
+    fn defaults_are_only_read_when_requested() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), ".env", "LUNE_TEST_A=default\n");
+        write(dir.path(), "explicit.env", "LUNE_TEST_B=explicit\n");
+
+        let vars = read(dir.path(), &["explicit.env"], false).unwrap();
+        assert_eq!(vars.len(), 1);
+        assert_eq!(vars["LUNE_TEST_B"], "explicit");
+    }
+
+    #[test]
+    fn real_environment_takes_precedence() {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            dir.path(),
+            ".env",
+            "PATH=overwritten\nLUNE_TEST_A=default\n",
+        );
+
+        let vars = read(dir.path(), &[], true).unwrap();
+        assert!(!vars.contains_key("PATH"));
+        assert_eq!(vars["LUNE_TEST_A"], "default");
+    }
+
+    #[test]
+    fn values_may_be_quoted() {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            dir.path(),
+            ".env",
+            concat!(
+                "# A comment\n",
+                "LUNE_TEST_PLAIN=plain\n",
+                "LUNE_TEST_SPACES=\"with spaces\"\n",
+                "LUNE_TEST_SINGLE='single # quoted'\n",
+                "LUNE_TEST_DOUBLE=\"double\\nquoted\"\n",
+                "LUNE_TEST_EMPTY=\"\"\n",
+                "export LUNE_TEST_EXPORT=exported\n",
+            ),
+        );
+
+        let vars = read(dir.path(), &[], true).unwrap();
+        assert_eq!(vars["LUNE_TEST_PLAIN"], "plain");
+        assert_eq!(vars["LUNE_TEST_SPACES"], "with spaces");
+        assert_eq!(vars["LUNE_TEST_SINGLE"], "single # quoted");
+        assert_eq!(vars["LUNE_TEST_DOUBLE"], "double\nquoted");
+        assert_eq!(vars["LUNE_TEST_EMPTY"], "");
+        assert_eq!(vars["LUNE_TEST_EXPORT"], "exported");
+    }
+
+    #[test]
+    fn missing_and_invalid_files_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let err = read(dir.path(), &["missing.env"], false).unwrap_err();
+        assert!(err.to_string().contains("missing.env"));
+
+        write(dir.path(), "invalid.env", "LUNE_TEST_A='unterminated\n");
+        let err = read(dir.path(), &["invalid.env"], false).unwrap_err();
+        assert!(err.to_string().contains("invalid.env"));
+
+        write(dir.path(), "unquoted.env", "LUNE_TEST_A=unquoted spaces\n");
+        assert!(read(dir.path(), &["unquoted.env"], false).is_err());
+    }
+}
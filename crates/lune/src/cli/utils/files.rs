@@ -162,44 +162,18 @@ pub fn discover_script_path_including_lune_dirs(path: &str) -> Result<PathBuf> {
 }
 
 pub fn parse_lune_description_from_file(contents: &str) -> Option<String> {
-    let mut comment_lines = Vec::new();
-    for line in contents.lines() {
-        if let Some(stripped) = line.strip_prefix(LUNE_COMMENT_PREFIX) {
-            comment_lines.push(stripped);
-        } else {
-            break;
-        }
-    }
+    // NOTE: Shebangs and type checking directives such as `--!strict`
+    // must be at the very top of a file, so the description comes after them
+    let comment_lines = contents
+        .lines()
+        .skip_while(|line| line.starts_with("#!") || line.starts_with("--!"))
+        .map_while(|line| line.strip_prefix(LUNE_COMMENT_PREFIX))
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>();
     if comment_lines.is_empty() {
         None
     } else {
-        let shortest_indent = comment_lines.iter().fold(usize::MAX, |acc, line| {
-            let first_alphanumeric = line.find(char::is_alphanumeric).unwrap();
-            acc.min(first_alphanumeric)
-        });
-        let unindented_lines = comment_lines
-            .iter()
-            .map(|line| line[shortest_indent..].to_string())
-            .collect::<Vec<_>>()
-            .join(" ");
-        Some(unindented_lines)
-    }
-}
-
-pub fn strip_shebang(mut contents: Vec<u8>) -> Vec<u8> {
-    if contents.starts_with(b"#!") {
-        if let Some(first_newline_idx) =
-            contents
-                .iter()
-                .enumerate()
-                .find_map(|(idx, c)| if *c == b'\n' { Some(idx) } else { None })
-        {
-            // NOTE: We keep the newline here on purpose to preserve
-            // correct line numbers in stack traces, the only reason
-            // we strip the shebang is to get the lua script to parse
-            // and the extra newline is not really a problem for that
-            contents.drain(..first_newline_idx);
-        }
+        Some(comment_lines.join(" "))
     }
-    contents
 }
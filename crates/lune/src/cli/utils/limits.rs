@@ -0,0 +1,222 @@
+use std::{str::FromStr, time::Duration};
+
+use anyhow::Result;
+use clap::Args;
+
+use lune::Runtime;
+
+/**
+    How long to wait for a script to stop after its time limit has been
+    reached, before forcefully exiting, in case it is stuck in native code.
+*/
+const TIMEOUT_GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+/**
+    Arguments for limiting the resources that a script may use.
+*/
+#[derive(Debug, Default, Clone, Args)]
+pub struct LimitArgs {
+    /// Maximum amount of memory the script may use, such as `512MB` or `2GB`
+    #[clap(long, value_name = "SIZE")]
+    max_memory: Option<ByteSize>,
+    /// Maximum amount of time the script may run for, such as `60s` or `5m`
+    #[clap(long, value_name = "DURATION")]
+    timeout: Option<TimeoutDuration>,
+}
+
+impl LimitArgs {
+    /**
+        Applies all of the limits to the given runtime.
+    */
+    pub fn apply(&self, rt: &mut Runtime) -> Result<()> {
+        if let Some(max_memory) = self.max_memory {
+            rt.set_memory_limit(max_memory.0)?;
+        }
+        if let Some(timeout) = self.timeout {
+            rt.set_time_limit(timeout.0, TIMEOUT_GRACE_PERIOD);
+        }
+        Ok(())
+    }
+
+    /**
+        Converts the limits back into command line arguments,
+        so that they may be passed along to a child Lune process.
+    */
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(max_memory) = self.max_memory {
+            args.push(format!("--max-memory={}", max_memory.0));
+        }
+        if let Some(timeout) = self.timeout {
+            args.push(format!("--timeout={}ms", timeout.0.as_millis()));
+        }
+        args
+    }
+}
+
+/**
+    A size in bytes, parsed from a string such as `512MB`.
+
+    Units are case-insensitive and in powers of 1024,
+    a number without a unit is a number of bytes.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByteSize(usize);
+
+impl FromStr for ByteSize {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        let (number, unit) = s.split_at(split);
+
+        let number = number
+            .parse::<usize>()
+            .map_err(|_| format!("invalid size '{s}', expected a number such as `512MB`"))?;
+        let multiplier: usize = match unit.trim().to_ascii_lowercase().as_str() {
+            "" | "b" => 1,
+            "k" | "kb" | "kib" => 1024,
+            "m" | "mb" | "mib" => 1024 * 1024,
+            "g" | "gb" | "gib" => 1024 * 1024 * 1024,
+            _ => {
+                return Err(format!(
+                    "invalid size unit '{unit}', expected one of B, KB, MB, GB"
+                ))
+            }
+        };
+
+        number
+            .checked_mul(multiplier)
+            .map(Self)
+            .ok_or_else(|| format!("size '{s}' is too large"))
+    }
+}
+
+/**
+    A duration, parsed from a string such as `60s`.
+
+    Supported units are `ms`, `s`, `m`, and `h`,
+    a number without a unit is a number of seconds.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeoutDuration(Duration);
+
+impl FromStr for TimeoutDuration {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        let (number, unit) = s.split_at(split);
+
+        let number = number
+            .parse::<u64>()
+            .map_err(|_| format!("invalid duration '{s}', expected a number such as `60s`"))?;
+        let duration = match unit.trim().to_ascii_lowercase().as_str() {
+            "ms" => Some(Duration::from_millis(number)),
+            "" | "s" => Some(Duration::from_secs(number)),
+            "m" => number.checked_mul(60).map(Duration::from_secs),
+            "h" => number.checked_mul(60 * 60).map(Duration::from_secs),
+            _ => {
+                return Err(format!(
+                    "invalid duration unit '{unit}', expected one of ms, s, m, h"
+                ))
+            }
+        };
+
+        duration
+            .map(Self)
+            .ok_or_else(|| format!("duration '{s}' is too large"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bytes(s: &str) -> Result<usize, String> {
+        s.parse::<ByteSize>().map(|size| size.0)
+    }
+
+    fn duration(s: &str) -> Result<Duration, String> {
+        s.parse::<TimeoutDuration>().map(|timeout| timeout.0)
+    }
+
+    #[test]
+    fn byte_sizes_parse_units() {
+        assert_eq!(bytes("512"), Ok(512));
+        assert_eq!(bytes("512B"), Ok(512));
+        assert_eq!(bytes("4kb"), Ok(4 * 1024));
+        assert_eq!(bytes("4KiB"), Ok(4 * 1024));
+        assert_eq!(bytes("512MB"), Ok(512 * 1024 * 1024));
+        assert_eq!(bytes("512 m"), Ok(512 * 1024 * 1024));
+        assert_eq!(bytes(" 2GB "), Ok(2 * 1024 * 1024 * 1024));
+    }
+
+    #[test]
+    fn byte_sizes_reject_overflow() {
+        let too_many_kilobytes = format!("{}KB", usize::MAX / 1024 + 1);
+        assert!(bytes(&too_many_kilobytes)
+            .unwrap_err()
+            .contains("too large"));
+        assert!(bytes(&format!("{}0", usize::MAX)).is_err());
+        assert_eq!(bytes(&usize::MAX.to_string()), Ok(usize::MAX));
+    }
+
+    #[test]
+    fn byte_sizes_reject_invalid_input() {
+        assert!(bytes("").is_err());
+        assert!(bytes("MB").is_err());
+        assert!(bytes("-1MB").is_err());
+        assert!(bytes("1.5GB").is_err());
+        assert!(bytes("12TB").unwrap_err().contains("unit"));
+        assert!(bytes("12 MB extra").is_err());
+    }
+
+    #[test]
+    fn durations_parse_units() {
+        assert_eq!(duration("60"), Ok(Duration::from_secs(60)));
+        assert_eq!(duration("60s"), Ok(Duration::from_secs(60)));
+        assert_eq!(duration("250ms"), Ok(Duration::from_millis(250)));
+        assert_eq!(duration("5m"), Ok(Duration::from_secs(5 * 60)));
+        assert_eq!(duration("2H"), Ok(Duration::from_secs(2 * 60 * 60)));
+        assert_eq!(duration(" 10 s "), Ok(Duration::from_secs(10)));
+    }
+
+    #[test]
+    fn durations_reject_overflow() {
+        let too_many_minutes = format!("{}m", u64::MAX / 60 + 1);
+        assert!(duration(&too_many_minutes)
+            .unwrap_err()
+            .contains("too large"));
+        let too_many_hours = format!("{}h", u64::MAX / (60 * 60) + 1);
+        assert!(duration(&too_many_hours).unwrap_err().contains("too large"));
+        assert!(duration(&format!("{}0s", u64::MAX)).is_err());
+        assert_eq!(
+            duration(&format!("{}ms", u64::MAX)),
+            Ok(Duration::from_millis(u64::MAX))
+        );
+    }
+
+    #[test]
+    fn durations_reject_invalid_input() {
+        assert!(duration("").is_err());
+        assert!(duration("s").is_err());
+        assert!(duration("-5s").is_err());
+        assert!(duration("1.5s").is_err());
+        assert!(duration("3d").unwrap_err().contains("unit"));
+        assert!(duration("60 seconds").is_err());
+    }
+
+    #[test]
+    fn limits_roundtrip_through_args() {
+        let limits = LimitArgs {
+            max_memory: Some("1KB".parse().unwrap()),
+            timeout: Some("2s".parse().unwrap()),
+        };
+        assert_eq!(limits.to_args(), ["--max-memory=1024", "--timeout=2000ms"]);
+        for arg in limits.to_args() {
+            let (_, value) = arg.split_once('=').unwrap();
+            assert!(bytes(value).is_ok() || duration(value).is_ok());
+        }
+    }
+}
@@ -1,6 +1,11 @@
 #![allow(clippy::match_same_arms)]
 
-use std::{cmp::Ordering, ffi::OsStr, fmt::Write as _, path::PathBuf};
+use std::{
+    cmp::Ordering,
+    ffi::OsStr,
+    fmt::Write as _,
+    path::{Path, PathBuf},
+};
 
 use anyhow::{bail, Result};
 use console::Style;
@@ -29,6 +34,11 @@ pub async fn find_lune_scripts(in_home_dir: bool) -> Result<Vec<(String, String)
             while let Some(entry) = dir.next_entry().await? {
                 let meta = entry.metadata().await?;
                 if meta.is_file() {
+                    // Only read files that are actually scripts, the lune
+                    // directory may also contain data or other large files
+                    if !is_script_file_path(&entry.path()) {
+                        continue;
+                    }
                     let contents = fs::read(entry.path()).await?;
                     files.push((entry, meta, contents));
                 } else if meta.is_dir() {
@@ -50,10 +60,7 @@ pub async fn find_lune_scripts(in_home_dir: bool) -> Result<Vec<(String, String)
             let parsed: Vec<_> = files
                 .iter()
                 .filter(|(entry, _, _)| {
-                    let mut is_match = matches!(
-                        entry.path().extension().and_then(OsStr::to_str),
-                        Some("lua" | "luau")
-                    );
+                    let mut is_match = is_script_file_path(&entry.path());
 
                     // If the entry is not a lua or luau file, and is a directory,
                     // then we check if it contains a init.lua(u), and if it does,
@@ -87,6 +94,13 @@ pub async fn find_lune_scripts(in_home_dir: bool) -> Result<Vec<(String, String)
     }
 }
 
+fn is_script_file_path(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(OsStr::to_str),
+        Some("lua" | "luau")
+    )
+}
+
 pub fn sort_lune_scripts(scripts: Vec<(String, String)>) -> Vec<(String, String)> {
     let mut sorted = scripts;
     sorted.sort_by(|left, right| {
@@ -0,0 +1,49 @@
+use anyhow::Result;
+use clap::Args;
+
+use lune::Runtime;
+
+/**
+    Arguments for configuring the output of the `log` standard library.
+*/
+#[derive(Debug, Default, Clone, Args)]
+pub struct LogArgs {
+    /// Minimum level of log records for the script to write, overrides the `LUNE_LOG_LEVEL`
+    /// environment variable - this is separate from `--log-level`, which is for the runtime
+    #[clap(long, value_name = "LEVEL", value_parser = ["trace", "debug", "info", "warn", "error"])]
+    script_log_level: Option<String>,
+    /// Format for the script to write log records in,
+    /// overrides the `LUNE_LOG_FORMAT` environment variable
+    #[clap(long, value_name = "FORMAT", value_parser = ["pretty", "json"])]
+    script_log_format: Option<String>,
+}
+
+impl LogArgs {
+    /**
+        Applies the log configuration to the given runtime.
+    */
+    pub fn apply(&self, rt: &mut Runtime) -> Result<()> {
+        if let Some(level) = &self.script_log_level {
+            rt.set_log_level(level)?;
+        }
+        if let Some(format) = &self.script_log_format {
+            rt.set_log_format(format)?;
+        }
+        Ok(())
+    }
+
+    /**
+        Converts the log configuration back into command line arguments,
+        so that it may be passed along to a child Lune process.
+    */
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(level) = &self.script_log_level {
+            args.push(format!("--script-log-level={level}"));
+        }
+        if let Some(format) = &self.script_log_format {
+            args.push(format!("--script-log-format={format}"));
+        }
+        args
+    }
+}
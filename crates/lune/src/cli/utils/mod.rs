@@ -1,2 +1,11 @@
+pub mod dotenv;
 pub mod files;
+pub mod limits;
 pub mod listing;
+pub mod logging;
+pub mod output;
+pub mod remote;
+pub mod sandbox;
+pub mod stdin;
+pub mod timer;
+pub mod watch;
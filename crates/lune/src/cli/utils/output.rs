@@ -0,0 +1,42 @@
+use std::str::FromStr;
+
+use anyhow::Result;
+use serde::Serialize;
+
+/**
+    Format used for output of CLI commands.
+
+    Text output is meant for humans and may change between versions,
+    JSON output is meant to be consumed by external tools.
+*/
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+impl OutputFormat {
+    pub fn is_json(self) -> bool {
+        matches!(self, Self::Json)
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = &'static str;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            _ => Err("invalid output format, expected one of `text` or `json`"),
+        }
+    }
+}
+
+/**
+    Prints the given value to stdout as a single line of JSON.
+*/
+pub fn print_json<T: Serialize>(value: &T) -> Result<()> {
+    println!("{}", serde_json::to_string(value)?);
+    Ok(())
+}
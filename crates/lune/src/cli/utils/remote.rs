@@ -0,0 +1,201 @@
+use std::{
+    collections::HashMap,
+    io::IsTerminal,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+use console::style;
+use dialoguer::Confirm;
+use directories::UserDirs;
+use sha2::{Digest, Sha256};
+use similar::{ChangeTag, TextDiff};
+use tokio::fs;
+
+/**
+    Returns `true` if the given script path is a remote url that should be fetched.
+*/
+pub fn is_remote_script_url(path: &str) -> bool {
+    path.starts_with("https://") || path.starts_with("http://")
+}
+
+/**
+    Fetches a remote script, and asks the user to confirm that it should be run.
+
+    The user is shown the SHA256 hash of the script together with either its full
+    source code, or a diff against the source code that was previously trusted.
+    All of this is written to stderr, so that it never mixes with script output.
+
+    Scripts that exactly match a previously trusted script are not shown again,
+    and confirmation may be skipped entirely by passing `skip_confirmation`.
+    Scripts are only trusted for later runs once confirmed interactively, so
+    that skipping confirmation, for example in CI, never trusts anything.
+
+    Scripts from plain `http://` urls could be modified by anyone in between
+    us and the server, and are rejected unless `allow_http` is passed.
+
+    # Errors
+
+    Errors if the url is insecure and not allowed, if the script
+    could not be fetched, or if the user declined to run it.
+*/
+pub async fn fetch_remote_script(
+    url: &str,
+    skip_confirmation: bool,
+    allow_http: bool,
+) -> Result<Vec<u8>> {
+    fetch_remote_script_in(&remote_scripts_dir()?, url, skip_confirmation, allow_http).await
+}
+
+async fn fetch_remote_script_in(
+    dir: &Path,
+    url: &str,
+    skip_confirmation: bool,
+    allow_http: bool,
+) -> Result<Vec<u8>> {
+    if url.starts_with("http://") && !allow_http {
+        bail!(
+            "Refusing to run remote script from insecure url '{url}'\
+            \nUse an https:// url instead, or pass --allow-http to run it anyway"
+        );
+    }
+
+    let response = reqwest::get(url)
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .with_context(|| format!("Failed to fetch remote script from '{url}'"))?;
+    let contents = response.bytes().await?.to_vec();
+    let hash = format!("{:x}", Sha256::digest(&contents));
+
+    let mut trusted = read_trusted_hashes(dir).await;
+    let previous_hash = trusted.get(url).cloned();
+    if previous_hash.as_deref() == Some(hash.as_str()) {
+        return Ok(contents);
+    }
+
+    let source = String::from_utf8_lossy(&contents);
+    eprintln!("Remote script {}", style(url).blue());
+    eprintln!("SHA256 {}\n", style(&hash).dim());
+    let previous_source = match &previous_hash {
+        Some(previous) => fs::read_to_string(dir.join(previous).with_extension("luau"))
+            .await
+            .ok(),
+        None => None,
+    };
+    match previous_source {
+        Some(previous_source) => {
+            eprintln!("Changes since this script was last trusted:\n");
+            print_source_diff(&previous_source, &source);
+        }
+        None => eprintln!("{source}"),
+    }
+    eprintln!();
+
+    if skip_confirmation {
+        return Ok(contents);
+    }
+    if !std::io::stdin().is_terminal() {
+        bail!("Remote scripts must be confirmed interactively, or run with --yes");
+    }
+    let confirmed = Confirm::new()
+        .with_prompt("Run this script?")
+        .default(false)
+        .interact()?;
+    if !confirmed {
+        bail!("Remote script was not trusted, aborting");
+    }
+
+    fs::create_dir_all(dir).await?;
+    fs::write(dir.join(&hash).with_extension("luau"), &contents).await?;
+    trusted.insert(url.to_string(), hash);
+    fs::write(
+        dir.join("trusted.json"),
+        serde_json::to_vec_pretty(&trusted)?,
+    )
+    .await?;
+
+    Ok(contents)
+}
+
+fn remote_scripts_dir() -> Result<PathBuf> {
+    Ok(UserDirs::new()
+        .context("Failed to find user home directory")?
+        .home_dir()
+        .join(".lune")
+        .join(".remote"))
+}
+
+async fn read_trusted_hashes(dir: &Path) -> HashMap<String, String> {
+    match fs::read(dir.join("trusted.json")).await {
+        Ok(contents) => serde_json::from_slice(&contents).unwrap_or_default(),
+        Err(_) => HashMap::new(),
+    }
+}
+
+fn print_source_diff(old: &str, new: &str) {
+    let diff = TextDiff::from_lines(old, new);
+    for change in diff.iter_all_changes() {
+        let line = change.value().trim_end_matches('\n');
+        match change.tag() {
+            ChangeTag::Equal => eprintln!("  {}", style(line).dim()),
+            ChangeTag::Delete => eprintln!("{}", style(format!("- {line}")).red()),
+            ChangeTag::Insert => eprintln!("{}", style(format!("+ {line}")).green()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io::Write, net::TcpListener};
+
+    use super::*;
+
+    /**
+        Serves the given script once over plain http on a local port, returning its url.
+    */
+    fn serve_script_once(contents: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/script.luau", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            // NOTE: The request is never read, the response is
+            // simply written back and the connection is closed
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{contents}",
+                contents.len()
+            )
+            .unwrap();
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn skipped_confirmations_do_not_trust_scripts() {
+        let dir = tempfile::tempdir().unwrap();
+        let url = serve_script_once("print('hello')");
+
+        let contents = fetch_remote_script_in(dir.path(), &url, true, true)
+            .await
+            .unwrap();
+        assert_eq!(contents, b"print('hello')");
+        assert!(read_trusted_hashes(dir.path()).await.is_empty());
+        assert!(!dir.path().join("trusted.json").exists());
+    }
+
+    #[tokio::test]
+    async fn insecure_urls_are_rejected() {
+        let err = fetch_remote_script("http://localhost:1/script.luau", true, false)
+            .await
+            .expect_err("insecure url should be rejected");
+        assert!(err.to_string().contains("--allow-http"));
+    }
+
+    #[tokio::test]
+    async fn insecure_urls_may_be_allowed() {
+        let err = fetch_remote_script("http://localhost:1/script.luau", true, true)
+            .await
+            .expect_err("nothing should be listening on port 1");
+        assert!(err.to_string().contains("Failed to fetch"));
+    }
+}
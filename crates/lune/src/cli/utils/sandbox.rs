@@ -0,0 +1,293 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Args;
+
+use lune::Runtime;
+
+/**
+    Arguments for restricting what a script is allowed to access.
+*/
+#[derive(Debug, Default, Clone, Args)]
+pub struct SandboxArgs {
+    /// Disable the `fs` standard library, and deny filesystem access to all other
+    /// libraries - modules may still be loaded from the filesystem using `require`
+    #[clap(long)]
+    no_fs: bool,
+    /// Disable the `net` standard library, and deny network access to all other libraries
+    #[clap(long)]
+    no_net: bool,
+    /// Disable the `process` standard library
+    #[clap(long)]
+    no_process: bool,
+    /// Disable the given standard libraries, separated by commas
+    #[clap(long, value_name = "LIBRARIES", value_delimiter = ',')]
+    disable_lib: Option<Vec<String>>,
+    /// Disable all standard libraries except for the given ones, separated by commas
+    #[clap(long, value_name = "LIBRARIES", value_delimiter = ',')]
+    allow_lib: Option<Vec<String>>,
+    /// Only allow reading files inside of the given paths, separated by commas - this
+    /// does not apply to modules loaded using `require`, which may be read from anywhere
+    #[clap(long, value_name = "PATHS", value_delimiter = ',')]
+    allow_read: Option<Vec<PathBuf>>,
+    /// Only allow writing files inside of the given paths, separated by commas
+    #[clap(long, value_name = "PATHS", value_delimiter = ',')]
+    allow_write: Option<Vec<PathBuf>>,
+    /// Only allow network access to the given hosts, in `host[:port]` format, separated by commas
+    #[clap(long, value_name = "HOSTS", value_delimiter = ',')]
+    allow_net: Option<Vec<String>>,
+}
+
+impl SandboxArgs {
+    /**
+        Applies all of the restrictions to the given runtime.
+    */
+    pub fn apply(&self, rt: &mut Runtime) -> Result<()> {
+        if let Some(allowed) = &self.allow_lib {
+            rt.restrict_libraries(non_empty(allowed))?;
+        }
+        if let Some(disabled) = &self.disable_lib {
+            rt.disable_libraries(non_empty(disabled))?;
+        }
+        let disabled = [
+            (self.no_fs, "fs"),
+            (self.no_net, "net"),
+            (self.no_process, "process"),
+        ];
+        rt.disable_libraries(
+            disabled
+                .into_iter()
+                .filter_map(|(disabled, name)| disabled.then_some(name)),
+        )?;
+        // NOTE: Other libraries, such as `archive`, `sqlite` and `roblox`, also
+        // access files and the network, so disabling `fs` or `net` must also
+        // deny all access to them instead of only removing the library itself
+        let (allow_read, allow_write) = if self.no_fs {
+            (Some(&[][..]), Some(&[][..]))
+        } else {
+            (self.allow_read.as_deref(), self.allow_write.as_deref())
+        };
+        let allow_net = if self.no_net {
+            Some(&[][..])
+        } else {
+            self.allow_net.as_deref()
+        };
+        // NOTE: An empty list, such as `--allow-read=`, is parsed as a
+        // single empty value, and must allow nothing rather than the
+        // current directory, which an empty path would otherwise resolve to
+        if let Some(paths) = allow_read {
+            rt.allow_fs_read(non_empty(paths));
+        }
+        if let Some(paths) = allow_write {
+            rt.allow_fs_write(non_empty(paths));
+        }
+        if let Some(hosts) = allow_net {
+            rt.allow_net_hosts(non_empty(hosts))?;
+        }
+        Ok(())
+    }
+
+    /**
+        Converts the restrictions back into command line arguments,
+        so that they may be passed along to a child Lune process.
+    */
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if self.no_fs {
+            args.push("--no-fs".to_string());
+        }
+        if self.no_net {
+            args.push("--no-net".to_string());
+        }
+        if self.no_process {
+            args.push("--no-process".to_string());
+        }
+        if let Some(disabled) = &self.disable_lib {
+            args.push(format!("--disable-lib={}", disabled.join(",")));
+        }
+        if let Some(allowed) = &self.allow_lib {
+            args.push(format!("--allow-lib={}", allowed.join(",")));
+        }
+        if let Some(paths) = &self.allow_read {
+            args.push(format!("--allow-read={}", join_paths(paths)));
+        }
+        if let Some(paths) = &self.allow_write {
+            args.push(format!("--allow-write={}", join_paths(paths)));
+        }
+        if let Some(hosts) = &self.allow_net {
+            args.push(format!("--allow-net={}", hosts.join(",")));
+        }
+        args
+    }
+}
+
+fn join_paths(paths: &[PathBuf]) -> String {
+    paths
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn non_empty<T: AsRef<std::ffi::OsStr>>(values: &[T]) -> impl Iterator<Item = &T> {
+    values.iter().filter(|value| !value.as_ref().is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+
+    fn no_fs() -> SandboxArgs {
+        SandboxArgs {
+            no_fs: true,
+            ..SandboxArgs::default()
+        }
+    }
+
+    fn no_net() -> SandboxArgs {
+        SandboxArgs {
+            no_net: true,
+            ..SandboxArgs::default()
+        }
+    }
+
+    /**
+        Runs the given script in the given directory with the given
+        restrictions applied, returning the error message it errored with.
+    */
+    async fn run_sandboxed(args: &SandboxArgs, dir: &Path, script: &str) -> String {
+        let mut rt = Runtime::new(false);
+        args.apply(&mut rt).unwrap();
+        let script = format!(
+            "local dir = {dir:?}\nlocal ok, err = pcall(function()\n{script}\nend)\nassert(not ok)\nreturn tostring(err)",
+            dir = dir.display().to_string()
+        );
+        let run = rt.run_captured("sandbox", script).await.unwrap();
+        assert!(run.success(), "script should error: {}", run.stderr);
+        run.values[0].as_str().unwrap().to_string()
+    }
+
+    #[cfg(feature = "std-archive")]
+    #[tokio::test]
+    async fn no_fs_denies_archive_files() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("source")).unwrap();
+        let err = run_sandboxed(
+            &no_fs(),
+            dir.path(),
+            r#"require("@lune/archive").create(dir .. "/out.zip", dir .. "/source")"#,
+        )
+        .await;
+        assert!(err.contains("is not allowed"), "{err}");
+        assert!(!dir.path().join("out.zip").exists());
+    }
+
+    #[cfg(feature = "std-sqlite")]
+    #[tokio::test]
+    async fn no_fs_denies_sqlite_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let err = run_sandboxed(
+            &no_fs(),
+            dir.path(),
+            r#"require("@lune/sqlite").open(dir .. "/db.sqlite")"#,
+        )
+        .await;
+        assert!(err.contains("is not allowed"), "{err}");
+        assert!(!dir.path().join("db.sqlite").exists());
+    }
+
+    #[cfg(feature = "std-roblox")]
+    #[tokio::test]
+    async fn no_fs_denies_roblox_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let err = run_sandboxed(
+            &no_fs(),
+            dir.path(),
+            r#"
+            local roblox = require("@lune/roblox")
+            local ok, err = pcall(roblox.serializePlaceToFile, roblox.Instance.new("DataModel"), dir .. "/place.rbxl")
+            assert(not ok)
+            roblox.serializeModelToFile({ roblox.Instance.new("Folder") }, dir .. "/model.rbxm")
+            "#,
+        )
+        .await;
+        assert!(err.contains("is not allowed"), "{err}");
+        assert!(!dir.path().join("place.rbxl").exists());
+        assert!(!dir.path().join("model.rbxm").exists());
+    }
+
+    #[cfg(feature = "std-bench")]
+    #[tokio::test]
+    async fn no_fs_denies_bench_baselines() {
+        let dir = tempfile::tempdir().unwrap();
+        let err = run_sandboxed(
+            &no_fs(),
+            dir.path(),
+            r#"require("@lune/bench").run("noop", function() end, { iterations = 1, baseline = dir .. "/baseline.json" })"#,
+        )
+        .await;
+        assert!(err.contains("is not allowed"), "{err}");
+        assert!(!dir.path().join("baseline.json").exists());
+    }
+
+    #[cfg(feature = "std-net")]
+    #[tokio::test]
+    async fn no_fs_denies_net_response_caches() {
+        let dir = tempfile::tempdir().unwrap();
+        let err = run_sandboxed(
+            &no_fs(),
+            dir.path(),
+            r#"require("@lune/net").request({ url = "http://127.0.0.1:1", options = { cacheDir = dir .. "/cache" } })"#,
+        )
+        .await;
+        assert!(err.contains("is not allowed"), "{err}");
+        assert!(!dir.path().join("cache").exists());
+    }
+
+    #[cfg(feature = "std-roblox")]
+    #[tokio::test]
+    async fn no_net_denies_roblox_asset_downloads() {
+        let dir = tempfile::tempdir().unwrap();
+        let err = run_sandboxed(
+            &no_net(),
+            dir.path(),
+            r#"require("@lune/roblox").downloadAsset(1818)"#,
+        )
+        .await;
+        assert!(err.contains("is not allowed"), "{err}");
+    }
+
+    #[cfg(all(unix, feature = "std-fs"))]
+    #[tokio::test]
+    async fn allow_read_denies_copying_symlinks() {
+        let dir = tempfile::tempdir().unwrap();
+        let allowed = dir.path().join("allowed");
+        let secret = dir.path().join("secret");
+        std::fs::create_dir_all(allowed.join("source")).unwrap();
+        std::fs::create_dir(&secret).unwrap();
+        std::fs::write(secret.join("secret.txt"), "secret").unwrap();
+        std::os::unix::fs::symlink(&secret, allowed.join("source").join("link")).unwrap();
+
+        let args = SandboxArgs {
+            allow_read: Some(vec![allowed.clone()]),
+            allow_write: Some(vec![allowed.clone()]),
+            ..SandboxArgs::default()
+        };
+        let err = run_sandboxed(
+            &args,
+            &allowed,
+            r#"require("@lune/fs").copy(dir .. "/source", dir .. "/copy")"#,
+        )
+        .await;
+        assert!(err.contains("Symlinks are not yet supported"), "{err}");
+        assert!(!allowed.join("copy/link/secret.txt").exists());
+    }
+
+    #[test]
+    fn disabled_libraries_are_passed_to_child_processes() {
+        assert_eq!(no_fs().to_args(), ["--no-fs"]);
+        assert_eq!(no_net().to_args(), ["--no-net"]);
+    }
+}
@@ -0,0 +1,79 @@
+use std::str::FromStr;
+
+use anyhow::{Context, Result};
+use mlua::prelude::*;
+use tokio::io::{stdin, AsyncReadExt as _};
+
+/**
+    A format to pre-parse piped stdin contents into, before a script runs.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StdinFormat {
+    Text,
+    Bytes,
+    Json,
+}
+
+impl FromStr for StdinFormat {
+    type Err = &'static str;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "text" => Ok(Self::Text),
+            "bytes" => Ok(Self::Bytes),
+            "json" => Ok(Self::Json),
+            _ => Err("invalid stdin format, expected one of `text`, `bytes`, or `json`"),
+        }
+    }
+}
+
+/**
+    Piped stdin contents, parsed into a value that may be given to a script as a global.
+
+    - Text is given as a string
+    - Bytes are given as a buffer
+    - JSON is given as a decoded Lua value
+*/
+#[derive(Debug, Clone)]
+pub enum StdinValue {
+    Text(String),
+    Bytes(Vec<u8>),
+    Json(serde_json::Value),
+}
+
+impl StdinValue {
+    /**
+        Reads the entire contents of stdin and parses it using the given format.
+    */
+    pub async fn read(format: StdinFormat) -> Result<Self> {
+        let mut contents = Vec::new();
+        stdin()
+            .read_to_end(&mut contents)
+            .await
+            .context("Failed to read from stdin")?;
+        Ok(match format {
+            StdinFormat::Text => {
+                Self::Text(String::from_utf8(contents).context("Stdin was not valid UTF-8")?)
+            }
+            StdinFormat::Bytes => Self::Bytes(contents),
+            StdinFormat::Json => {
+                Self::Json(serde_json::from_slice(&contents).context("Stdin was not valid JSON")?)
+            }
+        })
+    }
+}
+
+impl IntoLua<'_> for StdinValue {
+    fn into_lua(self, lua: &Lua) -> LuaResult<LuaValue> {
+        match self {
+            Self::Text(text) => text.into_lua(lua),
+            Self::Bytes(bytes) => lua.create_buffer(bytes)?.into_lua(lua),
+            Self::Json(json) => {
+                let options = LuaSerializeOptions::new()
+                    .set_array_metatable(false)
+                    .serialize_none_to_null(false)
+                    .serialize_unit_to_null(false);
+                lua.to_value_with(&json, options)
+            }
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use anyhow::Result;
+use clap::Args;
+
+use lune::Runtime;
+
+/**
+    Arguments for configuring the timer used by the `task` standard library.
+*/
+#[derive(Debug, Default, Clone, Args)]
+pub struct TimerArgs {
+    /// Resolution of `task.wait` and `task.delay`, such as `1ms` or `100us`, overrides the
+    /// `LUNE_TIMER_RESOLUTION` environment variable - values below `1ms` use more CPU
+    #[clap(long, value_name = "DURATION")]
+    timer_resolution: Option<String>,
+}
+
+impl TimerArgs {
+    /**
+        Applies the timer configuration to the given runtime.
+    */
+    pub fn apply(&self, rt: &mut Runtime) -> Result<()> {
+        if let Some(resolution) = &self.timer_resolution {
+            rt.set_timer_resolution(resolution)?;
+        }
+        Ok(())
+    }
+
+    /**
+        Converts the timer configuration back into command line arguments,
+        so that it may be passed along to a child Lune process.
+    */
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(resolution) = &self.timer_resolution {
+            args.push(format!("--timer-resolution={resolution}"));
+        }
+        args
+    }
+}
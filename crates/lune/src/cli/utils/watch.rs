@@ -0,0 +1,107 @@
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use tokio::{fs, sync::mpsc, time::timeout};
+
+use crate::standalone::tracer::trace_requires;
+
+// NOTE: Editors and formatters commonly write a file several times
+// in quick succession when saving, so we wait for changes to settle
+const DEBOUNCE_DURATION: Duration = Duration::from_millis(100);
+
+/**
+    Watches a script, and all of the modules it requires, for changes.
+*/
+pub struct ScriptWatcher {
+    watcher: RecommendedWatcher,
+    events: mpsc::UnboundedReceiver<Event>,
+    watched_dirs: HashSet<PathBuf>,
+    watched_files: HashSet<PathBuf>,
+}
+
+impl ScriptWatcher {
+    /**
+        Creates a new watcher, which is not yet watching any files.
+    */
+    pub fn new() -> Result<Self> {
+        let (event_tx, events) = mpsc::unbounded_channel();
+        let watcher = notify::recommended_watcher(move |res: notify::Result<Event>| {
+            if let Ok(event) = res {
+                event_tx.send(event).ok();
+            }
+        })
+        .context("Failed to create file watcher")?;
+        Ok(Self {
+            watcher,
+            events,
+            watched_dirs: HashSet::new(),
+            watched_files: HashSet::new(),
+        })
+    }
+
+    /**
+        Finds the given script and all of the modules that it requires, and
+        starts watching them, replacing any previously watched files.
+
+        Requires are traced again every time this is called,
+        so that any newly added requires are also watched.
+    */
+    pub async fn watch_script(&mut self, script_path: &Path) -> Result<()> {
+        let script_contents = fs::read(script_path)
+            .await
+            .context("Failed to read script to watch")?;
+        let modules = trace_requires(script_path, &script_contents).await?;
+
+        let script_dir = script_path.parent().unwrap_or(Path::new(""));
+        let mut files = HashSet::new();
+        for path in std::iter::once(script_path.to_path_buf())
+            .chain(modules.keys().map(|module| script_dir.join(module)))
+        {
+            if let Ok(path) = fs::canonicalize(&path).await {
+                files.insert(path);
+            }
+        }
+
+        // NOTE: We watch directories instead of files, since many editors save
+        // files by replacing them, which would otherwise make us stop watching
+        for dir in files.iter().filter_map(|file| file.parent()) {
+            if !self.watched_dirs.contains(dir) {
+                self.watcher
+                    .watch(dir, RecursiveMode::NonRecursive)
+                    .with_context(|| format!("Failed to watch directory '{}'", dir.display()))?;
+                self.watched_dirs.insert(dir.to_path_buf());
+            }
+        }
+
+        self.watched_files = files;
+        Ok(())
+    }
+
+    /**
+        Waits until any of the watched files have changed, and then
+        until no more changes have been made for a short duration.
+    */
+    pub async fn wait_for_change(&mut self) {
+        while let Some(event) = self.events.recv().await {
+            if self.is_relevant(&event) {
+                break;
+            }
+        }
+        while let Ok(Some(_)) = timeout(DEBOUNCE_DURATION, self.events.recv()).await {}
+    }
+
+    fn is_relevant(&self, event: &Event) -> bool {
+        matches!(
+            event.kind,
+            EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+        ) && event
+            .paths
+            .iter()
+            .any(|path| self.watched_files.contains(path))
+    }
+}
@@ -0,0 +1,92 @@
+use std::{
+    backtrace::Backtrace,
+    env,
+    fmt::{Display, Write as _},
+    fs, panic,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use directories::UserDirs;
+
+use lune::CrashContext;
+use lune_utils::is_catching_panic;
+
+/**
+    Environment variable that may be set to enable crash reports,
+    for cases where the `--crash-reports` flag can not be given,
+    such as when running standalone executables.
+*/
+const CRASH_REPORTS_ENV_VAR: &str = "LUNE_CRASH_REPORTS";
+
+/**
+    Checks if crash reports have been enabled using the environment.
+*/
+pub fn enabled_by_env() -> bool {
+    env::var(CRASH_REPORTS_ENV_VAR)
+        .map(|value| !matches!(value.trim(), "" | "0" | "false"))
+        .unwrap_or(false)
+}
+
+/**
+    Installs a panic hook that writes a crash report file for any internal panics.
+
+    The report contains the Lune version, the Rust backtrace, the active
+    script, and the Luau call stack at the time of the panic, if available.
+
+    The default panic message is still printed, followed by the path to the report.
+    Panics that are caught and converted into Lua errors do not create reports.
+*/
+pub fn install() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        default_hook(info);
+        if is_catching_panic() {
+            return;
+        }
+        let report = create_report(info);
+        match write_report(&report) {
+            Ok(path) => eprintln!(
+                "\nLune crashed! A crash report has been written to:\n{}\n\
+                Please attach it when reporting this issue.",
+                path.display()
+            ),
+            Err(e) => eprintln!("\nLune crashed, and the crash report could not be written: {e}"),
+        }
+    }));
+}
+
+fn create_report(info: &dyn Display) -> String {
+    let context = CrashContext::capture();
+    let backtrace = Backtrace::force_capture();
+
+    let mut report = String::new();
+    let _ = writeln!(report, "Lune version: {}", env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(
+        report,
+        "Platform: {}-{}",
+        env::consts::OS,
+        env::consts::ARCH
+    );
+    let _ = writeln!(report, "Panic: {info}");
+    let _ = writeln!(report);
+    let _ = writeln!(report, "{context}");
+    let _ = writeln!(report, "Rust backtrace:\n{backtrace}");
+    report
+}
+
+fn write_report(report: &str) -> std::io::Result<PathBuf> {
+    let dir = UserDirs::new().map_or_else(env::temp_dir, |dirs| {
+        dirs.home_dir().join(".lune").join(".crashes")
+    });
+    fs::create_dir_all(&dir)?;
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or_default();
+    let path = dir.join(format!("crash-{timestamp}-{}.txt", std::process::id()));
+
+    fs::write(&path, report)?;
+    Ok(path)
+}
@@ -9,4 +9,7 @@ pub use lune_roblox as roblox;
 #[cfg(test)]
 mod tests;
 
-pub use crate::rt::{Runtime, RuntimeError, RuntimeResult};
+pub use crate::rt::{
+    strip_shebang, CapturedRun, CrashContext, Lune, Runtime, RuntimeBuilder, RuntimeDriver,
+    RuntimeError, RuntimeResult, SchedulerWaker, Serde, ThreadId, PLUGIN_ABI_VERSION,
+};
@@ -0,0 +1,95 @@
+use tracing::Level;
+use tracing_subscriber::{
+    filter::EnvFilter,
+    fmt::{format::FmtSpan, time::uptime},
+};
+
+/**
+    Prefixes for the log targets of crates that belong to Lune itself,
+    and that should emit logs when the log level is set using the command line.
+
+    Targets are matched by prefix, so `lune` also matches every
+    standard library crate, such as `lune_std_fs` and `lune_std_net`.
+
+    Logs from other crates, such as networking and async
+    runtime internals, are usually not useful for users.
+*/
+const LUNE_LOG_TARGET_PREFIXES: &[&str] = &["lune", "mlua_luau_scheduler"];
+
+/**
+    Initializes logging for the current process.
+
+    If a log level is given, all logs from Lune at that level or above will be emitted,
+    otherwise logs are filtered using the `RUST_LOG` environment variable, if it is set.
+
+    Spans, such as filesystem and network operations, will emit
+    a log when they close, which also includes their duration.
+*/
+pub fn init(level: Option<Level>, json: bool) {
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(create_filter(level))
+        .with_span_events(FmtSpan::CLOSE)
+        .with_target(true)
+        .with_timer(uptime())
+        .with_level(true)
+        .with_writer(std::io::stderr);
+
+    if json {
+        builder.json().init();
+    } else {
+        builder.compact().init();
+    }
+}
+
+fn create_filter(level: Option<Level>) -> EnvFilter {
+    match level {
+        None => EnvFilter::from_default_env(),
+        Some(level) => {
+            LUNE_LOG_TARGET_PREFIXES
+                .iter()
+                .fold(EnvFilter::default(), |filter, prefix| {
+                    filter.add_directive(
+                        format!("{prefix}={level}")
+                            .parse()
+                            .expect("log directive is valid"),
+                    )
+                })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing_subscriber::layer::SubscriberExt;
+
+    use super::*;
+
+    fn is_enabled(level: Level, check: impl FnOnce() -> bool) -> bool {
+        let subscriber = tracing_subscriber::registry().with(create_filter(Some(level)));
+        tracing::subscriber::with_default(subscriber, check)
+    }
+
+    #[test]
+    fn all_lune_crates_are_logged() {
+        assert!(is_enabled(Level::DEBUG, || {
+            tracing::enabled!(target: "lune", Level::DEBUG)
+                && tracing::enabled!(target: "lune_std_sqlite", Level::DEBUG)
+                && tracing::enabled!(target: "lune_std_archive::zip", Level::DEBUG)
+                && tracing::enabled!(target: "mlua_luau_scheduler", Level::DEBUG)
+        }));
+    }
+
+    #[test]
+    fn levels_below_the_given_level_are_not_logged() {
+        assert!(!is_enabled(Level::WARN, || {
+            tracing::enabled!(target: "lune_std_fs", Level::INFO)
+        }));
+    }
+
+    #[test]
+    fn other_crates_are_not_logged() {
+        assert!(!is_enabled(Level::TRACE, || {
+            tracing::enabled!(target: "hyper", Level::ERROR)
+        }));
+    }
+}
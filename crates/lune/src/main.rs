@@ -5,27 +5,37 @@ use std::process::ExitCode;
 #[cfg(feature = "cli")]
 pub(crate) mod cli;
 
+pub(crate) mod crash;
+pub(crate) mod logging;
 pub(crate) mod standalone;
 
 use lune_utils::fmt::Label;
 
-#[tokio::main(flavor = "multi_thread")]
-async fn main() -> ExitCode {
-    tracing_subscriber::fmt()
-        .compact()
-        .with_env_filter(tracing_subscriber::filter::EnvFilter::from_default_env())
-        .with_target(true)
-        .with_timer(tracing_subscriber::fmt::time::uptime())
-        .with_level(true)
-        .init();
-
-    if let Some(bin) = standalone::check().await {
-        return standalone::run(bin).await.unwrap();
+fn main() -> ExitCode {
+    if let Some(bin) = standalone::check() {
+        logging::init(None, false);
+        if crash::enabled_by_env() {
+            crash::install();
+        }
+        return create_async_runtime()
+            .block_on(standalone::run(bin))
+            .unwrap();
     }
 
     #[cfg(feature = "cli")]
     {
-        match cli::Cli::new().run().await {
+        let cli = cli::Cli::new();
+        // NOTE: Env files must be loaded before the async runtime has started
+        // any threads, since setting environment variables is not thread-safe
+        if let Err(err) = cli.load_env_files() {
+            eprintln!("{}\n{err:?}", Label::Error);
+            return ExitCode::FAILURE;
+        }
+        logging::init(cli.log_level(), cli.log_json());
+        if cli.crash_reports() || crash::enabled_by_env() {
+            crash::install();
+        }
+        match create_async_runtime().block_on(cli.run()) {
             Ok(code) => code,
             Err(err) => {
                 eprintln!("{}\n{err:?}", Label::Error);
@@ -40,3 +50,10 @@ async fn main() -> ExitCode {
         ExitCode::FAILURE
     }
 }
+
+fn create_async_runtime() -> tokio::runtime::Runtime {
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .expect("Failed to create async runtime")
+}
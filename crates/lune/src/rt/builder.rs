@@ -0,0 +1,331 @@
+use std::{error::Error, future::Future, path::PathBuf};
+
+use mlua::prelude::*;
+
+use super::{host::host_error, Runtime, RuntimeResult};
+
+type SetupFn = Box<dyn FnOnce(&Lua) -> LuaResult<()>>;
+type ResolverFn = Box<dyn Fn(&str) -> Option<Vec<u8>>>;
+
+/**
+    Entry point for embedding Lune in other applications.
+
+    See [`Lune::builder`] for more information.
+*/
+#[derive(Debug, Clone, Copy)]
+pub struct Lune;
+
+impl Lune {
+    /**
+        Creates a new [`RuntimeBuilder`], which can be used to configure
+        which builtins are available, inject custom globals and userdata,
+        and set arguments, before creating a [`Runtime`] to run scripts in.
+
+        ```rust,no_run
+        # async fn example() -> lune::RuntimeResult<()> {
+        let mut runtime = lune::Lune::builder()
+            .libraries(["task", "serde"])
+            .global("appName", "my-app")
+            .args(["--verbose"])
+            .build()?;
+
+        runtime.run("inline", "print(appName)").await?;
+        runtime.run_file("scripts/job.luau").await?;
+        # Ok(())
+        # }
+        ```
+    */
+    #[must_use]
+    pub fn builder() -> RuntimeBuilder {
+        RuntimeBuilder::default()
+    }
+}
+
+/**
+    A builder for a Lune [`Runtime`].
+
+    Created using [`Lune::builder`].
+*/
+pub struct RuntimeBuilder {
+    codegen: bool,
+    args: Vec<String>,
+    libraries: Option<Vec<String>>,
+    disabled_libraries: Vec<String>,
+    require_resolver: Option<ResolverFn>,
+    plugins: Vec<PathBuf>,
+    setup: Vec<SetupFn>,
+}
+
+impl RuntimeBuilder {
+    /**
+        Sets if native code generation should be enabled, if supported.
+
+        Enabled by default.
+    */
+    #[must_use]
+    pub fn codegen(mut self, enabled: bool) -> Self {
+        self.codegen = enabled;
+        self
+    }
+
+    /**
+        Sets arguments to give in `process.args` for Lune scripts.
+    */
+    #[must_use]
+    pub fn args<A, S>(mut self, args: A) -> Self
+    where
+        A: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.args = args.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /**
+        Makes *only* the standard libraries with the given names available to scripts.
+
+        By default, all standard libraries that were enabled at compile time are available.
+    */
+    #[must_use]
+    pub fn libraries<A, S>(mut self, names: A) -> Self
+    where
+        A: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.libraries = Some(names.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /**
+        Makes the standard libraries with the given names unavailable to scripts.
+    */
+    #[must_use]
+    pub fn without_libraries<A, S>(mut self, names: A) -> Self
+    where
+        A: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.disabled_libraries
+            .extend(names.into_iter().map(Into::into));
+        self
+    }
+
+    /**
+        Sets a function used to resolve the contents of required modules, letting
+        scripts be loaded from databases, embedded assets, or virtual filesystems.
+
+        See [`Runtime::set_require_resolver`] for more information.
+    */
+    #[must_use]
+    pub fn require_resolver<F>(mut self, resolver: F) -> Self
+    where
+        F: Fn(&str) -> Option<Vec<u8>> + 'static,
+    {
+        self.require_resolver = Some(Box::new(resolver));
+        self
+    }
+
+    /**
+        Loads the native plugin at the given path when the runtime is built.
+
+        See [`Runtime::load_plugin`] for more information.
+    */
+    #[must_use]
+    pub fn plugin(mut self, path: impl Into<PathBuf>) -> Self {
+        self.plugins.push(path.into());
+        self
+    }
+
+    /**
+        Sets a global value, which will be available to all scripts run in the runtime.
+
+        The value may be any value that can be converted into a Lua
+        value, including custom userdata that implements [`LuaUserData`].
+    */
+    #[must_use]
+    pub fn global<V>(self, name: impl Into<String>, value: V) -> Self
+    where
+        V: for<'lua> IntoLua<'lua> + 'static,
+    {
+        let name = name.into();
+        self.setup(move |lua| lua.globals().set(name, value))
+    }
+
+    /**
+        Sets a global function, which calls the given Rust function when called from scripts.
+
+        Arguments and return values are converted automatically, using [`FromLuaMulti`]
+        and [`IntoLuaMulti`], which are implemented for most common types such as
+        numbers, strings, vectors, maps, options, and tuples of these types.
+        Types implementing serde traits may also be passed using [`Serde`].
+
+        Errors returned by the function are thrown as Lua errors.
+
+        ```rust,no_run
+        # fn example() -> lune::RuntimeResult<()> {
+        let runtime = lune::Lune::builder()
+            .function("add", |(a, b): (i64, i64)| {
+                a.checked_add(b).ok_or("Integer overflow")
+            })
+            .build()?;
+        # Ok(())
+        # }
+        ```
+
+        [`Serde`]: super::Serde
+    */
+    #[must_use]
+    pub fn function<F, A, R, E>(self, name: impl Into<String>, f: F) -> Self
+    where
+        F: Fn(A) -> Result<R, E> + 'static,
+        A: for<'lua> FromLuaMulti<'lua>,
+        R: for<'lua> IntoLuaMulti<'lua>,
+        E: Into<Box<dyn Error + Send + Sync>>,
+    {
+        let name = name.into();
+        self.setup(move |lua| {
+            let function = lua.create_function(move |_, args: A| f(args).map_err(host_error))?;
+            lua.globals().set(name, function)
+        })
+    }
+
+    /**
+        Sets a global function, which calls the given async Rust function when called from scripts.
+
+        The calling script will yield until the returned future completes, letting
+        other scripts and tasks run in the meantime. Arguments, return values, and
+        errors are converted the same way as for [`RuntimeBuilder::function`].
+
+        ```rust,no_run
+        # fn example() -> lune::RuntimeResult<()> {
+        let runtime = lune::Lune::builder()
+            .async_function("fetchName", |id: u64| async move {
+                tokio::fs::read_to_string(format!("names/{id}.txt")).await
+            })
+            .build()?;
+        # Ok(())
+        # }
+        ```
+    */
+    #[must_use]
+    pub fn async_function<F, A, FR, R, E>(self, name: impl Into<String>, f: F) -> Self
+    where
+        F: Fn(A) -> FR + 'static,
+        A: for<'lua> FromLuaMulti<'lua>,
+        FR: Future<Output = Result<R, E>> + 'static,
+        R: for<'lua> IntoLuaMulti<'lua>,
+        E: Into<Box<dyn Error + Send + Sync>>,
+    {
+        let name = name.into();
+        self.setup(move |lua| {
+            let function = lua.create_async_function(move |_, args: A| {
+                let fut = f(args);
+                async move { fut.await.map_err(host_error) }
+            })?;
+            lua.globals().set(name, function)
+        })
+    }
+
+    /**
+        Sets a global with the given name for the userdata type `T`,
+        giving scripts access to the functions added to it using
+        [`LuaUserDataMethods::add_function`], such as constructors.
+
+        Instances of the type may also be passed to and from any functions,
+        and their fields and methods will be available to scripts.
+
+        ```rust,no_run
+        use mlua::prelude::*;
+
+        struct Counter(u32);
+
+        impl LuaUserData for Counter {
+            fn add_methods<'lua, M: LuaUserDataMethods<'lua, Self>>(methods: &mut M) {
+                methods.add_function("new", |_, ()| Ok(Counter(0)));
+                methods.add_method_mut("increment", |_, this, ()| {
+                    this.0 += 1;
+                    Ok(this.0)
+                });
+            }
+        }
+
+        # fn example() -> lune::RuntimeResult<()> {
+        let mut runtime = lune::Lune::builder()
+            .userdata_type::<Counter>("Counter")
+            .build()?;
+        // Scripts can now use `Counter.new()` and `counter:increment()`
+        # Ok(())
+        # }
+        ```
+    */
+    #[must_use]
+    pub fn userdata_type<T>(self, name: impl Into<String>) -> Self
+    where
+        T: LuaUserData + 'static,
+    {
+        let name = name.into();
+        self.setup(move |lua| lua.globals().set(name, lua.create_proxy::<T>()?))
+    }
+
+    /**
+        Adds a function that will be called with the Luau VM once the runtime has been
+        created, after all standard globals have been injected, but before any scripts run.
+
+        This can be used for any setup that needs access to the Luau VM,
+        such as creating Lua functions or tables to inject as globals.
+    */
+    #[must_use]
+    pub fn setup<F>(mut self, f: F) -> Self
+    where
+        F: FnOnce(&Lua) -> LuaResult<()> + 'static,
+    {
+        self.setup.push(Box::new(f));
+        self
+    }
+
+    /**
+        Creates the [`Runtime`].
+
+        # Errors
+
+        Errors if any of the given library names are not known standard libraries,
+        if any of the plugins could not be loaded, or if any of the setup functions
+        return an error.
+    */
+    pub fn build(self) -> RuntimeResult<Runtime> {
+        let mut runtime = Runtime::new(self.codegen).with_args(self.args);
+
+        if let Some(libraries) = self.libraries {
+            runtime.restrict_libraries(libraries)?;
+        }
+        runtime.disable_libraries(self.disabled_libraries)?;
+
+        if let Some(resolver) = self.require_resolver {
+            runtime.set_require_resolver(resolver);
+        }
+
+        for plugin in self.plugins {
+            runtime.load_plugin(plugin)?;
+        }
+
+        for setup in self.setup {
+            runtime.setup(setup)?;
+        }
+
+        Ok(runtime)
+    }
+}
+
+impl Default for RuntimeBuilder {
+    fn default() -> Self {
+        Self {
+            codegen: true,
+            args: Vec::new(),
+            libraries: None,
+            disabled_libraries: Vec::new(),
+            require_resolver: None,
+            plugins: Vec::new(),
+            setup: Vec::new(),
+        }
+    }
+}
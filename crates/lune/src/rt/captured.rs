@@ -0,0 +1,40 @@
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+
+/**
+    The result of running a script using [`Runtime::run_captured`].
+
+    [`Runtime::run_captured`]: super::Runtime::run_captured
+*/
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CapturedRun {
+    /**
+        The exit code of the script, which is `0` for scripts
+        that completed successfully without calling `process.exit`.
+    */
+    pub exit_code: u8,
+    /**
+        Everything the script wrote to standard output.
+    */
+    pub stdout: String,
+    /**
+        Everything the script wrote to standard error, including any formatted errors.
+    */
+    pub stderr: String,
+    /**
+        The values returned by the script.
+
+        Values that can not be represented as JSON, such as functions, are converted to `null`.
+    */
+    pub values: Vec<JsonValue>,
+}
+
+impl CapturedRun {
+    /**
+        Returns `true` if the script exited with a code of `0`.
+    */
+    #[must_use]
+    pub fn success(&self) -> bool {
+        self.exit_code == 0
+    }
+}
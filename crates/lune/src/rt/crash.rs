@@ -0,0 +1,153 @@
+use std::{
+    cell::RefCell,
+    fmt::{Display, Formatter, Result as FmtResult},
+    rc::{Rc, Weak},
+};
+
+use mlua::prelude::*;
+
+const DEBUG_INFO_KEY: &str = "_LUNE_CRASH_DEBUG_INFO";
+
+thread_local! {
+    static ACTIVE_SCRIPT: RefCell<Option<ActiveScript>> = const { RefCell::new(None) };
+}
+
+struct ActiveScript {
+    name: String,
+    lua: Weak<Lua>,
+}
+
+/**
+    Guard that marks a script as actively running on the current
+    thread, and clears it again once the script has finished.
+*/
+pub(crate) struct ActiveScriptGuard {
+    previous: Option<ActiveScript>,
+}
+
+impl ActiveScriptGuard {
+    pub(crate) fn new(name: impl Into<String>, lua: &Rc<Lua>) -> Self {
+        // NOTE: Scripts may replace or remove the debug library, so we grab
+        // debug.info before the script runs to be able to inspect stacks later
+        if let Ok(info) = lua
+            .globals()
+            .get::<_, LuaTable>("debug")
+            .and_then(|debug| debug.get::<_, LuaFunction>("info"))
+        {
+            lua.set_named_registry_value(DEBUG_INFO_KEY, info).ok();
+        }
+        let active = ActiveScript {
+            name: name.into(),
+            lua: Rc::downgrade(lua),
+        };
+        let previous = ACTIVE_SCRIPT.with(|cell| cell.borrow_mut().replace(active));
+        Self { previous }
+    }
+}
+
+impl Drop for ActiveScriptGuard {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        ACTIVE_SCRIPT.with(|cell| {
+            if let Ok(mut active) = cell.try_borrow_mut() {
+                *active = previous;
+            }
+        });
+    }
+}
+
+/**
+    Context about the script that was running on the current thread, if any.
+
+    This is meant to be captured from inside of a panic hook, to give
+    crash reports more information than just the Rust backtrace.
+*/
+#[derive(Debug, Clone, Default)]
+pub struct CrashContext {
+    /// Name of the script that was running, if any.
+    pub script: Option<String>,
+    /// The Luau call stack at the time of capture, innermost frame first.
+    pub luau_stack: Vec<String>,
+}
+
+impl CrashContext {
+    /**
+        Captures context about the script running on the current thread.
+
+        Returns an empty context if no script is currently
+        running on this thread, this will never panic.
+    */
+    #[must_use]
+    pub fn capture() -> Self {
+        ACTIVE_SCRIPT
+            .try_with(|cell| {
+                let Ok(active) = cell.try_borrow() else {
+                    return Self::default();
+                };
+                let Some(active) = active.as_ref() else {
+                    return Self::default();
+                };
+                Self {
+                    script: Some(active.name.clone()),
+                    luau_stack: active
+                        .lua
+                        .upgrade()
+                        .map(|lua| capture_luau_stack(&lua))
+                        .unwrap_or_default(),
+                }
+            })
+            .unwrap_or_default()
+    }
+}
+
+impl Display for CrashContext {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match &self.script {
+            Some(script) => writeln!(f, "Active script: {script}")?,
+            None => writeln!(f, "Active script: none")?,
+        }
+        if self.luau_stack.is_empty() {
+            writeln!(f, "Luau stack: unavailable")?;
+        } else {
+            writeln!(f, "Luau stack:")?;
+            for frame in &self.luau_stack {
+                writeln!(f, "    {frame}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/**
+    Captures the call stack of the Luau thread that is currently running.
+
+    Scripts spend most of their time inside of coroutines, such as the ones
+    created by `task.spawn`, so the stack of the main thread is usually not
+    the interesting one - we explicitly inspect the running thread instead.
+*/
+fn capture_luau_stack(lua: &Lua) -> Vec<String> {
+    let Ok(info) = lua.named_registry_value::<LuaFunction>(DEBUG_INFO_KEY) else {
+        return Vec::new();
+    };
+    let thread = lua.current_thread();
+
+    // NOTE: debug.info is called on the thread that we are inspecting,
+    // so level 0 is always debug.info itself, and we skip past it
+    let mut frames = Vec::new();
+    let mut level = 1;
+    while let Ok((Some(source), Some(line), name)) =
+        info.call::<_, (Option<String>, Option<i64>, Option<String>)>((&thread, level, "sln"))
+    {
+        let name = name
+            .as_deref()
+            .filter(|name| !name.is_empty())
+            .unwrap_or("<anonymous>");
+        if line > 0 {
+            frames.push(format!("{source}:{line} in function {name}"));
+        } else {
+            frames.push(format!("{source} in function {name}"));
+        }
+        level += 1;
+    }
+    frames
+}
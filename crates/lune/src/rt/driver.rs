@@ -0,0 +1,124 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use mlua::prelude::*;
+use mlua_luau_scheduler::{Scheduler, SchedulerDriver, SchedulerWaker, ThreadId};
+
+use super::RuntimeResult;
+
+/**
+    A driver for a Lune runtime, letting a host application
+    run scripts while also driving the runtime forward itself.
+
+    Created using [`Runtime::driver`], see [`SchedulerDriver`] for more information.
+
+    [`Runtime::driver`]: super::Runtime::driver
+*/
+pub struct RuntimeDriver<'rt> {
+    lua: &'rt Lua,
+    sched: &'rt Scheduler<'rt>,
+    driver: SchedulerDriver<'rt, 'rt>,
+    got_any_error: Arc<AtomicBool>,
+}
+
+impl<'rt> RuntimeDriver<'rt> {
+    pub(super) fn new(
+        lua: &'rt Lua,
+        sched: &'rt Scheduler<'rt>,
+        got_any_error: Arc<AtomicBool>,
+    ) -> Self {
+        Self {
+            lua,
+            sched,
+            driver: sched.driver(),
+            got_any_error,
+        }
+    }
+
+    /**
+        Spawns a Lune script, which will start running the next time the runtime is driven.
+
+        Returns a [`ThreadId`] that can be used to get the result of the script once it completes.
+
+        # Errors
+
+        Errors if the script could not be loaded, such as when it contains syntax errors.
+    */
+    pub fn spawn(
+        &self,
+        script_name: impl AsRef<str>,
+        script_contents: impl AsRef<[u8]>,
+    ) -> RuntimeResult<ThreadId> {
+        let main = self
+            .lua
+            .load(script_contents.as_ref())
+            .set_name(script_name.as_ref());
+        Ok(self.sched.push_thread_back(main, ())?)
+    }
+
+    /**
+        Takes the values returned by a script spawned using [`RuntimeDriver::spawn`].
+
+        Returns `None` if the script has not yet completed, or if its result was already taken.
+
+        # Errors
+
+        Errors if the script errored, in which case the error
+        has also been written to stderr when it happened.
+    */
+    pub fn take_result(&self, id: ThreadId) -> Option<RuntimeResult<Vec<LuaValue<'rt>>>> {
+        let res = self.sched.get_thread_result(id)?;
+        Some(res.map(LuaMultiValue::into_vec).map_err(Into::into))
+    }
+
+    /**
+        Waits until there is work to do, and then does as much of it as possible without waiting.
+
+        Returns once any work has been done, once a script has set an exit code,
+        or once the driver has been woken up using a [`SchedulerWaker`].
+    */
+    pub async fn tick(&self) {
+        self.driver.tick().await;
+    }
+
+    /**
+        Does as much work as possible without waiting, and returns `true` if any work was done.
+    */
+    pub fn try_tick(&self) -> bool {
+        self.driver.try_tick()
+    }
+
+    /**
+        Returns `true` if there are no scripts or tasks left to run.
+    */
+    #[must_use]
+    pub fn is_idle(&self) -> bool {
+        self.driver.is_idle()
+    }
+
+    /**
+        Returns a new [`SchedulerWaker`], which can be used from any thread to wake up this driver.
+    */
+    #[must_use]
+    pub fn waker(&self) -> SchedulerWaker {
+        self.driver.waker()
+    }
+
+    /**
+        Returns the exit code that has been set by a script, if any.
+    */
+    #[must_use]
+    pub fn exit_code(&self) -> Option<u8> {
+        self.sched.get_exit_code()
+    }
+
+    /**
+        Returns `true` if any script or task has errored while this driver was running.
+    */
+    #[must_use]
+    pub fn had_errors(&self) -> bool {
+        self.got_any_error.load(Ordering::SeqCst)
+    }
+}
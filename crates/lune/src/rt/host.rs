@@ -0,0 +1,83 @@
+use std::error::Error;
+
+use mlua::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
+
+type BoxedError = Box<dyn Error + Send + Sync>;
+
+// NOTE: These match the options used by the serde standard library,
+// so that values look the same as if they were decoded from JSON
+const LUA_SERIALIZE_OPTIONS: LuaSerializeOptions = LuaSerializeOptions::new()
+    .set_array_metatable(false)
+    .serialize_none_to_null(false)
+    .serialize_unit_to_null(false);
+
+const LUA_DESERIALIZE_OPTIONS: LuaDeserializeOptions = LuaDeserializeOptions::new()
+    .sort_keys(true)
+    .deny_recursive_tables(false)
+    .deny_unsupported_types(true);
+
+/**
+    A wrapper for passing any type implementing [`Serialize`] and
+    [`Deserialize`] between Rust host functions and Lune scripts.
+
+    Structs are converted to and from tables, enums to and from strings
+    or tables, and `None` to and from `nil`, the same as when values
+    are encoded and decoded using the `serde` standard library.
+
+    ```rust,no_run
+    use lune::{Lune, Serde};
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct User {
+        name: String,
+        admin: bool,
+    }
+
+    # fn example() -> lune::RuntimeResult<()> {
+    let runtime = Lune::builder()
+        .function("promote", |Serde(mut user): Serde<User>| {
+            user.admin = true;
+            Ok::<_, std::io::Error>(Serde(user))
+        })
+        .build()?;
+    # Ok(())
+    # }
+    ```
+
+    [`Deserialize`]: serde::Deserialize
+*/
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Serde<T>(pub T);
+
+impl<'lua, T> FromLua<'lua> for Serde<T>
+where
+    T: DeserializeOwned,
+{
+    fn from_lua(value: LuaValue<'lua>, lua: &'lua Lua) -> LuaResult<Self> {
+        lua.from_value_with(value, LUA_DESERIALIZE_OPTIONS)
+            .map(Self)
+    }
+}
+
+impl<'lua, T> IntoLua<'lua> for Serde<T>
+where
+    T: Serialize,
+{
+    fn into_lua(self, lua: &'lua Lua) -> LuaResult<LuaValue<'lua>> {
+        lua.to_value_with(&self.0, LUA_SERIALIZE_OPTIONS)
+    }
+}
+
+/**
+    Converts an error returned from a host function into a Lua error.
+
+    Lua errors are passed through as they are, so that host
+    functions may also return errors created using [`LuaError`].
+*/
+pub(super) fn host_error(error: impl Into<BoxedError>) -> LuaError {
+    match error.into().downcast::<LuaError>() {
+        Ok(error) => *error,
+        Err(error) => LuaError::external(error),
+    }
+}
@@ -0,0 +1,115 @@
+use std::{path::PathBuf, time::Duration};
+
+use mlua::prelude::*;
+
+use lune_std::LuneStandardLibrary;
+
+use super::{runtime::MemoryLimit, timeout::TimeLimit, Runtime, RuntimeResult};
+
+/**
+    The restrictions and limits of a Luau VM, captured so that they
+    can be applied to scripts spawned using `process.spawnLune`.
+
+    Spawned scripts must never be able to do anything that
+    the script spawning them would not be allowed to do.
+*/
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Inherited {
+    disabled_libraries: Vec<&'static str>,
+    fs_read: Option<Vec<PathBuf>>,
+    fs_write: Option<Vec<PathBuf>>,
+    net_hosts: Option<Vec<String>>,
+    memory_limit: Option<usize>,
+    time_limit: Option<(Duration, Duration)>,
+}
+
+impl Inherited {
+    /**
+        Captures the current restrictions and limits of the given Luau VM.
+
+        Time limits are captured as the time remaining, meaning a
+        spawned script can not be used to extend the time limit.
+    */
+    pub(crate) fn capture(lua: &Lua) -> Self {
+        Self {
+            disabled_libraries: LuneStandardLibrary::ALL
+                .iter()
+                .filter(|library| library.is_disabled(lua))
+                .map(LuneStandardLibrary::name)
+                .collect(),
+            #[cfg(feature = "std-fs")]
+            fs_read: lune_std::allowed_read_paths(lua),
+            #[cfg(feature = "std-fs")]
+            fs_write: lune_std::allowed_write_paths(lua),
+            #[cfg(feature = "std-net")]
+            net_hosts: lune_std::allowed_net_hosts(lua),
+            memory_limit: lua.app_data_ref::<MemoryLimit>().map(|limit| limit.0),
+            time_limit: TimeLimit::remaining(lua),
+            ..Self::default()
+        }
+    }
+
+    /**
+        Applies the captured restrictions and limits to the given runtime.
+    */
+    pub(crate) fn apply(self, rt: &mut Runtime) -> RuntimeResult<()> {
+        rt.disable_libraries(self.disabled_libraries)?;
+        if let Some(paths) = self.fs_read {
+            rt.allow_fs_read(paths);
+        }
+        if let Some(paths) = self.fs_write {
+            rt.allow_fs_write(paths);
+        }
+        if let Some(hosts) = self.net_hosts {
+            rt.allow_net_hosts(hosts)?;
+        }
+        if let Some(bytes) = self.memory_limit {
+            rt.set_memory_limit(bytes)?;
+        }
+        if let Some((limit, grace_period)) = self.time_limit {
+            rt.set_time_limit(limit, grace_period);
+        }
+        Ok(())
+    }
+
+    /**
+        Converts the captured restrictions and limits into
+        arguments for `lune run`, for spawning a child process.
+
+        An empty list of paths or hosts is passed as an empty
+        argument, which means that nothing at all is allowed.
+    */
+    pub(crate) fn to_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if !self.disabled_libraries.is_empty() {
+            args.push(format!(
+                "--disable-lib={}",
+                self.disabled_libraries.join(",")
+            ));
+        }
+        if let Some(paths) = &self.fs_read {
+            args.push(format!("--allow-read={}", join_paths(paths)));
+        }
+        if let Some(paths) = &self.fs_write {
+            args.push(format!("--allow-write={}", join_paths(paths)));
+        }
+        if let Some(hosts) = &self.net_hosts {
+            args.push(format!("--allow-net={}", hosts.join(",")));
+        }
+        if let Some(bytes) = self.memory_limit {
+            args.push(format!("--max-memory={bytes}"));
+        }
+        if let Some((limit, _)) = self.time_limit {
+            args.push(format!("--timeout={}ms", limit.as_millis().max(1)));
+        }
+        args
+    }
+}
+
+fn join_paths(paths: &[PathBuf]) -> String {
+    paths
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
@@ -1,5 +1,25 @@
+mod builder;
+mod captured;
+mod crash;
+mod driver;
+mod host;
+#[cfg(feature = "std-process")]
+mod inherit;
+mod plugin;
 mod result;
 mod runtime;
+mod timeout;
 
+#[cfg(test)]
+mod tests;
+
+pub use self::builder::{Lune, RuntimeBuilder};
+pub use self::captured::CapturedRun;
+pub use self::crash::CrashContext;
+pub use self::driver::RuntimeDriver;
+pub use self::host::Serde;
+pub use self::plugin::PLUGIN_ABI_VERSION;
 pub use self::result::{RuntimeError, RuntimeResult};
-pub use self::runtime::Runtime;
+pub use self::runtime::{strip_shebang, Runtime};
+
+pub use mlua_luau_scheduler::{SchedulerWaker, ThreadId};
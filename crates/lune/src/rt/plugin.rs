@@ -0,0 +1,90 @@
+use std::{
+    ffi::{c_char, CStr},
+    path::Path,
+};
+
+use libloading::{Library, Symbol};
+use mlua::{ffi::lua_CFunction, prelude::*};
+
+use super::RuntimeResult;
+
+/**
+    The version of the native plugin interface supported by this version of Lune.
+
+    Native plugins are dynamic libraries that export the following C functions:
+
+    - `uint32_t lune_plugin_abi_version(void)` - returns the version of
+      the plugin interface that the plugin was built for, which must
+      be equal to this constant for the plugin to be loaded.
+    - `const char* lune_plugin_name(void)` - returns the name of the
+      plugin as a nul-terminated string, which scripts use to require
+      the module provided by the plugin using `require("@plugin/name")`.
+    - `int lune_plugin_open(lua_State* L)` - creates the module provided by the
+      plugin, pushes it onto the stack of the given Luau state and returns `1`.
+
+    Plugins interact with scripts through the Luau C API, and must
+    be built against the same version of Luau that Lune was built with.
+*/
+pub const PLUGIN_ABI_VERSION: u32 = 1;
+
+type AbiVersionFn = unsafe extern "C" fn() -> u32;
+type NameFn = unsafe extern "C" fn() -> *const c_char;
+
+/**
+    Loads the native plugin at the given path, and creates its module.
+
+    Returns the name of the plugin along with the created module.
+*/
+pub(super) fn load_plugin<'lua>(
+    lua: &'lua Lua,
+    path: &Path,
+) -> RuntimeResult<(String, LuaValue<'lua>)> {
+    let context = |message: &str| format!("Failed to load plugin '{}' - {message}", path.display());
+
+    // SAFETY: Loading a library runs its initialization routines, which is
+    // inherently unsafe, but plugins are explicitly trusted by the user
+    let library = unsafe { Library::new(path) }
+        .map_err(|e| LuaError::external(e).context(context("could not open library")))?;
+
+    // SAFETY: The signatures of these functions are part of the plugin
+    // interface, which we verify the version of before calling anything else
+    let (name, open) = unsafe {
+        let abi_version: Symbol<AbiVersionFn> = library
+            .get(b"lune_plugin_abi_version\0")
+            .map_err(|e| LuaError::external(e).context(context("missing ABI version")))?;
+        let abi_version = abi_version();
+        if abi_version != PLUGIN_ABI_VERSION {
+            return Err(LuaError::runtime(context(&format!(
+                "plugin was built for ABI version {abi_version}, \
+                but this version of Lune supports ABI version {PLUGIN_ABI_VERSION}"
+            )))
+            .into());
+        }
+
+        let name: Symbol<NameFn> = library
+            .get(b"lune_plugin_name\0")
+            .map_err(|e| LuaError::external(e).context(context("missing name")))?;
+        let name = name();
+        if name.is_null() {
+            return Err(LuaError::runtime(context("plugin name was null")).into());
+        }
+        let name = CStr::from_ptr(name).to_string_lossy().into_owned();
+
+        let open: Symbol<lua_CFunction> = library
+            .get(b"lune_plugin_open\0")
+            .map_err(|e| LuaError::external(e).context(context("missing open function")))?;
+
+        (name, *open)
+    };
+
+    // SAFETY: The open function is a valid C function according to the plugin interface
+    let module = unsafe { lua.create_c_function(open) }?
+        .call::<_, LuaValue>(())
+        .map_err(|e| e.context(context("plugin failed to open")))?;
+
+    // NOTE: Plugins are never unloaded, since functions created by
+    // them may be referenced by the Luau VM for as long as it exists
+    std::mem::forget(library);
+
+    Ok((name, module))
+}
@@ -16,6 +16,7 @@ pub type RuntimeResult<T, E = RuntimeError> = Result<T, E>;
 pub struct RuntimeError {
     error: LuaError,
     disable_colors: bool,
+    reported: bool,
 }
 
 impl RuntimeError {
@@ -43,6 +44,36 @@ impl RuntimeError {
         self
     }
 
+    /**
+        Marks the error as having already been reported by the runtime.
+    */
+    #[must_use]
+    pub(crate) fn mark_reported(mut self) -> Self {
+        self.reported = true;
+        self
+    }
+
+    /**
+        Returns `true` if the error has already been reported by the runtime, meaning
+        that it was written to stderr while the script was running, the same as errors
+        in any other threads, and that it does not need to be displayed again.
+    */
+    #[must_use]
+    pub fn is_reported(&self) -> bool {
+        self.reported
+    }
+
+    /**
+        Returns `true` if the error is a syntax error, meaning
+        that the source code could not be parsed and never ran.
+
+        See [`mlua::Error::SyntaxError`] for more information.
+    */
+    #[must_use]
+    pub fn is_syntax_error(&self) -> bool {
+        matches!(self.error, LuaError::SyntaxError { .. })
+    }
+
     /**
         Returns `true` if the error can likely be fixed by appending more input to the source code.
 
@@ -65,6 +96,7 @@ impl From<LuaError> for RuntimeError {
         Self {
             error: value,
             disable_colors: false,
+            reported: false,
         }
     }
 }
@@ -74,6 +106,7 @@ impl From<&LuaError> for RuntimeError {
         Self {
             error: value.clone(),
             disable_colors: false,
+            reported: false,
         }
     }
 }
@@ -1,18 +1,29 @@
 #![allow(clippy::missing_panics_doc)]
 
 use std::{
+    path::Path,
     rc::Rc,
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc,
     },
+    time::Duration,
 };
 
 use mlua::prelude::*;
 use mlua_luau_scheduler::{Functions, Scheduler};
 use self_cell::self_cell;
+use serde_json::Value as JsonValue;
 
-use super::{RuntimeError, RuntimeResult};
+use lune_utils::CapturedOutput;
+
+use super::{
+    crash::ActiveScriptGuard, plugin::load_plugin, timeout::TimeLimit, CapturedRun, RuntimeDriver,
+    RuntimeError, RuntimeResult,
+};
+
+#[cfg(feature = "std-process")]
+use super::inherit::Inherited;
 
 // NOTE: We need to use self_cell to create a self-referential
 // struct storing both the Lua VM and the scheduler. The scheduler
@@ -39,26 +50,32 @@ impl RuntimeInner {
             let sched = Scheduler::new(lua);
             let fns = Functions::new(lua)?;
 
+            // NOTE: Thread origins make errors in spawned threads much easier to
+            // track down, so we enable them by default, while letting embedders
+            // that spawn lots of threads opt out of capturing call stacks
+            sched.set_thread_origins_enabled(true);
+
             // Overwrite some globals that are not compatible with our scheduler
             let co = lua.globals().get::<_, LuaTable>("coroutine")?;
             co.set("resume", fns.resume.clone())?;
             co.set("wrap", fns.wrap.clone())?;
 
             // Inject all the globals that are enabled
-            #[cfg(any(
-                feature = "std-datetime",
-                feature = "std-fs",
-                feature = "std-luau",
-                feature = "std-net",
-                feature = "std-process",
-                feature = "std-regex",
-                feature = "std-roblox",
-                feature = "std-serde",
-                feature = "std-stdio",
-                feature = "std-task",
-            ))]
+            #[cfg(feature = "std-any")]
             {
                 lune_std::set_global_version(lua, env!("CARGO_PKG_VERSION"));
+                #[cfg(feature = "std-luau")]
+                lune_std::set_codegen_enabled(lua, codegen);
+                #[cfg(feature = "std-process")]
+                {
+                    lune_std::set_lune_script_runner(lua, move |lua| {
+                        let inherited = Inherited::capture(lua);
+                        Box::new(move |path, args| {
+                            run_file_in_new_runtime(codegen, &path, args, inherited)
+                        })
+                    });
+                    lune_std::set_lune_process_args(lua, |lua| Inherited::capture(lua).to_args());
+                }
                 lune_std::inject_globals(lua)?;
             }
 
@@ -67,18 +84,7 @@ impl RuntimeInner {
 
             // _G table needs to be injected again after sandboxing,
             // otherwise it will be read-only and completely unusable
-            #[cfg(any(
-                feature = "std-datetime",
-                feature = "std-fs",
-                feature = "std-luau",
-                feature = "std-net",
-                feature = "std-process",
-                feature = "std-regex",
-                feature = "std-roblox",
-                feature = "std-serde",
-                feature = "std-stdio",
-                feature = "std-task",
-            ))]
+            #[cfg(feature = "std-any")]
             {
                 let g_table = lune_std::LuneStandardGlobal::GTable;
                 lua.globals().set(g_table.name(), g_table.create(lua)?)?;
@@ -97,11 +103,18 @@ impl RuntimeInner {
     }
 }
 
+/**
+    The memory limit of a Luau VM, stored so that it can be inherited by spawned scripts.
+*/
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct MemoryLimit(pub(crate) usize);
+
 /**
     A Lune runtime.
 */
 pub struct Runtime {
     inner: RuntimeInner,
+    time_limit: Option<TimeLimit>,
 }
 
 impl Runtime {
@@ -115,6 +128,7 @@ impl Runtime {
     pub fn new(codegen: bool) -> Self {
         Self {
             inner: RuntimeInner::create(codegen).expect("Failed to create runtime"),
+            time_limit: None,
         }
     }
 
@@ -132,11 +146,373 @@ impl Runtime {
         self
     }
 
+    /**
+        Sets a global value, which will be available to all scripts run in this runtime.
+
+        # Errors
+
+        Errors if the value could not be converted into a Lua value.
+    */
+    pub fn set_global<V>(&mut self, name: impl AsRef<str>, value: V) -> RuntimeResult<()>
+    where
+        V: for<'lua> IntoLua<'lua>,
+    {
+        let lua = self.inner.lua();
+        lua.globals().set(name.as_ref(), value)?;
+        Ok(())
+    }
+
+    /**
+        Disables the standard libraries with the given names,
+        making any attempt to `require` them result in an error.
+
+        # Errors
+
+        Errors if any of the given names is not a known standard library.
+    */
+    pub fn disable_libraries<A, S>(&mut self, names: A) -> RuntimeResult<()>
+    where
+        A: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        set_disabled_libraries(self.inner.lua(), names, false)
+    }
+
+    /**
+        Disables all standard libraries *except* for the ones with the given names.
+
+        # Errors
+
+        Errors if any of the given names is not a known standard library.
+    */
+    pub fn restrict_libraries<A, S>(&mut self, names: A) -> RuntimeResult<()>
+    where
+        A: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        set_disabled_libraries(self.inner.lua(), names, true)
+    }
+
+    /**
+        Sets a function used to resolve the contents of required modules,
+        letting scripts be loaded from sources other than the filesystem.
+
+        The function receives the path of the module being required, relative to the
+        current working directory, and should return either the source code or the
+        compiled bytecode of the module, or `None` to read it from the filesystem.
+    */
+    pub fn set_require_resolver<F>(&mut self, resolver: F)
+    where
+        F: Fn(&str) -> Option<Vec<u8>> + 'static,
+    {
+        #[cfg(feature = "std-any")]
+        lune_std::set_require_resolver(self.inner.lua(), resolver);
+        #[cfg(not(feature = "std-any"))]
+        drop(resolver);
+    }
+
+    /**
+        Loads the native plugin at the given path, making the module it provides
+        available to scripts using `require("@plugin/name")`.
+
+        If no standard libraries are enabled, the module is instead
+        set as a global with the same name as the plugin.
+
+        See [`PLUGIN_ABI_VERSION`] for the interface that plugins must implement.
+
+        [`PLUGIN_ABI_VERSION`]: super::PLUGIN_ABI_VERSION
+
+        # Errors
+
+        Errors if the plugin could not be loaded, if it was built for a different
+        version of the plugin interface, if it failed to create its module,
+        or if a plugin with the same name has already been loaded.
+    */
+    pub fn load_plugin(&mut self, path: impl AsRef<Path>) -> RuntimeResult<()> {
+        let lua = self.inner.lua();
+        let (name, module) = load_plugin(lua, path.as_ref())?;
+        #[cfg(feature = "std-any")]
+        lune_std::register_plugin_module(lua, name, module)?;
+        #[cfg(not(feature = "std-any"))]
+        lua.globals().set(name, module)?;
+        Ok(())
+    }
+
+    /**
+        Restricts reading from the filesystem to the given paths, and any paths inside of them.
+
+        Paths are canonicalized before being checked, meaning symlinks
+        and relative path components can not be used to escape them.
+    */
+    pub fn allow_fs_read<A, P>(&mut self, paths: A)
+    where
+        A: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        #[cfg(feature = "std-fs")]
+        lune_std::allow_read_paths(self.inner.lua(), paths);
+        #[cfg(not(feature = "std-fs"))]
+        drop(paths);
+    }
+
+    /**
+        Restricts writing to the filesystem to the given paths, and any paths inside of them.
+
+        Paths are canonicalized before being checked, meaning symlinks
+        and relative path components can not be used to escape them.
+    */
+    pub fn allow_fs_write<A, P>(&mut self, paths: A)
+    where
+        A: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        #[cfg(feature = "std-fs")]
+        lune_std::allow_write_paths(self.inner.lua(), paths);
+        #[cfg(not(feature = "std-fs"))]
+        drop(paths);
+    }
+
+    /**
+        Restricts network operations to the given hosts, in `host[:port]` format.
+
+        This applies to outgoing requests, including any redirects,
+        to web sockets, and to the addresses that servers may listen on.
+
+        # Errors
+
+        Errors if any of the given hosts are not valid.
+    */
+    pub fn allow_net_hosts<A, S>(&mut self, hosts: A) -> RuntimeResult<()>
+    where
+        A: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        #[cfg(feature = "std-net")]
+        lune_std::allow_net_hosts(self.inner.lua(), hosts)?;
+        #[cfg(not(feature = "std-net"))]
+        drop(hosts);
+        Ok(())
+    }
+
+    /**
+        Sets the minimum level of records written by the `log` standard library,
+        one of `trace`, `debug`, `info`, `warn`, or `error`.
+
+        # Errors
+
+        Errors if the given level is not a valid log level.
+    */
+    pub fn set_log_level(&mut self, level: impl AsRef<str>) -> RuntimeResult<()> {
+        #[cfg(feature = "std-log")]
+        {
+            let level = level.as_ref().parse().map_err(LuaError::runtime)?;
+            lune_std::set_log_level(self.inner.lua(), level);
+        }
+        #[cfg(not(feature = "std-log"))]
+        drop(level);
+        Ok(())
+    }
+
+    /**
+        Sets the format of records written by the `log` standard library,
+        one of `pretty` or `json`.
+
+        # Errors
+
+        Errors if the given format is not a valid log format.
+    */
+    pub fn set_log_format(&mut self, format: impl AsRef<str>) -> RuntimeResult<()> {
+        #[cfg(feature = "std-log")]
+        {
+            let format = format.as_ref().parse().map_err(LuaError::runtime)?;
+            lune_std::set_log_format(self.inner.lua(), format);
+        }
+        #[cfg(not(feature = "std-log"))]
+        drop(format);
+        Ok(())
+    }
+
+    /**
+        Sets the resolution of the timer used by `task.wait` and `task.delay`, such as `1ms` or `100us`.
+
+        Resolutions below one millisecond make waits more precise, at the cost of using more CPU.
+
+        # Errors
+
+        Errors if the given resolution is not a valid duration.
+    */
+    pub fn set_timer_resolution(&mut self, resolution: impl AsRef<str>) -> RuntimeResult<()> {
+        #[cfg(feature = "std-task")]
+        {
+            let resolution = resolution.as_ref().parse().map_err(LuaError::runtime)?;
+            lune_std::set_timer_resolution(self.inner.lua(), resolution);
+        }
+        #[cfg(not(feature = "std-task"))]
+        drop(resolution);
+        Ok(())
+    }
+
+    /**
+        Sets whether errors in spawned and deferred threads should include
+        the call stack that created the thread in their tracebacks.
+
+        This is enabled by default, but may be disabled to make spawning threads cheaper.
+    */
+    pub fn set_thread_origins_enabled(&mut self, enabled: bool) {
+        self.inner.scheduler().set_thread_origins_enabled(enabled);
+    }
+
+    /**
+        Sets the maximum amount of memory, in bytes, that scripts may use.
+
+        Allocations exceeding the limit will fail with a memory error, which
+        may be caught using `pcall`, or otherwise stops the running script.
+
+        # Errors
+
+        Errors if memory limits are not available for the current Luau VM.
+    */
+    pub fn set_memory_limit(&mut self, bytes: usize) -> RuntimeResult<()> {
+        let lua = self.inner.lua();
+        lua.set_memory_limit(bytes)?;
+        lua.set_app_data(MemoryLimit(bytes));
+        Ok(())
+    }
+
+    /**
+        Sets a time limit for scripts running in this runtime, starting from when this is called.
+
+        Once the limit has been reached, an error is raised in any running Luau code.
+        If the runtime has not stopped after the given grace period, the
+        current process will print the active Luau stack and exit.
+    */
+    pub fn set_time_limit(&mut self, limit: Duration, grace_period: Duration) {
+        self.time_limit = Some(TimeLimit::install(self.inner.lua(), limit, grace_period));
+    }
+
+    /**
+        Calls the given function with the Luau VM of this runtime.
+    */
+    pub(super) fn setup(&mut self, f: impl FnOnce(&Lua) -> LuaResult<()>) -> RuntimeResult<()> {
+        f(self.inner.lua())?;
+        Ok(())
+    }
+
+    /**
+        Creates a [`RuntimeDriver`] for this runtime, letting a host application
+        spawn scripts and drive the runtime forward from its own event loop,
+        instead of waiting for scripts to complete using [`Runtime::run`].
+
+        Errors from scripts are written to stderr, the same as when using [`Runtime::run`].
+    */
+    pub fn driver(&mut self) -> RuntimeDriver<'_> {
+        let got_any_error = self.install_error_callback();
+        RuntimeDriver::new(self.inner.lua(), self.inner.scheduler(), got_any_error)
+    }
+
+    /**
+        Runs a Lune script file inside of the current runtime.
+
+        The path of the file, without its extension, is used as the name of the script.
+
+        # Errors
+
+        This function will return an error if the file could not be read, or if the script fails to run.
+    */
+    pub async fn run_file(&mut self, path: impl AsRef<Path>) -> RuntimeResult<(u8, Vec<LuaValue>)> {
+        let path = path.as_ref();
+        let contents = tokio::fs::read(path).await.map_err(|e| {
+            LuaError::external(e).context(format!("Failed to read script '{}'", path.display()))
+        })?;
+        let script_name = path.with_extension("").display().to_string();
+        self.run(script_name, strip_shebang(contents)).await
+    }
+
+    /**
+        Runs a Lune script inside of the current runtime, capturing its output.
+
+        Anything the script writes to standard output and standard error using
+        builtins is captured instead of being written to the current process,
+        and the values returned by the script are converted to JSON values.
+
+        Errors thrown by the script are written to the captured standard error,
+        and are reflected in the exit code, instead of being returned.
+
+        # Errors
+
+        This function will return an error if the script could not be started, such
+        as when it contains syntax errors, or if the values it returned could not be
+        converted to JSON, such as when returning a table that contains itself.
+    */
+    pub async fn run_captured(
+        &mut self,
+        script_name: impl AsRef<str>,
+        script_contents: impl AsRef<[u8]>,
+    ) -> RuntimeResult<CapturedRun> {
+        let output = CapturedOutput::new();
+        output.install(self.inner.lua());
+
+        let result = self
+            .run_inner(script_name.as_ref(), script_contents.as_ref())
+            .await;
+        CapturedOutput::uninstall(self.inner.lua());
+
+        // NOTE: Errors from the main thread have already been written
+        // to the captured stderr by the error callback, so we only need
+        // to convert the returned values when the script succeeded
+        let (exit_code, main_thread_res) = result?;
+        let values = match main_thread_res {
+            Ok(values) => {
+                let lua = self.inner.lua();
+                let options = LuaDeserializeOptions::new().deny_unsupported_types(false);
+                values
+                    .into_iter()
+                    .map(|value| lua.from_value_with::<JsonValue>(value, options))
+                    .collect::<LuaResult<Vec<_>>>()?
+            }
+            Err(_) => Vec::new(),
+        };
+
+        Ok(CapturedRun {
+            exit_code,
+            stdout: String::from_utf8_lossy(&output.take_stdout()).into_owned(),
+            stderr: String::from_utf8_lossy(&output.take_stderr()).into_owned(),
+            values,
+        })
+    }
+
+    /**
+        Runs a Lune script file inside of the current runtime, capturing its output.
+
+        See [`Runtime::run_captured`] for more information about how output is captured.
+
+        # Errors
+
+        This function will return an error if the file could not be read, or if the script could not be started.
+    */
+    pub async fn run_file_captured(
+        &mut self,
+        path: impl AsRef<Path>,
+    ) -> RuntimeResult<CapturedRun> {
+        let path = path.as_ref();
+        let contents = tokio::fs::read(path).await.map_err(|e| {
+            LuaError::external(e).context(format!("Failed to read script '{}'", path.display()))
+        })?;
+        let script_name = path.with_extension("").display().to_string();
+        self.run_captured(script_name, strip_shebang(contents))
+            .await
+    }
+
     /**
         Runs a Lune script inside of the current runtime.
 
         This will preserve any modifications to global values / context.
 
+        The script may yield at the top level, such as by calling `task.wait`, without
+        being wrapped in a coroutine. This function keeps running until the script and
+        any threads it spawned have all finished, or until `process.exit` is called.
+        If the script yields and is never resumed, no values are returned for it.
+
         # Errors
 
         This function will return an error if the script fails to run.
@@ -146,36 +522,168 @@ impl Runtime {
         script_name: impl AsRef<str>,
         script_contents: impl AsRef<[u8]>,
     ) -> RuntimeResult<(u8, Vec<LuaValue>)> {
+        let (exit_code, main_thread_res) = self
+            .run_inner(script_name.as_ref(), script_contents.as_ref())
+            .await?;
+        // NOTE: Errors in the main thread are reported by our error
+        // callback as they happen, same as errors in any other thread
+        let values = main_thread_res.map_err(|e| RuntimeError::from(e).mark_reported())?;
+        Ok((exit_code, values))
+    }
+
+    /**
+        Runs a script, returning its exit code along with the result of its main thread.
+
+        Errors if the script could not be started, such as when it contains syntax errors.
+    */
+    async fn run_inner(
+        &self,
+        script_name: &str,
+        script_contents: &[u8],
+    ) -> RuntimeResult<(u8, LuaResult<Vec<LuaValue>>)> {
         let lua = self.inner.lua();
         let sched = self.inner.scheduler();
 
+        // Mark the script as active, so that crash reports can include it
+        let _active = ActiveScriptGuard::new(script_name, self.inner.borrow_owner());
+
         // Add error callback to format errors nicely + store status
-        let got_any_error = Arc::new(AtomicBool::new(false));
-        let got_any_inner = Arc::clone(&got_any_error);
-        self.inner.scheduler().set_error_callback(move |e| {
-            got_any_inner.store(true, Ordering::SeqCst);
-            eprintln!("{}", RuntimeError::from(e));
-        });
+        let got_any_error = self.install_error_callback();
 
         // Load our "main" thread
-        let main = lua
-            .load(script_contents.as_ref())
-            .set_name(script_name.as_ref());
+        let main = lua.load(script_contents).set_name(script_name);
 
         // Run it on our scheduler until it and any other spawned threads complete
         let main_thread_id = sched.push_thread_back(main, ())?;
         sched.run().await;
 
+        // NOTE: The main thread may not have finished if it yielded and was never
+        // resumed, or if the process exited early, meaning nothing was returned
         let main_thread_res = match sched.get_thread_result(main_thread_id) {
             Some(res) => res,
-            None => LuaValue::Nil.into_lua_multi(lua),
-        }?;
+            None => Ok(LuaMultiValue::new()),
+        };
 
         Ok((
             sched
                 .get_exit_code()
                 .unwrap_or(u8::from(got_any_error.load(Ordering::SeqCst))),
-            main_thread_res.into_vec(),
+            main_thread_res.map(LuaMultiValue::into_vec),
+        ))
+    }
+
+    /**
+        Sets the scheduler error callback to format errors nicely, writing them to the
+        captured stderr if output is being captured, and to stderr otherwise.
+
+        Returns a flag that is set once any error has been encountered.
+    */
+    fn install_error_callback(&self) -> Arc<AtomicBool> {
+        let got_any_error = Arc::new(AtomicBool::new(false));
+        let got_any_inner = Arc::clone(&got_any_error);
+        let captured = CapturedOutput::get(self.inner.lua());
+        self.inner.scheduler().set_error_callback(move |e| {
+            got_any_inner.store(true, Ordering::SeqCst);
+            match &captured {
+                Some(output) => {
+                    let formatted = RuntimeError::from(e).disable_colors();
+                    output.write_stderr(format!("{formatted}\n").as_bytes());
+                }
+                None => eprintln!("{}", RuntimeError::from(e)),
+            }
+        });
+        got_any_error
+    }
+}
+
+/**
+    Runs a Lune script file in a new runtime, with its own Luau VM and async runtime,
+    blocking the current thread until it has finished. Used for `process.spawnLune`.
+
+    The new runtime is given the same restrictions and limits as the one spawning it.
+*/
+#[cfg(feature = "std-process")]
+fn run_file_in_new_runtime(
+    codegen: bool,
+    path: &Path,
+    args: Vec<String>,
+    inherited: Inherited,
+) -> Result<lune_std::LuneScriptResult, String> {
+    let async_rt = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| e.to_string())?;
+    let run = async_rt
+        .block_on(async {
+            let mut rt = Runtime::new(codegen).with_args(args);
+            inherited.apply(&mut rt)?;
+            rt.run_file_captured(path).await
+        })
+        .map_err(|e| e.disable_colors().to_string())?;
+    Ok(lune_std::LuneScriptResult {
+        exit_code: run.exit_code,
+        stdout: run.stdout,
+        stderr: run.stderr,
+        values: run.values,
+    })
+}
+
+/**
+    Removes the shebang line from the start of a script, if it has one,
+    so that scripts with a shebang can be parsed as regular Luau code.
+*/
+#[must_use]
+pub fn strip_shebang(mut contents: Vec<u8>) -> Vec<u8> {
+    if contents.starts_with(b"#!") {
+        // NOTE: We keep the newline here to preserve line numbers in stack traces
+        let end = contents
+            .iter()
+            .position(|c| *c == b'\n')
+            .unwrap_or(contents.len());
+        contents.drain(..end);
+    }
+    contents
+}
+
+#[cfg(feature = "std-any")]
+fn set_disabled_libraries<A, S>(lua: &Lua, names: A, invert: bool) -> RuntimeResult<()>
+where
+    A: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    use lune_std::LuneStandardLibrary;
+
+    let libraries = names
+        .into_iter()
+        .map(|name| name.as_ref().parse())
+        .collect::<Result<Vec<LuneStandardLibrary>, _>>()
+        .map_err(LuaError::runtime)?;
+
+    if invert {
+        let disabled = LuneStandardLibrary::ALL
+            .iter()
+            .filter(|library| !libraries.contains(library))
+            .copied();
+        lune_std::disable_libraries(lua, disabled);
+    } else {
+        lune_std::disable_libraries(lua, libraries);
+    }
+
+    Ok(())
+}
+
+#[cfg(not(feature = "std-any"))]
+fn set_disabled_libraries<A, S>(_lua: &Lua, names: A, _invert: bool) -> RuntimeResult<()>
+where
+    A: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    match names.into_iter().next() {
+        None => Ok(()),
+        Some(name) => Err(LuaError::runtime(format!(
+            "Unknown standard library '{}'\nNo standard libraries are enabled",
+            name.as_ref()
         ))
+        .into()),
     }
 }
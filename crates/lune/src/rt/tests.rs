@@ -0,0 +1,292 @@
+use std::{collections::HashMap, path::Path, process::Command};
+
+use mlua::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{CrashContext, Lune, Serde, PLUGIN_ABI_VERSION};
+
+#[derive(Serialize, Deserialize)]
+struct User {
+    name: String,
+    admin: bool,
+}
+
+struct Counter(u32);
+
+impl LuaUserData for Counter {
+    fn add_methods<'lua, M: LuaUserDataMethods<'lua, Self>>(methods: &mut M) {
+        methods.add_function("new", |_, start: u32| Ok(Counter(start)));
+        methods.add_method_mut("increment", |_, this, ()| {
+            this.0 += 1;
+            Ok(this.0)
+        });
+    }
+}
+
+#[tokio::test]
+async fn builder_sets_globals() {
+    let mut runtime = Lune::builder()
+        .codegen(false)
+        .global("appName", "my-app")
+        .setup(|lua| {
+            let table = lua.create_table()?;
+            table.set("answer", 42)?;
+            lua.globals().set("config", table)
+        })
+        .build()
+        .unwrap();
+
+    let run = runtime
+        .run_captured("builder", "return appName, config.answer")
+        .await
+        .unwrap();
+    assert!(run.success());
+    assert_eq!(run.values, [json!("my-app"), json!(42)]);
+}
+
+#[tokio::test]
+async fn captured_runs_contain_output_and_values() {
+    let mut runtime = Lune::builder().build().unwrap();
+
+    let run = runtime
+        .run_captured(
+            "captured",
+            "print(\"hello\")\nreturn 1, \"two\", { three = 3 }",
+        )
+        .await
+        .unwrap();
+    assert!(run.success());
+    assert_eq!(run.stdout, "hello\n");
+    assert_eq!(run.stderr, "");
+    assert_eq!(run.values, [json!(1), json!("two"), json!({ "three": 3 })]);
+}
+
+#[tokio::test]
+async fn captured_runs_contain_errors() {
+    let mut runtime = Lune::builder().build().unwrap();
+
+    let run = runtime
+        .run_captured("captured", "error(\"something went wrong\")")
+        .await
+        .unwrap();
+    assert!(!run.success());
+    assert!(
+        run.stderr.contains("something went wrong"),
+        "{}",
+        run.stderr
+    );
+    assert!(run.values.is_empty());
+}
+
+#[tokio::test]
+async fn host_functions_are_callable() {
+    let mut runtime = Lune::builder()
+        .function("add", |(a, b): (i64, i64)| {
+            a.checked_add(b).ok_or("Integer overflow")
+        })
+        .async_function("double", |n: i64| async move {
+            tokio::task::yield_now().await;
+            Ok::<_, std::io::Error>(n * 2)
+        })
+        .function("promote", |Serde(mut user): Serde<User>| {
+            user.admin = true;
+            Ok::<_, std::io::Error>(Serde(user))
+        })
+        .userdata_type::<Counter>("Counter")
+        .build()
+        .unwrap();
+
+    let run = runtime
+        .run_captured(
+            "host",
+            "local counter = Counter.new(10)\
+            \ncounter:increment()\
+            \nlocal ok, err = pcall(add, 2^62, 2^62)\
+            \nreturn add(1, 2), double(21), promote({ name = \"a\", admin = false }),\
+            \n    counter:increment(), ok",
+        )
+        .await
+        .unwrap();
+    assert!(run.success(), "{}", run.stderr);
+    assert_eq!(
+        run.values,
+        [
+            json!(3),
+            json!(42),
+            json!({ "name": "a", "admin": true }),
+            json!(12),
+            json!(false),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn host_function_errors_are_catchable() {
+    let mut runtime = Lune::builder()
+        .function("fail", |(): ()| Err::<(), _>("host function failed"))
+        .build()
+        .unwrap();
+
+    let run = runtime
+        .run_captured(
+            "host",
+            "local ok, err = pcall(fail)\nreturn ok, tostring(err)",
+        )
+        .await
+        .unwrap();
+    assert!(run.success(), "{}", run.stderr);
+    assert_eq!(run.values[0], json!(false));
+    let message = run.values[1].as_str().unwrap();
+    assert!(message.contains("host function failed"), "{message}");
+}
+
+#[tokio::test]
+async fn crash_contexts_contain_the_running_thread_stack() {
+    let mut runtime = Lune::builder()
+        .setup(|lua| {
+            let capture = lua.create_function(|_, ()| {
+                let context = CrashContext::capture();
+                Ok((context.script, context.luau_stack))
+            })?;
+            lua.globals().set("captureStack", capture)
+        })
+        .build()
+        .unwrap();
+
+    let run = runtime
+        .run_captured(
+            "crash",
+            "local task = require(\"@lune/task\")\
+            \nlocal function inner() return captureStack() end\
+            \nlocal script, stack\
+            \ntask.spawn(function() task.wait() script, stack = inner() end)\
+            \ntask.wait(0.05)\
+            \nreturn script, stack",
+        )
+        .await
+        .unwrap();
+    assert!(run.success(), "{}", run.stderr);
+    assert_eq!(run.values[0], json!("crash"));
+    assert_eq!(
+        run.values[1],
+        json!([
+            "[C] in function <anonymous>",
+            "[string \"crash\"]:2 in function inner",
+            "[string \"crash\"]:4 in function <anonymous>",
+        ])
+    );
+}
+
+#[test]
+fn missing_plugins_fail_to_build() {
+    let result = Lune::builder()
+        .plugin("this plugin does not exist.so")
+        .build();
+    let err = result.err().expect("missing plugin should fail to load");
+    assert!(err.to_string().contains("could not open library"), "{err}");
+}
+
+/**
+    Compiles a minimal native plugin with the given name and ABI version,
+    returning the path to the compiled library inside of the given directory.
+
+    The plugin does not use the Luau C API, and creates a module that is `nil`.
+*/
+fn compile_plugin(dir: &Path, name: &str, abi_version: u32) -> std::path::PathBuf {
+    let source = format!(
+        "#[no_mangle]\
+        \npub extern \"C\" fn lune_plugin_abi_version() -> u32 {{ {abi_version} }}\
+        \n#[no_mangle]\
+        \npub extern \"C\" fn lune_plugin_name() -> *const std::ffi::c_char {{\
+        \n    c\"{name}\".as_ptr()\
+        \n}}\
+        \n#[no_mangle]\
+        \npub extern \"C\" fn lune_plugin_open(_: *mut std::ffi::c_void) -> std::ffi::c_int {{ 0 }}"
+    );
+    let source_path = dir.join(format!("{name}.rs"));
+    std::fs::write(&source_path, source).unwrap();
+
+    let library_path = dir.join(format!(
+        "{}{name}{}",
+        std::env::consts::DLL_PREFIX,
+        std::env::consts::DLL_SUFFIX
+    ));
+    let rustc = std::env::var_os("RUSTC").unwrap_or_else(|| "rustc".into());
+    let status = Command::new(rustc)
+        .args(["--edition", "2021", "--crate-type", "cdylib", "-o"])
+        .arg(&library_path)
+        .arg(&source_path)
+        .status()
+        .unwrap();
+    assert!(status.success(), "failed to compile test plugin");
+
+    library_path
+}
+
+#[test]
+fn plugins_with_other_abi_versions_are_rejected() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = compile_plugin(dir.path(), "old_plugin", PLUGIN_ABI_VERSION + 1);
+
+    let result = Lune::builder().plugin(path).build();
+    let err = result
+        .err()
+        .expect("plugin with other ABI version should fail");
+    assert!(err.to_string().contains("ABI version"), "{err}");
+}
+
+#[cfg(feature = "std-any")]
+mod require {
+    use super::*;
+
+    #[tokio::test]
+    async fn plugins_are_requirable() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = compile_plugin(dir.path(), "test_plugin", PLUGIN_ABI_VERSION);
+
+        let mut runtime = Lune::builder().plugin(path).build().unwrap();
+        let run = runtime
+            .run_captured(
+                "plugin",
+                "local ok = pcall(require, \"@plugin/test_plugin\")\
+                \nlocal missingOk = pcall(require, \"@plugin/missing_plugin\")\
+                \nreturn ok, missingOk",
+            )
+            .await
+            .unwrap();
+        assert!(run.success(), "{}", run.stderr);
+        assert_eq!(run.values, [json!(true), json!(false)]);
+    }
+
+    #[tokio::test]
+    async fn requires_use_the_custom_resolver() {
+        let modules = HashMap::from([
+            ("virtual/module.luau", "return require(\"./inner\")"),
+            ("virtual/inner/init.luau", "return { value = 42 }"),
+        ]);
+        let mut runtime = Lune::builder()
+            .require_resolver(move |path| modules.get(path).map(|s| s.as_bytes().to_vec()))
+            .build()
+            .unwrap();
+
+        let run = runtime
+            .run_captured("resolver", "return require(\"./virtual/module\").value")
+            .await
+            .unwrap();
+        assert!(run.success(), "{}", run.stderr);
+        assert_eq!(run.values, [json!(42)]);
+    }
+}
+
+#[test]
+fn shebangs_are_stripped() {
+    use crate::strip_shebang;
+
+    assert_eq!(
+        strip_shebang(b"#!/usr/bin/env lune\nprint(1)".to_vec()),
+        b"\nprint(1)"
+    );
+    assert_eq!(strip_shebang(b"#!/usr/bin/env lune".to_vec()), b"");
+    assert_eq!(strip_shebang(b"print(1)".to_vec()), b"print(1)");
+}
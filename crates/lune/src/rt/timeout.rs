@@ -0,0 +1,113 @@
+use std::{
+    process,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use mlua::prelude::*;
+
+use lune_utils::CodedError;
+
+use super::crash::CrashContext;
+
+/**
+    A time limit for scripts running in a Lune runtime.
+
+    Once the limit has been reached, an error will be raised in any running
+    Luau code, using the VM interrupt callback. If the runtime has still not
+    stopped after the grace period has passed, the process is forcefully exited.
+
+    The force exit is cancelled when this struct is dropped.
+*/
+pub(crate) struct TimeLimit {
+    finished: Arc<AtomicBool>,
+}
+
+/**
+    When the time limit of a Luau VM runs out, stored
+    so that it can be inherited by spawned scripts.
+*/
+#[derive(Debug, Clone, Copy)]
+struct TimeLimitDeadline {
+    deadline: Instant,
+    grace_period: Duration,
+}
+
+impl TimeLimit {
+    pub(crate) fn install(lua: &Lua, limit: Duration, grace_period: Duration) -> Self {
+        let deadline = Instant::now() + limit;
+        lua.set_app_data(TimeLimitDeadline {
+            deadline,
+            grace_period,
+        });
+        let finished = Arc::new(AtomicBool::new(false));
+        let context = Arc::new(Mutex::new(None::<CrashContext>));
+
+        let interrupt_context = Arc::clone(&context);
+        lua.set_interrupt(move |_| {
+            if Instant::now() < deadline {
+                return Ok(LuaVmState::Continue);
+            }
+            if let Ok(mut context) = interrupt_context.lock() {
+                context.get_or_insert_with(CrashContext::capture);
+            }
+            Err(CodedError::new(
+                "TimedOut",
+                format!(
+                    "Script exceeded the time limit of {}",
+                    format_duration(limit)
+                ),
+            )
+            .into())
+        });
+
+        let watchdog_finished = Arc::clone(&finished);
+        thread::spawn(move || {
+            thread::sleep(limit + grace_period);
+            if watchdog_finished.load(Ordering::SeqCst) {
+                return;
+            }
+            eprintln!(
+                "Script exceeded the time limit of {} and did not stop within {}, exiting",
+                format_duration(limit),
+                format_duration(grace_period)
+            );
+            match context.lock().ok().and_then(|c| c.clone()) {
+                Some(context) => eprint!("{context}"),
+                None => eprintln!("No Luau code was running, the script was likely waiting"),
+            }
+            process::exit(1);
+        });
+
+        Self { finished }
+    }
+
+    /**
+        Gets the time remaining until the time limit of the given Luau VM
+        runs out, if any, together with its grace period.
+    */
+    pub(crate) fn remaining(lua: &Lua) -> Option<(Duration, Duration)> {
+        lua.app_data_ref::<TimeLimitDeadline>().map(|limit| {
+            let remaining = limit.deadline.saturating_duration_since(Instant::now());
+            (remaining, limit.grace_period)
+        })
+    }
+}
+
+impl Drop for TimeLimit {
+    fn drop(&mut self) {
+        self.finished.store(true, Ordering::SeqCst);
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    if duration.subsec_millis() == 0 {
+        format!("{}s", duration.as_secs())
+    } else {
+        format!("{}ms", duration.as_millis())
+    }
+}
@@ -1,4 +1,4 @@
-use std::{env, path::PathBuf};
+use std::{collections::BTreeMap, env, path::PathBuf};
 
 use anyhow::{bail, Result};
 use mlua::Compiler as LuaCompiler;
@@ -7,17 +7,36 @@ use tokio::fs;
 
 pub static CURRENT_EXE: Lazy<PathBuf> =
     Lazy::new(|| env::current_exe().expect("failed to get current exe"));
-const MAGIC: &[u8; 8] = b"cr3sc3nt";
 
 /*
-    TODO: Right now all we do is append the bytecode to the end
-    of the binary, but we will need a more flexible solution in
-    the future to store many files as well as their metadata.
+    NOTE: The magic bytes also identify the format of the metadata chunk, and must be
+    changed whenever the format changes, so that a binary never tries to read metadata
+    in a format that it does not understand - the original format, which only contained
+    the bytecode of the entrypoint script, used `cr3sc3nt` for its magic bytes
+*/
+const MAGIC: &[u8; 8] = b"cr3sc3n2";
+
+/**
+    The earliest version of Lune that reads the current metadata format.
+
+    Base executables older than this version would not be able to read the metadata
+    that gets appended to them, and can not be used to build standalone binaries.
+*/
+pub const MIN_BASE_VERSION: &str = "0.9.0";
 
-    The best solution here is most likely to use a well-supported
-    and rust-native binary serialization format with a stable
-    specification, one that also supports byte arrays well without
-    overhead, so the best solution seems to currently be Postcard:
+/*
+    The metadata chunk is appended to the end of the base binary, and consists of:
+
+    1. The size-prefixed bytecode of the entrypoint script
+    2. Any number of size-prefixed module paths, each followed by size-prefixed module bytecode
+    3. The size of the metadata chunk, not including this size or the magic bytes
+    4. The magic bytes, used to detect if a binary is a standalone binary
+
+    All sizes are stored as big-endian 64-bit unsigned integers.
+
+    FUTURE: If we ever need to store more than just files here, we should most likely switch
+    to a well-supported and rust-native binary serialization format with a stable specification,
+    one that also supports byte arrays well without overhead, such as Postcard:
 
     https://github.com/jamesmunns/postcard
     https://crates.io/crates/postcard
@@ -26,10 +45,14 @@ const MAGIC: &[u8; 8] = b"cr3sc3nt";
 /**
     Metadata for a standalone Lune executable. Can be used to
     discover and load the bytecode contained in a standalone binary.
+
+    Bundled modules are keyed by their path relative to the entrypoint,
+    the same way that paths are given to the runtime require resolver.
 */
 #[derive(Debug, Clone)]
 pub struct Metadata {
     pub bytecode: Vec<u8>,
+    pub modules: BTreeMap<String, Vec<u8>>,
 }
 
 impl Metadata {
@@ -37,20 +60,20 @@ impl Metadata {
         Returns whether or not the currently executing Lune binary
         is a standalone binary, and if so, the bytes of the binary.
     */
-    pub async fn check_env() -> (bool, Vec<u8>) {
-        let contents = fs::read(CURRENT_EXE.to_path_buf())
-            .await
-            .unwrap_or_default();
+    pub fn check_env() -> (bool, Vec<u8>) {
+        let contents = std::fs::read(CURRENT_EXE.as_path()).unwrap_or_default();
         let is_standalone = contents.ends_with(MAGIC);
         (is_standalone, contents)
     }
 
     /**
-        Creates a patched standalone binary from the given script contents.
+        Creates a patched standalone binary from the given script
+        contents, and the contents of any modules that it requires.
     */
     pub async fn create_env_patched_bin(
         base_exe_path: PathBuf,
         script_contents: impl Into<Vec<u8>>,
+        module_contents: BTreeMap<String, Vec<u8>>,
     ) -> Result<Vec<u8>> {
         let compiler = LuaCompiler::new()
             .set_optimization_level(2)
@@ -59,11 +82,15 @@ impl Metadata {
 
         let mut patched_bin = fs::read(base_exe_path).await?;
 
-        // Compile luau input into bytecode
+        // Compile luau input and modules into bytecode
         let bytecode = compiler.compile(script_contents.into());
+        let modules = module_contents
+            .into_iter()
+            .map(|(path, contents)| (path, compiler.compile(contents)))
+            .collect();
 
         // Append the bytecode / metadata to the end
-        let meta = Self { bytecode };
+        let meta = Self { bytecode, modules };
         patched_bin.extend_from_slice(&meta.to_bytes());
 
         Ok(patched_bin)
@@ -78,15 +105,25 @@ impl Metadata {
             bail!("not a standalone binary")
         }
 
-        // Extract bytecode size
-        let bytecode_size_bytes = &bytes[bytes.len() - 16..bytes.len() - 8];
-        let bytecode_size =
-            usize::try_from(u64::from_be_bytes(bytecode_size_bytes.try_into().unwrap()))?;
+        // Extract metadata chunk size
+        let chunk_end = bytes.len() - 16;
+        let chunk_size_bytes = &bytes[chunk_end..chunk_end + 8];
+        let chunk_size = usize::try_from(u64::from_be_bytes(chunk_size_bytes.try_into().unwrap()))?;
+        let Some(chunk_start) = chunk_end.checked_sub(chunk_size) else {
+            bail!("standalone binary metadata is corrupted")
+        };
 
-        // Extract bytecode
-        let bytecode = bytes[bytes.len() - 16 - bytecode_size..].to_vec();
+        // Extract bytecode, followed by any bundled modules
+        let mut chunk = &bytes[chunk_start..chunk_end];
+        let bytecode = read_sized(&mut chunk)?.to_vec();
+        let mut modules = BTreeMap::new();
+        while !chunk.is_empty() {
+            let path = String::from_utf8(read_sized(&mut chunk)?.to_vec())?;
+            let module_bytecode = read_sized(&mut chunk)?.to_vec();
+            modules.insert(path, module_bytecode);
+        }
 
-        Ok(Self { bytecode })
+        Ok(Self { bytecode, modules })
     }
 
     /**
@@ -94,9 +131,126 @@ impl Metadata {
     */
     pub fn to_bytes(&self) -> Vec<u8> {
         let mut bytes = Vec::new();
-        bytes.extend_from_slice(&self.bytecode);
-        bytes.extend_from_slice(&(self.bytecode.len() as u64).to_be_bytes());
+        write_sized(&mut bytes, &self.bytecode);
+        for (path, module_bytecode) in &self.modules {
+            write_sized(&mut bytes, path.as_bytes());
+            write_sized(&mut bytes, module_bytecode);
+        }
+        let chunk_size = bytes.len() as u64;
+        bytes.extend_from_slice(&chunk_size.to_be_bytes());
         bytes.extend_from_slice(MAGIC);
         bytes
     }
 }
+
+fn read_sized<'a>(bytes: &mut &'a [u8]) -> Result<&'a [u8]> {
+    if bytes.len() < 8 {
+        bail!("standalone binary metadata is corrupted")
+    }
+    let (size_bytes, rest) = bytes.split_at(8);
+    let size = usize::try_from(u64::from_be_bytes(size_bytes.try_into().unwrap()))?;
+    if rest.len() < size {
+        bail!("standalone binary metadata is corrupted")
+    }
+    let (value, rest) = rest.split_at(size);
+    *bytes = rest;
+    Ok(value)
+}
+
+fn write_sized(bytes: &mut Vec<u8>, value: &[u8]) {
+    bytes.extend_from_slice(&(value.len() as u64).to_be_bytes());
+    bytes.extend_from_slice(value);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata() -> Metadata {
+        Metadata {
+            bytecode: b"entrypoint".to_vec(),
+            modules: BTreeMap::from([
+                ("a.luau".to_string(), b"module a".to_vec()),
+                ("lib/init.luau".to_string(), Vec::new()),
+            ]),
+        }
+    }
+
+    #[test]
+    fn metadata_roundtrips_through_bytes() {
+        let mut bin = b"base executable".to_vec();
+        bin.extend_from_slice(&metadata().to_bytes());
+
+        let meta = Metadata::from_bytes(&bin).unwrap();
+        assert_eq!(meta.bytecode, metadata().bytecode);
+        assert_eq!(meta.modules, metadata().modules);
+    }
+
+    #[test]
+    fn binaries_without_metadata_are_rejected() {
+        assert!(Metadata::from_bytes(b"").is_err());
+        assert!(Metadata::from_bytes(MAGIC).is_err());
+        assert!(Metadata::from_bytes(b"base executable without any metadata").is_err());
+
+        // Binaries using the original format must not be read as the current format
+        let mut legacy = metadata().to_bytes();
+        let magic_start = legacy.len() - MAGIC.len();
+        legacy[magic_start..].copy_from_slice(b"cr3sc3nt");
+        assert!(Metadata::from_bytes(legacy).is_err());
+    }
+
+    /**
+        Appends the given chunk to a base executable, along with its size and the magic bytes.
+    */
+    fn with_chunk(chunk: &[u8]) -> Vec<u8> {
+        let mut bin = b"base executable".to_vec();
+        bin.extend_from_slice(chunk);
+        bin.extend_from_slice(&(chunk.len() as u64).to_be_bytes());
+        bin.extend_from_slice(MAGIC);
+        bin
+    }
+
+    #[test]
+    fn corrupted_metadata_is_rejected() {
+        // Chunk size larger than the binary itself
+        let mut too_large = metadata().to_bytes();
+        let size_start = too_large.len() - 16;
+        too_large[size_start..size_start + 8].copy_from_slice(&u64::MAX.to_be_bytes());
+        assert!(Metadata::from_bytes(too_large).is_err());
+
+        // Chunk that ends in the middle of a size
+        let mut chunk = Vec::new();
+        write_sized(&mut chunk, b"entrypoint");
+        chunk.extend_from_slice(&[0, 0, 0]);
+        assert!(Metadata::from_bytes(with_chunk(&chunk)).is_err());
+
+        // Module size that points past the end of the chunk
+        let mut chunk = Vec::new();
+        write_sized(&mut chunk, b"entrypoint");
+        write_sized(&mut chunk, b"a.luau");
+        chunk.extend_from_slice(&1000u64.to_be_bytes());
+        chunk.extend_from_slice(b"module a");
+        assert!(Metadata::from_bytes(with_chunk(&chunk)).is_err());
+
+        // Module path without any module bytecode
+        let mut chunk = Vec::new();
+        write_sized(&mut chunk, b"entrypoint");
+        write_sized(&mut chunk, b"a.luau");
+        assert!(Metadata::from_bytes(with_chunk(&chunk)).is_err());
+
+        // Module path that is not valid utf-8
+        let mut chunk = Vec::new();
+        write_sized(&mut chunk, b"entrypoint");
+        write_sized(&mut chunk, &[0xFF, 0xFE]);
+        write_sized(&mut chunk, b"module");
+        assert!(Metadata::from_bytes(with_chunk(&chunk)).is_err());
+
+        // A valid chunk built the same way as the ones above should be read correctly
+        let mut chunk = Vec::new();
+        write_sized(&mut chunk, b"entrypoint");
+        write_sized(&mut chunk, b"a.luau");
+        write_sized(&mut chunk, b"module a");
+        let meta = Metadata::from_bytes(with_chunk(&chunk)).unwrap();
+        assert_eq!(meta.modules["a.luau"], b"module a");
+    }
+}
@@ -12,8 +12,8 @@ use self::metadata::Metadata;
     Returns whether or not the currently executing Lune binary
     is a standalone binary, and if so, the bytes of the binary.
 */
-pub async fn check() -> Option<Vec<u8>> {
-    let (is_standalone, patched_bin) = Metadata::check_env().await;
+pub fn check() -> Option<Vec<u8>> {
+    let (is_standalone, patched_bin) = Metadata::check_env();
     if is_standalone {
         Some(patched_bin)
     } else {
@@ -29,13 +29,20 @@ pub async fn run(patched_bin: impl AsRef<[u8]>) -> Result<ExitCode> {
     let args = env::args().skip(1).collect::<Vec<_>>();
     let meta = Metadata::from_bytes(patched_bin).expect("must be a standalone binary");
 
+    let Metadata { bytecode, modules } = meta;
+
+    // Modules bundled into the binary are resolved from the metadata
+    // chunk, and all other modules are read from the filesystem
     let mut rt = Runtime::new(true).with_args(args);
+    rt.set_require_resolver(move |path| modules.get(path).cloned());
 
-    let result = rt.run("STANDALONE", meta.bytecode).await;
+    let result = rt.run("STANDALONE", bytecode).await;
 
     Ok(match result {
         Err(err) => {
-            eprintln!("{err}");
+            if !err.is_reported() {
+                eprintln!("{err}");
+            }
             ExitCode::FAILURE
         }
         Ok((code, _)) => ExitCode::from(code),
@@ -1,14 +1,215 @@
-/*
-    TODO: Implement tracing of requires here
-
-    Rough steps / outline:
-
-    1. Create a new tracer struct using a main entrypoint script path
-    2. Some kind of discovery mechanism that goes through all require chains (failing on recursive ones)
-       2a. Conversion of script-relative paths to cwd-relative paths + normalization
-       2b. Cache all found files in a map of file path -> file contents
-       2c. Prepend some kind of symbol to paths that can tell our runtime `require` function that it
-           should look up a bundled/standalone script, a good symbol here is probably a dollar sign ($)
-    3. ???
-    4. Profit
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use tokio::fs;
+use tracing::warn;
+
+use lune_utils::path::clean_path;
+
+/**
+    Traces all modules required by the given entrypoint script, following
+    require chains through any modules that are found along the way.
+
+    Returns a map of module paths to the contents of each module. Module paths are
+    relative to the directory containing the entrypoint and use `/` as the separator,
+    making them the same paths that the require resolver receives when the entrypoint
+    runs as a standalone binary.
+
+    Only requires with a single string literal argument are traced, since aliases,
+    libraries, plugins and dynamic requires can not be resolved up front. Any required
+    module that can not be found is skipped, and read from the filesystem at runtime.
+
+    Requires that appear inside of comments and strings are also traced, see [`find_require_paths`].
+
+    # Errors
+
+    Errors if a required module exists but could not be read.
+*/
+pub async fn trace_requires(
+    entrypoint: &Path,
+    entrypoint_contents: &[u8],
+) -> Result<BTreeMap<String, Vec<u8>>> {
+    let root = entrypoint.parent().unwrap_or(Path::new(""));
+
+    let mut modules = BTreeMap::new();
+    let mut pending = find_require_paths(entrypoint_contents)
+        .into_iter()
+        .map(clean_path)
+        .collect::<Vec<_>>();
+
+    while let Some(rel_path) = pending.pop() {
+        let Some((module_path, contents)) = read_module(root, &rel_path).await? else {
+            warn!(
+                "failed to find required module '{}' - it will be read from the filesystem instead",
+                rel_path.display()
+            );
+            continue;
+        };
+
+        let module_key = module_key(&module_path);
+        if modules.contains_key(&module_key) {
+            continue;
+        }
+
+        // NOTE: Requires are relative to the file they are in, so
+        // nested requires must be resolved from this module's directory
+        let module_dir = module_path.parent().unwrap_or(Path::new(""));
+        pending.extend(
+            find_require_paths(&contents)
+                .into_iter()
+                .map(|path| clean_path(module_dir.join(path))),
+        );
+
+        modules.insert(module_key, contents);
+    }
+
+    Ok(modules)
+}
+
+/**
+    Finds the module for the given require path, relative to the given root directory.
+
+    This tries the same file paths, in the same order, as `require` does at runtime.
 */
+async fn read_module(root: &Path, rel_path: &Path) -> Result<Option<(PathBuf, Vec<u8>)>> {
+    let rel_init = rel_path.join("init");
+    let candidates = [
+        rel_path.to_path_buf(),
+        append_extension(rel_path, "luau"),
+        append_extension(rel_path, "lua"),
+        append_extension(&rel_init, "luau"),
+        append_extension(&rel_init, "lua"),
+    ];
+
+    for candidate in candidates {
+        let full_path = root.join(&candidate);
+        if fs::metadata(&full_path).await.is_ok_and(|m| m.is_file()) {
+            let contents = fs::read(&full_path).await.with_context(|| {
+                format!("failed to read required module '{}'", full_path.display())
+            })?;
+            return Ok(Some((candidate, contents)));
+        }
+    }
+
+    Ok(None)
+}
+
+/**
+    Finds all string literal paths passed to `require` in the given source code.
+
+    Paths for libraries, plugins and aliases, which all start with `@`, are ignored.
+
+    The source code is scanned for requires without being parsed, meaning that requires
+    inside of comments and strings are also found, and the modules they point to are also
+    bundled, if they exist. Bundled modules only ever run if they are actually required.
+*/
+fn find_require_paths(source: &[u8]) -> Vec<String> {
+    let source = String::from_utf8_lossy(source);
+    let is_ident_char = |c: char| c.is_alphanumeric() || matches!(c, '_' | '.' | ':');
+
+    let mut paths = Vec::new();
+    for (index, _) in source.match_indices("require") {
+        // Make sure this is a call to the global and not a method or part of another name
+        if source[..index]
+            .chars()
+            .next_back()
+            .is_some_and(is_ident_char)
+        {
+            continue;
+        }
+
+        let rest = source[index + "require".len()..].trim_start();
+        let rest = rest.strip_prefix('(').map_or(rest, str::trim_start);
+        let Some(quote) = rest
+            .chars()
+            .next()
+            .filter(|c| matches!(c, '"' | '\'' | '`'))
+        else {
+            continue;
+        };
+        let Some(end) = rest[1..].find(quote) else {
+            continue;
+        };
+
+        let path = &rest[1..=end];
+        let is_interpolated = quote == '`' && path.contains('{');
+        if !path.is_empty() && !path.starts_with('@') && !path.contains('\\') && !is_interpolated {
+            paths.push(path.to_string());
+        }
+    }
+    paths
+}
+
+fn module_key(path: &Path) -> String {
+    path.components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn append_extension(path: impl Into<PathBuf>, ext: &'static str) -> PathBuf {
+    let mut new = path.into();
+    match new.extension() {
+        Some(e) => new.set_extension(format!("{}.{ext}", e.to_string_lossy())),
+        None => new.set_extension(ext),
+    };
+    new
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(dir: &Path, path: &str, contents: &str) {
+        let path = dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn require_paths_are_found() {
+        let source = br#"
+            local a = require("./a")
+            local b = require "./b.luau"
+            local c = require('../c')
+            local d = require(`./d`)
+            local fs = require("@lune/fs")
+            local alias = require("@alias/module")
+            local dynamic = require(`./{name}`)
+            local method = module:require("./method")
+            local field = module.require("./field")
+            local other = myrequire("./other")
+            -- local commented = require("./commented")
+            local text = "require('./string')"
+        "#;
+        assert_eq!(
+            find_require_paths(source),
+            ["./a", "./b.luau", "../c", "./d", "./commented", "./string"]
+        );
+    }
+
+    #[tokio::test]
+    async fn requires_are_traced_through_modules() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), "main.luau", "");
+        write(dir.path(), "a.luau", "return require('./lib')");
+        write(dir.path(), "lib/init.luau", "return require('./util')");
+        write(dir.path(), "lib/util.lua", "return require('../a')");
+
+        let entrypoint = dir.path().join("main.luau");
+        let source = br#"
+            require("./a")
+            require("./missing")
+            require("@lune/fs")
+        "#;
+        let modules = trace_requires(&entrypoint, source).await.unwrap();
+        assert_eq!(
+            modules.keys().collect::<Vec<_>>(),
+            ["a.luau", "lib/init.luau", "lib/util.lua"]
+        );
+        assert_eq!(modules["a.luau"], b"return require('./lib')");
+    }
+}
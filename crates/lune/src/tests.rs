@@ -14,7 +14,10 @@ use crate::Runtime;
 const ARGS: &[&str] = &["Foo", "Bar"];
 
 macro_rules! create_tests {
-    ($($name:ident: $value:expr,)*) => { $(
+    ($($name:ident: $value:expr,)*) => {
+        create_tests! { @configure |_| {}; $($name: $value,)* }
+    };
+    (@configure $configure:expr; $($name:ident: $value:expr,)*) => { $(
         #[tokio::test(flavor = "multi_thread")]
         async fn $name() -> Result<ExitCode> {
             // We need to change the current directory to the workspace root since
@@ -38,6 +41,8 @@ macro_rules! create_tests {
                     .map(ToString::to_string)
                     .collect::<Vec<_>>()
             );
+            let configure: fn(&mut Runtime) = $configure;
+            configure(&mut lune);
             let script_name = full_name
 				.trim_end_matches(".luau")
 				.trim_end_matches(".lua")
@@ -48,18 +53,37 @@ macro_rules! create_tests {
     )* }
 }
 
-#[cfg(any(
-    feature = "std-datetime",
-    feature = "std-fs",
-    feature = "std-luau",
-    feature = "std-net",
-    feature = "std-process",
-    feature = "std-regex",
-    feature = "std-roblox",
-    feature = "std-serde",
-    feature = "std-stdio",
-    feature = "std-task",
-))]
+// NOTE: Sandboxed tests run with filesystem access restricted to the workspace,
+// letting them check that scripts can not use a library to escape from it
+macro_rules! create_sandboxed_tests {
+    ($($name:ident: $value:expr,)*) => {
+        create_tests! { @configure sandbox; $($name: $value,)* }
+    };
+}
+
+#[allow(dead_code)]
+fn sandbox(lune: &mut Runtime) {
+    let workspace_dir_str = format!("{}/../../", env!("CARGO_MANIFEST_DIR"));
+    let workspace_dir = clean_path_and_make_absolute(PathBuf::from(workspace_dir_str));
+    lune.allow_fs_read([&workspace_dir]);
+    lune.allow_fs_write([&workspace_dir]);
+}
+
+// NOTE: Precise timer tests run with a timer resolution below one millisecond,
+// letting them check that waits are more precise than with the default resolution
+macro_rules! create_precise_timer_tests {
+    ($($name:ident: $value:expr,)*) => {
+        create_tests! { @configure precise_timer; $($name: $value,)* }
+    };
+}
+
+#[allow(dead_code)]
+fn precise_timer(lune: &mut Runtime) {
+    lune.set_timer_resolution("50us")
+        .expect("timer resolution should be valid");
+}
+
+#[cfg(feature = "std-any")]
 create_tests! {
     require_aliases: "require/tests/aliases",
     require_async: "require/tests/async",
@@ -75,9 +99,11 @@ create_tests! {
     require_siblings: "require/tests/siblings",
     require_state: "require/tests/state",
 
+    global_collectgarbage: "globals/collectgarbage",
     global_g_table: "globals/_G",
     global_version: "globals/_VERSION",
     global_coroutine: "globals/coroutine",
+    global_error_object: "globals/ErrorObject",
     global_error: "globals/error",
     global_pcall: "globals/pcall",
     global_type: "globals/type",
@@ -85,6 +111,35 @@ create_tests! {
     global_warn: "globals/warn",
 }
 
+#[cfg(feature = "std-archive")]
+create_tests! {
+    archive_tar: "archive/tar",
+    archive_traversal: "archive/traversal",
+    archive_zip: "archive/zip",
+}
+
+#[cfg(all(feature = "std-archive", feature = "std-fs"))]
+create_tests! {
+    archive_files: "archive/files",
+}
+
+#[cfg(feature = "std-bench")]
+create_tests! {
+    bench_run: "bench/run",
+}
+
+#[cfg(all(feature = "std-bench", feature = "std-fs", feature = "std-serde"))]
+create_tests! {
+    bench_baseline: "bench/baseline",
+}
+
+#[cfg(feature = "std-crypto")]
+create_tests! {
+    crypto_hash: "crypto/hash",
+    crypto_hmac: "crypto/hmac",
+    crypto_random: "crypto/random",
+}
+
 #[cfg(feature = "std-datetime")]
 create_tests! {
     datetime_format_local_time: "datetime/formatLocalTime",
@@ -93,12 +148,30 @@ create_tests! {
     datetime_from_local_time: "datetime/fromLocalTime",
     datetime_from_universal_time: "datetime/fromUniversalTime",
     datetime_from_unix_timestamp: "datetime/fromUnixTimestamp",
+    datetime_from_unix_timestamp_millis: "datetime/fromUnixTimestampMillis",
     datetime_now: "datetime/now",
     datetime_to_iso_date: "datetime/toIsoDate",
     datetime_to_local_time: "datetime/toLocalTime",
     datetime_to_universal_time: "datetime/toUniversalTime",
 }
 
+#[cfg(feature = "std-expect")]
+create_tests! {
+    expect_deep_equal: "expect/deepEqual",
+    expect_matchers: "expect/matchers",
+    expect_never: "expect/never",
+}
+
+#[cfg(all(
+    feature = "std-ffi",
+    unix,
+    any(target_arch = "x86_64", target_arch = "aarch64")
+))]
+create_tests! {
+    ffi_call: "ffi/call",
+    ffi_struct: "ffi/struct",
+}
+
 #[cfg(feature = "std-fs")]
 create_tests! {
     fs_files: "fs/files",
@@ -106,6 +179,15 @@ create_tests! {
     fs_dirs: "fs/dirs",
     fs_metadata: "fs/metadata",
     fs_move: "fs/move",
+    fs_hash: "fs/hash",
+    fs_watch: "fs/watch",
+}
+
+#[cfg(feature = "std-image")]
+create_tests! {
+    image_encode: "image/encode",
+    image_info: "image/info",
+    image_resize: "image/resize",
 }
 
 #[cfg(feature = "std-luau")]
@@ -116,22 +198,46 @@ create_tests! {
     luau_safeenv: "luau/safeenv",
 }
 
+#[cfg(feature = "std-ipc")]
+create_tests! {
+    ipc_errors: "ipc/errors",
+    ipc_roundtrip: "ipc/roundtrip",
+}
+
+#[cfg(feature = "std-log")]
+create_tests! {
+    log_child: "log/child",
+    log_format: "log/format",
+    log_levels: "log/levels",
+}
+
 #[cfg(feature = "std-net")]
 create_tests! {
     net_request_codes: "net/request/codes",
     net_request_compression: "net/request/compression",
     net_request_methods: "net/request/methods",
+    net_request_options: "net/request/options",
     net_request_query: "net/request/query",
     net_request_redirect: "net/request/redirect",
+    net_request_stream: "net/request/stream",
     net_url_encode: "net/url/encode",
     net_url_decode: "net/url/decode",
+    net_serve_buffers: "net/serve/buffers",
+    net_serve_mock: "net/serve/mock",
     net_serve_requests: "net/serve/requests",
+    net_serve_stream: "net/serve/stream",
     net_serve_websockets: "net/serve/websockets",
     net_socket_basic: "net/socket/basic",
+    net_socket_keepalive: "net/socket/keepalive",
     net_socket_wss: "net/socket/wss",
     net_socket_wss_rw: "net/socket/wss_rw",
 }
 
+#[cfg(all(feature = "std-net", feature = "std-fs"))]
+create_tests! {
+    net_request_cache: "net/request/cache",
+}
+
 #[cfg(feature = "std-process")]
 create_tests! {
     process_args: "process/args",
@@ -148,10 +254,13 @@ create_tests! {
     process_spawn_non_blocking: "process/create/non_blocking",
     process_spawn_status: "process/create/status",
     process_spawn_stream: "process/create/stream",
+    process_spawn_lune_basic: "process/spawnLune/basic",
+    process_spawn_lune_errors: "process/spawnLune/errors",
 }
 
 #[cfg(feature = "std-regex")]
 create_tests! {
+    regex_find_all: "regex/findAll",
     regex_general: "regex/general",
     regex_metamethods: "regex/metamethods",
     regex_replace: "regex/replace",
@@ -167,6 +276,7 @@ create_tests! {
     roblox_datatype_color_sequence_keypoint: "roblox/datatypes/ColorSequenceKeypoint",
     roblox_datatype_enum: "roblox/datatypes/Enum",
     roblox_datatype_faces: "roblox/datatypes/Faces",
+    roblox_datatype_float_curve_key: "roblox/datatypes/FloatCurveKey",
     roblox_datatype_font: "roblox/datatypes/Font",
     roblox_datatype_number_range: "roblox/datatypes/NumberRange",
     roblox_datatype_number_sequence: "roblox/datatypes/NumberSequence",
@@ -178,6 +288,7 @@ create_tests! {
     roblox_datatype_udim2: "roblox/datatypes/UDim2",
     roblox_datatype_region3: "roblox/datatypes/Region3",
     roblox_datatype_region3int16: "roblox/datatypes/Region3int16",
+    roblox_datatype_rotation_curve_key: "roblox/datatypes/RotationCurveKey",
     roblox_datatype_vector2: "roblox/datatypes/Vector2",
     roblox_datatype_vector2int16: "roblox/datatypes/Vector2int16",
     roblox_datatype_vector3: "roblox/datatypes/Vector3",
@@ -191,11 +302,14 @@ create_tests! {
     roblox_instance_attributes: "roblox/instance/attributes",
     roblox_instance_new: "roblox/instance/new",
     roblox_instance_properties: "roblox/instance/properties",
+    roblox_instance_raw_properties: "roblox/instance/rawProperties",
     roblox_instance_tags: "roblox/instance/tags",
 
+    roblox_instance_classes_collection_service: "roblox/instance/classes/CollectionService",
     roblox_instance_classes_data_model: "roblox/instance/classes/DataModel",
     roblox_instance_classes_workspace: "roblox/instance/classes/Workspace",
     roblox_instance_classes_terrain: "roblox/instance/classes/Terrain",
+    roblox_instance_classes_world_root: "roblox/instance/classes/WorldRoot",
 
     roblox_instance_custom_async: "roblox/instance/custom/async",
     roblox_instance_custom_methods: "roblox/instance/custom/methods",
@@ -210,6 +324,7 @@ create_tests! {
     roblox_instance_methods_find_first_child: "roblox/instance/methods/FindFirstChild",
     roblox_instance_methods_find_first_child_of_class: "roblox/instance/methods/FindFirstChildOfClass",
     roblox_instance_methods_find_first_child_which_is_a: "roblox/instance/methods/FindFirstChildWhichIsA",
+    roblox_instance_methods_find_first_descendant: "roblox/instance/methods/FindFirstDescendant",
     roblox_instance_methods_get_children: "roblox/instance/methods/GetChildren",
     roblox_instance_methods_get_debug_id: "roblox/instance/methods/GetDebugId",
     roblox_instance_methods_get_descendants: "roblox/instance/methods/GetDescendants",
@@ -217,6 +332,7 @@ create_tests! {
     roblox_instance_methods_is_a: "roblox/instance/methods/IsA",
     roblox_instance_methods_is_ancestor_of: "roblox/instance/methods/IsAncestorOf",
     roblox_instance_methods_is_descendant_of: "roblox/instance/methods/IsDescendantOf",
+    roblox_instance_methods_query_descendants: "roblox/instance/methods/QueryDescendants",
 
     roblox_misc_typeof: "roblox/misc/typeof",
 
@@ -232,12 +348,38 @@ create_tests! {
     serde_compression_roundtrip: "serde/compression/roundtrip",
     serde_json_decode: "serde/json/decode",
     serde_json_encode: "serde/json/encode",
+    serde_json_values: "serde/json/values",
     serde_toml_decode: "serde/toml/decode",
     serde_toml_encode: "serde/toml/encode",
+    serde_yaml_decode: "serde/yaml/decode",
+    serde_yaml_encode: "serde/yaml/encode",
     serde_hashing_hash: "serde/hashing/hash",
     serde_hashing_hmac: "serde/hashing/hmac",
 }
 
+#[cfg(feature = "std-serial")]
+create_tests! {
+    serial_options: "serial/options",
+}
+
+#[cfg(feature = "std-sqlite")]
+create_tests! {
+    sqlite_execute: "sqlite/execute",
+    sqlite_statement: "sqlite/statement",
+    sqlite_transaction: "sqlite/transaction",
+    sqlite_values: "sqlite/values",
+}
+
+#[cfg(all(feature = "std-process", feature = "std-fs"))]
+create_sandboxed_tests! {
+    process_spawn_lune_sandbox: "process/spawnLune/sandbox",
+}
+
+#[cfg(feature = "std-sqlite")]
+create_sandboxed_tests! {
+    sqlite_sandbox: "sqlite/sandbox",
+}
+
 #[cfg(feature = "std-stdio")]
 create_tests! {
     stdio_format: "stdio/format",
@@ -253,5 +395,11 @@ create_tests! {
     task_defer: "task/defer",
     task_delay: "task/delay",
     task_spawn: "task/spawn",
+    task_toplevel: "task/toplevel",
     task_wait: "task/wait",
 }
+
+#[cfg(feature = "std-task")]
+create_precise_timer_tests! {
+    task_wait_precise: "task/wait_precise",
+}
@@ -0,0 +1,134 @@
+#![allow(clippy::cargo_common_metadata)]
+#![cfg(all(feature = "cli", feature = "std-stdio"))]
+
+use std::{
+    io::Write,
+    process::{Command, Output, Stdio},
+};
+
+/**
+    Runs the given script using the `lune` binary, with
+    the given input piped to it through stdin.
+*/
+fn run_with_stdin(script: &str, args: &[&str], input: &[u8]) -> Output {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("script.luau");
+    std::fs::write(&path, script).unwrap();
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_lune"))
+        .arg("run")
+        .args(args)
+        .arg(&path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    // NOTE: Stdin must be closed after writing for the script to see the end of it
+    let mut stdin = child.stdin.take().unwrap();
+    stdin.write_all(input).unwrap();
+    drop(stdin);
+
+    let output = child.wait_with_output().unwrap();
+    assert!(
+        output.status.success(),
+        "script failed:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    output
+}
+
+fn stdout(output: &Output) -> &str {
+    std::str::from_utf8(&output.stdout).unwrap()
+}
+
+#[test]
+fn read_line_reads_lines_until_eof() {
+    let script = r#"
+        local stdio = require("@lune/stdio")
+        assert(stdio.readLine() == "first")
+        assert(stdio.readLine() == "second", "carriage returns should be removed")
+        assert(stdio.readLine() == "")
+        assert(stdio.readLine() == "last", "the final line may have no newline")
+        assert(stdio.readLine() == nil, "reading past the end should give nil")
+        print("ok")
+    "#;
+    let output = run_with_stdin(script, &[], b"first\nsecond\r\n\nlast");
+    assert_eq!(stdout(&output), "ok\n");
+}
+
+#[test]
+fn read_reads_at_most_count_bytes() {
+    let script = r#"
+        local stdio = require("@lune/stdio")
+        assert(stdio.read(3) == "abc")
+        assert(stdio.read(2) == "de")
+        assert(stdio.read() == "fgh")
+        assert(stdio.read() == nil, "reading past the end should give nil")
+        print("ok")
+    "#;
+    let output = run_with_stdin(script, &[], b"abcdefgh");
+    assert_eq!(stdout(&output), "ok\n");
+}
+
+#[test]
+fn read_limits_huge_counts() {
+    let script = r#"
+        local stdio = require("@lune/stdio")
+        local input = stdio.read(2 ^ 40)
+        assert(input ~= nil and #input <= 64 * 1024, "read should be limited")
+        local rest = stdio.readToEnd()
+        print(#input + #rest)
+    "#;
+    let output = run_with_stdin(script, &[], &vec![b'a'; 100_000]);
+    assert_eq!(stdout(&output), "100000\n");
+}
+
+#[test]
+fn reads_share_buffered_input() {
+    let script = r#"
+        local stdio = require("@lune/stdio")
+        assert(stdio.readLine() == "header")
+        assert(stdio.read(4) == "body")
+        assert(stdio.readToEnd() == "\nrest\nof\ninput\n")
+        print("ok")
+    "#;
+    let output = run_with_stdin(script, &[], b"header\nbody\nrest\nof\ninput\n");
+    assert_eq!(stdout(&output), "ok\n");
+}
+
+#[test]
+fn binary_input_is_read_unchanged() {
+    let script = r#"
+        local stdio = require("@lune/stdio")
+        local input = stdio.readToEnd()
+        assert(input == "\0\255\n\254", "binary input did not match")
+        print(#input)
+    "#;
+    let output = run_with_stdin(script, &[], b"\0\xff\n\xfe");
+    assert_eq!(stdout(&output), "4\n");
+}
+
+#[test]
+fn stdin_flag_sets_global() {
+    let script = r"
+        print(typeof(stdin))
+    ";
+    let text = run_with_stdin(script, &["--stdin=text"], b"hello");
+    assert_eq!(stdout(&text), "string\n");
+    let bytes = run_with_stdin(script, &["--stdin=bytes"], b"\0\xff");
+    assert_eq!(stdout(&bytes), "buffer\n");
+
+    let script = r#"
+        assert(stdin.name == "lune")
+        assert(#stdin.values == 3 and stdin.values[3] == 3)
+        print("ok")
+    "#;
+    let json = run_with_stdin(
+        script,
+        &["--stdin=json"],
+        br#"{"name":"lune","values":[1,2,3]}"#,
+    );
+    assert_eq!(stdout(&json), "ok\n");
+}
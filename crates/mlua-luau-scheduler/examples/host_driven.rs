@@ -0,0 +1,94 @@
+#![allow(clippy::missing_errors_doc)]
+#![allow(clippy::missing_panics_doc)]
+#![allow(clippy::cargo_common_metadata)]
+
+use std::{
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use async_io::{block_on, Timer};
+
+use mlua::prelude::*;
+use mlua_luau_scheduler::Scheduler;
+
+const MAIN_SCRIPT: &str = include_str!("./lua/host_driven.luau");
+
+pub fn main() -> LuaResult<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .with_target(false)
+        .without_time()
+        .init();
+
+    // Set up persistent Lua environment
+    let lua = Lua::new();
+    lua.globals().set(
+        "sleep",
+        lua.create_async_function(|_, duration: f64| async move {
+            let before = Instant::now();
+            let after = Timer::after(Duration::from_secs_f64(duration)).await;
+            Ok((after - before).as_secs_f64())
+        })?,
+    )?;
+
+    // Create a driver, letting us run the scheduler in our own event loop
+    let sched = Scheduler::new(&lua);
+    let driver = sched.driver();
+    let waker = driver.waker();
+
+    // Simulate requests coming in from another thread, waking the driver for each one
+    let (tx, rx) = mpsc::channel();
+    let producer = thread::spawn(move || {
+        for request in 1..=3 {
+            thread::sleep(Duration::from_millis(5));
+            tx.send(request).unwrap();
+            waker.wake();
+        }
+    });
+
+    // Drive the scheduler until all requests have been handled
+    let mut pending = Vec::new();
+    let mut handled = Vec::new();
+    while handled.len() < 3 {
+        for request in rx.try_iter() {
+            let thread = lua.load(MAIN_SCRIPT);
+            pending.push(sched.push_thread_back(thread, request)?);
+        }
+        block_on(driver.tick());
+        pending.retain(|id| match sched.get_thread_result(*id) {
+            Some(result) => {
+                handled.push(result.unwrap().into_vec());
+                false
+            }
+            None => true,
+        });
+    }
+
+    producer.join().unwrap();
+    assert!(driver.is_idle());
+    assert!(!driver.try_tick());
+
+    // Verify that each request got handled
+    let mut messages = handled
+        .into_iter()
+        .map(|values| String::from_lua(values[0].clone(), &lua))
+        .collect::<LuaResult<Vec<_>>>()?;
+    messages.sort();
+    assert_eq!(
+        messages,
+        [
+            "Handled request #1",
+            "Handled request #2",
+            "Handled request #3"
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_host_driven() -> LuaResult<()> {
+    main()
+}
@@ -0,0 +1,366 @@
+#![allow(clippy::module_name_repetitions)]
+
+use std::{
+    rc::{Rc, Weak as WeakRc},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Weak as WeakArc,
+    },
+    thread::panicking,
+};
+
+use futures_lite::prelude::*;
+use mlua::prelude::*;
+
+use async_executor::{Executor, LocalExecutor};
+use event_listener::Event;
+use tracing::{debug, trace, trace_span, Instrument};
+
+use crate::{
+    queue::FuturesQueue,
+    scheduler::{Scheduler, ERR_METADATA_ALREADY_ATTACHED, ERR_METADATA_REMOVED},
+    status::Status,
+    thread_id::ThreadId,
+    util::{run_until_yield, ThreadResult},
+};
+
+/**
+    A handle that can be used to wake up a [`SchedulerDriver`] waiting in [`SchedulerDriver::tick`].
+
+    This handle is `Send` and `Sync`, and may be used from any thread, for example
+    to let the host application know that it has new work to push to the scheduler.
+*/
+#[derive(Debug, Clone)]
+pub struct SchedulerWaker {
+    woken: Arc<AtomicBool>,
+    event: Arc<Event>,
+}
+
+impl SchedulerWaker {
+    fn new() -> Self {
+        Self {
+            woken: Arc::new(AtomicBool::new(false)),
+            event: Arc::new(Event::new()),
+        }
+    }
+
+    /**
+        Wakes up the driver, making any current or next call to [`SchedulerDriver::tick`] return.
+    */
+    pub fn wake(&self) {
+        self.woken.store(true, Ordering::SeqCst);
+        self.event.notify(usize::MAX);
+    }
+
+    fn reset(&self) {
+        self.woken.store(false, Ordering::SeqCst);
+    }
+
+    async fn listen(&self) {
+        if !self.woken.load(Ordering::SeqCst) {
+            let listener = self.event.listen();
+            // NOTE: Need to check again, we could have been
+            // woken up while creating our listener
+            if !self.woken.load(Ordering::SeqCst) {
+                listener.await;
+            }
+        }
+    }
+}
+
+/**
+    A driver for a [`Scheduler`], letting a host application run it step by step.
+
+    Created using [`Scheduler::driver`], and [`Scheduler::run`] uses one internally.
+
+    While a driver exists, the scheduler is considered to be running, and it may be driven
+    forward by awaiting [`SchedulerDriver::tick`] from any async runtime or event loop,
+    or without waiting by calling [`SchedulerDriver::try_tick`]. Unlike [`Scheduler::run`],
+    the driver does not stop once all Lua threads have completed, which means that new
+    threads may be pushed to the scheduler at any point while the driver exists.
+
+    Dropping the driver stops the scheduler, discarding any threads and tasks that
+    have not yet completed, after which the scheduler may be driven or run again.
+*/
+pub struct SchedulerDriver<'sched, 'lua> {
+    sched: &'sched Scheduler<'lua>,
+    local_exec: LocalExecutor<'sched>,
+    main_exec: Arc<Executor<'static>>,
+    fut_queue: Rc<FuturesQueue<'static>>,
+    waker: SchedulerWaker,
+}
+
+impl<'sched, 'lua> SchedulerDriver<'sched, 'lua> {
+    pub(crate) fn new(sched: &'sched Scheduler<'lua>) -> Self {
+        /*
+            Create new executors to use - note that we do not need create multiple executors
+            for work stealing, the user may do that themselves if they want to and it will work
+            just fine, as long as anything async is .await-ed from within a Lua async function.
+
+            The main purpose of the two executors here is just to have one with
+            the Send bound, and another (local) one without it, for Lua scheduling.
+
+            We also use the main executor to drive the main loop forward,
+            saving a tiny bit of processing from going on the Lua executor itself.
+        */
+        let local_exec = LocalExecutor::new();
+        let main_exec = Arc::new(Executor::new());
+        let fut_queue = Rc::new(FuturesQueue::new());
+
+        /*
+            Store the main executor and queue in Lua, so that they may be used with LuaSchedulerExt.
+
+            Also ensure we do not already have an executor or queues - these are definite user errors
+            and may happen if the user tries to run multiple schedulers on the same Lua state at once.
+        */
+        let lua = sched.lua;
+        assert!(
+            lua.app_data_ref::<WeakArc<Executor>>().is_none(),
+            "{ERR_METADATA_ALREADY_ATTACHED}"
+        );
+        assert!(
+            lua.app_data_ref::<WeakRc<FuturesQueue>>().is_none(),
+            "{ERR_METADATA_ALREADY_ATTACHED}"
+        );
+
+        lua.set_app_data(Arc::downgrade(&main_exec));
+        lua.set_app_data(Rc::downgrade(&fut_queue.clone()));
+
+        sched.set_status(Status::Running);
+
+        Self {
+            sched,
+            local_exec,
+            main_exec,
+            fut_queue,
+            waker: SchedulerWaker::new(),
+        }
+    }
+
+    /**
+        Returns a new [`SchedulerWaker`] that can be used to wake up this driver.
+    */
+    #[must_use]
+    pub fn waker(&self) -> SchedulerWaker {
+        self.waker.clone()
+    }
+
+    /**
+        Returns `true` if there are no Lua threads or tasks left to run.
+
+        Note that new threads may still be pushed to the scheduler after this returns `true`.
+    */
+    #[must_use]
+    pub fn is_idle(&self) -> bool {
+        self.local_exec.is_empty()
+            && self.sched.queue_spawn.is_empty()
+            && self.sched.queue_defer.is_empty()
+            && self.fut_queue.is_empty()
+    }
+
+    /**
+        Waits until there is work to do, and then does as much of it as possible without waiting.
+
+        Returns once any work has been done, once an exit code has been set,
+        or once the driver has been woken up using a [`SchedulerWaker`].
+    */
+    pub async fn tick(&self) {
+        self.main_exec.run(self.step()).await;
+    }
+
+    /**
+        Does as much work as possible without waiting, running any Lua threads
+        and tasks that are ready, and returns `true` if any work was done.
+
+        This is useful for driving the scheduler from an event loop that is not async,
+        and will never run anything if an exit code has been set for the scheduler.
+    */
+    pub fn try_tick(&self) -> bool {
+        let mut progressed = false;
+        while self.sched.exit.get().is_none() {
+            let mut any = self.process_queues() > 0;
+            while self.local_exec.try_tick() {
+                any = true;
+            }
+            while self.main_exec.try_tick() {
+                any = true;
+            }
+            if !any {
+                break;
+            }
+            progressed = true;
+        }
+        progressed
+    }
+
+    /**
+        Runs the scheduler until all Lua threads have completed, or an exit code has been set.
+    */
+    pub(crate) async fn run(&self) {
+        self.main_exec
+            .run(async { while !self.step().await {} })
+            .await;
+    }
+
+    /**
+        Waits for the next action to perform, and performs it.
+
+        Returns `true` if an exit code was set, or if there is no remaining work.
+    */
+    async fn step(&self) -> bool {
+        /*
+            Manually tick the Lua executor, while running under the main executor.
+            Each tick we wait for the next action to perform, in prioritized order:
+
+            1. The exit event is triggered by setting an exit code
+            2. A Lua thread is available to run on the spawned queue
+            3. A Lua thread is available to run on the deferred queue
+            4. A new thread-local future is available to run on the local executor
+            5. The driver was woken up by the host application
+            6. Task(s) scheduled on the Lua executor have made progress and should be polled again
+
+            This ordering is vital to ensure that we don't accidentally exit the main loop
+            when there are new Lua threads to enqueue and potentially more work to be done.
+        */
+        let fut_exit = self.sched.exit.listen(); // 1
+        let fut_spawn = self.sched.queue_spawn.wait_for_item(); // 2
+        let fut_defer = self.sched.queue_defer.wait_for_item(); // 3
+        let fut_futs = self.fut_queue.wait_for_item(); // 4
+        let fut_wake = self.waker.listen(); // 5
+
+        // 6
+        let mut num_processed = 0;
+        let span_tick = trace_span!("Scheduler::tick");
+        let fut_tick = async {
+            self.local_exec.tick().await;
+            // NOTE: Try to do as much work as possible instead of just a single tick()
+            num_processed += 1;
+            while self.local_exec.try_tick() {
+                num_processed += 1;
+            }
+        };
+
+        // 1 + 2 + 3 + 4 + 5 + 6
+        fut_exit
+            .or(fut_spawn)
+            .or(fut_defer)
+            .or(fut_futs)
+            .or(fut_wake)
+            .or(fut_tick.instrument(span_tick.or_current()))
+            .await;
+
+        self.waker.reset();
+
+        // Check if we should exit
+        if self.sched.exit.get().is_some() {
+            debug!("exit signal received");
+            return true;
+        }
+
+        // Process spawned threads first, then deferred threads, then futures
+        let num_queued = self.process_queues();
+
+        // Empty executor = we didn't spawn any new Lua tasks
+        // above, and there are no remaining tasks to run later
+        trace!(
+            futures_processed = num_processed,
+            items_queued = num_queued,
+            "loop"
+        );
+        self.local_exec.is_empty()
+            && self.sched.queue_spawn.is_empty()
+            && self.sched.queue_defer.is_empty()
+    }
+
+    /**
+        Drains all queues, spawning their threads and futures on the local executor.
+
+        Returns the total number of threads and futures that were spawned.
+    */
+    fn process_queues(&self) -> usize {
+        let lua = self.sched.lua;
+        let mut num_spawned = 0;
+        let mut num_deferred = 0;
+        let mut num_futures = 0;
+        {
+            let _span = trace_span!("Scheduler::drain_spawned").entered();
+            for (thread, args) in self.sched.queue_spawn.drain_items(lua) {
+                self.process_thread(thread, args);
+                num_spawned += 1;
+            }
+        }
+        {
+            let _span = trace_span!("Scheduler::drain_deferred").entered();
+            for (thread, args) in self.sched.queue_defer.drain_items(lua) {
+                self.process_thread(thread, args);
+                num_deferred += 1;
+            }
+        }
+        {
+            let _span = trace_span!("Scheduler::drain_futures").entered();
+            for fut in self.fut_queue.drain_items() {
+                self.local_exec.spawn(fut).detach();
+                num_futures += 1;
+            }
+        }
+        trace!(
+            futures_spawned = num_futures,
+            lua_threads_spawned = num_spawned,
+            lua_threads_deferred = num_deferred,
+            "queues"
+        );
+        num_spawned + num_deferred + num_futures
+    }
+
+    fn process_thread(&self, thread: LuaThread<'lua>, args: LuaMultiValue<'lua>) {
+        // NOTE: Thread may have been cancelled from Lua
+        // before we got here, so we need to check it again
+        if thread.status() != LuaThreadStatus::Resumable {
+            return;
+        }
+
+        // Check if we should be tracking this thread
+        let sched = self.sched;
+        let id = ThreadId::from(&thread);
+        let id_tracked = sched.result_map.is_tracked(id);
+
+        // Create our future which will run the thread and store its final result
+        let fut = async move {
+            // Run until yield and check if we got a final result
+            if let Some(res) = run_until_yield(thread.clone(), args).await {
+                if let Err(e) = res.as_ref() {
+                    let e = sched.origins.attach(sched.lua, &thread, e);
+                    sched.error_callback.call(&e);
+                }
+                if thread.status() != LuaThreadStatus::Resumable {
+                    sched.origins.remove(sched.lua, &thread);
+                    if id_tracked {
+                        let thread_res = ThreadResult::new(res, sched.lua);
+                        sched.result_map.insert(id, thread_res);
+                    }
+                }
+            }
+        };
+
+        // Spawn it on the executor
+        self.local_exec.spawn(fut).detach();
+    }
+}
+
+impl Drop for SchedulerDriver<'_, '_> {
+    fn drop(&mut self) {
+        self.sched.set_status(Status::Completed);
+        let lua = self.sched.lua;
+        if panicking() {
+            // Do not cause further panics if already panicking, as
+            // this may abort the program instead of safely unwinding
+            lua.remove_app_data::<WeakArc<Executor>>();
+            lua.remove_app_data::<WeakRc<FuturesQueue>>();
+        } else {
+            lua.remove_app_data::<WeakArc<Executor>>()
+                .expect(ERR_METADATA_REMOVED);
+            lua.remove_app_data::<WeakRc<FuturesQueue>>()
+                .expect(ERR_METADATA_REMOVED);
+        }
+    }
+}
@@ -4,6 +4,7 @@ use mlua::prelude::*;
 
 use crate::{
     error_callback::ThreadErrorCallback,
+    origin::ThreadOrigins,
     queue::{DeferredThreadQueue, SpawnedThreadQueue},
     result_map::ThreadResultMap,
     thread_id::ThreadId,
@@ -106,6 +107,10 @@ impl<'lua> Functions<'lua> {
             .app_data_ref::<ThreadResultMap>()
             .expect(ERR_METADATA_NOT_ATTACHED)
             .clone();
+        let origins = lua
+            .app_data_ref::<ThreadOrigins>()
+            .expect(ERR_METADATA_NOT_ATTACHED)
+            .clone();
 
         let resume_queue = defer_queue.clone();
         let resume_map = result_map.clone();
@@ -161,11 +166,14 @@ impl<'lua> Functions<'lua> {
             .into_function()?;
 
         let spawn_map = result_map.clone();
+        let spawn_origins = origins.clone();
         let spawn = lua.create_function(
             move |lua, (tof, args): (LuaThreadOrFunction, LuaMultiValue)| {
                 let _span = tracing::trace_span!("Scheduler::fn_spawn").entered();
                 let thread = tof.into_thread(lua)?;
                 if thread.status() == LuaThreadStatus::Resumable {
+                    let id = ThreadId::from(&thread);
+                    spawn_origins.capture(lua, &thread, "spawn")?;
                     // NOTE: We need to resume the thread once instantly for correct behavior,
                     // and only if we get the pending value back we can spawn to async executor
                     match thread.resume::<_, LuaMultiValue>(args.clone()) {
@@ -175,7 +183,7 @@ impl<'lua> Functions<'lua> {
                             } else {
                                 // Not pending, store the value if thread is done
                                 if thread.status() != LuaThreadStatus::Resumable {
-                                    let id = ThreadId::from(&thread);
+                                    spawn_origins.remove(lua, &thread);
                                     if spawn_map.is_tracked(id) {
                                         let res = ThreadResult::new(Ok(v), lua);
                                         spawn_map.insert(id, res);
@@ -184,9 +192,9 @@ impl<'lua> Functions<'lua> {
                             }
                         }
                         Err(e) => {
-                            error_callback.call(&e);
+                            error_callback.call(&spawn_origins.attach(lua, &thread, &e));
+                            spawn_origins.remove(lua, &thread);
                             // Not pending, store the error
-                            let id = ThreadId::from(&thread);
                             if spawn_map.is_tracked(id) {
                                 let res = ThreadResult::new(Err(e), lua);
                                 spawn_map.insert(id, res);
@@ -198,11 +206,13 @@ impl<'lua> Functions<'lua> {
             },
         )?;
 
+        let defer_origins = origins.clone();
         let defer = lua.create_function(
             move |lua, (tof, args): (LuaThreadOrFunction, LuaMultiValue)| {
                 let _span = tracing::trace_span!("Scheduler::fn_defer").entered();
                 let thread = tof.into_thread(lua)?;
                 if thread.status() == LuaThreadStatus::Resumable {
+                    defer_origins.capture(lua, &thread, "defer")?;
                     defer_queue.push_item(lua, &thread, args)?;
                 }
                 Ok(thread)
@@ -216,6 +226,7 @@ impl<'lua> Functions<'lua> {
         let close_key = lua.create_registry_value(close)?;
         let cancel = lua.create_function(move |lua, thread: LuaThread| {
             let _span = tracing::trace_span!("Scheduler::fn_cancel").entered();
+            origins.remove(lua, &thread);
             let close: LuaFunction = lua.registry_value(&close_key)?;
             match close.call(thread) {
                 Err(LuaError::CoroutineInactive) | Ok(()) => Ok(()),
@@ -1,8 +1,10 @@
 #![allow(clippy::cargo_common_metadata)]
 
+mod driver;
 mod error_callback;
 mod exit;
 mod functions;
+mod origin;
 mod queue;
 mod result_map;
 mod scheduler;
@@ -11,7 +13,9 @@ mod thread_id;
 mod traits;
 mod util;
 
+pub use driver::{SchedulerDriver, SchedulerWaker};
 pub use functions::Functions;
+pub use origin::ThreadOrigin;
 pub use scheduler::Scheduler;
 pub use status::Status;
 pub use thread_id::ThreadId;
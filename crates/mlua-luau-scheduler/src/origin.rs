@@ -0,0 +1,216 @@
+use std::{cell::Cell, fmt::Write as _, rc::Rc, sync::Arc};
+
+use mlua::prelude::*;
+
+/**
+    Maximum number of stack frames to capture for thread origins.
+
+    Threads may be spawned very frequently, so we limit this to keep spawning cheap.
+*/
+const MAX_ORIGIN_FRAMES: usize = 16;
+
+/**
+    The location that a thread was created from, such as a call to `task.spawn`.
+
+    Errors in threads are reported after the code that created them has moved
+    on, meaning their stack traces would otherwise end at the scheduler - this
+    lets the location where they were created be included in tracebacks.
+*/
+#[derive(Debug, Clone)]
+pub struct ThreadOrigin {
+    traceback: Rc<str>,
+}
+
+impl ThreadOrigin {
+    /**
+        Captures the current Lua call stack as a thread origin.
+
+        Must be called from within a Rust function that has been called from Lua, and
+        the given function name should be the name of that function, such as `spawn`.
+    */
+    pub(crate) fn capture(lua: &Lua, function_name: &str) -> Self {
+        let mut traceback = format!("stack traceback:\n\t[C]: in function '{function_name}'");
+        // NOTE: Level 0 is the Rust function we are currently
+        // inside of, so we start at level 1 which is the caller
+        // Formatting here matches tracebacks in callback errors
+        let mut level = 1;
+        while level <= MAX_ORIGIN_FRAMES {
+            let Some(debug) = lua.inspect_stack(level) else {
+                break;
+            };
+            level += 1;
+            let source = debug.source();
+            let names = debug.names();
+            let short_src = source.short_src.as_deref().unwrap_or("?");
+            // Skip internal frames used by mlua to implement async functions
+            if short_src.contains("__mlua") {
+                continue;
+            }
+            let _ = write!(traceback, "\n\t{short_src}:");
+            let line = debug.curr_line();
+            if line > 0 {
+                let _ = write!(traceback, "{line}:");
+            }
+            match names.name.as_deref() {
+                Some(name) => {
+                    let _ = write!(traceback, " in function '{name}'");
+                }
+                None => traceback.push_str(" in ?"),
+            }
+        }
+        Self {
+            traceback: traceback.into(),
+        }
+    }
+}
+
+/**
+    Origins of all threads that are currently known to the scheduler.
+
+    Origins are stored in a table with weak keys, keyed by the threads themselves,
+    meaning that they are removed once their thread has been garbage collected, and
+    that a new thread can never pick up the origin of an old thread that it replaced.
+
+    Capturing origins is not free, so it must be enabled using [`ThreadOrigins::set_enabled`].
+*/
+#[derive(Debug, Clone)]
+pub(crate) struct ThreadOrigins {
+    enabled: Rc<Cell<bool>>,
+    table: Rc<LuaRegistryKey>,
+}
+
+impl ThreadOrigins {
+    pub fn new(lua: &Lua) -> LuaResult<Self> {
+        let table = lua.create_table()?;
+        let meta = lua.create_table_from([("__mode", "k")])?;
+        table.set_metatable(Some(meta));
+        Ok(Self {
+            enabled: Rc::new(Cell::new(false)),
+            table: Rc::new(lua.create_registry_value(table)?),
+        })
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.get()
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.set(enabled);
+    }
+
+    /**
+        Captures the current Lua call stack as the origin of the given
+        thread, if capturing origins is enabled, otherwise does nothing.
+
+        See [`ThreadOrigin::capture`] for more details.
+    */
+    pub fn capture(&self, lua: &Lua, thread: &LuaThread, function_name: &str) -> LuaResult<()> {
+        if self.is_enabled() {
+            self.insert(lua, thread, &ThreadOrigin::capture(lua, function_name))?;
+        }
+        Ok(())
+    }
+
+    pub fn insert(&self, lua: &Lua, thread: &LuaThread, origin: &ThreadOrigin) -> LuaResult<()> {
+        let table = lua.registry_value::<LuaTable>(&self.table)?;
+        table.raw_set(thread.clone(), origin.traceback.as_ref())
+    }
+
+    /**
+        Removes the origin of the given thread, if one was recorded.
+
+        Should be called once a thread has finished running, so that its origin does
+        not need to be kept around until the thread has been garbage collected.
+    */
+    pub fn remove(&self, lua: &Lua, thread: &LuaThread) {
+        if !self.is_enabled() {
+            return;
+        }
+        if let Ok(table) = lua.registry_value::<LuaTable>(&self.table) {
+            table.raw_set(thread.clone(), LuaValue::Nil).ok();
+        }
+    }
+
+    /**
+        Attaches the origin of the given thread, if one was recorded, to the given error.
+    */
+    pub fn attach(&self, lua: &Lua, thread: &LuaThread, error: &LuaError) -> LuaError {
+        let traceback = if self.is_enabled() {
+            lua.registry_value::<LuaTable>(&self.table)
+                .and_then(|table| table.raw_get::<_, Option<LuaString>>(thread.clone()))
+                .ok()
+                .flatten()
+        } else {
+            None
+        };
+        match traceback {
+            Some(traceback) => LuaError::CallbackError {
+                traceback: traceback.to_string_lossy().into_owned(),
+                cause: Arc::new(error.clone()),
+            },
+            None => error.clone(),
+        }
+    }
+
+    #[cfg(test)]
+    fn len(&self, lua: &Lua) -> usize {
+        let table = lua.registry_value::<LuaTable>(&self.table).unwrap();
+        table.pairs::<LuaValue, LuaValue>().count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn origins_are_removed_with_their_threads() -> LuaResult<()> {
+        let lua = Lua::new();
+        let origins = ThreadOrigins::new(&lua)?;
+        origins.set_enabled(true);
+
+        let func = lua.create_function(|_, ()| Ok(()))?;
+        let mut threads = Vec::new();
+        for _ in 0..100 {
+            let thread = lua.create_thread(func.clone())?;
+            origins.insert(&lua, &thread, &ThreadOrigin::capture(&lua, "spawn"))?;
+            threads.push(thread);
+        }
+        lua.gc_collect()?;
+        assert_eq!(origins.len(&lua), 100);
+
+        drop(threads);
+        lua.gc_collect()?;
+        lua.gc_collect()?;
+        assert_eq!(origins.len(&lua), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn origins_are_only_attached_when_enabled() -> LuaResult<()> {
+        let lua = Lua::new();
+        let origins = ThreadOrigins::new(&lua)?;
+
+        let func = lua.create_function(|_, ()| Ok(()))?;
+        let thread = lua.create_thread(func)?;
+        let error = LuaError::runtime("oops");
+
+        origins.capture(&lua, &thread, "spawn")?;
+        assert!(matches!(
+            origins.attach(&lua, &thread, &error),
+            LuaError::RuntimeError(_)
+        ));
+
+        origins.set_enabled(true);
+        origins.capture(&lua, &thread, "spawn")?;
+        match origins.attach(&lua, &thread, &error) {
+            LuaError::CallbackError { traceback, .. } => {
+                assert!(traceback.contains("in function 'spawn'"));
+            }
+            other => panic!("expected origin to be attached, got {other:?}"),
+        }
+
+        Ok(())
+    }
+}
@@ -136,4 +136,8 @@ impl<'fut> FuturesQueue<'fut> {
             self.event.listen().await;
         }
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
 }
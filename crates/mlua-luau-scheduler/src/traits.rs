@@ -11,6 +11,7 @@ use tracing::trace;
 
 use crate::{
     exit::Exit,
+    origin::{ThreadOrigin, ThreadOrigins},
     queue::{DeferredThreadQueue, FuturesQueue, SpawnedThreadQueue},
     result_map::ThreadResultMap,
     scheduler::Scheduler,
@@ -142,6 +143,29 @@ pub trait LuaSchedulerExt<'lua> {
         Panics if called outside of a running [`Scheduler`].
     */
     fn wait_for_thread(&'lua self, id: ThreadId) -> impl Future<Output = ()>;
+
+    /**
+        Captures the current Lua call stack, to be used as the origin of threads created later.
+
+        Must be called from within a Rust function that has been called from Lua, and
+        the given function name should be the name of that function, such as `serve`.
+
+        Returns `None` if capturing thread origins has not been enabled for the current
+        [`Scheduler`] using [`Scheduler::set_thread_origins_enabled`], or if called outside of one.
+    */
+    fn capture_thread_origin(&'lua self, function_name: &str) -> Option<ThreadOrigin>;
+
+    /**
+        Sets the origin of the given thread.
+
+        Any error in the thread will include the call stack of the origin in its traceback.
+        The origin is removed once the thread has finished running or has been collected.
+
+        # Panics
+
+        Panics if called outside of a running [`Scheduler`].
+    */
+    fn set_thread_origin(&'lua self, thread: &LuaThread<'lua>, origin: &ThreadOrigin);
 }
 
 /**
@@ -326,6 +350,23 @@ impl<'lua> LuaSchedulerExt<'lua> for Lua {
         map.remove(id).map(|r| r.value(self))
     }
 
+    fn capture_thread_origin(&'lua self, function_name: &str) -> Option<ThreadOrigin> {
+        let enabled = self
+            .app_data_ref::<ThreadOrigins>()
+            .is_some_and(|origins| origins.is_enabled());
+        enabled.then(|| ThreadOrigin::capture(self, function_name))
+    }
+
+    fn set_thread_origin(&'lua self, thread: &LuaThread<'lua>, origin: &ThreadOrigin) {
+        let origins = self
+            .app_data_ref::<ThreadOrigins>()
+            .expect("thread origins can only be set from within an active scheduler")
+            .clone();
+        origins
+            .insert(self, thread, origin)
+            .expect("failed to set thread origin");
+    }
+
     fn wait_for_thread(&'lua self, id: ThreadId) -> impl Future<Output = ()> {
         let map = self
             .app_data_ref::<ThreadResultMap>()